@@ -1,44 +1,2817 @@
+import math
+from typing import ClassVar
+
+import numpy as np
+
 class RsQuantile:
-    def __init__(self, q: float): ...
-    def update(self, x: float): ...
+    def __init__(
+        self,
+        q: float = 0.5,
+        n_markers: int = 5,
+        algorithm: str = "p2",
+        compression: float = 100.0,
+    ): ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsQuantile": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsQuantile": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsQuantile": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsQuantile", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsQuantile": ...
+    def __copy__(self) -> "RsQuantile": ...
+    def __deepcopy__(self, memo) -> "RsQuantile": ...
 
 class RsEWMean:
-    def __init__(self, alpha: float): ...
-    def update(self, x: float): ...
+    def __init__(
+        self,
+        alpha: float | None = None,
+        *,
+        span: float | None = None,
+        halflife: float | None = None,
+        com: float | None = None,
+    ): ...
+    @property
+    def alpha(self) -> float: ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsEWMean": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsEWMean": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsEWMean": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsEWMean", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsEWMean": ...
+    def __copy__(self) -> "RsEWMean": ...
+    def __deepcopy__(self, memo) -> "RsEWMean": ...
 
 class RsEWVar:
-    def __init__(self, alpha: float): ...
-    def update(self, x: float): ...
+    def __init__(
+        self,
+        alpha: float | None = None,
+        *,
+        span: float | None = None,
+        halflife: float | None = None,
+        com: float | None = None,
+    ): ...
+    @property
+    def alpha(self) -> float: ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsEWVar": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsEWVar": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsEWVar": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsEWVar", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsEWVar": ...
+    def __copy__(self) -> "RsEWVar": ...
+    def __deepcopy__(self, memo) -> "RsEWVar": ...
 
 class RsIQR:
     def __init__(self, q_inf: float, q_sup: float): ...
-    def update(self, x: float): ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsIQR": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsIQR": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsIQR": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsIQR", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsIQR": ...
+    def __copy__(self) -> "RsIQR": ...
+    def __deepcopy__(self, memo) -> "RsIQR": ...
 
 class RsKurtosis:
     def __init__(self, bias: bool): ...
-    def update(self, x: float): ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def merge(self, other: "RsKurtosis"): ...
+    def __iadd__(self, other: "RsKurtosis") -> "RsKurtosis": ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsKurtosis": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsKurtosis": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsKurtosis": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsKurtosis", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsKurtosis": ...
+    def __copy__(self) -> "RsKurtosis": ...
+    def __deepcopy__(self, memo) -> "RsKurtosis": ...
 
 class RsPeakToPeak:
     def __init__(self): ...
-    def update(self, x: float): ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def merge(self, other: "RsPeakToPeak"): ...
+    def __iadd__(self, other: "RsPeakToPeak") -> "RsPeakToPeak": ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPeakToPeak": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPeakToPeak": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPeakToPeak": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPeakToPeak", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPeakToPeak": ...
+    def __copy__(self) -> "RsPeakToPeak": ...
+    def __deepcopy__(self, memo) -> "RsPeakToPeak": ...
 
 class RsSkew:
     def __init__(self, bias: float): ...
-    def update(self, x: float): ...
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def revert(self, x: float): ...
+    def merge(self, other: "RsSkew"): ...
+    def __iadd__(self, other: "RsSkew") -> "RsSkew": ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSkew": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSkew": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSkew": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSkew", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSkew": ...
+    def __copy__(self) -> "RsSkew": ...
+    def __deepcopy__(self, memo) -> "RsSkew": ...
 
 class RsRollingQuantile:
     def __init__(self, q: float, window_size: int): ...
-    def update(self, x: float): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
     def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def revert(self, x: float): ...
+    def rank(self, x: float) -> int: ...
+    def get_many(self, qs: list[float]) -> list[float]: ...
+    def window(self, sorted: bool = False) -> np.ndarray: ...
+    def __len__(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingQuantile": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingQuantile": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingQuantile": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingQuantile", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingQuantile": ...
+    def __copy__(self) -> "RsRollingQuantile": ...
+    def __deepcopy__(self, memo) -> "RsRollingQuantile": ...
 
 class RsRollingIQR:
     def __init__(self, q_inf: float, q_sup: float, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def revert(self, x: float): ...
+    def rank(self, x: float) -> int: ...
+    def get_many(self, qs: list[float]) -> list[float]: ...
+    def window(self, sorted: bool = False) -> np.ndarray: ...
+    def __len__(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingIQR": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingIQR": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingIQR": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingIQR", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingIQR": ...
+    def __copy__(self) -> "RsRollingIQR": ...
+    def __deepcopy__(self, memo) -> "RsRollingIQR": ...
+
+class RsRollingMean:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def revert(self, x: float): ...
+    def window(self) -> np.ndarray: ...
+    def __len__(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingMean": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingMean": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingMean": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingMean", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingMean": ...
+    def __copy__(self) -> "RsRollingMean": ...
+    def __deepcopy__(self, memo) -> "RsRollingMean": ...
+
+class RsRollingVar:
+    def __init__(self, window_size: int, ddof: int = 1): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def revert(self, x: float): ...
+    @property
+    def mean(self) -> float: ...
+    def window(self) -> np.ndarray: ...
+    def __len__(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingVar": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingVar": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingVar": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingVar", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingVar": ...
+    def __copy__(self) -> "RsRollingVar": ...
+    def __deepcopy__(self, memo) -> "RsRollingVar": ...
+
+class RsRollingMin:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def window(self) -> np.ndarray: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingMin": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingMin": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingMin": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingMin", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingMin": ...
+    def __copy__(self) -> "RsRollingMin": ...
+    def __deepcopy__(self, memo) -> "RsRollingMin": ...
+
+class RsRollingMax:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def window(self) -> np.ndarray: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingMax": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingMax": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingMax": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingMax", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingMax": ...
+    def __copy__(self) -> "RsRollingMax": ...
+    def __deepcopy__(self, memo) -> "RsRollingMax": ...
+
+class RsRollingPeakToPeak:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def window(self) -> np.ndarray: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingPeakToPeak": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingPeakToPeak": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingPeakToPeak": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingPeakToPeak", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingPeakToPeak": ...
+    def __copy__(self) -> "RsRollingPeakToPeak": ...
+    def __deepcopy__(self, memo) -> "RsRollingPeakToPeak": ...
+
+class RsRollingMAD:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    def median(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def window(self, sorted: bool = False) -> np.ndarray: ...
+    def __len__(self) -> int: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingMAD": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingMAD": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingMAD": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingMAD", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingMAD": ...
+    def __copy__(self) -> "RsRollingMAD": ...
+    def __deepcopy__(self, memo) -> "RsRollingMAD": ...
+
+class RsGaussianMixture:
+    def __init__(
+        self, n_components: int, covariance_type: str = "full", reg_covar: float = 1e-6
+    ): ...
+    def learn_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict[int, float]: ...
+    def predict_one(self, x: dict[str, float]) -> int | None: ...
+    def score_one(self, x: dict[str, float]) -> float: ...
+    @property
+    def weights(self) -> list[float]: ...
+    @property
+    def means(self) -> list[dict[str, float]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsGaussianMixture": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsGaussianMixture": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsGaussianMixture": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsGaussianMixture", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsGaussianMixture": ...
+    def __copy__(self) -> "RsGaussianMixture": ...
+    def __deepcopy__(self, memo) -> "RsGaussianMixture": ...
+
+class RsKernelDensity:
+    def __init__(self, bandwidth: float | None = None, max_centers: int = 256): ...
+    def update(self, x: float): ...
+    def density(self, x: float) -> float: ...
+    def cdf(self, x: float) -> float: ...
+    @property
+    def bandwidth(self) -> float: ...
+    @property
+    def n_centers(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsKernelDensity": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsKernelDensity": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsKernelDensity": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsKernelDensity", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsKernelDensity": ...
+    def __copy__(self) -> "RsKernelDensity": ...
+    def __deepcopy__(self, memo) -> "RsKernelDensity": ...
+
+class RsECDF:
+    def __init__(self, compression: float = 100.0): ...
+    def update(self, x: float): ...
+    def cdf(self, x: float) -> float: ...
+    def ppf(self, q: float) -> float: ...
+    def get_many(self, qs: list[float]) -> list[float]: ...
+    @property
+    def n(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsECDF": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsECDF": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsECDF": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsECDF", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsECDF": ...
+    def __copy__(self) -> "RsECDF": ...
+    def __deepcopy__(self, memo) -> "RsECDF": ...
+
+class RsTDigest:
+    def __init__(self, compression: float = 100.0): ...
+    def update(self, x: float, w: float = 1.0): ...
+    def quantile(self, q: float) -> float: ...
+    def cdf(self, x: float) -> float: ...
+    def merge(self, other: "RsTDigest"): ...
+    def __iadd__(self, other: "RsTDigest") -> "RsTDigest": ...
+    @property
+    def n(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsTDigest": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsTDigest": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsTDigest": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsTDigest", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsTDigest": ...
+    def __copy__(self) -> "RsTDigest": ...
+    def __deepcopy__(self, memo) -> "RsTDigest": ...
+
+class RsCov:
+    def __init__(self, ddof: int = 1): ...
+    def update(self, x: float, y: float, w: float = 1.0): ...
+    def revert(self, x: float, y: float, w: float = 1.0): ...
+    def merge(self, other: "RsCov"): ...
+    def __iadd__(self, other: "RsCov") -> "RsCov": ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    @property
+    def ddof(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsCov": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsCov": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsCov": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsCov", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsCov": ...
+    def __copy__(self) -> "RsCov": ...
+    def __deepcopy__(self, memo) -> "RsCov": ...
+
+class RsPearsonCorr:
+    def __init__(self, ddof: int = 1): ...
+    def update(self, x: float, y: float, w: float = 1.0): ...
+    def revert(self, x: float, y: float, w: float = 1.0): ...
+    def merge(self, other: "RsPearsonCorr"): ...
+    def __iadd__(self, other: "RsPearsonCorr") -> "RsPearsonCorr": ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    @property
+    def ddof(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPearsonCorr": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPearsonCorr": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPearsonCorr": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPearsonCorr", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPearsonCorr": ...
+    def __copy__(self) -> "RsPearsonCorr": ...
+    def __deepcopy__(self, memo) -> "RsPearsonCorr": ...
+
+class RsCovMatrix:
+    def __init__(self, ddof: int = 1, *, n_features: int | None = None): ...
+    def update(self, x: dict[str, float] | np.ndarray): ...
+    def update_many(self, x: np.ndarray): ...
+    def revert(self, x: dict[str, float] | np.ndarray): ...
+    def merge(self, other: "RsCovMatrix"): ...
+    def __iadd__(self, other: "RsCovMatrix") -> "RsCovMatrix": ...
+    def get(self) -> np.ndarray: ...
+    def correlation(self) -> np.ndarray: ...
+    @property
+    def features(self) -> list[str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsCovMatrix": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsCovMatrix": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsCovMatrix": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsCovMatrix", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsCovMatrix": ...
+    def __copy__(self) -> "RsCovMatrix": ...
+    def __deepcopy__(self, memo) -> "RsCovMatrix": ...
+
+class RsPrecisionMatrix:
+    def __init__(self, shrinkage: float | None = None): ...
+    def update(self, x: dict[str, float]): ...
+    def get(self) -> np.ndarray: ...
+    @property
+    def shrinkage(self) -> float: ...
+    def shrunk_covariance(self) -> np.ndarray: ...
+    def shrunk_precision(self) -> np.ndarray: ...
+    def mahalanobis(self, x: dict[str, float], shrunk: bool = False) -> float: ...
+    @property
+    def features(self) -> list[str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPrecisionMatrix": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPrecisionMatrix": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPrecisionMatrix": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPrecisionMatrix", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPrecisionMatrix": ...
+    def __copy__(self) -> "RsPrecisionMatrix": ...
+    def __deepcopy__(self, memo) -> "RsPrecisionMatrix": ...
+
+class RsWhitener:
+    def __init__(self, method: str = "zca", eps: float = 1e-5, refresh_every: int = 100): ...
+    def learn_one(self, x: dict[str, float]): ...
+    def transform_one(self, x: dict[str, float]) -> dict[int | str, float]: ...
+    def refresh(self): ...
+    @property
+    def features(self) -> list[str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsWhitener": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsWhitener": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsWhitener": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsWhitener", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsWhitener": ...
+    def __copy__(self) -> "RsWhitener": ...
+    def __deepcopy__(self, memo) -> "RsWhitener": ...
+
+class RsWelchTTest:
+    def __init__(self): ...
+    def update_a(self, x: float): ...
+    def update_b(self, x: float): ...
+    @property
+    def statistic(self) -> float: ...
+    @property
+    def df(self) -> float: ...
+    @property
+    def p_value(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsWelchTTest": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsWelchTTest": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsWelchTTest": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsWelchTTest", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsWelchTTest": ...
+    def __copy__(self) -> "RsWelchTTest": ...
+    def __deepcopy__(self, memo) -> "RsWelchTTest": ...
+
+class RsChiSquareTest:
+    def __init__(self): ...
+    def update(self, x: int | str, y: int | str, w: float = 1.0): ...
+    @property
+    def statistic(self) -> float: ...
+    @property
+    def df(self) -> int: ...
+    @property
+    def p_value(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsChiSquareTest": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsChiSquareTest": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsChiSquareTest": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsChiSquareTest", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsChiSquareTest": ...
+    def __copy__(self) -> "RsChiSquareTest": ...
+    def __deepcopy__(self, memo) -> "RsChiSquareTest": ...
+
+class RsLogLoss:
+    def __init__(self): ...
+    def update(self, y_true: bool, y_pred: float): ...
+    def get(self) -> float: ...
+    @property
+    def bigger_is_better(self) -> bool: ...
+    @property
+    def requires_labels(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLogLoss": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLogLoss": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLogLoss": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLogLoss", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLogLoss": ...
+    def __copy__(self) -> "RsLogLoss": ...
+    def __deepcopy__(self, memo) -> "RsLogLoss": ...
+
+class RsAccuracy:
+    def __init__(self): ...
+    def update(self, y_true: int | str, y_pred: int | str): ...
+    def get(self) -> float: ...
+    @property
+    def bigger_is_better(self) -> bool: ...
+    @property
+    def requires_labels(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsAccuracy": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsAccuracy": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsAccuracy": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsAccuracy", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsAccuracy": ...
+    def __copy__(self) -> "RsAccuracy": ...
+    def __deepcopy__(self, memo) -> "RsAccuracy": ...
+
+class RsMannWhitneyU:
+    def __init__(self, reference_size: int, recent_size: int): ...
+    def update(self, x: float): ...
+    @property
+    def statistic(self) -> float: ...
+    @property
+    def z_score(self) -> float: ...
+    @property
+    def p_value(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsMannWhitneyU": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsMannWhitneyU": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsMannWhitneyU": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsMannWhitneyU", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsMannWhitneyU": ...
+    def __copy__(self) -> "RsMannWhitneyU": ...
+    def __deepcopy__(self, memo) -> "RsMannWhitneyU": ...
+
+class RsPairedComparison:
+    def __init__(self): ...
+    def update(self, loss_a: float, loss_b: float): ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def mean_difference(self) -> float: ...
+    @property
+    def wins(self) -> tuple[int, int, int]: ...
+    @property
+    def t_statistic(self) -> float: ...
+    @property
+    def t_p_value(self) -> float: ...
+    @property
+    def sign_p_value(self) -> float: ...
+    @property
+    def wilcoxon_statistic(self) -> float: ...
+    @property
+    def wilcoxon_z_score(self) -> float: ...
+    @property
+    def wilcoxon_p_value(self) -> float: ...
+    def summary(self) -> dict[str, float | int | tuple[int, int, int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPairedComparison": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPairedComparison": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPairedComparison": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPairedComparison", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPairedComparison": ...
+    def __copy__(self) -> "RsPairedComparison": ...
+    def __deepcopy__(self, memo) -> "RsPairedComparison": ...
+
+class RsWassersteinDrift:
+    def __init__(
+        self, window_size: int, compression: float = 100.0, n_quantiles: int = 100
+    ): ...
+    def update(self, x: float): ...
+    def reset_reference(self): ...
+    @property
+    def wasserstein(self) -> float: ...
+    @property
+    def energy(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsWassersteinDrift": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsWassersteinDrift": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsWassersteinDrift": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsWassersteinDrift", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsWassersteinDrift": ...
+    def __copy__(self) -> "RsWassersteinDrift": ...
+    def __deepcopy__(self, memo) -> "RsWassersteinDrift": ...
+
+class RsAdaptiveMean:
+    def __init__(
+        self, delta: float = 0.002, clock: int = 32, grace_period: int = 10
+    ): ...
+    def update(self, x: float): ...
+    def get(self) -> float: ...
+    def revert(self, x: float): ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    @property
+    def n_detections(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsAdaptiveMean": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsAdaptiveMean": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsAdaptiveMean": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsAdaptiveMean", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsAdaptiveMean": ...
+    def __copy__(self) -> "RsAdaptiveMean": ...
+    def __deepcopy__(self, memo) -> "RsAdaptiveMean": ...
+
+class RsAdaptiveVar:
+    def __init__(
+        self, delta: float = 0.002, clock: int = 32, grace_period: int = 10
+    ): ...
+    def update(self, x: float): ...
+    def get(self) -> float: ...
+    def revert(self, x: float): ...
+    @property
+    def mean(self) -> float: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    @property
+    def n_detections(self) -> int: ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsAdaptiveVar": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsAdaptiveVar": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsAdaptiveVar": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsAdaptiveVar", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsAdaptiveVar": ...
+    def __copy__(self) -> "RsAdaptiveVar": ...
+    def __deepcopy__(self, memo) -> "RsAdaptiveVar": ...
+
+class RsADWIN:
+    def __init__(
+        self, delta: float = 0.002, clock: int = 32, grace_period: int = 10
+    ): ...
+    def update(self, x: float) -> tuple[bool, bool]: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def estimation(self) -> float: ...
+    @property
+    def variance(self) -> float: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    @property
+    def n_detections(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsADWIN": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsADWIN": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsADWIN": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsADWIN", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsADWIN": ...
+    def __copy__(self) -> "RsADWIN": ...
+    def __deepcopy__(self, memo) -> "RsADWIN": ...
+
+class RsMultinomialDrift:
+    def __init__(self, window_size: int, alpha: float = 0.01): ...
+    def update(self, x: int | str): ...
+    def reset_reference(self): ...
+    @property
+    def statistic(self) -> float: ...
+    @property
+    def df(self) -> int: ...
+    @property
+    def p_value(self) -> float: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsMultinomialDrift": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsMultinomialDrift": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsMultinomialDrift": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsMultinomialDrift", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsMultinomialDrift": ...
+    def __copy__(self) -> "RsMultinomialDrift": ...
+    def __deepcopy__(self, memo) -> "RsMultinomialDrift": ...
+
+class RsPageHinkley:
+    def __init__(
+        self,
+        min_instances: int = 30,
+        delta: float = 0.005,
+        threshold: float = 50.0,
+        alpha: float = 0.9999,
+        mode: str = "both",
+    ): ...
+    def update(self, x: float) -> tuple[bool, bool]: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPageHinkley": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPageHinkley": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPageHinkley": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPageHinkley", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPageHinkley": ...
+    def __copy__(self) -> "RsPageHinkley": ...
+    def __deepcopy__(self, memo) -> "RsPageHinkley": ...
+
+class RsDDM:
+    def __init__(
+        self, warm_start: int = 30, warning_threshold: float = 2.0, drift_threshold: float = 3.0
+    ): ...
+    def update(self, x: bool) -> tuple[bool, bool]: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    @property
+    def warning_detected(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsDDM": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsDDM": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsDDM": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsDDM", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsDDM": ...
+    def __copy__(self) -> "RsDDM": ...
+    def __deepcopy__(self, memo) -> "RsDDM": ...
+
+class RsEDDM:
+    def __init__(self, warm_start: int = 30, alpha: float = 0.95, beta: float = 0.9): ...
+    def update(self, x: bool) -> tuple[bool, bool]: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    @property
+    def warning_detected(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsEDDM": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsEDDM": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsEDDM": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsEDDM", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsEDDM": ...
+    def __copy__(self) -> "RsEDDM": ...
+    def __deepcopy__(self, memo) -> "RsEDDM": ...
+
+class RsKSWIN:
+    def __init__(
+        self,
+        alpha: float = 0.005,
+        window_size: int = 100,
+        stat_size: int = 30,
+        seed: int | None = None,
+    ): ...
+    def update(self, x: float) -> tuple[bool, bool]: ...
+    @property
+    def statistic(self) -> float: ...
+    @property
+    def p_value(self) -> float: ...
+    @property
+    def drift_detected(self) -> bool: ...
+    def __len__(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsKSWIN": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsKSWIN": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsKSWIN": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsKSWIN", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsKSWIN": ...
+    def __copy__(self) -> "RsKSWIN": ...
+    def __deepcopy__(self, memo) -> "RsKSWIN": ...
+
+class RsCircularStats:
+    def __init__(self, degrees: bool = False): ...
+    def update(self, x: float): ...
+    def revert(self, x: float): ...
+    @property
+    def mean(self) -> float: ...
+    @property
+    def var(self) -> float: ...
+    @property
+    def std(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsCircularStats": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsCircularStats": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsCircularStats": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsCircularStats", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsCircularStats": ...
+    def __copy__(self) -> "RsCircularStats": ...
+    def __deepcopy__(self, memo) -> "RsCircularStats": ...
+
+class RsRollingCircularStats:
+    def __init__(self, window_size: int, degrees: bool = False): ...
+    def update(self, x: float): ...
+    @property
+    def mean(self) -> float: ...
+    @property
+    def var(self) -> float: ...
+    @property
+    def std(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def window(self) -> np.ndarray: ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingCircularStats": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingCircularStats": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingCircularStats": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingCircularStats", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingCircularStats": ...
+    def __copy__(self) -> "RsRollingCircularStats": ...
+    def __deepcopy__(self, memo) -> "RsRollingCircularStats": ...
+
+class RsPercentileRank:
+    def __init__(self, compression: float = 100.0): ...
+    def update(self, x: float): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPercentileRank": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPercentileRank": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPercentileRank": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPercentileRank", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPercentileRank": ...
+    def __copy__(self) -> "RsPercentileRank": ...
+    def __deepcopy__(self, memo) -> "RsPercentileRank": ...
+
+class RsMAD:
+    def __init__(self, compression: float = 100.0): ...
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    def median(self) -> float: ...
+    @property
+    def n(self) -> float: ...
+    def merge(self, other: "RsMAD"): ...
+    def __iadd__(self, other: "RsMAD") -> "RsMAD": ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsMAD": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsMAD": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsMAD": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsMAD", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsMAD": ...
+    def __copy__(self) -> "RsMAD": ...
+    def __deepcopy__(self, memo) -> "RsMAD": ...
+
+class RsRollingPercentileRank:
+    def __init__(self, window_size: int): ...
+    # Every value of a window counts once, so w can only be 1.
+    def update(self, x: float, w: float = 1.0): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def window(self) -> np.ndarray: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRollingPercentileRank": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRollingPercentileRank": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRollingPercentileRank": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRollingPercentileRank", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRollingPercentileRank": ...
+    def __copy__(self) -> "RsRollingPercentileRank": ...
+    def __deepcopy__(self, memo) -> "RsRollingPercentileRank": ...
+
+class RsShift:
+    def __init__(self, amount: int = 1, fill_value: float = math.nan): ...
+    def update(self, x: float): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def amount(self) -> int: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsShift": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsShift": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsShift": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsShift", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsShift": ...
+    def __copy__(self) -> "RsShift": ...
+    def __deepcopy__(self, memo) -> "RsShift": ...
+
+class RsAutoCorr:
+    def __init__(self, lag: int): ...
+    def update(self, x: float): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def lag(self) -> int: ...
+    def revert(self, x: float): ...
+    def update_many(self, values) -> None: ...
+    def transform_many(self, values) -> np.ndarray: ...
+    def __repr__(self) -> str: ...
+    def snapshot(self) -> "RsFrozenStat": ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsAutoCorr": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsAutoCorr": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsAutoCorr": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsAutoCorr", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsAutoCorr": ...
+    def __copy__(self) -> "RsAutoCorr": ...
+    def __deepcopy__(self, memo) -> "RsAutoCorr": ...
+
+class RsTimeEWMean:
+    def __init__(self, halflife: float): ...
+    def update(self, x: float, t: float): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsTimeEWMean": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsTimeEWMean": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsTimeEWMean": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsTimeEWMean", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsTimeEWMean": ...
+    def __copy__(self) -> "RsTimeEWMean": ...
+    def __deepcopy__(self, memo) -> "RsTimeEWMean": ...
+
+class RsTimeEWVar:
+    def __init__(self, halflife: float): ...
+    def update(self, x: float, t: float): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def mean(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsTimeEWVar": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsTimeEWVar": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsTimeEWVar": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsTimeEWVar", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsTimeEWVar": ...
+    def __copy__(self) -> "RsTimeEWVar": ...
+    def __deepcopy__(self, memo) -> "RsTimeEWVar": ...
+
+class RsDecayedTopK:
+    def __init__(self, capacity: int, halflife: float): ...
+    def update(self, key: int | str, w: float = 1.0, t: float | None = None): ...
+    def get(self, key: int | str) -> float: ...
+    def top(self, k: int | None = None) -> list[tuple[int | str, float, float]]: ...
+    def __len__(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsDecayedTopK": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsDecayedTopK": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsDecayedTopK": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsDecayedTopK", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsDecayedTopK": ...
+    def __copy__(self) -> "RsDecayedTopK": ...
+    def __deepcopy__(self, memo) -> "RsDecayedTopK": ...
+
+class RsSpaceSaving:
+    def __init__(self, k: int): ...
+    def update(self, key: int | str, w: float = 1.0): ...
+    def get(self, key: int | str) -> float: ...
+    def error(self, key: int | str) -> float: ...
+    def most_common(self, n: int | None = None) -> list[tuple[int | str, float]]: ...
+    def merge(self, other: "RsSpaceSaving"): ...
+    def __iadd__(self, other: "RsSpaceSaving") -> "RsSpaceSaving": ...
+    @property
+    def total(self) -> float: ...
+    @property
+    def k(self) -> int: ...
+    def __len__(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSpaceSaving": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSpaceSaving": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSpaceSaving": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSpaceSaving", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSpaceSaving": ...
+    def __copy__(self) -> "RsSpaceSaving": ...
+    def __deepcopy__(self, memo) -> "RsSpaceSaving": ...
+
+class RsDistinctCount:
+    def __init__(self, threshold: int = 1000, precision: int = 12): ...
+    def update(self, item: int | str): ...
+    def get(self) -> int: ...
+    @property
+    def is_exact(self) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsDistinctCount": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsDistinctCount": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsDistinctCount": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsDistinctCount", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsDistinctCount": ...
+    def __copy__(self) -> "RsDistinctCount": ...
+    def __deepcopy__(self, memo) -> "RsDistinctCount": ...
+
+class RsHyperLogLog:
+    def __init__(self, precision: int = 12): ...
+    def update(self, item: int | str): ...
+    def get(self) -> int: ...
+    def merge(self, other: "RsHyperLogLog"): ...
+    def __iadd__(self, other: "RsHyperLogLog") -> "RsHyperLogLog": ...
+    @property
+    def precision(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsHyperLogLog": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsHyperLogLog": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsHyperLogLog": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsHyperLogLog", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsHyperLogLog": ...
+    def __copy__(self) -> "RsHyperLogLog": ...
+    def __deepcopy__(self, memo) -> "RsHyperLogLog": ...
+
+class RsHistogram:
+    def __init__(self, max_bins: int = 256): ...
     def update(self, x: float): ...
+    def cdf(self, x: float) -> float: ...
+    def iter_bins(self) -> list[tuple[float, float, int]]: ...
+    def merge(self, other: "RsHistogram"): ...
+    def __iadd__(self, other: "RsHistogram") -> "RsHistogram": ...
+    @property
+    def n(self) -> int: ...
+    def __len__(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsHistogram": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsHistogram": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsHistogram": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsHistogram", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsHistogram": ...
+    def __copy__(self) -> "RsHistogram": ...
+    def __deepcopy__(self, memo) -> "RsHistogram": ...
+
+class RsCountMin:
+    def __init__(self, width: int, depth: int, seed: int | None = None): ...
+    def update(self, key: int | str, w: float = 1.0): ...
+    def query(self, key: int | str) -> float: ...
+    def merge(self, other: "RsCountMin"): ...
+    def __iadd__(self, other: "RsCountMin") -> "RsCountMin": ...
+    @property
+    def total(self) -> float: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def depth(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsCountMin": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsCountMin": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsCountMin": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsCountMin", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsCountMin": ...
+    def __copy__(self) -> "RsCountMin": ...
+    def __deepcopy__(self, memo) -> "RsCountMin": ...
+
+class RsExpHistogram:
+    def __init__(self, window_size: int, eps: float): ...
+    def update(self, bit: bool): ...
+    def get(self) -> float: ...
+    @property
+    def window_size(self) -> int: ...
+    @property
+    def n_buckets(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsExpHistogram": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsExpHistogram": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsExpHistogram": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsExpHistogram", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsExpHistogram": ...
+    def __copy__(self) -> "RsExpHistogram": ...
+    def __deepcopy__(self, memo) -> "RsExpHistogram": ...
+
+class RsCounter:
+    def __init__(self, max_size: int | None = None, fading: float = 1.0): ...
+    def update(self, key: int | str, w: float = 1.0): ...
+    def get(self, key: int | str) -> float: ...
+    def frequency(self, key: int | str) -> float: ...
+    def most_common(self, n: int | None = None) -> list[tuple[int | str, float]]: ...
+    def mode(self) -> int | str | None: ...
+    def entropy(self) -> float: ...
+    @property
+    def total(self) -> float: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, key: int | str) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsCounter": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsCounter": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsCounter": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsCounter", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsCounter": ...
+    def __copy__(self) -> "RsCounter": ...
+    def __deepcopy__(self, memo) -> "RsCounter": ...
+
+class RsMode:
+    def __init__(
+        self,
+        ties: str = "first",
+        seed: int | None = None,
+        max_size: int | None = None,
+        fading: float = 1.0,
+    ): ...
+    def update(self, key: int | str, w: float = 1.0): ...
+    def get(self) -> int | str | None: ...
+    def modes(self) -> list[int | str]: ...
+    @property
+    def ties(self) -> str: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsMode": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsMode": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsMode": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsMode", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsMode": ...
+    def __copy__(self) -> "RsMode": ...
+    def __deepcopy__(self, memo) -> "RsMode": ...
+
+class RsStatPipeline:
+    def __init__(self, stats: list): ...
+    def update(self, x: float) -> list[float]: ...
+    def get(self) -> list[float]: ...
+    def update_many(self, values): ...
+    @property
+    def stats(self) -> list: ...
+    def __len__(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsStatPipeline": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsStatPipeline": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsStatPipeline": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsStatPipeline", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsStatPipeline": ...
+    def __copy__(self) -> "RsStatPipeline": ...
+    def __deepcopy__(self, memo) -> "RsStatPipeline": ...
+
+class RsGroupedStat:
+    def __init__(self, stat_factory): ...
+    def update(self, key: int | str, x: float): ...
+    def get(self, key: int | str) -> float: ...
+    def keys(self) -> list[int | str]: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, key: int | str) -> bool: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsGroupedStat": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsGroupedStat": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsGroupedStat": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsGroupedStat", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsGroupedStat": ...
+    def __copy__(self) -> "RsGroupedStat": ...
+    def __deepcopy__(self, memo) -> "RsGroupedStat": ...
+
+class RsColumnStats:
+    def __init__(self, stat, parallel: bool = False): ...
+    def update(self, x: list[float]): ...
+    def update_many(self, x): ...
+    def get(self) -> np.ndarray: ...
+    @property
+    def n_columns(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsColumnStats": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsColumnStats": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsColumnStats": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsColumnStats", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsColumnStats": ...
+    def __copy__(self) -> "RsColumnStats": ...
+    def __deepcopy__(self, memo) -> "RsColumnStats": ...
+
+class RsDiff:
+    def __init__(self, stat_a, stat_b): ...
+    def update(self, x: float) -> float: ...
     def get(self) -> float: ...
+    @property
+    def stats(self) -> tuple: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsDiff": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsDiff": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsDiff": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsDiff", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsDiff": ...
+    def __copy__(self) -> "RsDiff": ...
+    def __deepcopy__(self, memo) -> "RsDiff": ...
+
+class RsRatio:
+    def __init__(self, stat_a, stat_b): ...
+    def update(self, x: float) -> float: ...
+    def get(self) -> float: ...
+    @property
+    def stats(self) -> tuple: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRatio": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRatio": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRatio": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRatio", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRatio": ...
+    def __copy__(self) -> "RsRatio": ...
+    def __deepcopy__(self, memo) -> "RsRatio": ...
+
+class RsSkipMissing:
+    def __init__(self, stat, placeholders: bool = False): ...
+    def update(self, x: float | None): ...
+    def get(self) -> float: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def n_missing(self) -> int: ...
+    @property
+    def stat(self): ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSkipMissing": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSkipMissing": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSkipMissing": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSkipMissing", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSkipMissing": ...
+    def __copy__(self) -> "RsSkipMissing": ...
+    def __deepcopy__(self, memo) -> "RsSkipMissing": ...
+
+class RsFrozenStat:
+    def __init__(self, stat): ...
+    def get(self) -> float: ...
+    def thaw(self): ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsFrozenStat": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsFrozenStat": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsFrozenStat": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsFrozenStat", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsFrozenStat": ...
+    def __copy__(self) -> "RsFrozenStat": ...
+    def __deepcopy__(self, memo) -> "RsFrozenStat": ...
+
+class RsRng:
+    def __init__(self, seed: int | None = None): ...
+    def random(self) -> float: ...
+    def randrange(self, n: int) -> int: ...
+    def poisson(
+        self, n_models: int, lam: float = 1.0, n_samples: int | None = None
+    ) -> np.ndarray: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRng": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRng": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRng": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRng", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRng": ...
+    def __copy__(self) -> "RsRng": ...
+    def __deepcopy__(self, memo) -> "RsRng": ...
+
+class RsIsotonicCalibrator:
+    def __init__(self, max_blocks: int = 100): ...
+    def learn_one(self, p: float, y: bool): ...
+    def transform_one(self, p: float) -> float: ...
+    @property
+    def n_blocks(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsIsotonicCalibrator": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsIsotonicCalibrator": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsIsotonicCalibrator": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsIsotonicCalibrator", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsIsotonicCalibrator": ...
+    def __copy__(self) -> "RsIsotonicCalibrator": ...
+    def __deepcopy__(self, memo) -> "RsIsotonicCalibrator": ...
+
+class RsPlattCalibrator:
+    def __init__(self, lr: float = 0.01): ...
+    def learn_one(self, p: float, y: bool): ...
+    def transform_one(self, p: float) -> float: ...
+    @property
+    def coefficients(self) -> tuple[float, float]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsPlattCalibrator": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsPlattCalibrator": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsPlattCalibrator": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsPlattCalibrator", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsPlattCalibrator": ...
+    def __copy__(self) -> "RsPlattCalibrator": ...
+    def __deepcopy__(self, memo) -> "RsPlattCalibrator": ...
+
+def update_all(stats: list, values) -> None: ...
+def set_seed(seed: int | None = None) -> None: ...
+def bench(n: int = 100_000, seed: int = 42) -> list[dict]: ...
+def bench_distances(
+    n_samples: int = 10_000, n_features: int = 100, repeat: int = 10, seed: int = 42
+) -> list[dict]: ...
+def sigmoid(x): ...
+def softmax(x): ...
+def logsumexp(x) -> float: ...
+def clip(x, lower: float, upper: float): ...
+def majority_vote(predictions, weights=None, temperature: float | None = None): ...
+def average_proba(probas, weights=None, temperature: float | None = None) -> dict: ...
+def average(predictions, weights=None, temperature: float | None = None) -> float: ...
+def cholesky_update(l, x, downdate: bool = False) -> np.ndarray: ...
+def qr_add_row(r, row) -> np.ndarray: ...
+def solve_triangular(a, b, lower: bool = False) -> np.ndarray: ...
+def get_distance(a, b, metric: str = "sqeuclidean", p: float = 2.0) -> float: ...
+def get_1_to_n_distances(
+    x, samples, metric: str = "sqeuclidean", p: float = 2.0, n_jobs: int = 1
+) -> np.ndarray: ...
+def evaluate_progressive(model, x, y, metric, arrivals=None, moments=None): ...
+
+class RsCsvReader:
+    def __iter__(self) -> "RsCsvReader": ...
+    def __next__(self) -> tuple[dict, object]: ...
+
+def iter_csv(
+    path,
+    target: str | list[str] | None = None,
+    converters: dict | None = None,
+    drop: list[str] | None = None,
+    drop_nones: bool = False,
+    delimiter: str = ",",
+) -> RsCsvReader: ...
+
+class RsArffReader:
+    def __iter__(self) -> "RsArffReader": ...
+    def __next__(self) -> tuple[dict, object]: ...
+
+def iter_arff(path, target: str | list[str] | None = None) -> RsArffReader: ...
+
+class RsLibsvmReader:
+    def __iter__(self) -> "RsLibsvmReader": ...
+    def __next__(self) -> tuple[dict[str, float], object]: ...
+
+def iter_libsvm(path, target_type=None) -> RsLibsvmReader: ...
+
+class RsShuffle:
+    def __iter__(self) -> "RsShuffle": ...
+    def __next__(self): ...
+
+def shuffle(iterable, buffer_size: int, seed: int | None = None) -> RsShuffle: ...
+
+class RsAnomalyInjector:
+    @property
+    def n_anomalies(self) -> int: ...
+    def __iter__(self) -> "RsAnomalyInjector": ...
+    def __next__(self) -> tuple[dict, bool]: ...
+
+def inject_anomalies(
+    stream,
+    rate: float,
+    kinds: list[str] | None = None,
+    magnitude: float = 5.0,
+    duration: int = 20,
+    seed: int | None = None,
+) -> RsAnomalyInjector: ...
+
+class RsSuccessiveHalving:
+    def __init__(
+        self,
+        models: list[RsIsotonicCalibrator | RsPlattCalibrator],
+        metric: RsLogLoss | RsAccuracy,
+        budget: int,
+        eta: float = 2.0,
+    ): ...
+    def learn_one(self, x: float, y: bool): ...
+    def learn_many(self, x, y): ...
+    def predict_proba_one(self, x: float) -> float: ...
+    @property
+    def best_model(self) -> RsIsotonicCalibrator | RsPlattCalibrator: ...
+    @property
+    def best(self) -> int: ...
+    @property
+    def survivors(self) -> list[int]: ...
+    def scores(self) -> list[float]: ...
+    @property
+    def n_rungs(self) -> int: ...
+    @property
+    def budget_used(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSuccessiveHalving": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSuccessiveHalving": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSuccessiveHalving": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSuccessiveHalving", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSuccessiveHalving": ...
+    def __copy__(self) -> "RsSuccessiveHalving": ...
+    def __deepcopy__(self, memo) -> "RsSuccessiveHalving": ...
+
+class RsBanditSelection:
+    def __init__(
+        self,
+        models: list[RsIsotonicCalibrator | RsPlattCalibrator],
+        metric: RsLogLoss | RsAccuracy,
+        policy: str = "epsilon_greedy",
+        epsilon: float = 0.1,
+        decay: float = 0.0,
+        delta: float = 1.0,
+        burn_in: int = 0,
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: float, y: bool): ...
+    def learn_many(self, x, y): ...
+    def predict_proba_one(self, x: float) -> float: ...
+    @property
+    def best_model(self) -> RsIsotonicCalibrator | RsPlattCalibrator | None: ...
+    @property
+    def best(self) -> int | None: ...
+    @property
+    def counts(self) -> list[int]: ...
+    def scores(self) -> list[float]: ...
+    @property
+    def policy(self) -> str: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsBanditSelection": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsBanditSelection": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsBanditSelection": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsBanditSelection", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsBanditSelection": ...
+    def __copy__(self) -> "RsBanditSelection": ...
+    def __deepcopy__(self, memo) -> "RsBanditSelection": ...
+
+class RsLogisticRegression:
+    def __init__(self, lr: float = 0.01, l2: float = 0.0): ...
+    def learn_one(self, x: dict[str, float], y: bool): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict[bool, float]: ...
+    def predict_one(self, x: dict[str, float]) -> bool: ...
+    @property
+    def weights(self) -> dict[str, float]: ...
+    @property
+    def intercept(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLogisticRegression": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLogisticRegression": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLogisticRegression": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLogisticRegression", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLogisticRegression": ...
+    def __copy__(self) -> "RsLogisticRegression": ...
+    def __deepcopy__(self, memo) -> "RsLogisticRegression": ...
+
+class RsLinearRegression:
+    def __init__(self, lr: float = 0.01, l2: float = 0.0): ...
+    def learn_one(self, x: dict[str, float], y: float): ...
+    def predict_one(self, x: dict[str, float]) -> float: ...
+    @property
+    def weights(self) -> dict[str, float]: ...
+    @property
+    def intercept(self) -> float: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLinearRegression": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLinearRegression": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLinearRegression": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLinearRegression", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLinearRegression": ...
+    def __copy__(self) -> "RsLinearRegression": ...
+    def __deepcopy__(self, memo) -> "RsLinearRegression": ...
+
+class RsOutputCodeClassifier:
+    def __init__(
+        self,
+        classifier: RsLogisticRegression,
+        code_size: int,
+        coding_method: str = "random",
+        decoding: str = "soft",
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: dict[str, float], y: int | str): ...
+    def predict_one(self, x: dict[str, float]) -> int | str | None: ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict[int | str, float]: ...
+    def distances(self, x: dict[str, float]) -> dict[int | str, float]: ...
+    @property
+    def code_book(self) -> dict[int | str, list[int]]: ...
+    @property
+    def code_size(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsOutputCodeClassifier": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsOutputCodeClassifier": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsOutputCodeClassifier": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsOutputCodeClassifier", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsOutputCodeClassifier": ...
+    def __copy__(self) -> "RsOutputCodeClassifier": ...
+    def __deepcopy__(self, memo) -> "RsOutputCodeClassifier": ...
+
+class RsClassifierChain:
+    def __init__(self, model: RsLogisticRegression, order: list[str] | None = None): ...
+    def learn_one(self, x: dict[str, float], y: dict[str, bool]): ...
+    def predict_one(self, x: dict[str, float]) -> dict[str, bool]: ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict[str, dict[bool, float]]: ...
+    @property
+    def order(self) -> list[str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsClassifierChain": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsClassifierChain": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsClassifierChain": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsClassifierChain", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsClassifierChain": ...
+    def __copy__(self) -> "RsClassifierChain": ...
+    def __deepcopy__(self, memo) -> "RsClassifierChain": ...
+
+class RsRegressorChain:
+    def __init__(self, model: RsLinearRegression, order: list[str] | None = None): ...
+    def learn_one(self, x: dict[str, float], y: dict[str, float]): ...
+    def predict_one(self, x: dict[str, float]) -> dict[str, float]: ...
+    @property
+    def order(self) -> list[str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRegressorChain": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRegressorChain": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRegressorChain": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRegressorChain", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRegressorChain": ...
+    def __copy__(self) -> "RsRegressorChain": ...
+    def __deepcopy__(self, memo) -> "RsRegressorChain": ...
+
+class RsRandomUnderSampler:
+    def __init__(
+        self,
+        classifier: RsLogisticRegression | RsOutputCodeClassifier,
+        desired_dist: dict,
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: dict[str, float], y): ...
+    def predict_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict: ...
+    @property
+    def classifier(self) -> RsLogisticRegression | RsOutputCodeClassifier: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRandomUnderSampler": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRandomUnderSampler": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRandomUnderSampler": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRandomUnderSampler", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRandomUnderSampler": ...
+    def __copy__(self) -> "RsRandomUnderSampler": ...
+    def __deepcopy__(self, memo) -> "RsRandomUnderSampler": ...
+
+class RsRandomOverSampler:
+    def __init__(
+        self,
+        classifier: RsLogisticRegression | RsOutputCodeClassifier,
+        desired_dist: dict,
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: dict[str, float], y): ...
+    def predict_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict: ...
+    @property
+    def classifier(self) -> RsLogisticRegression | RsOutputCodeClassifier: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRandomOverSampler": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRandomOverSampler": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRandomOverSampler": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRandomOverSampler", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRandomOverSampler": ...
+    def __copy__(self) -> "RsRandomOverSampler": ...
+    def __deepcopy__(self, memo) -> "RsRandomOverSampler": ...
+
+class RsHardSamplingClassifier:
+    def __init__(
+        self,
+        classifier: RsLogisticRegression | RsOutputCodeClassifier,
+        size: int,
+        p: float,
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: dict[str, float], y): ...
+    def predict_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict: ...
+    @property
+    def classifier(self) -> RsLogisticRegression | RsOutputCodeClassifier: ...
+    @property
+    def buffer(self) -> list[tuple[float, dict[str, float], object]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsHardSamplingClassifier": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsHardSamplingClassifier": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsHardSamplingClassifier": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsHardSamplingClassifier", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsHardSamplingClassifier": ...
+    def __copy__(self) -> "RsHardSamplingClassifier": ...
+    def __deepcopy__(self, memo) -> "RsHardSamplingClassifier": ...
+
+class RsLeveragingBaggingClassifier:
+    def __init__(
+        self,
+        model: RsLogisticRegression | RsOutputCodeClassifier,
+        n_models: int = 10,
+        w: float = 6.0,
+        adwin_delta: float = 0.002,
+        bagging_method: str = "bag",
+        seed: int | None = None,
+        parallel: bool = False,
+    ): ...
+    def learn_one(self, x: dict[str, float], y): ...
+    def predict_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict: ...
+    @property
+    def models(self) -> list[RsLogisticRegression | RsOutputCodeClassifier]: ...
+    @property
+    def errors(self) -> list[float]: ...
+    @property
+    def n_replacements(self) -> int: ...
+    @property
+    def n_models(self) -> int: ...
+    @property
+    def bagging_method(self) -> str: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLeveragingBaggingClassifier": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLeveragingBaggingClassifier": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLeveragingBaggingClassifier": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLeveragingBaggingClassifier", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLeveragingBaggingClassifier": ...
+    def __copy__(self) -> "RsLeveragingBaggingClassifier": ...
+    def __deepcopy__(self, memo) -> "RsLeveragingBaggingClassifier": ...
+
+class RsADWINBaggingClassifier:
+    def __init__(
+        self,
+        model: RsLogisticRegression | RsOutputCodeClassifier,
+        n_models: int = 10,
+        adwin_delta: float = 0.002,
+        seed: int | None = None,
+        parallel: bool = False,
+    ): ...
+    def learn_one(self, x: dict[str, float], y): ...
+    def predict_one(self, x: dict[str, float]): ...
+    def predict_proba_one(self, x: dict[str, float]) -> dict: ...
+    @property
+    def models(self) -> list[RsLogisticRegression | RsOutputCodeClassifier]: ...
+    @property
+    def errors(self) -> list[float]: ...
+    @property
+    def n_replacements(self) -> int: ...
+    @property
+    def n_models(self) -> int: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsADWINBaggingClassifier": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsADWINBaggingClassifier": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsADWINBaggingClassifier": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsADWINBaggingClassifier", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsADWINBaggingClassifier": ...
+    def __copy__(self) -> "RsADWINBaggingClassifier": ...
+    def __deepcopy__(self, memo) -> "RsADWINBaggingClassifier": ...
+
+class RsSEA:
+    def __init__(self, variant: int = 0, noise: float = 0.0, seed: int | None = None): ...
+    def __iter__(self) -> "RsSEA": ...
+    def __next__(self) -> tuple[dict[int, float], bool]: ...
+    def take(self, n: int) -> list[tuple[dict[int, float], bool]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSEA": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSEA": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSEA": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSEA", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSEA": ...
+    def __copy__(self) -> "RsSEA": ...
+    def __deepcopy__(self, memo) -> "RsSEA": ...
+
+class RsHyperplane:
+    def __init__(
+        self,
+        seed: int | None = None,
+        n_features: int = 10,
+        n_drift_features: int = 2,
+        mag_change: float = 0.0,
+        noise_percentage: float = 0.05,
+        sigma: float = 0.1,
+    ): ...
+    def __iter__(self) -> "RsHyperplane": ...
+    def __next__(self) -> tuple[dict[int, float], int]: ...
+    def take(self, n: int) -> list[tuple[dict[int, float], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsHyperplane": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsHyperplane": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsHyperplane": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsHyperplane", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsHyperplane": ...
+    def __copy__(self) -> "RsHyperplane": ...
+    def __deepcopy__(self, memo) -> "RsHyperplane": ...
+
+class RsAgrawal:
+    def __init__(
+        self,
+        classification_function: int = 0,
+        seed: int | None = None,
+        balance_classes: bool = False,
+        perturbation: float = 0.0,
+    ): ...
+    def __iter__(self) -> "RsAgrawal": ...
+    def __next__(self) -> tuple[dict[str, float | int], int]: ...
+    def take(self, n: int) -> list[tuple[dict[str, float | int], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsAgrawal": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsAgrawal": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsAgrawal": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsAgrawal", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsAgrawal": ...
+    def __copy__(self) -> "RsAgrawal": ...
+    def __deepcopy__(self, memo) -> "RsAgrawal": ...
+
+class RsRandomRBF:
+    def __init__(
+        self,
+        seed_model: int | None = None,
+        seed_sample: int | None = None,
+        n_classes: int = 2,
+        n_features: int = 10,
+        n_centroids: int = 50,
+        change_speed: float = 0.0,
+        n_drift_centroids: int = 50,
+    ): ...
+    def __iter__(self) -> "RsRandomRBF": ...
+    def __next__(self) -> tuple[dict[int, float], int]: ...
+    def take(self, n: int) -> list[tuple[dict[int, float], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRandomRBF": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRandomRBF": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRandomRBF": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRandomRBF", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRandomRBF": ...
+    def __copy__(self) -> "RsRandomRBF": ...
+    def __deepcopy__(self, memo) -> "RsRandomRBF": ...
+
+class RsRandomTree:
+    def __init__(
+        self,
+        seed_tree: int | None = None,
+        seed_sample: int | None = None,
+        n_classes: int = 2,
+        n_num_features: int = 5,
+        n_cat_features: int = 5,
+        n_categories_per_feature: int = 5,
+        max_tree_depth: int = 5,
+        first_leaf_level: int = 3,
+        fraction_leaves_per_level: float = 0.15,
+    ): ...
+    @property
+    def depth(self) -> int: ...
+    def __iter__(self) -> "RsRandomTree": ...
+    def __next__(self) -> tuple[dict[str, float | int], int]: ...
+    def take(self, n: int) -> list[tuple[dict[str, float | int], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsRandomTree": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsRandomTree": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsRandomTree": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsRandomTree", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsRandomTree": ...
+    def __copy__(self) -> "RsRandomTree": ...
+    def __deepcopy__(self, memo) -> "RsRandomTree": ...
+
+class RsLED:
+    def __init__(
+        self,
+        seed: int | None = None,
+        noise_percentage: float = 0.0,
+        irrelevant_features: bool = False,
+        n_drift_features: int = 0,
+    ): ...
+    def __iter__(self) -> "RsLED": ...
+    def __next__(self) -> tuple[dict[int, int], int]: ...
+    def take(self, n: int) -> list[tuple[dict[int, int], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLED": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLED": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLED": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLED", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLED": ...
+    def __copy__(self) -> "RsLED": ...
+    def __deepcopy__(self, memo) -> "RsLED": ...
+
+class RsSTAGGER:
+    def __init__(
+        self,
+        classification_function: int = 0,
+        seed: int | None = None,
+        balance_classes: bool = False,
+        noise: float = 0.0,
+    ): ...
+    @property
+    def classification_function(self) -> int: ...
+    def generate_drift(self): ...
+    def __iter__(self) -> "RsSTAGGER": ...
+    def __next__(self) -> tuple[dict[str, int], int]: ...
+    def take(self, n: int) -> list[tuple[dict[str, int], int]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSTAGGER": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSTAGGER": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSTAGGER": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSTAGGER", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSTAGGER": ...
+    def __copy__(self) -> "RsSTAGGER": ...
+    def __deepcopy__(self, memo) -> "RsSTAGGER": ...
+
+_Generator = (
+    RsSEA
+    | RsHyperplane
+    | RsAgrawal
+    | RsRandomRBF
+    | RsRandomTree
+    | RsLED
+    | RsSTAGGER
+    | "RsConceptDriftStream"
+)
+
+class RsConceptDriftStream:
+    def __init__(
+        self,
+        stream: _Generator | None = None,
+        drift_stream: _Generator | None = None,
+        position: int = 5000,
+        width: int = 1000,
+        seed: int | None = None,
+        alpha: float | None = None,
+    ): ...
+    @property
+    def width(self) -> int: ...
+    def drift_probability(self) -> float: ...
+    def __iter__(self) -> "RsConceptDriftStream": ...
+    def __next__(self) -> tuple[dict, int | bool]: ...
+    def take(self, n: int) -> list[tuple[dict, int | bool]]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsConceptDriftStream": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsConceptDriftStream": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsConceptDriftStream": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsConceptDriftStream", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsConceptDriftStream": ...
+    def __copy__(self) -> "RsConceptDriftStream": ...
+    def __deepcopy__(self, memo) -> "RsConceptDriftStream": ...
+
+class RsSAMKNN:
+    def __init__(
+        self,
+        n_neighbors: int = 5,
+        distance_weighting: bool = True,
+        window_size: int = 5000,
+        ltm_size: float = 0.4,
+        min_stm_size: int = 50,
+        stm_aprox_adaption: bool | None = True,
+        use_ltm: bool = True,
+        seed: int | None = None,
+    ): ...
+    def learn_one(self, x: dict[str, float], y: int | str): ...
+    def predict_one(self, x: dict[str, float]) -> int | str | None: ...
+    @property
+    def stm_size(self) -> int: ...
+    @property
+    def ltm_size(self) -> int: ...
+    @property
+    def stm_labels(self) -> list[int | str]: ...
+    @property
+    def ltm_labels(self) -> list[int | str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsSAMKNN": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsSAMKNN": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsSAMKNN": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsSAMKNN", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsSAMKNN": ...
+    def __copy__(self) -> "RsSAMKNN": ...
+    def __deepcopy__(self, memo) -> "RsSAMKNN": ...
+
+class RsWindow:
+    def __init__(self, window_size: int, metric: str = "euclidean", p: float = 2.0): ...
+    def append(self, x: dict[str, float], y: int | str) -> int | str | None: ...
+    def search(self, x: dict[str, float], k: int) -> tuple[list[int], list[float]]: ...
+    def labels(self) -> list[int | str]: ...
+    def clear(self): ...
+    @property
+    def window_size(self) -> int: ...
+    def __len__(self) -> int: ...
+    def __getitem__(self, i: int) -> tuple[dict[str, float], int | str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsWindow": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsWindow": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsWindow": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsWindow", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsWindow": ...
+    def __copy__(self) -> "RsWindow": ...
+    def __deepcopy__(self, memo) -> "RsWindow": ...
+
+class RsKDTree:
+    def __init__(self, metric: str = "euclidean", p: float = 2.0): ...
+    def insert(self, x: dict[str, float], y: int | str): ...
+    def remove_oldest(self) -> int | str | None: ...
+    def query(self, x: dict[str, float], k: int) -> tuple[list[int], list[float]]: ...
+    def labels(self) -> list[int | str]: ...
+    def __len__(self) -> int: ...
+    def __getitem__(self, i: int) -> tuple[dict[str, float], int | str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsKDTree": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsKDTree": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsKDTree": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsKDTree", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsKDTree": ...
+    def __copy__(self) -> "RsKDTree": ...
+    def __deepcopy__(self, memo) -> "RsKDTree": ...
+
+class RsLSHIndex:
+    def __init__(
+        self,
+        window_size: int,
+        n_tables: int = 10,
+        n_hashes: int = 8,
+        bucket_width: float = 4.0,
+        metric: str = "euclidean",
+        seed: int | None = None,
+    ): ...
+    def insert(self, x: dict[str, float], y: int | str) -> int | str | None: ...
+    def query(
+        self, x: dict[str, float], k: int, probes: int = 0
+    ) -> tuple[list[int], list[float]]: ...
+    def labels(self) -> list[int | str]: ...
+    def __len__(self) -> int: ...
+    def __getitem__(self, i: int) -> tuple[dict[str, float], int | str]: ...
+    def save(self, path, compress: bool = False): ...
+    @staticmethod
+    def load(path) -> "RsLSHIndex": ...
+    def to_bytes(self, format: str = "bincode") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes, format: str = "bincode") -> "RsLSHIndex": ...
+    def to_dict(self) -> dict: ...
+    @staticmethod
+    def from_dict(data: dict) -> "RsLSHIndex": ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def allclose(self, other: "RsLSHIndex", rtol: float = 1e-5, atol: float = 1e-8) -> bool: ...
+    def __sizeof__(self) -> int: ...
+    def memory_usage(self) -> int: ...
+    def clone(self) -> "RsLSHIndex": ...
+    def __copy__(self) -> "RsLSHIndex": ...
+    def __deepcopy__(self, memo) -> "RsLSHIndex": ...
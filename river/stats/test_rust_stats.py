@@ -0,0 +1,3395 @@
+from __future__ import annotations
+
+import array
+import bisect
+import collections
+import copy
+import gzip
+import json
+import math
+import pickle
+import random
+import statistics
+import sys
+import threading
+
+import pytest
+
+from river.stats import _rust_stats as rs
+
+
+def univariate(stat):
+    for i in range(20):
+        stat.update(float(i % 7))
+    return stat
+
+
+def load_bindings():
+    yield univariate(rs.RsQuantile(0.3, n_markers=7))
+    yield univariate(rs.RsQuantile(0.9, algorithm="tdigest", compression=20))
+    yield univariate(rs.RsEWMean(span=10))
+    yield univariate(rs.RsEWVar(halflife=3))
+    yield univariate(rs.RsIQR(0.25, 0.75))
+    yield univariate(rs.RsKurtosis(False))
+    yield univariate(rs.RsPeakToPeak())
+    yield univariate(rs.RsSkew(True))
+    yield univariate(rs.RsRollingQuantile(0.5, 10))
+    yield univariate(rs.RsRollingIQR(0.25, 0.75, 10))
+    yield univariate(rs.RsRollingMean(10))
+    yield univariate(rs.RsRollingVar(10, ddof=0))
+    yield univariate(rs.RsKernelDensity(max_centers=8))
+    yield univariate(rs.RsECDF())
+    yield univariate(rs.RsCircularStats(degrees=True))
+    yield univariate(rs.RsRollingCircularStats(5))
+    yield univariate(rs.RsPercentileRank())
+    yield univariate(rs.RsRollingPercentileRank(5))
+    yield univariate(rs.RsMannWhitneyU(5, 5))
+    yield univariate(rs.RsWassersteinDrift(5))
+    yield univariate(rs.RsAdaptiveMean(delta=0.01))
+    yield univariate(rs.RsAdaptiveVar(clock=8))
+    yield univariate(rs.RsShift(3))
+    yield univariate(rs.RsRollingMin(4))
+    yield univariate(rs.RsRollingMax(4))
+    yield univariate(rs.RsRollingPeakToPeak(4))
+    yield univariate(rs.RsMAD(compression=20))
+    yield univariate(rs.RsRollingMAD(5))
+    yield univariate(rs.RsAutoCorr(2))
+    yield univariate(rs.RsStatPipeline([rs.RsEWMean(0.5), rs.RsRollingQuantile(0.5, 5)]))
+    yield univariate(rs.RsDiff(rs.RsEWMean(span=3), rs.RsEWMean(span=10)))
+    yield univariate(rs.RsRatio(rs.RsEWVar(0.5), rs.RsEWMean(0.5)))
+    yield univariate(rs.RsSkipMissing(rs.RsRollingQuantile(0.5, 5), placeholders=True))
+    yield rs.RsFrozenStat(univariate(rs.RsSkew(False)))
+
+    mixture = rs.RsGaussianMixture(2)
+    cov = rs.RsCovMatrix()
+    precision = rs.RsPrecisionMatrix(shrinkage=0.1)
+    pair_cov = rs.RsCov()
+    pair_corr = rs.RsPearsonCorr(ddof=0)
+    for i in range(20):
+        x = {"a": float(i % 3), "b": float(i % 5)}
+        mixture.learn_one(x)
+        cov.update(x)
+        precision.update(x)
+        pair_cov.update(x["a"], x["b"], 1 + i % 2)
+        pair_corr.update(x["a"], x["b"])
+    yield mixture
+    yield cov
+    yield precision
+    yield pair_cov
+    yield pair_corr
+
+    t_test = rs.RsWelchTTest()
+    chi2 = rs.RsChiSquareTest()
+    grouped = rs.RsGroupedStat(rs.RsEWMean(0.5))
+    columns = rs.RsColumnStats(rs.RsRollingQuantile(0.5, 5))
+    top_k = rs.RsDecayedTopK(3, 5.0)
+    distinct = rs.RsDistinctCount(threshold=5)
+    histogram = rs.RsHistogram(max_bins=4)
+    count_min = rs.RsCountMin(8, 3, seed=1)
+    hll = rs.RsHyperLogLog(precision=4)
+    digest = rs.RsTDigest(compression=10)
+    exp_histogram = rs.RsExpHistogram(8, 0.5)
+    space_saving = rs.RsSpaceSaving(3)
+    counter = rs.RsCounter(max_size=3, fading=0.9)
+    mode = rs.RsMode(ties="random", seed=7)
+    multinomial = rs.RsMultinomialDrift(5)
+    adwin = rs.RsADWIN(clock=4)
+    page_hinkley = rs.RsPageHinkley(min_instances=5, threshold=5.0)
+    ddm = rs.RsDDM(warm_start=5)
+    eddm = rs.RsEDDM(warm_start=2)
+    kswin = rs.RsKSWIN(window_size=10, stat_size=4, seed=1)
+    time_mean = rs.RsTimeEWMean(2.0)
+    time_var = rs.RsTimeEWVar(2.0)
+    isotonic = rs.RsIsotonicCalibrator(max_blocks=4)
+    platt = rs.RsPlattCalibrator(lr=0.1)
+    whitener = rs.RsWhitener(refresh_every=7)
+    log_loss = rs.RsLogLoss()
+    accuracy = rs.RsAccuracy()
+    for i in range(20):
+        t_test.update_a(float(i))
+        t_test.update_b(float(i % 4))
+        chi2.update(i % 2, str(i % 3))
+        grouped.update(i % 3, float(i))
+        columns.update([float(i), float(-i)])
+        top_k.update(i % 4)
+        distinct.update(str(i))
+        histogram.update(float(i % 7))
+        count_min.update(str(i % 5), 0.5)
+        hll.update(i % 11)
+        digest.update(float(i % 9), 1 + i % 2)
+        exp_histogram.update(i % 3 != 0)
+        space_saving.update(i % 5 // 2 if i % 2 else str(i % 7))
+        counter.update(i % 5)
+        mode.update(i % 4)
+        multinomial.update(i % 3)
+        adwin.update(float(i // 10))
+        page_hinkley.update(float(i // 10))
+        ddm.update(i % 3 == 0)
+        eddm.update(i % 4 == 0)
+        kswin.update(float(i % 6))
+        time_mean.update(float(i), float(i) / 2)
+        time_var.update(float(i), float(i) / 2)
+        isotonic.learn_one((i % 10) / 10, i % 3 == 0)
+        platt.learn_one((i % 10) / 10, i % 3 == 0)
+        whitener.learn_one({"a": float(i), "b": float(i % 7)})
+        log_loss.update(i % 3 == 0, (i % 10) / 10)
+        accuracy.update(i % 3, i % 2)
+    yield from (t_test, chi2, grouped, columns, top_k, distinct, counter, multinomial)
+    yield from (mode, time_mean, time_var, isotonic, platt, whitener, log_loss, accuracy)
+    yield from (adwin, page_hinkley, ddm, eddm, kswin, histogram, count_min, hll, digest)
+    yield from (exp_histogram, space_saving)
+    yield rs.RsRng(seed=42)
+    drift = rs.RsConceptDriftStream(rs.RsSEA(seed=1), rs.RsSEA(variant=1, seed=2), 5, 2, seed=1)
+    halving = rs.RsSuccessiveHalving(
+        [rs.RsPlattCalibrator(0.1), rs.RsIsotonicCalibrator(), rs.RsPlattCalibrator(0.5)],
+        rs.RsLogLoss(),
+        budget=60,
+    )
+    for i in range(30):
+        halving.learn_one((i % 10) / 10, i % 3 == 0)
+    yield halving
+    paired = rs.RsPairedComparison()
+    for i in range(30):
+        paired.update(i % 7, i % 5)
+    yield paired
+    bandit = rs.RsBanditSelection(
+        [rs.RsPlattCalibrator(0.1), rs.RsIsotonicCalibrator()], rs.RsAccuracy(), "ucb"
+    )
+    for i in range(30):
+        bandit.learn_one((i % 10) / 10, i % 3 == 0)
+    yield bandit
+    logistic = rs.RsLogisticRegression(lr=0.1, l2=0.01)
+    ecoc = rs.RsOutputCodeClassifier(rs.RsLogisticRegression(), 6, "exact", "hamming", seed=1)
+    for i in range(30):
+        logistic.learn_one({"a": float(i % 4), "b": 1.0}, i % 3 == 0)
+        ecoc.learn_one({"a": float(i % 4), "b": 1.0}, str(i % 3))
+    yield from (logistic, ecoc)
+    linear = rs.RsLinearRegression(l2=0.01)
+    classifier_chain = rs.RsClassifierChain(rs.RsLogisticRegression())
+    regressor_chain = rs.RsRegressorChain(rs.RsLinearRegression(), order=["v", "u"])
+    for i in range(30):
+        linear.learn_one({"a": float(i % 4)}, float(i % 5))
+        classifier_chain.learn_one({"a": float(i % 4)}, {"p": i % 2 == 0, "q": i % 3 == 0})
+        regressor_chain.learn_one({"a": float(i % 4)}, {"u": float(i % 5), "v": float(i % 3)})
+    yield from (linear, classifier_chain, regressor_chain)
+    under = rs.RsRandomUnderSampler(rs.RsLogisticRegression(), {False: 0.5, True: 0.5}, seed=1)
+    over = rs.RsRandomOverSampler(ecoc, {"0": 0.4, "1": 0.3, "2": 0.3}, seed=1)
+    for i in range(30):
+        under.learn_one({"a": float(i % 4)}, i % 5 == 0)
+        over.learn_one({"a": float(i % 4)}, str(i % 7 % 3))
+    yield from (under, over)
+    hard = rs.RsHardSamplingClassifier(rs.RsLogisticRegression(), size=5, p=0.3, seed=1)
+    for i in range(30):
+        hard.learn_one({"a": float(i % 4)}, i % 3 == 0)
+    yield hard
+    leveraging = rs.RsLeveragingBaggingClassifier(rs.RsLogisticRegression(), 3, seed=1)
+    adwin_bagging = rs.RsADWINBaggingClassifier(ecoc, 3, seed=1)
+    for i in range(30):
+        leveraging.learn_one({"a": float(i % 4)}, i % 3 == 0)
+        adwin_bagging.learn_one({"a": float(i % 4)}, str(i % 3))
+    yield from (leveraging, adwin_bagging)
+    sam = rs.RsSAMKNN(n_neighbors=3, window_size=40, min_stm_size=5, seed=1)
+    for i in range(100):
+        sam.learn_one({"a": float(i % 9), "b": float(i % 4)}, i % 9 > 4)
+    yield sam
+    window = rs.RsWindow(8, metric="manhattan")
+    for i in range(20):
+        window.append({"a": float(i % 5), "b": float(i)}, str(i % 3))
+    yield window
+    tree = rs.RsKDTree()
+    for i in range(40):
+        tree.insert({"a": float(i % 5), "b": float(i % 7)}, i % 2)
+        if i % 3 == 0:
+            tree.remove_oldest()
+    yield tree
+    index = rs.RsLSHIndex(10, n_tables=3, n_hashes=2, seed=1)
+    for i in range(25):
+        index.insert({"a": float(i % 5), "b": float(i % 7)}, i % 2)
+    yield index
+    rbf = rs.RsRandomRBF(seed_model=1, seed_sample=1, change_speed=0.1, n_drift_centroids=5)
+    tree = rs.RsRandomTree(seed_tree=1, seed_sample=1)
+    led = rs.RsLED(seed=1, noise_percentage=0.1, irrelevant_features=True, n_drift_features=3)
+    generators = (
+        rs.RsSEA(seed=1),
+        rs.RsHyperplane(seed=1),
+        rs.RsAgrawal(seed=1),
+        rbf,
+        tree,
+        led,
+        rs.RsSTAGGER(seed=1),
+    )
+    for generator in (*generators, drift):
+        generator.take(5)
+        yield generator
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+def test_pickling_round_trip(binding):
+    restored = pickle.loads(pickle.dumps(binding))
+    assert restored.__class__ is binding.__class__
+    assert restored == binding
+    assert copy.copy(binding) == binding
+    assert copy.deepcopy(binding) == binding
+    assert binding.clone() == binding
+
+
+def load_non_finite_bindings():
+    # Fresh states holding non-finite floats, e.g. the bounds of an empty digest.
+    yield rs.RsShift()
+    yield rs.RsTDigest()
+    yield rs.RsECDF()
+    yield rs.RsMAD()
+    yield rs.RsPercentileRank()
+    yield rs.RsDDM()
+    yield rs.RsEDDM()
+    yield rs.RsPageHinkley()
+    stat = rs.RsSkipMissing(rs.RsRollingQuantile(0.5, 3), placeholders=True)
+    stat.update(None)
+    yield stat
+
+
+@pytest.mark.parametrize(
+    "binding",
+    [*load_bindings(), *load_non_finite_bindings()],
+    ids=lambda b: b.__class__.__name__,
+)
+def test_dict_round_trip(binding):
+    state = binding.to_dict()
+    assert state["class"] == binding.__class__.__name__
+    assert json.loads(json.dumps(state)) == state
+    assert binding.__class__.from_dict(state) == binding
+    assert binding.__class__.from_bytes(binding.to_bytes("json"), "json") == binding
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+def test_save_and_load_round_trip(binding, tmp_path):
+    path, compressed = tmp_path / "state.bin", tmp_path / "state.bin.gz"
+    binding.save(path)
+    binding.save(str(compressed), compress=True)
+    assert path.read_bytes() == binding.__getstate__()
+    assert gzip.decompress(compressed.read_bytes()) == path.read_bytes()
+    for p in (path, compressed):
+        restored = binding.__class__.load(p)
+        assert restored.__class__ is binding.__class__
+        assert restored == binding
+
+
+def test_loading_a_bad_file_raises(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        rs.RsEWMean.load(tmp_path / "missing.bin")
+    path = tmp_path / "garbage.bin"
+    path.write_bytes(b"not a state")
+    with pytest.raises(ValueError):
+        rs.RsEWMean.load(path)
+    stat = rs.RsRollingMean(5)
+    stat.update(1.0)
+    stat.save(path, compress=True)
+    path.write_bytes(path.read_bytes()[:-8])
+    with pytest.raises(ValueError):
+        rs.RsRollingMean.load(path)
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+@pytest.mark.parametrize("format", ["bincode", "cbor", "msgpack", "json"])
+def test_encodings_round_trip(binding, format):
+    data = binding.to_bytes(format=format)
+    assert binding.__class__.from_bytes(data, format=format) == binding
+    if format == "json":
+        assert json.loads(data) == binding.to_dict()
+    elif format == "bincode":
+        assert data == binding.__getstate__()
+
+
+def test_encodings_check_the_class_and_the_version():
+    stat = rs.RsEWMean(0.5)
+    stat.update(2.0)
+    for format in ["cbor", "msgpack", "json"]:
+        with pytest.raises(ValueError, match="the state is that of RsEWMean, not RsEWVar"):
+            rs.RsEWVar.from_bytes(stat.to_bytes(format), format)
+    document = json.loads(stat.to_bytes("json"))
+    assert (document["class"], document["version"]) == ("RsEWMean", 2)
+    document["version"] = 1
+    with pytest.raises(ValueError, match="only version 2 can be read from this format"):
+        rs.RsEWMean.from_bytes(json.dumps(document).encode(), "json")
+    with pytest.raises(ValueError, match="unknown format 'yaml'"):
+        stat.to_bytes("yaml")
+    # JSON has no literal for the non-finite floats, which are written as strings.
+    stat.update(math.inf)
+    for format in ["cbor", "msgpack", "json"]:
+        assert rs.RsEWMean.from_bytes(stat.to_bytes(format), format).get() == math.inf
+    assert json.loads(stat.to_bytes("json"))["state"]["ewmean"]["mean"] == "inf"
+    assert rs.RsEWMean.from_dict(stat.to_dict()).get() == math.inf
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+def test_sizeof_counts_the_rust_heap(binding):
+    assert binding.__sizeof__() > binding.memory_usage() >= 0
+    assert sys.getsizeof(binding) >= binding.__sizeof__()
+
+
+def test_memory_usage_follows_the_windows_and_the_groups():
+    small, large = rs.RsRollingQuantile(0.5, 10), rs.RsRollingQuantile(0.5, 10_000)
+    for x in range(20_000):
+        small.update(float(x))
+        large.update(float(x))
+    # The values are held twice, in arrival order and sorted.
+    assert large.memory_usage() >= 2 * 8 * 10_000
+    assert small.memory_usage() < 2 * 8 * 100
+    assert rs.RsCountMin(1000, 5).memory_usage() >= 8 * 1000 * 5
+    grouped = rs.RsGroupedStat(rs.RsRollingMean(100))
+    usage = grouped.memory_usage()
+    for key in range(50):
+        for x in range(100):
+            grouped.update(key, float(x))
+    assert grouped.memory_usage() >= usage + 50 * 100 * 8
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+def test_bindings_are_unhashable(binding):
+    with pytest.raises(TypeError, match="unhashable type"):
+        hash(binding)
+
+
+def test_eq_and_allclose_compare_the_states():
+    a, b = rs.RsEWMean(0.5), rs.RsEWMean(0.5)
+    a.update(1.0)
+    b.update(1.0)
+    assert a == b and not a != b
+    b.update(1.0 + 1e-12)
+    a.update(1.0)
+    assert a != b
+    assert a.allclose(b)
+    assert not a.allclose(b, rtol=0.0, atol=0.0)
+    assert a.allclose(b, rtol=0.0, atol=1e-11)
+    with pytest.raises(ValueError, match="non-negative"):
+        a.allclose(b, rtol=-1.0)
+    # Other classes and orderings aren't comparable.
+    assert a != rs.RsEWVar(0.5)
+    with pytest.raises(TypeError):
+        a < b
+    # NaN matches NaN, and the hash maps match whatever their iteration order.
+    assert rs.RsShift(2) == rs.RsShift(2)
+    counters = rs.RsCounter(), rs.RsCounter()
+    for counter in counters:
+        for key in range(100):
+            counter.update(key)
+            counter.update(str(key))
+    assert counters[0] == counters[1]
+
+
+def test_state_dicts_are_checked():
+    counter = rs.RsCounter()
+    for key in ("a", 3, "a"):
+        counter.update(key)
+    state = counter.to_dict()
+    assert state["state"]["counter"]["tallies"][0][0] in ({"Str": "a"}, {"Int": 3})
+    assert rs.RsCounter.from_dict(state) == counter
+    with pytest.raises(ValueError, match="the state is that of RsCounter, not RsMode"):
+        rs.RsMode.from_dict(state)
+    with pytest.raises(ValueError, match="has version 7"):
+        rs.RsCounter.from_dict({**state, "version": 7})
+    with pytest.raises(ValueError, match="failed to decode the state"):
+        rs.RsCounter.from_dict({**state, "state": math.nan})
+    with pytest.raises(ValueError, match="keys of a state dict"):
+        rs.RsCounter.from_dict({**state, 1: 2})
+
+
+def test_copies_are_independent():
+    stat = rs.RsRollingQuantile(0.5, 5)
+    for x in range(10):
+        stat.update(float(x))
+    copies = [stat.clone(), copy.copy(stat), copy.deepcopy(stat)]
+    for c in copies:
+        c.update(100.0)
+    assert stat.get() == 7.0
+    assert all(c.get() == 8.0 for c in copies)
+    assert all(c.__class__ is rs.RsRollingQuantile for c in copies)
+
+
+WINDOW_VIEWS = [
+    lambda: rs.RsRollingQuantile(0.5, 5),
+    lambda: rs.RsRollingIQR(0.25, 0.75, 5),
+    lambda: rs.RsRollingCircularStats(5),
+    lambda: rs.RsRollingPercentileRank(5),
+    lambda: rs.RsRollingMAD(5),
+]
+
+
+@pytest.mark.parametrize("make", WINDOW_VIEWS)
+def test_window_views_are_read_only(make):
+    stat = make()
+    for x in range(8):
+        stat.update(float(x))
+    with memoryview(stat) as view:
+        assert view.readonly and view.format == "d" and view.ndim == 1
+        assert view.tolist() == [3.0, 4.0, 5.0, 6.0, 7.0]
+        with pytest.raises(TypeError):
+            view[0] = 1.0
+
+
+@pytest.mark.parametrize("make", WINDOW_VIEWS)
+def test_state_is_not_replaced_under_a_view(make):
+    stat, other = make(), make()
+    for x in range(3):
+        stat.update(float(x))
+    for x in range(8):
+        other.update(float(x))
+    view = memoryview(stat)
+    with pytest.raises(BufferError):
+        stat.__setstate__(other.__getstate__())
+    assert view.tolist() == [0.0, 1.0, 2.0]
+    view.release()
+    stat.__setstate__(other.__getstate__())
+    assert stat == other
+    with memoryview(stat) as view:
+        assert len(view) == 5
+    stat.update(8.0)
+
+
+@pytest.mark.parametrize("make", WINDOW_VIEWS)
+def test_copies_taken_under_a_view_can_be_updated(make):
+    stat = make()
+    for x in range(3):
+        stat.update(float(x))
+    with memoryview(stat):
+        copies = [stat.clone(), copy.copy(stat), copy.deepcopy(stat)]
+        for c in copies:
+            c.update(3.0)
+            assert c != stat
+        with pytest.raises(BufferError):
+            stat.update(3.0)
+
+
+def test_stats_taken_under_a_view_can_be_updated():
+    stat = rs.RsRollingQuantile(0.5, 5)
+    stat.update(1.0)
+    with memoryview(stat):
+        pipeline = rs.RsStatPipeline([stat])
+        pipeline.update(3.0)
+        assert stat.get() == 1.0
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsQuantile(0.3),
+        lambda: rs.RsEWVar(alpha=0.2),
+        lambda: rs.RsSkew(False),
+        lambda: rs.RsRollingQuantile(0.5, 10),
+        lambda: rs.RsRollingVar(10),
+        lambda: rs.RsAdaptiveMean(),
+    ],
+)
+def test_update_many_matches_update(make):
+    rng = random.Random(42)
+    values = [rng.gauss(0, 1) for _ in range(200)]
+    one_by_one, from_buffer, from_list = make(), make(), make()
+    for x in values:
+        one_by_one.update(x)
+    from_buffer.update_many(array.array("d", values))
+    from_list.update_many(values)
+    assert from_buffer == one_by_one
+    assert from_list == one_by_one
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsEWMean(span=5),
+        lambda: rs.RsKurtosis(False),
+        lambda: rs.RsRollingIQR(0.25, 0.75, 10),
+        lambda: rs.RsAdaptiveVar(),
+        lambda: rs.RsRollingPercentileRank(10),
+    ],
+)
+def test_transform_many_returns_the_value_after_each_update(make):
+    rng = random.Random(32)
+    values = [rng.gauss(0, 1) for _ in range(200)]
+    one_by_one, expected = make(), []
+    for x in values:
+        one_by_one.update(x)
+        expected.append(one_by_one.get())
+    stat = make()
+    transformed = stat.transform_many(array.array("d", values))
+    assert transformed.shape == (200,)
+    assert transformed.tolist() == pytest.approx(expected, nan_ok=True)
+    assert stat == one_by_one
+
+
+def test_transform_many_stops_at_a_refused_value():
+    stat = rs.RsAdaptiveMean()
+    with pytest.raises(ValueError, match="x should not be NaN"):
+        stat.transform_many([1.0, float("nan"), 3.0])
+    assert stat.get() == 1.0
+
+
+def test_update_many_reads_arrow_arrays():
+    pa = pytest.importorskip("pyarrow")
+    values = [float(i % 7) for i in range(100)]
+    expected = rs.RsRollingMean(10)
+    for x in values:
+        expected.update(x)
+    # The chunks are read in turn, a slice from its offset, and other types are cast.
+    batches = [
+        pa.chunked_array([values[:30], values[30:]]),
+        pa.array([-1.0] * 5 + values)[5:],
+        pa.array([i % 7 for i in range(100)], type=pa.int64()),
+    ]
+    for batch in batches:
+        stat = rs.RsRollingMean(10)
+        stat.update_many(batch)
+        assert stat == expected
+    # Null entries are read as NaN.
+    with pytest.raises(ValueError, match="NaN"):
+        rs.RsRollingMean(10).update_many(pa.array([1.0, None, 3.0]))
+    table = pa.table({"a": values, "b": [-x for x in values]})
+    columns, rows = rs.RsColumnStats(rs.RsEWMean(0.5)), rs.RsColumnStats(rs.RsEWMean(0.5))
+    columns.update_many(table)
+    rows.update_many([[x, -x] for x in values])
+    assert columns == rows
+
+
+def test_update_many_reads_polars_series():
+    pl = pytest.importorskip("polars")
+    values = [float(i % 7) for i in range(100)]
+    expected, stat = rs.RsEWVar(0.2), rs.RsEWVar(0.2)
+    for x in values:
+        expected.update(x)
+    stat.update_many(pl.Series(values))
+    assert stat == expected
+    frame = pl.DataFrame({"a": values, "b": [-x for x in values]})
+    columns, rows = rs.RsColumnStats(rs.RsEWMean(0.5)), rs.RsColumnStats(rs.RsEWMean(0.5))
+    columns.update_many(frame)
+    rows.update_many([[x, -x] for x in values])
+    assert columns == rows
+
+
+def test_update_many_reads_any_numeric_buffer():
+    values = [float(i % 7) for i in range(100)]
+    expected = rs.RsRollingMean(10)
+    for x in values:
+        expected.update(x)
+    # Integer and single-precision buffers are converted, strided ones are copied.
+    batches = [
+        array.array("q", map(int, values)),
+        array.array("h", map(int, values)),
+        array.array("f", values),
+        memoryview(array.array("d", [x for x in values for _ in range(2)]))[::2],
+        tuple(values),
+    ]
+    for batch in batches:
+        stat = rs.RsRollingMean(10)
+        stat.update_many(batch)
+        assert stat == expected
+    flat = array.array("d", [v for x in values for v in (x, -x)])
+    matrix = memoryview(flat).cast("B").cast("d", [100, 2])
+    with pytest.raises(ValueError, match="expected a 1D array, got 2 dimensions"):
+        rs.RsRollingMean(10).update_many(matrix)
+    columns, rows = rs.RsColumnStats(rs.RsEWMean(0.5)), rs.RsColumnStats(rs.RsEWMean(0.5))
+    columns.update_many(matrix)
+    rows.update_many([[x, -x] for x in values])
+    assert columns == rows
+    with pytest.raises(ValueError, match="rows should have the same length"):
+        rows.update_many([[1.0, 2.0], [3.0]])
+
+
+def test_update_many_reads_pandas_objects():
+    pd = pytest.importorskip("pandas")
+    values = [float(i % 7) for i in range(100)]
+    expected, stat = rs.RsEWVar(0.2), rs.RsEWVar(0.2)
+    for x in values:
+        expected.update(x)
+    stat.update_many(pd.Series(values, index=range(100, 200)))
+    assert stat == expected
+    # Missing values are read as NaN.
+    with pytest.raises(ValueError, match="NaN"):
+        rs.RsRollingMean(3).update_many(pd.Series([1.0, None, 2.0], dtype="Float64"))
+    frame = pd.DataFrame({"a": values, "b": [-int(x) for x in values]})
+    columns, rows = rs.RsColumnStats(rs.RsEWMean(0.5)), rs.RsColumnStats(rs.RsEWMean(0.5))
+    columns.update_many(frame)
+    rows.update_many([[x, -x] for x in values])
+    assert columns == rows
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsQuantile(1.5),
+        lambda: rs.RsIQR(-0.1, 0.5),
+        lambda: rs.RsIQR(0.75, 0.25),
+        lambda: rs.RsRollingQuantile(float("nan"), 10),
+        lambda: rs.RsRollingQuantile(0.5, 0),
+        lambda: rs.RsRollingIQR(0.25, 1.5, 10),
+        lambda: rs.RsRollingIQR(0.25, 0.75, 0),
+        lambda: rs.RsRollingMean(0),
+        lambda: rs.RsECDF(0.5),
+        lambda: rs.RsECDF(float("nan")),
+        lambda: rs.RsPercentileRank(float("nan")),
+    ],
+)
+def test_invalid_arguments_raise_value_error(make):
+    with pytest.raises(ValueError):
+        make()
+
+
+@pytest.mark.parametrize("cls", [rs.RsEWMean, rs.RsEWVar])
+def test_ew_smoothing_from_span_halflife_or_com(cls):
+    assert cls(span=9).alpha == pytest.approx(0.2)
+    assert cls(com=3).alpha == pytest.approx(0.25)
+    assert cls(halflife=1).alpha == pytest.approx(0.5)
+    rng = random.Random(20)
+    values = [rng.gauss(0, 1) for _ in range(100)]
+    stat, expected = cls(com=4), cls(0.2)
+    for x in values:
+        stat.update(x)
+        expected.update(x)
+    assert stat.allclose(expected)
+
+    for kwargs in [
+        {},
+        {"alpha": 0.5, "span": 3},
+        {"span": 3, "com": 1},
+        {"alpha": 1.5},
+        {"span": 0.5},
+        {"halflife": 0},
+        {"com": -1},
+        {"com": float("nan")},
+    ]:
+        with pytest.raises(ValueError):
+            cls(**kwargs)
+
+
+def test_ew_mean_halves_a_pulse_every_halflife():
+    mean = rs.RsEWMean(halflife=3)
+    mean.update(1.0)
+    for _ in range(3):
+        mean.update(0.0)
+    assert mean.get() == pytest.approx(0.5)
+    for _ in range(3):
+        mean.update(0.0)
+    assert mean.get() == pytest.approx(0.25)
+
+
+def test_time_ew_stats_weigh_the_values_by_their_age():
+    rng = random.Random(21)
+    mean, var = rs.RsTimeEWMean(halflife=5), rs.RsTimeEWVar(halflife=5)
+    xs, ts, t = [], [], 0.0
+    for _ in range(200):
+        # Irregular gaps, some of them 0.
+        t += rng.choice([0.0, 0.5, 1.0, 7.0])
+        x = rng.gauss(t / 10, 1)
+        mean.update(x, t)
+        var.update(x, t)
+        xs.append(x)
+        ts.append(t)
+        weights = [2 ** (-(t - ti) / 5) for ti in ts]
+        expected = sum(w * xi for w, xi in zip(weights, xs)) / sum(weights)
+        squares = sum(w * (xi - expected) ** 2 for w, xi in zip(weights, xs))
+        assert mean.get() == pytest.approx(expected)
+        assert var.mean == pytest.approx(expected)
+        assert var.get() == pytest.approx(squares / sum(weights))
+
+    with pytest.raises(ValueError, match="t should not decrease"):
+        mean.update(0.0, t - 1)
+    with pytest.raises(ValueError, match="t should not be NaN"):
+        var.update(0.0, float("nan"))
+    for cls in [rs.RsTimeEWMean, rs.RsTimeEWVar]:
+        for halflife in [0, -1, float("nan")]:
+            with pytest.raises(ValueError, match="halflife should be positive"):
+                cls(halflife)
+
+
+@pytest.mark.parametrize("ddof", [0, 1])
+def test_rolling_mean_and_var(ddof):
+    rng = random.Random(7)
+    values = [rng.uniform(-1e3, 1e3) for _ in range(500)]
+    mean, var = rs.RsRollingMean(20), rs.RsRollingVar(20, ddof=ddof)
+    for i, x in enumerate(values):
+        mean.update(x)
+        var.update(x)
+        window = values[max(0, i - 19) : i + 1]
+        expected_mean = sum(window) / len(window)
+        squares = sum((v - expected_mean) ** 2 for v in window)
+        expected_var = squares / (len(window) - ddof) if len(window) > ddof else 0.0
+        assert mean.get() == pytest.approx(expected_mean)
+        assert var.get() == pytest.approx(expected_var)
+    assert len(var) == 20
+
+
+@pytest.mark.parametrize("make", [lambda: rs.RsSkew(False), lambda: rs.RsKurtosis(True)])
+def test_revert_undoes_update(make):
+    rng = random.Random(3)
+    kept = [rng.gauss(5, 2) for _ in range(50)]
+    removed = [rng.expovariate(0.5) for _ in range(30)]
+    stat, expected = make(), make()
+    for x in kept + removed:
+        stat.update(x)
+    for x in reversed(removed):
+        stat.revert(x)
+    for x in kept:
+        expected.update(x)
+    assert stat.get() == pytest.approx(expected.get())
+    for x in kept:
+        stat.revert(x)
+    with pytest.raises(ValueError):
+        stat.revert(1.0)
+
+
+def test_repr_shows_the_arguments_and_the_value():
+    ewm = rs.RsEWMean(0.5)
+    for x in (1.0, 4.0):
+        ewm.update(x)
+    assert repr(ewm) == "RsEWMean(alpha=0.5): 2.5"
+    assert repr(rs.RsQuantile(0.3, algorithm="tdigest")) == (
+        "RsQuantile(q=0.3, algorithm='tdigest'): nan"
+    )
+    assert repr(rs.RsRollingVar(10, ddof=0)) == "RsRollingVar(window_size=10, ddof=0): 0.0"
+    assert repr(rs.RsSkew(True)).startswith("RsSkew(bias=True): ")
+    assert repr(rs.RsPeakToPeak()).startswith("RsPeakToPeak(): ")
+    # Equal states compare equal, so a pickled stat round-trips.
+    assert pickle.loads(pickle.dumps(ewm)) == ewm
+    ewm.update(2.0)
+    assert pickle.loads(pickle.dumps(ewm)) != rs.RsEWMean(0.5)
+
+
+def test_revert_is_refused_by_the_other_stats():
+    with pytest.raises(NotImplementedError):
+        rs.RsEWMean(0.5).revert(1.0)
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsQuantile(0.3),
+        lambda: rs.RsEWMean(0.2),
+        lambda: rs.RsEWVar(0.2),
+        lambda: rs.RsIQR(0.25, 0.75),
+        lambda: rs.RsKurtosis(False),
+        lambda: rs.RsPeakToPeak(),
+        lambda: rs.RsSkew(True),
+    ],
+)
+def test_whole_weights_repeat_the_update(make):
+    rng = random.Random(11)
+    weighted, repeated = make(), make()
+    for _ in range(300):
+        x, w = rng.gauss(3, 2), rng.choice([0, 0, 1, 1, 2, 3])
+        weighted.update(x, w)
+        for _ in range(w):
+            repeated.update(x)
+        assert weighted.get() == pytest.approx(repeated.get(), abs=1e-9)
+    with pytest.raises(ValueError):
+        weighted.update(1.0, -1.0)
+
+
+def test_fractional_weights_of_the_moments():
+    rng = random.Random(12)
+    pairs = [(rng.expovariate(1), rng.uniform(0.1, 2)) for _ in range(200)]
+    skew, kurtosis = rs.RsSkew(True), rs.RsKurtosis(True)
+    for x, w in pairs:
+        skew.update(x, w)
+        kurtosis.update(x, w)
+    n = sum(w for _, w in pairs)
+    mean = sum(w * x for x, w in pairs) / n
+    m2, m3, m4 = (sum(w * (x - mean) ** k for x, w in pairs) / n for k in (2, 3, 4))
+    assert skew.get() == pytest.approx(m3 / m2**1.5)
+    assert kurtosis.get() == pytest.approx(m4 / m2**2 - 3)
+    # Half a value moves the exponentially weighted mean less than a whole one.
+    ewm = rs.RsEWMean(0.5)
+    ewm.update(4.0)
+    ewm.update(0.0, 0.5)
+    assert ewm.get() == pytest.approx(4 / 2**0.5)
+    tdigest = rs.RsQuantile(0.5, algorithm="tdigest")
+    for x, w in pairs:
+        tdigest.update(x, w)
+    above = sum(w for x, w in pairs if x > tdigest.get())
+    assert above == pytest.approx(n / 2, rel=0.05)
+    with pytest.raises(ValueError):
+        rs.RsQuantile(0.5).update(1.0, 0.5)
+    with pytest.raises(ValueError):
+        rs.RsIQR(0.25, 0.75).update(1.0, 1.5)
+
+
+@pytest.mark.parametrize("cls", [rs.RsSkew, rs.RsKurtosis])
+def test_merge_pools_the_central_moments(cls):
+    rng = random.Random(22)
+    values = [rng.expovariate(1) for _ in range(511)]
+    # Shards of very different sizes, one of them empty.
+    bounds = [0, 1, 1, 11, 511]
+    shards = [cls(True) for _ in bounds[1:]]
+    for shard, (lo, hi) in zip(shards, zip(bounds, bounds[1:])):
+        for x in values[lo:hi]:
+            shard.update(x)
+    merged = cls(True)
+    for shard in shards:
+        merged.merge(shard)
+    mean = statistics.fmean(values)
+    m2, m3, m4 = (statistics.fmean((x - mean) ** k for x in values) for k in (2, 3, 4))
+    expected = m3 / m2**1.5 if cls is rs.RsSkew else m4 / m2**2 - 3
+    assert merged.get() == pytest.approx(expected)
+    # The merged moments carry on like those of a single stat.
+    whole = cls(True)
+    for x in values:
+        whole.update(x)
+    for x in [rng.expovariate(1) for _ in range(100)]:
+        merged.update(x)
+        whole.update(x)
+    assert merged.get() == pytest.approx(whole.get())
+    with pytest.raises(TypeError):
+        merged.merge(rs.RsEWMean(0.5))
+
+
+@pytest.mark.parametrize(
+    "make", [lambda: rs.RsKurtosis(False), lambda: rs.RsSkew(True), lambda: rs.RsPeakToPeak()]
+)
+def test_iadd_merges_the_shards(make):
+    rng = random.Random(4)
+    shards, whole, merged = [make() for _ in range(3)], make(), make()
+    for i in range(600):
+        x = rng.lognormvariate(0, 1)
+        shards[i % 3].update(x)
+        whole.update(x)
+    for shard in shards:
+        merged += shard
+    assert merged.get() == pytest.approx(whole.get())
+    with pytest.raises(TypeError):
+        merged += rs.RsEWMean(0.5)
+
+
+def test_cov_matrix_merge_aligns_the_features():
+    rng = random.Random(6)
+    samples = []
+    for i in range(300):
+        x = {"a": rng.gauss(0, 1), "b": rng.gauss(2, 3)}
+        if i % 2:
+            x["c"] = x["a"] + rng.gauss(0, 0.5)
+        samples.append(x)
+    # The right shard sees the features in another order, so some pairs are transposed.
+    first = {"c": -1.0, "b": 1.0}
+    left, right, whole = rs.RsCovMatrix(), rs.RsCovMatrix(), rs.RsCovMatrix()
+    for x in samples[:150]:
+        left.update(x)
+    for x in [first] + samples[150:]:
+        right.update(x)
+    for x in samples[:150] + [first] + samples[150:]:
+        whole.update(x)
+    assert right.features == ["b", "c", "a"]
+    left += right
+    assert left.features == whole.features == ["a", "b", "c"]
+    assert left.allclose(whole, rtol=1e-9)
+
+
+def test_pairwise_cov_and_corr():
+    rng = random.Random(11)
+    xs = [rng.gauss(0, 2) for _ in range(200)]
+    ys = [x + rng.gauss(1, 1) for x in xs]
+    cov, corr = rs.RsCov(), rs.RsPearsonCorr()
+    left, right = rs.RsCov(), rs.RsCov()
+    for i, (x, y) in enumerate(zip(xs, ys)):
+        cov.update(x, y)
+        corr.update(x, y)
+        (left if i < 80 else right).update(x, y)
+    assert cov.n == 200
+    assert math.isclose(cov.get(), statistics.covariance(xs, ys), rel_tol=1e-9)
+    assert math.isclose(corr.get(), statistics.correlation(xs, ys), rel_tol=1e-9)
+    left += right
+    assert left.allclose(cov, rtol=1e-9)
+    # Reverting the last pairs gives back the stats of the first ones.
+    for x, y in zip(xs[50:], ys[50:]):
+        cov.revert(x, y)
+        corr.revert(x, y)
+    assert math.isclose(cov.get(), statistics.covariance(xs[:50], ys[:50]), rel_tol=1e-9)
+    assert math.isclose(corr.get(), statistics.correlation(xs[:50], ys[:50]), rel_tol=1e-9)
+    # A weight of 2 counts as two copies of the pair.
+    weighted, repeated = rs.RsCov(ddof=0), rs.RsCov(ddof=0)
+    for x, y in zip(xs[:20], ys[:20]):
+        weighted.update(x, y, 2.0)
+        repeated.update(x, y)
+        repeated.update(x, y)
+    assert weighted.allclose(repeated, rtol=1e-9)
+    with pytest.raises(ValueError):
+        weighted.revert(0.0, 0.0, 100.0)
+    with pytest.raises(ValueError):
+        weighted.update(0.0, 0.0, -1.0)
+    assert rs.RsPearsonCorr().get() == rs.RsCov().get() == 0.0
+
+
+def test_cov_matrix_of_vectors():
+    rng = random.Random(8)
+    rows = [[rng.gauss(0, 1), rng.gauss(2, 3), rng.gauss(-1, 0.5)] for _ in range(100)]
+    dicts, vectors, batch = rs.RsCovMatrix(), rs.RsCovMatrix(), rs.RsCovMatrix(n_features=3)
+    for row in rows:
+        dicts.update({str(j): v for j, v in enumerate(row)})
+        vectors.update(array.array("d", row))
+    batch.update_many(rows[:60])
+    batch.update_many(rows[60:])
+    assert vectors.features == batch.features == ["0", "1", "2"]
+    assert vectors.allclose(dicts, rtol=1e-12)
+    assert batch.allclose(dicts, rtol=1e-12)
+    vectors.revert(rows[-1])
+    dicts.revert({str(j): v for j, v in enumerate(rows[-1])})
+    assert vectors.allclose(dicts, rtol=1e-12)
+    with pytest.raises(ValueError, match="x has 2 values, but the matrix has 3 features"):
+        vectors.update([1.0, 2.0])
+    with pytest.raises(ValueError):
+        batch.update_many([[1.0, 2.0]])
+    with pytest.raises(ValueError):
+        rs.RsCovMatrix(n_features=3).revert([1.0, 2.0, 3.0])
+
+
+def test_cov_matrix_pairs_the_features_seen_together():
+    rng = random.Random(2)
+    samples = []
+    for i in range(200):
+        x = {"a": rng.gauss(0, 1), "b": rng.gauss(0, 2)}
+        if i % 3:
+            x["c"] = x["a"] - x["b"] + rng.gauss(0, 0.1)
+        samples.append(x)
+    cov, mle = rs.RsCovMatrix(), rs.RsCovMatrix(ddof=0)
+    for x in samples:
+        cov.update(x)
+        mle.update(x)
+    assert cov.features == ["a", "b", "c"]
+    matrix, corr, biased = cov.get().tolist(), cov.correlation().tolist(), mle.get().tolist()
+    # Each pair of features only counts the samples in which both are present.
+    for i, u in enumerate(cov.features):
+        for j, v in enumerate(cov.features):
+            xs, ys = zip(*[(x[u], x[v]) for x in samples if u in x and v in x])
+            n = len(xs)
+            assert matrix[i][j] == pytest.approx(statistics.covariance(xs, ys), rel=1e-9)
+            assert biased[i][j] == pytest.approx(matrix[i][j] * (n - 1) / n, rel=1e-9)
+            assert corr[i][j] == pytest.approx(statistics.correlation(xs, ys), rel=1e-9)
+    for x in samples[100:]:
+        cov.revert(x)
+    xs, ys = zip(*[(x["b"], x["c"]) for x in samples[:100] if "c" in x])
+    assert cov.get()[1, 2] == pytest.approx(statistics.covariance(xs, ys), rel=1e-9)
+    with pytest.raises(ValueError, match="feature 'd' has no observation to revert"):
+        cov.revert({"d": 1.0})
+
+
+def test_precision_matrix_inverts_the_covariance():
+    rng = random.Random(12)
+    samples = []
+    for _ in range(2000):
+        a = rng.gauss(0, 1)
+        samples.append({"a": a, "b": a + rng.gauss(1, 0.5)})
+    exact, shrunk, identity = (rs.RsPrecisionMatrix(s) for s in (0.0, 0.5, 1.0))
+    estimated = rs.RsPrecisionMatrix()
+    for x in samples:
+        for prec in (exact, shrunk, identity, estimated):
+            prec.update(x)
+    assert exact.features == ["a", "b"]
+    xs, ys = [x["a"] for x in samples], [x["b"] for x in samples]
+    n, mean_a, mean_b = len(samples), statistics.fmean(xs), statistics.fmean(ys)
+    saa, sbb, sab = statistics.variance(xs), statistics.variance(ys), statistics.covariance(xs, ys)
+
+    def mahalanobis(x, saa, sbb, sab):
+        da, db = x["a"] - mean_a, x["b"] - mean_b
+        return (sbb * da * da - 2 * sab * da * db + saa * db * db) / (saa * sbb - sab * sab)
+
+    x = {"a": 1.0, "b": -1.0}
+    # The inverse starts from the identity matrix, which is soon outweighed by the samples.
+    assert exact.mahalanobis(x) == pytest.approx(mahalanobis(x, saa, sbb, sab), rel=0.01)
+    mle = [s * (n - 1) / n for s in (saa, sbb, sab)]
+    assert exact.mahalanobis(x, shrunk=True) == pytest.approx(mahalanobis(x, *mle), rel=1e-9)
+    mu = (mle[0] + mle[1]) / 2
+    expected = mahalanobis(x, mle[0] / 2 + mu / 2, mle[1] / 2 + mu / 2, mle[2] / 2)
+    assert shrunk.mahalanobis(x, shrunk=True) == pytest.approx(expected, rel=1e-9)
+    distance = ((x["a"] - mean_a) ** 2 + (x["b"] - mean_b) ** 2) / mu
+    assert identity.mahalanobis(x, shrunk=True) == pytest.approx(distance, rel=1e-9)
+    assert 0 < estimated.shrinkage < 0.1
+    det = saa * sbb - sab * sab
+    inverse = [sbb / det, -sab / det, -sab / det, saa / det]
+    assert [v for row in exact.get().tolist() for v in row] == pytest.approx(inverse, rel=0.01)
+    with pytest.raises(ValueError):
+        rs.RsPrecisionMatrix(shrinkage=1.5)
+
+
+def test_precision_matrix_skips_dicts_without_features():
+    prec, plain = rs.RsPrecisionMatrix(), rs.RsPrecisionMatrix()
+    prec.update({})
+    assert prec.features == [] and prec.mahalanobis({}) == 0.0
+    for x in [1.0, 3.0, 2.0]:
+        prec.update({"a": x})
+        plain.update({"a": x})
+    assert prec == plain
+
+
+@pytest.mark.parametrize("covariance_type", ["full", "diag"])
+def test_gaussian_mixture_finds_the_clusters(covariance_type):
+    rng = random.Random(3)
+    gmm = rs.RsGaussianMixture(2, covariance_type=covariance_type)
+    assert gmm.predict_one({"x": 0.0}) is None
+    assert gmm.score_one({"x": 0.0}) == -math.inf
+    # The first two samples seed one component in each cluster.
+    for i in range(2000):
+        k = 0 if i == 0 or (i > 1 and rng.random() < 0.7) else 1
+        gmm.learn_one({"x": rng.gauss(10 * k - 5, 1), "y": rng.gauss(2 * k, 0.5)})
+    assert gmm.weights == pytest.approx([0.7, 0.3], abs=0.03)
+    assert gmm.means[0] == pytest.approx({"x": -5, "y": 0}, abs=0.1)
+    assert gmm.means[1] == pytest.approx({"x": 5, "y": 2}, abs=0.1)
+    proba = gmm.predict_proba_one({"x": 4.0, "y": 1.5})
+    assert sum(proba.values()) == pytest.approx(1)
+    assert proba[1] > 0.99
+    assert gmm.predict_one({"x": -4.0}) == 0
+    # At a center, the density is that of its component scaled by its weight.
+    expected = math.log(0.7 / (2 * math.pi * 1 * 0.5))
+    assert gmm.score_one({"x": -5.0, "y": 0.0}) == pytest.approx(expected, abs=0.1)
+    assert gmm.score_one({"x": 0.0, "y": 1.0}) < expected - 10
+    with pytest.raises(ValueError):
+        rs.RsGaussianMixture(0)
+    with pytest.raises(ValueError):
+        rs.RsGaussianMixture(2, covariance_type="spherical")
+    with pytest.raises(ValueError, match="reg_covar should be non-negative"):
+        rs.RsGaussianMixture(2, reg_covar=math.nan)
+
+
+def test_gaussian_mixture_keeps_the_layout_of_the_first_observation():
+    gmm, plain = rs.RsGaussianMixture(2), rs.RsGaussianMixture(2)
+    gmm.learn_one({})
+    assert gmm.weights == [] and gmm.score_one({}) == -math.inf
+    for x in [{"a": 1.0, "b": 2.0}, {"a": -1.0}, {"b": 0.5}, {"a": 0.3, "b": 0.2}]:
+        gmm.learn_one(x)
+        plain.learn_one(x)
+    gmm.learn_one({})
+    plain.learn_one({"a": 0.0, "b": 0.0})
+    assert gmm == plain
+    with pytest.raises(ValueError, match="feature 'c' isn't one of those of the first"):
+        gmm.learn_one({"a": 1.0, "c": 3.0})
+    assert gmm == plain
+
+
+def test_kernel_density_of_a_normal_sample():
+    rng = random.Random(5)
+    kde = rs.RsKernelDensity(max_centers=64)
+    assert kde.density(0.0) == kde.cdf(0.0) == 0.0
+    xs = [rng.gauss(0, 1) for _ in range(3000)]
+    for x in xs:
+        kde.update(x)
+    assert kde.n_centers == 64
+    # Silverman's rule of thumb.
+    assert kde.bandwidth == pytest.approx(1.06 * statistics.stdev(xs) * 3000**-0.2)
+    normal = statistics.NormalDist()
+    for x in [-2.0, -1.0, 0.0, 0.5, 1.5]:
+        assert kde.density(x) == pytest.approx(normal.pdf(x), abs=0.03)
+        assert kde.cdf(x) == pytest.approx(normal.cdf(x), abs=0.03)
+    grid = [i / 100 for i in range(-800, 800)]
+    assert sum(kde.density(x) for x in grid) / 100 == pytest.approx(1, abs=1e-3)
+    assert kde.cdf(-10.0) == pytest.approx(0, abs=1e-9)
+    assert kde.cdf(10.0) == pytest.approx(1)
+    fixed = rs.RsKernelDensity(bandwidth=0.5)
+    fixed.update(1.0)
+    fixed.update(1.0)
+    assert fixed.n_centers == 1 and fixed.bandwidth == 0.5
+    assert fixed.density(1.0) == pytest.approx(normal.pdf(0) / 0.5)
+    for bandwidth in [0.0, -1.0, math.nan]:
+        with pytest.raises(ValueError, match="bandwidth should be strictly positive"):
+            rs.RsKernelDensity(bandwidth=bandwidth)
+    with pytest.raises(ValueError):
+        rs.RsKernelDensity(max_centers=1)
+    for x in [math.nan, math.inf, -math.inf]:
+        with pytest.raises(ValueError, match="x should be finite"):
+            fixed.update(x)
+    assert fixed.n_centers == 1
+    assert fixed.density(1.0) == pytest.approx(normal.pdf(0) / 0.5)
+
+
+@pytest.mark.parametrize(
+    "make", [lambda: rs.RsRollingQuantile(0.5, 20), lambda: rs.RsRollingIQR(0.25, 0.75, 20)]
+)
+def test_rolling_quantile_window_and_rank(make):
+    rng = random.Random(23)
+    stat, window = make(), collections.deque(maxlen=20)
+    for _ in range(100):
+        x = float(rng.randint(0, 9))
+        stat.update(x)
+        window.append(x)
+        assert len(stat) == len(window)
+        for y in [-1.0, 4.0, 4.5, 10.0]:
+            assert stat.rank(y) == sum(v < y for v in window)
+    assert stat.window().tolist() == list(window)
+    assert stat.window(sorted=True).tolist() == sorted(window)
+
+
+@pytest.mark.parametrize(
+    "make", [lambda: rs.RsRollingQuantile(0.5, 20), lambda: rs.RsRollingIQR(0.25, 0.75, 20)]
+)
+def test_rolling_get_many_matches_one_quantile_per_stat(make):
+    qs = [0.0, 0.1, 0.25, 0.5, 0.9, 1.0]
+    stat, singles = make(), [rs.RsRollingQuantile(q, 20) for q in qs]
+    assert all(math.isnan(v) for v in stat.get_many(qs))
+    rng = random.Random(30)
+    for _ in range(100):
+        x = rng.uniform(-10, 10)
+        stat.update(x)
+        for single in singles:
+            single.update(x)
+        assert stat.get_many(qs) == [single.get() for single in singles]
+    assert stat.get_many([]) == []
+    with pytest.raises(ValueError):
+        stat.get_many([0.5, 1.5])
+
+
+@pytest.mark.parametrize("window_size", [1, 3, 10])
+def test_rolling_extrema_match_a_window_scan(window_size):
+    rng = random.Random(window_size)
+    stats = rs.RsRollingMin(window_size), rs.RsRollingMax(window_size)
+    ptp = rs.RsRollingPeakToPeak(window_size)
+    assert all(math.isnan(stat.get()) for stat in (*stats, ptp))
+    window = collections.deque(maxlen=window_size)
+    # Rounded values, so that there are ties.
+    for x in (round(rng.gauss(0, 3)) for _ in range(300)):
+        window.append(x)
+        for stat in (*stats, ptp):
+            stat.update(x)
+        assert stats[0].get() == min(window)
+        assert stats[1].get() == max(window)
+        assert ptp.get() == max(window) - min(window)
+    assert ptp.n == 300
+    with pytest.raises(ValueError):
+        rs.RsRollingMin(0)
+    with pytest.raises(ValueError):
+        ptp.update(math.nan)
+    for stat in (*stats, ptp):
+        assert stat.window().tolist() == list(window)
+
+
+@pytest.mark.parametrize("make", [rs.RsRollingMin, rs.RsRollingMax, rs.RsRollingPeakToPeak])
+def test_rolling_extrema_states_from_before_the_window_was_stored_load(make):
+    stat = make(5)
+    for i in range(8):
+        stat.update(float(i * i % 7))
+    state = stat.__getstate__()
+    # Version 1 ended before the window, which is the last field of the state.
+    assert state[:6] == b"RVRS\x02\x00"
+    old = make._from_state(b"RVRS\x01\x00" + state[6:-48])
+    assert old.get() == stat.get()
+    assert old.n == stat.n
+    for x in [3.0, -1.0, 4.0]:
+        old.update(x)
+        stat.update(x)
+        assert old.get() == stat.get()
+    assert old.window().tolist() == [3.0, -1.0, 4.0]
+    assert stat.window().tolist() == [1.0, 0.0, 3.0, -1.0, 4.0]
+
+
+def exact_mad(values):
+    median = statistics.median(values)
+    return statistics.median(abs(x - median) for x in values)
+
+
+def test_rolling_mad_is_exact():
+    rng = random.Random(5)
+    mad = rs.RsRollingMAD(7)
+    assert math.isnan(mad.get())
+    window = collections.deque(maxlen=7)
+    for x in (round(rng.expovariate(0.5), 1) for _ in range(200)):
+        mad.update(x)
+        window.append(x)
+        assert mad.get() == pytest.approx(exact_mad(window), abs=1e-12)
+        assert mad.median() == statistics.median(window)
+    assert mad.n == 200 and len(mad) == 7
+    assert memoryview(mad).tolist() == list(window)
+    assert mad.window().tolist() == list(window)
+    assert mad.window(sorted=True).tolist() == sorted(window)
+    with pytest.raises(ValueError):
+        rs.RsRollingMAD(0)
+
+
+def test_mad_estimates_the_deviation_of_the_stream():
+    rng = random.Random(9)
+    values = [rng.lognormvariate(0, 0.7) for _ in range(5_000)]
+    left, right, whole = rs.RsMAD(), rs.RsMAD(), rs.RsMAD()
+    for i, x in enumerate(values):
+        (left if i % 2 else right).update(x)
+        whole.update(x)
+    assert whole.n == len(values)
+    assert whole.get() == pytest.approx(exact_mad(values), rel=0.02)
+    assert whole.median() == pytest.approx(statistics.median(values), rel=0.02)
+    left += right
+    assert left.get() == pytest.approx(exact_mad(values), rel=0.02)
+    # A weight of 2 counts as two copies of the value.
+    weighted = rs.RsMAD()
+    for x in values[:1000]:
+        weighted.update(x, 2.0)
+    assert weighted.get() == pytest.approx(exact_mad(values[:1000]), rel=0.02)
+    assert math.isnan(rs.RsMAD().get())
+    for x in [math.nan, math.inf, -math.inf]:
+        with pytest.raises(ValueError, match="x should be finite"):
+            whole.update(x)
+    assert whole.get() == pytest.approx(exact_mad(values), rel=0.02)
+    # A null weight leaves the digest, extremes included, as it is.
+    nulls, plain = rs.RsMAD(), rs.RsMAD()
+    nulls.update(1e9, 0.0)
+    assert nulls.n == 0 and math.isnan(nulls.get())
+    for x in [1.0, 2.0, 4.0]:
+        nulls.update(x)
+        plain.update(x)
+    nulls.update(-1e9, 0.0)
+    assert nulls == plain
+    assert nulls.median() == 2.0
+
+
+def test_shift_and_auto_corr():
+    shift, filled = rs.RsShift(2), rs.RsShift(2, fill_value=0.0)
+    assert math.isnan(shift.get()) and filled.get() == 0.0
+    values = [float(i * i % 11) for i in range(30)]
+    for i, x in enumerate(values):
+        shift.update(x)
+        filled.update(x)
+        if i >= 2:
+            assert shift.get() == filled.get() == values[i - 2]
+    assert repr(filled).startswith("RsShift(amount=2, fill_value=0.0): ")
+    rng = random.Random(3)
+    xs = [rng.gauss(0, 1)]
+    for _ in range(299):
+        xs.append(0.8 * xs[-1] + rng.gauss(0, 1))
+    auto_corr = rs.RsAutoCorr(lag=3)
+    auto_corr.update_many(xs)
+    expected = statistics.correlation(xs[3:], xs[:-3])
+    assert math.isclose(auto_corr.get(), expected, rel_tol=1e-9)
+    # The stats compose with the others, e.g. for the change over the last steps.
+    change = rs.RsDiff(rs.RsShift(0), rs.RsShift(3))
+    for x in xs:
+        change.update(x)
+    assert change.get() == xs[-1] - xs[-4]
+    with pytest.raises(ValueError):
+        rs.RsAutoCorr(0)
+
+
+def test_diff_and_ratio_combine_two_stats():
+    fast, slow = rs.RsEWMean(span=3), rs.RsEWMean(span=10)
+    diff, ratio = rs.RsDiff(fast, slow), rs.RsRatio(fast, slow)
+    rng = random.Random(31)
+    for _ in range(100):
+        x = rng.uniform(1, 2)
+        assert diff.update(x) == diff.get()
+        ratio.update(x)
+        fast.update(x)
+        slow.update(x)
+        assert diff.get() == fast.get() - slow.get()
+        assert ratio.get() == fast.get() / slow.get()
+    # The combinators own copies of the stats given to them.
+    assert diff.stats == (fast, slow)
+    copies = diff.stats
+    copies[0].update(100.0)
+    assert diff.stats[0] == fast
+
+    empty = rs.RsRatio(rs.RsRollingMean(2), rs.RsRollingVar(2))
+    empty.update(1.0)
+    assert empty.get() == math.inf
+    assert math.isnan(rs.RsRatio(rs.RsRollingVar(2), rs.RsRollingVar(2)).update(1.0))
+    with pytest.raises(TypeError, match="is not a Rust univariate stat"):
+        rs.RsDiff(fast, object())
+
+
+def test_stat_pipeline_updates_its_own_copies():
+    rng = random.Random(16)
+    values = [rng.gauss(0, 1) for _ in range(100)]
+    stats = [rs.RsEWMean(0.3), rs.RsRollingQuantile(0.5, 10), rs.RsSkew(False)]
+    pipeline, batch = rs.RsStatPipeline(stats), rs.RsStatPipeline(stats)
+    assert len(pipeline) == 3
+    untouched, references = copy.deepcopy(stats), copy.deepcopy(stats)
+    for x in values:
+        returned = pipeline.update(x)
+        for stat in references:
+            stat.update(x)
+        assert returned == [stat.get() for stat in references]
+    assert pipeline.get() == returned
+    # The stats given to the pipeline are left untouched.
+    assert stats == untouched
+    batch.update_many(array.array("d", values))
+    assert batch == pipeline
+    assert pipeline.stats == references
+    with pytest.raises(TypeError):
+        rs.RsStatPipeline([rs.RsEWMean(0.5), statistics.mean])
+
+
+def test_grouped_stat_keeps_one_stat_per_key():
+    calls = []
+
+    def factory():
+        calls.append(None)
+        return rs.RsRollingMean(3)
+
+    grouped, by_key = rs.RsGroupedStat(factory), {}
+    for i in range(30):
+        key = "abc"[i % 3] if i % 2 else i % 3
+        grouped.update(key, float(i))
+        by_key.setdefault(key, []).append(float(i))
+    assert len(calls) == 1
+    assert len(grouped) == 6 and sorted(map(str, grouped.keys())) == sorted(map(str, by_key))
+    for key, xs in by_key.items():
+        assert grouped.get(key) == statistics.fmean(xs[-3:])
+    # Integer and string keys are kept apart.
+    assert 1 in grouped and "1" not in grouped
+    with pytest.raises(KeyError):
+        grouped.get("d")
+    template = rs.RsEWMean(0.5)
+    from_stat = rs.RsGroupedStat(template)
+    from_stat.update(1, 4.0)
+    assert from_stat.get(1) == 4.0 and template.get() == 0.0
+
+
+def test_column_stats_match_one_stat_per_column():
+    rng = random.Random(17)
+    rows = [[rng.gauss(0, 1), rng.gauss(5, 2), rng.expovariate(1)] for _ in range(200)]
+    by_row, serial, parallel = (
+        rs.RsColumnStats(rs.RsRollingQuantile(0.5, 20), parallel=p) for p in (False, False, True)
+    )
+    assert by_row.n_columns == 0
+    for row in rows:
+        by_row.update(row)
+    serial.update_many(rows[:50])
+    serial.update_many(rows[50:])
+    parallel.update_many(rows)
+    assert by_row.n_columns == 3
+    assert serial == by_row
+    columns = by_row.to_dict()["state"]["columns"]
+    assert parallel.to_dict()["state"]["columns"] == columns
+    medians = [statistics.median(row[j] for row in rows[-20:]) for j in range(3)]
+    assert by_row.get().tolist() == pytest.approx(medians)
+    with pytest.raises(ValueError, match="expected 3 columns, got 2"):
+        by_row.update([1.0, 2.0])
+    with pytest.raises(ValueError):
+        parallel.update_many([[1.0, 2.0]])
+
+
+def test_skip_missing_placeholders_keep_the_window_span():
+    values = [1.0, None, 3.0, float("nan"), 5.0, 6.0]
+    skipped = rs.RsSkipMissing(rs.RsRollingQuantile(0.0, 3))
+    held = rs.RsSkipMissing(rs.RsRollingQuantile(0.0, 3), placeholders=True)
+    for x in values:
+        skipped.update(x)
+        held.update(x)
+    assert (held.n, held.n_missing) == (6, 2)
+    # The minimum of the last 3 values, against the one of the values of the last 3 steps.
+    assert skipped.get() == 3.0
+    assert held.get() == 5.0
+    assert memoryview(held.stat).tolist()[1:] == [5.0, 6.0]
+    for x in [None, None, None]:
+        held.update(x)
+    assert math.isnan(held.get())
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsRollingMean(3),
+        lambda: rs.RsRollingVar(3),
+        lambda: rs.RsRollingMin(3),
+        lambda: rs.RsRollingMax(3),
+        lambda: rs.RsRollingPeakToPeak(3),
+    ],
+)
+def test_skip_missing_placeholders_are_refused_by_running_windows(make):
+    stat = rs.RsSkipMissing(make(), placeholders=True)
+    stat.update(1.0)
+    with pytest.raises(ValueError, match="placeholders"):
+        stat.update(None)
+    skipped = rs.RsSkipMissing(make())
+    skipped.update(None)
+    skipped.update(2.0)
+    assert skipped.n_missing == 1
+
+
+def test_ecdf_queries_follow_the_updates():
+    ecdf = rs.RsECDF()
+    for x in range(100):
+        ecdf.update(float(x))
+    assert ecdf.cdf(49.5) == pytest.approx(0.5, abs=0.01)
+    assert ecdf.ppf(0.5) == pytest.approx(49.5, abs=1)
+    for x in range(100, 200):
+        ecdf.update(float(x))
+    assert ecdf.cdf(99.5) == pytest.approx(0.5, abs=0.01)
+    assert ecdf.get_many([0.0, 1.0]) == [0.0, 199.0]
+    assert ecdf.n == 200
+
+
+def test_ecdf_rejects_non_finite_values():
+    ecdf, digest = rs.RsECDF(), rs.RsTDigest()
+    for x in [math.nan, math.inf, -math.inf]:
+        with pytest.raises(ValueError, match="x should be finite"):
+            ecdf.update(x)
+    assert ecdf.n == 0
+    for x in range(10):
+        ecdf.update(float(x))
+        digest.update(float(x))
+    assert math.isnan(ecdf.cdf(math.nan))
+    assert math.isnan(digest.cdf(math.nan))
+
+
+def test_ecdf_matches_the_sample_distribution():
+    rng = random.Random(9)
+    xs = sorted(rng.lognormvariate(0, 1) for _ in range(5000))
+    ecdf = rs.RsECDF()
+    assert math.isnan(ecdf.ppf(0.5))
+    for x in xs:
+        ecdf.update(x)
+    for q in [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99]:
+        x = xs[int(q * len(xs))]
+        assert ecdf.cdf(x) == pytest.approx(q, abs=0.01)
+        assert ecdf.ppf(q) == pytest.approx(x, rel=0.05)
+        assert ecdf.cdf(ecdf.ppf(q)) == pytest.approx(q, abs=0.01)
+    assert ecdf.cdf(xs[0] - 1) == 0.0 and ecdf.cdf(xs[-1]) == 1.0
+    assert ecdf.get_many([0.0, 0.5, 1.0]) == [xs[0], ecdf.ppf(0.5), xs[-1]]
+    with pytest.raises(ValueError):
+        ecdf.ppf(1.5)
+    with pytest.raises(ValueError):
+        ecdf.get_many([0.5, -0.1])
+
+
+def test_circular_stats_wrap_around():
+    angles = [350.0, 10.0, 20.0, 355.0, 5.0]
+    stats, radians = rs.RsCircularStats(degrees=True), rs.RsCircularStats()
+    assert stats.mean == stats.var == stats.std == 0.0
+    for a in angles:
+        stats.update(a)
+        radians.update(math.radians(a))
+    # Same values as scipy.stats.circmean, circvar and circstd.
+    sin = sum(math.sin(math.radians(a)) for a in angles)
+    cos = sum(math.cos(math.radians(a)) for a in angles)
+    r = math.hypot(sin, cos) / len(angles)
+    assert stats.mean == pytest.approx(math.degrees(math.atan2(sin, cos)))
+    assert stats.var == pytest.approx(1 - r)
+    assert stats.std == pytest.approx(math.degrees(math.sqrt(-2 * math.log(r))))
+    assert radians.mean == pytest.approx(math.radians(stats.mean))
+    assert radians.var == pytest.approx(stats.var)
+    assert stats.n == 5
+    stats.revert(350.0)
+    stats.revert(355.0)
+    assert stats.mean == pytest.approx(35 / 3, rel=0.01)
+    # Opposite directions cancel out.
+    opposite = rs.RsCircularStats(degrees=True)
+    opposite.update(90.0)
+    opposite.update(270.0)
+    assert opposite.var == pytest.approx(1)
+    rolling = rs.RsRollingCircularStats(3, degrees=True)
+    for i, a in enumerate(angles):
+        rolling.update(a)
+        window = rs.RsCircularStats(degrees=True)
+        for b in angles[max(i - 2, 0) : i + 1]:
+            window.update(b)
+        assert rolling.mean == pytest.approx(window.mean)
+        assert rolling.var == pytest.approx(window.var, abs=1e-12)
+        assert rolling.n == window.n
+    assert memoryview(rolling).tolist() == [math.radians(a) for a in angles[2:]]
+    assert rolling.window().tolist() == [math.radians(a) for a in angles[2:]]
+
+
+def test_percentile_rank_of_the_latest_value():
+    rng = random.Random(15)
+    xs = [rng.gauss(0, 1) for _ in range(2000)]
+    rank = rs.RsPercentileRank()
+    rank.update(xs[0])
+    assert rank.get() == 0.0
+    seen = sorted(xs[:1])
+    for x in xs[1:]:
+        rank.update(x)
+        expected = bisect.bisect(seen, x) / len(seen)
+        bisect.insort(seen, x)
+        if len(seen) > 100:
+            assert rank.get() == pytest.approx(expected, abs=0.02)
+    with pytest.raises(ValueError, match="x should not be NaN"):
+        rank.update(math.nan)
+    assert rank.get() == pytest.approx(expected, abs=0.02)
+    # Ties count for one half in the rolling version, which is exact.
+    rolling = rs.RsRollingPercentileRank(5)
+    ints = [rng.randrange(4) for _ in range(100)]
+    for i, x in enumerate(ints):
+        rolling.update(float(x))
+        window = ints[max(i - 5, 0) : i]
+        if window:
+            below = sum(y < x for y in window) + 0.5 * sum(y == x for y in window)
+            assert rolling.get() == below / len(window)
+        else:
+            assert rolling.get() == 0.0
+    assert memoryview(rolling).tolist() == [float(x) for x in ints[-5:]]
+    with pytest.raises(ValueError, match="x should not be NaN"):
+        rolling.update(math.nan)
+    assert rolling.window().tolist() == [float(x) for x in ints[-5:]]
+
+
+def test_snapshots_are_read_only_copies():
+    stat = rs.RsRollingMean(3)
+    for x in [1.0, 2.0, 3.0]:
+        stat.update(x)
+    frozen = stat.snapshot()
+    stat.update(10.0)
+    assert frozen.get() == 2.0
+    assert not hasattr(frozen, "update")
+    thawed = frozen.thaw()
+    assert type(thawed) is rs.RsRollingMean
+    thawed.update(4.0)
+    assert thawed.get() == 3.0 and frozen.get() == 2.0
+    assert rs.RsFrozenStat(stat).get() == stat.get()
+    assert pickle.loads(pickle.dumps(frozen)).get() == 2.0
+
+
+def test_batch_updates_run_concurrently_on_separate_stats():
+    rng = random.Random(18)
+    batches = [array.array("d", (rng.gauss(0, 1) for _ in range(20_000))) for _ in range(4)]
+    serial = [rs.RsRollingQuantile(0.5, 100) for _ in batches]
+    for stat, batch in zip(serial, batches):
+        stat.update_many(batch)
+    concurrent = [rs.RsRollingQuantile(0.5, 100) for _ in batches]
+    threads = [
+        threading.Thread(target=stat.update_many, args=(batch,))
+        for stat, batch in zip(concurrent, batches)
+    ]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert concurrent == serial
+
+
+def test_update_all_matches_one_batch_update_per_stat():
+    rng = random.Random(19)
+    values = array.array("d", (rng.gauss(0, 1) for _ in range(1_000)))
+    makers = [
+        lambda: rs.RsEWVar(alpha=0.2),
+        lambda: rs.RsQuantile(0.3),
+        lambda: rs.RsRollingQuantile(0.5, 50),
+        lambda: rs.RsSkew(False),
+    ]
+    expected, stats = [make() for make in makers], [make() for make in makers]
+    for stat in expected:
+        stat.update_many(values)
+    rs.update_all(stats, values)
+    assert stats == expected
+
+    with pytest.raises(RuntimeError):
+        rs.update_all([stats[0], stats[0]], values)
+    with pytest.raises(TypeError, match="is not a Rust univariate stat"):
+        rs.update_all([stats[0], object()], values)
+    assert stats == expected
+
+
+def test_bench_times_every_univariate_stat():
+    reports = rs.bench(n=1_000, seed=1)
+    names = [report["name"] for report in reports]
+    assert len(set(names)) == len(names)
+    assert {"RsEWMean", "RsQuantile", "RsRollingQuantile", "RsAdaptiveVar"} <= set(names)
+    for report in reports:
+        assert hasattr(rs, report["name"])
+        assert report["n"] == 1_000
+        assert 0 <= report["update_ns"] < math.inf
+        assert 0 <= report["get_ns"] < math.inf
+    with pytest.raises(ValueError, match="n should be at least 1"):
+        rs.bench(n=0)
+
+
+def test_counter_pruning_keeps_heavy_hitters():
+    counter = rs.RsCounter(max_size=2)
+    for key in ["a", "a", "b", "a", "c", "b", "d"]:
+        counter.update(key)
+    assert len(counter) == 2
+    assert counter.most_common(1) == [("a", 3.0)]
+    assert counter.frequency("a") == 0.75
+
+
+def test_counter_fading():
+    counter = rs.RsCounter(fading=0.5)
+    for key in ["a", "b", "b"]:
+        counter.update(key)
+    assert counter.get("a") == 0.25
+    assert counter.get("b") == 1.5
+    assert counter.mode() == "b"
+
+
+@pytest.mark.parametrize("ties, expected", [("first", "b"), ("last", "a"), ("lexicographic", "a")])
+def test_mode_tie_breaking(ties, expected):
+    mode = rs.RsMode(ties=ties)
+    for key in ["b", "a", "c", "b", "a"]:
+        mode.update(key)
+    assert mode.get() == expected
+    assert mode.modes() == ["a", "b"]
+
+
+def test_mode_random_ties_are_reproducible():
+    modes = []
+    for _ in range(2):
+        mode = rs.RsMode(ties="random", seed=42)
+        for key in range(10):
+            mode.update(key)
+        modes.append(mode.get())
+    assert modes[0] == modes[1]
+    assert modes[0] == mode.get()
+
+
+@pytest.mark.parametrize("method", ["zca", "pca"])
+def test_whitened_features_are_uncorrelated(method):
+    rng = rs.RsRng(seed=42)
+    whitener = rs.RsWhitener(method=method, refresh_every=1)
+    xs = []
+    for _ in range(500):
+        u, v, w = rng.random(), rng.random(), rng.random()
+        xs.append({"a": u, "b": u + v, "c": 2 * v - w})
+        whitener.learn_one(xs[-1])
+    zs = [whitener.transform_one(x) for x in xs]
+    keys = sorted(zs[0], key=str)
+    for i in keys:
+        for j in keys:
+            cov = sum(z[i] * z[j] for z in zs) / (len(zs) - 1)
+            assert abs(cov - (i == j)) < 1e-2
+
+
+def test_whitener_skips_dicts_without_features():
+    whitener, plain = rs.RsWhitener(refresh_every=1), rs.RsWhitener(refresh_every=1)
+    whitener.learn_one({})
+    assert whitener.features == [] and whitener.transform_one({}) == {}
+    for u, v in [(1.0, 0.5), (2.0, -1.0), (0.3, 0.7)]:
+        whitener.learn_one({"a": u, "b": v})
+        plain.learn_one({"a": u, "b": v})
+    assert whitener == plain
+
+
+def test_online_least_squares_with_qr_updates():
+    r = [[0.0] * 4 for _ in range(4)]
+    for x1, x2 in [(1.0, 0.5), (2.0, -1.0), (0.3, 0.7), (-1.5, 2.0), (0.0, 0.1)]:
+        r = rs.qr_add_row(r, [1.0, x1, x2, 1 + 2 * x1 - 3 * x2])
+    top = [row[:3] for row in r.tolist()[:3]]
+    rhs = [row[3] for row in r.tolist()[:3]]
+    coefs = rs.solve_triangular(top, rhs)
+    assert coefs.tolist() == pytest.approx([1.0, 2.0, -3.0])
+
+
+def test_cholesky_downdate_undoes_update():
+    l = [[2.0, 0.0], [1.0, 1.5]]
+    x = [0.5, -1.0]
+    updated = rs.cholesky_update(l, x)
+    assert rs.cholesky_update(updated, x, downdate=True).tolist() == [
+        pytest.approx(row) for row in l
+    ]
+    with pytest.raises(ValueError):
+        rs.cholesky_update(l, [10.0, 0.0], downdate=True)
+
+
+class PythonCalibrator:
+    """Drives a calibrator through the generic evaluation loop."""
+
+    def __init__(self, calibrator):
+        self.calibrator = calibrator
+
+    def predict_proba_one(self, x):
+        return self.calibrator.transform_one(x)
+
+    def predict_one(self, x):
+        return self.calibrator.transform_one(x) >= 0.5
+
+    def learn_one(self, x, y):
+        self.calibrator.learn_one(x, y)
+
+
+@pytest.mark.parametrize("metric", [rs.RsLogLoss, rs.RsAccuracy])
+@pytest.mark.parametrize("calibrator", [rs.RsIsotonicCalibrator, rs.RsPlattCalibrator])
+def test_native_progressive_validation_matches_python_loop(calibrator, metric):
+    rng = rs.RsRng(seed=42)
+    x = [rng.random() for _ in range(200)]
+    y = [rng.random() < p**2 for p in x]
+    native = rs.evaluate_progressive(calibrator(), x, y, metric())
+    python = rs.evaluate_progressive(PythonCalibrator(calibrator()), x, y, metric())
+    assert native.get() == pytest.approx(python.get())
+
+
+@pytest.mark.parametrize(
+    "model", [rs.RsPlattCalibrator, lambda: PythonCalibrator(rs.RsPlattCalibrator())]
+)
+def test_delayed_progressive_validation(model):
+    rng = rs.RsRng(seed=42)
+    x = [rng.random() for _ in range(100)]
+    y = [rng.random() < p**2 for p in x]
+
+    # Labels arriving with their sample give the undelayed score.
+    undelayed = rs.evaluate_progressive(model(), x, y, rs.RsLogLoss())
+    moments = [float(i) for i in range(len(x))]
+    same_time = rs.evaluate_progressive(model(), x, y, rs.RsLogLoss(), arrivals=moments)
+    assert same_time.get() == pytest.approx(undelayed.get())
+
+    # Labels arriving after the end leave the model untrained during the evaluation.
+    never = rs.evaluate_progressive(model(), x, y, rs.RsLogLoss(), arrivals=[1e9] * len(x))
+    raw = rs.RsLogLoss()
+    for p, label in zip(x, y):
+        raw.update(label, p)
+    assert never.get() == pytest.approx(raw.get())
+
+    delayed = rs.evaluate_progressive(
+        model(), x, y, rs.RsLogLoss(), arrivals=[i + 10.0 for i in range(len(x))]
+    )
+    assert delayed.get() != undelayed.get()
+
+
+def test_iter_csv(tmp_path):
+    path = tmp_path / "data.csv"
+    path.write_text('a,b,name,y\n1,2.5,"x, ""quoted""\nline",True\n\n3,-1,plain,\n4\n')
+    rows = list(rs.iter_csv(path, target="y", converters={"a": int, "b": float}, drop=["name"]))
+    assert rows == [
+        ({"a": 1, "b": 2.5}, "True"),
+        ({"a": 3, "b": -1.0}, ""),
+        ({"a": 4, "b": None}, None),
+    ]
+    x, _ = next(rs.iter_csv(path, converters={"y": lambda v: v == "True"}))
+    assert x == {"a": "1", "b": "2.5", "name": 'x, "quoted"\nline', "y": True}
+    assert list(rs.iter_csv(path, drop=["name", "y"], drop_nones=True))[-1] == ({"a": "4"}, None)
+
+
+def test_iter_csv_reads_gzip_files(tmp_path):
+    path = tmp_path / "data.csv.gz"
+    with gzip.open(path, "wt") as f:
+        f.write("a;b\n1;2\n")
+    rows = list(rs.iter_csv(path, target=["a", "b"], delimiter=";"))
+    assert rows == [({}, {"a": "1", "b": "2"})]
+
+
+def test_iter_arff(tmp_path):
+    path = tmp_path / "data.arff"
+    path.write_text(
+        "% comment\n"
+        "@RELATION test\n"
+        "@ATTRIBUTE 'x 1' NUMERIC\n"
+        "@attribute x2 string\n"
+        "@attribute class {no, yes}\n"
+        "@data\n"
+        "1.5, 'a, b', yes\n"
+        "?, c, no\n"
+        "{0 2, 1 d}\n"
+    )
+    assert list(rs.iter_arff(path, target="class")) == [
+        ({"x 1": 1.5, "x2": "a, b"}, "yes"),
+        ({"x 1": None, "x2": "c"}, "no"),
+        ({"x 1": 2.0, "x2": "d"}, "no"),
+    ]
+
+
+def test_iter_libsvm(tmp_path):
+    path = tmp_path / "data.libsvm"
+    path.write_text("+1 qid:3 1:0.5 10:-2 # comment\n\n-1 3:1e-3\n")
+    assert list(rs.iter_libsvm(path, target_type=int)) == [
+        ({"1": 0.5, "10": -2.0}, 1),
+        ({"3": 0.001}, -1),
+    ]
+
+
+def test_shuffle():
+    items = list(range(100))
+    shuffled = list(rs.shuffle(iter(items), buffer_size=10, seed=42))
+    assert sorted(shuffled) == items
+    assert shuffled != items
+    assert shuffled == list(rs.shuffle(items, buffer_size=10, seed=42))
+    # An item can't be yielded before the buffer holding it was filled.
+    assert all(x <= i + 10 for i, x in enumerate(shuffled))
+    assert list(rs.shuffle([], buffer_size=3)) == []
+
+
+
+def test_inject_anomalies():
+    stream = [({0: float(i % 10), "label": "a", "flag": True}, i) for i in range(1000)]
+    clean = list(rs.inject_anomalies(stream, rate=0.0))
+    assert [x for x, _ in clean] == [x for x, _ in stream]
+    assert not any(is_anomaly for _, is_anomaly in clean)
+
+    spikes = rs.inject_anomalies(stream, rate=0.05, kinds=["spike"], magnitude=10, seed=1)
+    samples = list(spikes)
+    assert 20 < spikes.n_anomalies < 80
+    assert sum(is_anomaly for _, is_anomaly in samples) == spikes.n_anomalies
+    for (x, is_anomaly), (original, _) in zip(samples, stream):
+        assert (x[0] != original[0]) == is_anomaly
+        assert x["label"] == "a" and x["flag"] is True
+        if is_anomaly:
+            assert abs(x[0] - original[0]) > 20
+    assert samples == list(rs.inject_anomalies(stream, 0.05, ["spike"], magnitude=10, seed=1))
+
+    # A shift lasts `duration` samples, and moves them all in the same direction.
+    shifts = list(rs.inject_anomalies(stream, 0.01, ["shift"], duration=5, seed=2))
+    flags = "".join("1" if is_anomaly else "0" for _, is_anomaly in shifts)
+    assert all(len(run) % 5 == 0 for run in flags.split("0") if run)
+    start = flags.index("1")
+    deltas = [shifts[i][0][0] - stream[i][0][0] for i in range(start, start + 5)]
+    assert all(d > 0 for d in deltas) or all(d < 0 for d in deltas)
+
+    bursts = list(rs.inject_anomalies([x for x, _ in stream], 0.01, ["burst"], seed=3))
+    assert any(is_anomaly for _, is_anomaly in bursts)
+    with pytest.raises(ValueError):
+        rs.inject_anomalies(stream, 0.1, ["dip"])
+
+
+def test_successive_halving():
+    rng = rs.RsRng(seed=42)
+    scores = [rng.random() for _ in range(1000)]
+    labels = [rng.random() < s**3 for s in scores]
+    models = [
+        rs.RsPlattCalibrator(lr=1e-6),
+        rs.RsIsotonicCalibrator(),
+        rs.RsPlattCalibrator(lr=0.05),
+        rs.RsPlattCalibrator(lr=1e-5),
+    ]
+    halving = rs.RsSuccessiveHalving(models, rs.RsLogLoss(), budget=400)
+    # log2(4) = 2 rungs: 4 models for 50 samples, then 2 models until the 100th sample.
+    for i, (x, y) in enumerate(zip(scores, labels)):
+        halving.learn_one(x, y)
+        if i == 49:
+            assert halving.n_rungs == 1 and halving.budget_used == 200
+            ranked = sorted(range(4), key=lambda m: halving.scores()[m])
+            assert halving.survivors == ranked[:2]
+    assert halving.n_rungs == 2 and halving.budget_used == 400
+    assert halving.survivors == [halving.best] and halving.best in (1, 2)
+    assert isinstance(halving.best_model, type(models[halving.best]))
+    assert halving.predict_proba_one(0.5) == halving.best_model.transform_one(0.5)
+    # The models passed are copied.
+    assert models[1].n_blocks == 0
+
+    batch = rs.RsSuccessiveHalving(models, rs.RsLogLoss(), budget=400)
+    batch.learn_many(scores, labels)
+    assert batch == halving
+    with pytest.raises(ValueError):
+        rs.RsSuccessiveHalving(models[:1], rs.RsLogLoss(), budget=400)
+    with pytest.raises(TypeError):
+        rs.RsSuccessiveHalving([rs.RsEWMean(0.5)] * 2, rs.RsLogLoss(), budget=400)
+
+
+@pytest.mark.parametrize("policy", ["epsilon_greedy", "ucb", "thompson"])
+def test_bandit_selection(policy):
+    rng = rs.RsRng(seed=42)
+    scores = [rng.random() for _ in range(3000)]
+    labels = [rng.random() < s**3 for s in scores]
+    models = [rs.RsPlattCalibrator(lr=1e-6), rs.RsIsotonicCalibrator()]
+    bandit = rs.RsBanditSelection(models, rs.RsAccuracy(), policy, burn_in=5, seed=1)
+    assert bandit.best is None and bandit.best_model is None
+    bandit.learn_one(scores[0], labels[0])
+    assert bandit.counts == [1, 1]
+    for x, y in zip(scores[1:], labels[1:]):
+        bandit.learn_one(x, y)
+    assert bandit.policy == policy
+    assert bandit.best == 1 and isinstance(bandit.best_model, rs.RsIsotonicCalibrator)
+    assert bandit.counts[1] > 2 * bandit.counts[0]
+    assert bandit.predict_proba_one(0.5) == bandit.best_model.transform_one(0.5)
+
+    batch = rs.RsBanditSelection(models, rs.RsAccuracy(), policy, burn_in=5, seed=1)
+    batch.learn_many(scores, labels)
+    assert batch == bandit
+    with pytest.raises(ValueError):
+        rs.RsBanditSelection(models, rs.RsLogLoss(), "softmax")
+
+
+def test_welch_t_test():
+    a = [19.1, 20.4, 21.3, 22.0, 18.7, 20.9, 21.7]
+    b = [23.4, 22.1, 24.8, 21.9, 25.3, 23.0, 22.6, 24.1]
+    test = rs.RsWelchTTest()
+    test.update_a(a[0])
+    test.update_b(b[0])
+    assert math.isnan(test.statistic) and math.isnan(test.df) and math.isnan(test.p_value)
+    for x in a[1:]:
+        test.update_a(x)
+    for x in b[1:]:
+        test.update_b(x)
+    # Same values as scipy.stats.ttest_ind(a, b, equal_var=False).
+    assert test.statistic == pytest.approx(-4.3292346921784, rel=1e-9)
+    assert test.df == pytest.approx(12.656830395635, rel=1e-9)
+    assert test.p_value == pytest.approx(0.00086854862324869, rel=1e-6)
+    # The p-value is two-sided, so swapping the samples only flips the sign of t.
+    swapped = rs.RsWelchTTest()
+    for x in a:
+        swapped.update_b(x)
+    for x in b:
+        swapped.update_a(x)
+    assert swapped.statistic == pytest.approx(-test.statistic)
+    assert swapped.p_value == pytest.approx(test.p_value)
+    constant = rs.RsWelchTTest()
+    for _ in range(3):
+        constant.update_a(1.0)
+        constant.update_b(1.0)
+    assert math.isnan(constant.p_value)
+
+
+def test_chi_square_test_of_independence():
+    test = rs.RsChiSquareTest()
+    assert test.statistic == 0.0 and test.df == 0 and test.p_value == 1.0
+    table = {("a", "x"): 10, ("a", "y"): 20, ("a", "z"): 30}
+    table.update({("b", "x"): 25, ("b", "y"): 15, ("b", "z"): 20})
+    for (x, y), w in table.items():
+        test.update(x, y, w)
+    # Same values as scipy.stats.chi2_contingency without Yates' correction, the survival
+    # function of the chi-square distribution with 2 degrees of freedom being exp(-x / 2).
+    assert test.statistic == pytest.approx(9.142857142857142, rel=1e-12)
+    assert test.df == 2
+    assert test.p_value == pytest.approx(math.exp(-test.statistic / 2), rel=1e-9)
+    # With 1 degree of freedom, the survival function is erfc(sqrt(x / 2)).
+    counts, repeated = rs.RsChiSquareTest(), rs.RsChiSquareTest()
+    for x, y, w in [(0, True, 12), (0, False, 5), (1, True, 7), (1, False, 16)]:
+        counts.update(x, y, w)
+        for _ in range(w):
+            repeated.update(x, y)
+    expected = 40 * (12 * 16 - 5 * 7) ** 2 / (17 * 23 * 19 * 21)
+    assert counts.statistic == pytest.approx(expected, rel=1e-12)
+    assert counts.p_value == pytest.approx(math.erfc(math.sqrt(expected / 2)), rel=1e-9)
+    assert repeated.statistic == pytest.approx(counts.statistic, rel=1e-12)
+    # Independent variables give a statistic of 0.
+    independent = rs.RsChiSquareTest()
+    for x in range(3):
+        for y in range(3):
+            independent.update(x, y, 1 + x)
+    assert independent.statistic == pytest.approx(0, abs=1e-12)
+    assert independent.df == 4 and independent.p_value == pytest.approx(1)
+
+
+def test_mann_whitney_u_over_sliding_windows():
+    rng = random.Random(13)
+    stream = [rng.gauss(0, 1) for _ in range(100)] + [rng.gauss(1.5, 1) for _ in range(20)]
+    test = rs.RsMannWhitneyU(30, 20)
+    assert test.statistic == test.z_score == 0.0 and test.p_value == pytest.approx(1)
+    for i, x in enumerate(stream, start=1):
+        test.update(x)
+        recent = stream[max(i - 20, 0) : i]
+        reference = stream[max(i - 50, 0) : max(i - 20, 0)]
+        u = sum((s < r) + 0.5 * (s == r) for r in reference for s in recent)
+        assert test.statistic == pytest.approx(u)
+        if reference:
+            n, m = len(reference), len(recent)
+            z = (u - n * m / 2) / math.sqrt(n * m * (n + m + 1) / 12)
+            assert test.z_score == pytest.approx(z)
+            # Same p-value as scipy.stats.mannwhitneyu without the continuity correction.
+            assert test.p_value == pytest.approx(math.erfc(abs(z) / math.sqrt(2)))
+        if i == 100:
+            assert test.p_value > 0.05
+        # The recent values are now all after the shift and the reference ones before it.
+        if i == 120:
+            assert test.z_score < 0 and test.p_value < 0.001
+    with pytest.raises(ValueError):
+        test.update(math.nan)
+    with pytest.raises(ValueError):
+        rs.RsMannWhitneyU(0, 5)
+
+
+def test_paired_comparison():
+    rng = rs.RsRng(seed=42)
+    pairs = [(round(rng.random(), 1), round(rng.random() + 0.1, 1)) for _ in range(300)]
+    paired = rs.RsPairedComparison()
+    for a, b in pairs:
+        paired.update(a, b)
+    d = [a - b for a, b in pairs]
+    n = len(d)
+    mean = sum(d) / n
+    var = sum((x - mean) ** 2 for x in d) / (n - 1)
+    assert paired.n == n
+    assert math.isclose(paired.t_statistic, mean / math.sqrt(var / n))
+    assert paired.t_p_value < 0.01
+
+    wins = (sum(x < 0 for x in d), sum(x > 0 for x in d), sum(x == 0 for x in d))
+    assert paired.wins == wins
+    m, k = wins[0] + wins[1], min(wins[:2])
+    p = min(1.0, 2 * sum(math.comb(m, i) for i in range(k + 1)) / 2**m)
+    assert math.isclose(paired.sign_p_value, p, rel_tol=1e-6)
+
+    # Signed ranks with mid-ranks for the tied magnitudes, which are many here.
+    nonzero = sorted(abs(x) for x in d if x != 0)
+    ranks = {}
+    for i, x in enumerate(nonzero):
+        ranks.setdefault(x, []).append(i + 1)
+    w_plus = sum(sum(ranks[abs(x)]) / len(ranks[abs(x)]) for x in d if x > 0)
+    assert math.isclose(paired.wilcoxon_statistic, w_plus)
+    m = len(nonzero)
+    ties = sum(len(r) ** 3 - len(r) for r in ranks.values())
+    z = (w_plus - m * (m + 1) / 4) / math.sqrt(m * (m + 1) * (2 * m + 1) / 24 - ties / 48)
+    assert math.isclose(paired.wilcoxon_z_score, z)
+    assert paired.wilcoxon_p_value < 0.01
+    assert paired.summary()["wins"] == wins
+
+    same = rs.RsPairedComparison()
+    for _ in range(5):
+        same.update(1.0, 1.0)
+    assert same.sign_p_value == 1.0 and math.isnan(same.t_statistic)
+
+
+def test_link_kernels_keep_the_keys_of_a_dict():
+    scores = {"b": -2.0, "a": 0.0, "c": 3.0}
+    probas = rs.sigmoid(scores)
+    assert list(probas) == ["b", "a", "c"]
+    assert probas == pytest.approx({k: 1 / (1 + math.exp(-v)) for k, v in scores.items()})
+    total = sum(math.exp(v) for v in scores.values())
+    assert rs.softmax(scores) == pytest.approx({k: math.exp(v) / total for k, v in scores.items()})
+    assert rs.logsumexp(scores) == pytest.approx(math.log(total))
+    assert rs.clip(scores, -1.0, 1.0) == {"b": -1.0, "a": 0.0, "c": 1.0}
+
+
+def test_link_kernels_dont_overflow():
+    assert rs.sigmoid({"x": 1_000.0, "y": -1_000.0}) == {"x": 1.0, "y": 0.0}
+    assert rs.logsumexp([1_000.0, 1_000.0]) == pytest.approx(1_000 + math.log(2))
+    assert rs.logsumexp([]) == -math.inf
+    assert rs.softmax({"x": 1_000.0, "y": 1_000.0 + math.log(3)}) == pytest.approx(
+        {"x": 0.25, "y": 0.75}
+    )
+    # The mass goes to the largest values when the sum is infinite.
+    inf = math.inf
+    assert rs.softmax({"x": -inf, "y": -inf}) == {"x": 0.5, "y": 0.5}
+    assert rs.softmax({"x": inf, "y": 1.0, "z": inf}) == {"x": 0.5, "y": 0.0, "z": 0.5}
+    for lower, upper in [(1.0, 0.0), (float("nan"), 1.0)]:
+        with pytest.raises(ValueError, match="lower should not be greater than upper"):
+            rs.clip([0.5], lower, upper)
+
+
+def test_link_kernels_of_a_batch_match_those_of_a_dict():
+    values = [-3.0, -0.5, 0.0, 2.0, 40.0]
+    as_dict = dict(enumerate(values))
+    batch = array.array("d", values)
+    assert rs.logsumexp(batch) == rs.logsumexp(as_dict)
+    assert rs.sigmoid(batch).tolist() == list(rs.sigmoid(as_dict).values())
+    assert rs.softmax(batch).tolist() == list(rs.softmax(as_dict).values())
+    assert rs.clip(values, 0.0, 1.0).tolist() == [0.0, 0.0, 0.0, 1.0, 1.0]
+
+
+def test_ensemble_aggregation():
+    assert rs.majority_vote(["a", "b", "b", None, "a"]) == "a"
+    assert rs.majority_vote(["a", "b", "b"], weights=[3, 1, 1]) == "a"
+    assert rs.majority_vote([{True: 0.8, False: 0.2}, {False: 0.6, True: 0.4}, True]) is True
+    assert rs.majority_vote([]) is None and rs.majority_vote([None]) is None
+
+    probas = [{"a": 0.2, "b": 0.8}, {"b": 0.5, "c": 0.5}]
+    assert rs.average_proba(probas) == pytest.approx({"a": 0.1, "b": 0.65, "c": 0.25})
+    assert list(rs.average_proba(probas)) == ["a", "b", "c"]
+    assert rs.average_proba(probas, weights=[0, 1]) == {"a": 0.0, "b": 0.5, "c": 0.5}
+    assert rs.average_proba([]) == {}
+
+    assert rs.average([1.0, 2.0, 6.0]) == 3.0
+    assert rs.average([1.0, 3.0], weights=[3, 1]) == 1.5
+    assert rs.average([]) == 0.0
+    # A softmax of the performances, the better member getting e / (1 + e) of the weight.
+    e = math.e
+    assert rs.average([0.0, 1.0], [0.0, 1.0], temperature=1.0) == pytest.approx(e / (1 + e))
+    assert rs.average([0.0, 1.0], [0.0, 100.0], temperature=1.0) == pytest.approx(1.0)
+    winner = rs.majority_vote(["a", "b"], [0.9, 0.1], temperature=0.1)
+    assert winner == "a"
+
+    with pytest.raises(ValueError):
+        rs.average([1.0, 2.0], weights=[1.0])
+    with pytest.raises(ValueError):
+        rs.average([1.0, 2.0], weights=[1.0, -1.0])
+    with pytest.raises(ValueError):
+        rs.average([1.0, 2.0], temperature=1.0)
+    with pytest.raises(ValueError):
+        rs.average([1.0, 2.0], weights=[1.0, 2.0], temperature=0.0)
+
+
+def test_isotonic_calibrator_pools_the_violators():
+    iso = rs.RsIsotonicCalibrator(max_blocks=50)
+    assert iso.transform_one(0.3) == 0.3
+    iso.learn_one(0.2, True)
+    iso.learn_one(0.4, False)
+    assert iso.n_blocks == 1
+    assert iso.transform_one(0.9) == 0.5
+    iso.learn_one(0.8, True)
+    assert iso.n_blocks == 2
+    # Interpolated between the centers of the blocks, at 0.3 and 0.8.
+    assert iso.transform_one(0.55) == pytest.approx(0.75)
+
+    rng = random.Random(26)
+    coarse = rs.RsIsotonicCalibrator(max_blocks=5)
+    for _ in range(20_000):
+        p = rng.uniform(0.01, 0.99)
+        y = rng.random() < p * p
+        iso.learn_one(p, y)
+        coarse.learn_one(p, y)
+    assert 1 < iso.n_blocks <= 50
+    assert coarse.n_blocks == 5
+    grid = [i / 20 for i in range(1, 20)]
+    calibrated = [iso.transform_one(p) for p in grid]
+    assert calibrated == sorted(calibrated)
+    assert calibrated == pytest.approx([p * p for p in grid], abs=0.05)
+
+
+def test_platt_calibrator_fits_the_logits():
+    platt = rs.RsPlattCalibrator()
+    assert platt.coefficients == (1.0, 0.0)
+    assert platt.transform_one(0.3) == pytest.approx(0.3)
+    rng = random.Random(26)
+    for _ in range(20_000):
+        p = rng.uniform(0.01, 0.99)
+        z = 2 * math.log(p / (1 - p)) - 1
+        platt.learn_one(p, rng.random() < 1 / (1 + math.exp(-z)))
+    a, b = platt.coefficients
+    assert a == pytest.approx(2, abs=0.3)
+    assert b == pytest.approx(-1, abs=0.3)
+    assert platt.transform_one(0.5) == pytest.approx(1 / (1 + math.exp(-b)))
+
+
+def test_calibrators_reject_bad_arguments():
+    for calibrator in [rs.RsIsotonicCalibrator(), rs.RsPlattCalibrator()]:
+        for p in [-0.1, 1.1, float("nan")]:
+            with pytest.raises(ValueError, match="p should be between 0 and 1"):
+                calibrator.learn_one(p, True)
+            with pytest.raises(ValueError, match="p should be between 0 and 1"):
+                calibrator.transform_one(p)
+    with pytest.raises(ValueError, match="max_blocks should be at least 2"):
+        rs.RsIsotonicCalibrator(max_blocks=1)
+    with pytest.raises(ValueError, match="lr should be positive"):
+        rs.RsPlattCalibrator(lr=0)
+
+
+def test_logistic_regression():
+    model = rs.RsLogisticRegression(lr=0.5)
+    assert model.predict_proba_one({"a": 1.0}) == {False: 0.5, True: 0.5}
+    for _ in range(200):
+        model.learn_one({"a": 1.0}, True)
+        model.learn_one({"a": -1.0}, False)
+    assert model.predict_one({"a": 2.0}) and not model.predict_one({"a": -2.0})
+    assert model.weights["a"] > 0 and model.predict_proba_one({"a": 1.0})[True] > 0.9
+    with pytest.raises(ValueError):
+        rs.RsLogisticRegression(lr=0.0)
+
+
+@pytest.mark.parametrize("decoding", ["soft", "hamming"])
+def test_output_code_classifier(decoding):
+    rng = rs.RsRng(seed=3)
+    centers = {"a": (0.0, 0.0), "b": (3.0, 0.0), "c": (0.0, 3.0), "d": (3.0, 3.0)}
+
+    def sample():
+        label = "abcd"[rng.randrange(4)]
+        cx, cy = centers[label]
+        return {"x": cx + rng.random() - 0.5, "y": cy + rng.random() - 0.5}, label
+
+    # Random codes can coincide, so the code book is seeded to keep the classes apart.
+    ecoc = rs.RsOutputCodeClassifier(
+        rs.RsLogisticRegression(lr=0.1), 10, decoding=decoding, seed=1
+    )
+    assert ecoc.predict_one({"x": 0.0, "y": 0.0}) is None
+    for _ in range(3000):
+        ecoc.learn_one(*sample())
+    correct = 0
+    for _ in range(500):
+        x, y = sample()
+        correct += ecoc.predict_one(x) == y
+    assert correct > 450
+    assert set(ecoc.code_book) == set(centers)
+    assert all(len(code) == ecoc.code_size for code in ecoc.code_book.values())
+    distances = ecoc.distances({"x": 3.0, "y": 3.0})
+    assert min(distances, key=distances.get) == "d"
+
+
+def test_output_code_classifier_exact_codes():
+    ecoc = rs.RsOutputCodeClassifier(rs.RsLogisticRegression(), 2, "exact", seed=1)
+    for label in range(4):
+        ecoc.learn_one({"x": 1.0}, label)
+    assert sorted(map(tuple, ecoc.code_book.values())) == [(0, 0), (0, 1), (1, 0), (1, 1)]
+    with pytest.raises(ValueError):
+        ecoc.learn_one({"x": 1.0}, 4)
+    with pytest.raises(ValueError):
+        rs.RsOutputCodeClassifier(rs.RsLogisticRegression(), 2, decoding="euclidean")
+
+
+def test_classifier_chain():
+    rng = rs.RsRng(seed=5)
+    chain = rs.RsClassifierChain(rs.RsLogisticRegression(lr=0.5))
+    assert chain.predict_one({"x": 1.0}) == {}
+    for _ in range(2000):
+        x = {"x": rng.random() * 2 - 1}
+        a = x["x"] > 0
+        # The model of "b" learns from the label of "a", along with x.
+        chain.learn_one(x, {"a": a, "b": not a})
+    assert chain.order == ["a", "b"]
+    for x, a in ((0.8, True), (-0.8, False)):
+        prediction = chain.predict_one({"x": x})
+        assert prediction == {"a": a, "b": not a}
+        assert list(prediction) == ["a", "b"]
+        proba = chain.predict_proba_one({"x": x})
+        assert proba["a"][a] > 0.5 and proba["b"][not a] > 0.5
+    with pytest.raises(ValueError):
+        chain.learn_one({"x": 1.0}, {"a": True})
+
+
+def test_regressor_chain():
+    rng = rs.RsRng(seed=6)
+    chain = rs.RsRegressorChain(rs.RsLinearRegression(lr=0.05), order=["u", "v"])
+    for _ in range(5000):
+        x = {"x": rng.random()}
+        u = 2 * x["x"] + 1
+        chain.learn_one(x, {"v": u - 3, "u": u})
+    prediction = chain.predict_one({"x": 0.5})
+    assert list(prediction) == ["u", "v"]
+    assert abs(prediction["u"] - 2.0) < 0.05 and abs(prediction["v"] + 1.0) < 0.05
+
+
+@pytest.mark.parametrize("sampler", ["under", "over"])
+def test_random_samplers(sampler):
+    Sampler = rs.RsRandomUnderSampler if sampler == "under" else rs.RsRandomOverSampler
+    rng = rs.RsRng(seed=7)
+    plain = rs.RsLogisticRegression(lr=0.01)
+    balanced = Sampler(plain, {False: 0.5, True: 0.5}, seed=1)
+    x = {"x": 1.0}
+    for _ in range(5000):
+        y = rng.random() < 0.1
+        plain.learn_one(x, y)
+        balanced.learn_one(x, y)
+    # The plain model learns the base rate, and the wrapped one that of the balanced stream.
+    assert plain.predict_proba_one(x)[True] < 0.2
+    assert 0.35 < balanced.predict_proba_one(x)[True] < 0.65
+    assert isinstance(balanced.classifier, rs.RsLogisticRegression)
+    assert balanced.predict_one(x) in (False, True)
+
+    ecoc = rs.RsOutputCodeClassifier(rs.RsLogisticRegression(), 4, seed=1)
+    multi = Sampler(ecoc, {"a": 0.5, "b": 0.25, "c": 0.25}, seed=2)
+    for i in range(100):
+        multi.learn_one({"x": float(i % 3)}, "abc"[i % 3])
+    assert sum(multi.predict_proba_one({"x": 1.0}).values()) == pytest.approx(1.0)
+    with pytest.raises(ValueError):
+        multi.learn_one({"x": 1.0}, "d")
+    with pytest.raises(ValueError):
+        balanced.learn_one(x, "a")
+    with pytest.raises(TypeError):
+        Sampler(rs.RsLinearRegression(), {0: 1.0})
+
+
+def test_hard_sampling_classifier():
+    model = rs.RsHardSamplingClassifier(rs.RsLogisticRegression(lr=0.1), size=10, p=0.5, seed=1)
+    rng = rs.RsRng(seed=8)
+    for _ in range(500):
+        x = {"x": rng.random() * 2 - 1}
+        model.learn_one(x, x["x"] > 0)
+    buffer = model.buffer
+    assert len(buffer) == 10
+    losses = [loss for loss, _, _ in buffer]
+    assert losses == sorted(losses, reverse=True)
+    # The examples keep their labels, given back as bools.
+    assert all(isinstance(y, bool) and y == (x["x"] > 0) for _, x, y in buffer)
+    assert model.predict_one({"x": 0.9}) is True and model.predict_one({"x": -0.9}) is False
+    assert model.predict_proba_one({"x": 0.9})[True] > 0.5
+    with pytest.raises(ValueError):
+        rs.RsHardSamplingClassifier(rs.RsLogisticRegression(), size=10, p=1.5)
+
+
+@pytest.mark.parametrize("bagging_method", ["bag", "me", "half", "wt", "subag"])
+def test_leveraging_bagging(bagging_method):
+    rng = rs.RsRng(seed=9)
+    stream = []
+    for i in range(6000):
+        x = {"x": rng.random() * 2 - 1}
+        # The concept flips halfway through.
+        stream.append((x, (x["x"] > 0) == (i < 3000)))
+
+    def run(parallel):
+        model = rs.RsLeveragingBaggingClassifier(
+            rs.RsLogisticRegression(lr=0.1),
+            n_models=5,
+            bagging_method=bagging_method,
+            seed=1,
+            parallel=parallel,
+        )
+        correct = 0
+        for i, (x, y) in enumerate(stream):
+            correct += i >= 5000 and model.predict_one(x) == y
+            model.learn_one(x, y)
+        return model, correct
+
+    model, correct = run(parallel=False)
+    assert model.n_replacements > 0
+    assert correct > 900
+    assert model.bagging_method == bagging_method and model.n_models == len(model.models) == 5
+    assert sum(model.predict_proba_one({"x": 0.5}).values()) == pytest.approx(1.0)
+    parallel, _ = run(parallel=True)
+    assert parallel.errors == model.errors
+    assert parallel.n_replacements == model.n_replacements
+
+
+def test_adwin_bagging():
+    ecoc = rs.RsOutputCodeClassifier(rs.RsLogisticRegression(lr=0.1), 6, seed=1)
+    model = rs.RsADWINBaggingClassifier(ecoc, n_models=3, seed=2)
+    assert model.predict_one({"x": 0.0}) is None
+    rng = rs.RsRng(seed=10)
+    for _ in range(2000):
+        x = rng.random() * 3
+        model.learn_one({"x": x, "y": 3 - x}, "abc"[int(x)])
+    assert model.predict_one({"x": 0.2, "y": 2.8}) == "a"
+    assert model.predict_one({"x": 2.8, "y": 0.2}) == "c"
+    assert all(0 <= error <= 1 for error in model.errors)
+    with pytest.raises(ValueError):
+        rs.RsLeveragingBaggingClassifier(rs.RsLogisticRegression(), bagging_method="boost")
+
+
+def test_set_seed_makes_the_generators_reproducible():
+    def draws(rng):
+        return [rng.random() for _ in range(5)] + [rng.randrange(10) for _ in range(5)]
+
+    rs.set_seed(7)
+    first = [draws(rs.RsRng()), draws(rs.RsRng())]
+    rs.set_seed(7)
+    own = rs.RsRng(seed=3)
+    assert [draws(rs.RsRng()), draws(rs.RsRng())] == first
+    assert first[0] != first[1]
+    # A generator with its own seed doesn't draw from the module-level one.
+    assert draws(own) == draws(rs.RsRng(seed=3))
+    rs.set_seed(8)
+    assert draws(rs.RsRng()) != first[0]
+
+    rng = rs.RsRng(seed=0)
+    draws(rng)
+    restored = pickle.loads(pickle.dumps(rng))
+    assert draws(restored) == draws(rng)
+    rs.set_seed()
+
+
+def test_rng_draws_are_uniform():
+    rng = rs.RsRng(seed=11)
+    floats = [rng.random() for _ in range(10_000)]
+    assert all(0 <= x < 1 for x in floats)
+    assert statistics.mean(floats) == pytest.approx(0.5, abs=0.01)
+    assert statistics.variance(floats) == pytest.approx(1 / 12, abs=0.005)
+    counts = collections.Counter(rng.randrange(6) for _ in range(6_000))
+    assert sorted(counts) == list(range(6))
+    assert all(abs(c - 1_000) < 100 for c in counts.values())
+    assert rng.randrange(1) == 0
+    with pytest.raises(ValueError, match="n should be at least 1"):
+        rng.randrange(0)
+    with pytest.raises(OverflowError):
+        rng.randrange(-1)
+
+
+@pytest.mark.parametrize("lam", [1.0, 6.0, 30.0])
+def test_poisson_weights_of_a_batch(lam):
+    rng = rs.RsRng(seed=25)
+    batch = rng.poisson(10, lam=lam, n_samples=2_000)
+    assert batch.shape == (2_000, 10)
+    weights = [w for row in batch.tolist() for w in row]
+    assert statistics.fmean(weights) == pytest.approx(lam, rel=0.03)
+    assert statistics.pvariance(weights) == pytest.approx(lam, rel=0.1)
+    if lam == 1.0:
+        assert weights.count(0) / len(weights) == pytest.approx(math.exp(-1), abs=0.01)
+    # A batch draws the same weights as one call per sample.
+    rng, single = rs.RsRng(seed=25), rs.RsRng(seed=25)
+    rows = rng.poisson(4, lam=lam, n_samples=3).tolist()
+    assert rows == [single.poisson(4, lam=lam).tolist() for _ in range(3)]
+
+
+def test_poisson_weights_of_zero_and_bad_rates():
+    rng = rs.RsRng(seed=0)
+    for lam in [-1.0, float("nan"), float("inf")]:
+        with pytest.raises(ValueError, match="lam should be finite and non-negative"):
+            rng.poisson(3, lam=lam)
+    assert rng.poisson(3, lam=0.0).tolist() == [0, 0, 0]
+    assert rng.poisson(0, n_samples=2).shape == (2, 0)
+
+
+def test_generators_are_reproducible():
+    makers = (
+        rs.RsSEA,
+        rs.RsHyperplane,
+        rs.RsAgrawal,
+        lambda seed: rs.RsRandomRBF(seed_model=seed, seed_sample=seed),
+        lambda seed: rs.RsRandomTree(seed_tree=seed, seed_sample=seed),
+        rs.RsLED,
+        rs.RsSTAGGER,
+        rs.RsConceptDriftStream,
+    )
+    for make in makers:
+        assert make(seed=7).take(20) == make(seed=7).take(20)
+        assert make(seed=7).take(20) != make(seed=8).take(20)
+        generator = make(seed=7)
+        generator.take(10)
+        restored = pickle.loads(pickle.dumps(generator))
+        assert restored.take(10) == make(seed=7).take(20)[10:]
+
+
+def test_sea():
+    samples = rs.RsSEA(variant=2, seed=42).take(1000)
+    assert all(list(x) == [0, 1, 2] and all(0 <= v < 10 for v in x.values()) for x, _ in samples)
+    assert all(y == (x[0] + x[1] > 7) for x, y in samples)
+    noisy = rs.RsSEA(variant=2, noise=0.2, seed=42).take(1000)
+    flipped = sum(y != (x[0] + x[1] > 7) for x, y in noisy)
+    assert 150 < flipped < 250
+    x, y = next(iter(rs.RsSEA(seed=42)))
+    assert isinstance(y, bool)
+    with pytest.raises(ValueError):
+        rs.RsSEA(variant=4)
+
+
+def test_hyperplane():
+    samples = rs.RsHyperplane(seed=42, n_features=4, noise_percentage=0.0).take(1000)
+    assert all(list(x) == [0, 1, 2, 3] and all(0 <= v < 1 for v in x.values()) for x, _ in samples)
+    assert {y for _, y in samples} == {0, 1}
+    with pytest.raises(ValueError):
+        rs.RsHyperplane(n_features=2, n_drift_features=3)
+    with pytest.raises(ValueError):
+        rs.RsHyperplane(sigma=1.5)
+
+
+def test_agrawal():
+    samples = rs.RsAgrawal(classification_function=0, seed=42).take(1000)
+    assert list(samples[0][0]) == [
+        "salary",
+        "commission",
+        "age",
+        "elevel",
+        "car",
+        "zipcode",
+        "hvalue",
+        "hyears",
+        "loan",
+    ]
+    assert all(y == int(x["age"] < 40 or x["age"] >= 60) for x, y in samples)
+    assert all(x["commission"] == 0 for x, _ in samples if x["salary"] >= 75000)
+    balanced = rs.RsAgrawal(classification_function=2, balance_classes=True, seed=42).take(10)
+    assert [y for _, y in balanced] == [1, 0] * 5
+    perturbed = rs.RsAgrawal(perturbation=0.5, seed=42).take(1000)
+    assert all(20 <= x["age"] <= 80 and 0 <= x["loan"] <= 500000 for x, _ in perturbed)
+    with pytest.raises(ValueError):
+        rs.RsAgrawal(classification_function=10)
+
+
+def test_concept_drift_stream():
+    before, after = rs.RsSEA(variant=0, seed=1), rs.RsSEA(variant=3, seed=2)
+    never = rs.RsConceptDriftStream(before, after, position=10**6, width=1, seed=3)
+    assert never.take(100) == before.take(100)
+    always = rs.RsConceptDriftStream(before, after, position=0, width=1, seed=3)
+    assert always.take(100) == after.take(100)
+
+    # The children are copies, and several drifts are chained by nesting the streams.
+    first = rs.RsConceptDriftStream(before, after, position=1000, width=100, seed=3)
+    second = rs.RsSEA(variant=2, seed=4)
+    stream = rs.RsConceptDriftStream(first, second, position=2000, width=1, seed=3)
+    samples = stream.take(3000)
+    for start, end, threshold in ((0, 900, 8), (1100, 1990, 9.5), (2010, 3000, 7)):
+        assert all(y == (x[0] + x[1] > threshold) for x, y in samples[start:end])
+
+    gradual = rs.RsConceptDriftStream(before, after, position=3, width=4, seed=3)
+    gradual.take(2)
+    assert gradual.drift_probability() == 0.5
+    assert rs.RsConceptDriftStream(alpha=45.0).width == 1
+    assert rs.RsConceptDriftStream(alpha=1.0).width == 57
+    with pytest.raises(ValueError):
+        rs.RsConceptDriftStream(alpha=0.0)
+    with pytest.raises(ValueError):
+        rs.RsConceptDriftStream(before, rs.RsHyperplane(n_features=4))
+    with pytest.raises(TypeError):
+        rs.RsConceptDriftStream(before, iter([]))
+
+
+def test_random_rbf():
+    static = rs.RsRandomRBF(seed_model=1, seed_sample=2, n_classes=3, n_features=4).take(1000)
+    assert all(list(x) == [0, 1, 2, 3] for x, _ in static)
+    assert {y for _, y in static} == {0, 1, 2}
+    # Without moving centroids, the speed doesn't matter.
+    still = rs.RsRandomRBF(1, 2, 3, 4, change_speed=0.5, n_drift_centroids=0).take(1000)
+    assert still == static
+    moving = rs.RsRandomRBF(1, 2, 3, 4, change_speed=0.5).take(1000)
+    assert moving[0][1] == static[0][1] and moving != static
+    with pytest.raises(ValueError):
+        rs.RsRandomRBF(n_centroids=0)
+
+
+def test_random_tree():
+    tree = rs.RsRandomTree(seed_tree=3, seed_sample=4, n_num_features=1, n_cat_features=2)
+    x, _ = next(tree)
+    assert list(x) == ["x_num_0", "x_cat_0", "x_cat_1"]
+    assert 1 <= tree.depth <= 5
+    assert all(x["x_cat_0"] in range(5) for x, _ in tree.take(100))
+
+    # The label is a function of the features.
+    categorical = rs.RsRandomTree(
+        seed_tree=3, seed_sample=4, n_classes=4, n_num_features=0, n_cat_features=3
+    )
+    labels = {}
+    for x, y in categorical.take(1000):
+        assert labels.setdefault(tuple(x.values()), y) == y
+    assert len(set(labels.values())) > 1
+    # Branches don't split twice on a categorical feature, so they end when there are none left.
+    assert categorical.depth <= 3
+
+    full = rs.RsRandomTree(seed_tree=3, max_tree_depth=4, first_leaf_level=4)
+    assert full.depth == 4
+    with pytest.raises(ValueError):
+        rs.RsRandomTree(n_num_features=0, n_cat_features=0)
+
+
+def test_led():
+    digits = [
+        [1, 1, 1, 0, 1, 1, 1],
+        [0, 0, 1, 0, 0, 1, 0],
+        [1, 0, 1, 1, 1, 0, 1],
+        [1, 0, 1, 1, 0, 1, 1],
+        [0, 1, 1, 1, 0, 1, 0],
+        [1, 1, 0, 1, 0, 1, 1],
+        [1, 1, 0, 1, 1, 1, 1],
+        [1, 0, 1, 0, 0, 1, 0],
+        [1, 1, 1, 1, 1, 1, 1],
+        [1, 1, 1, 1, 0, 1, 1],
+    ]
+    samples = rs.RsLED(seed=42).take(500)
+    assert all(list(x.values()) == digits[y] for x, y in samples)
+    assert {y for _, y in samples} == set(range(10))
+
+    noisy = rs.RsLED(seed=42, noise_percentage=0.11).take(2000)
+    flipped = sum(x[i] != digits[y][i] for x, y in noisy for i in range(7))
+    assert 0.05 < flipped / 14000 < 0.15
+
+    # The drifting segments are swapped with irrelevant bits.
+    drifting = rs.RsLED(seed=42, irrelevant_features=True, n_drift_features=4).take(500)
+    assert all(list(x) == list(range(24)) for x, _ in drifting)
+    in_place = [i for i in range(7) if all(x[i] == digits[y][i] for x, y in drifting)]
+    assert len(in_place) == 3
+    with pytest.raises(ValueError):
+        rs.RsLED(n_drift_features=8)
+
+
+def test_stagger():
+    samples = rs.RsSTAGGER(classification_function=1, seed=42).take(500)
+    assert all(list(x) == ["size", "color", "shape"] for x, _ in samples)
+    assert all(y == int(x["color"] == 2 or x["shape"] == 0) for x, y in samples)
+    balanced = rs.RsSTAGGER(seed=42, balance_classes=True).take(10)
+    assert [y for _, y in balanced] == [1, 0] * 5
+    noisy = rs.RsSTAGGER(classification_function=2, seed=42, noise=0.2).take(2000)
+    flipped = sum(y != int(x["size"] > 0) for x, y in noisy)
+    assert 300 < flipped < 500
+
+    stagger = rs.RsSTAGGER(seed=42)
+    stagger.generate_drift()
+    assert stagger.classification_function in (1, 2)
+    with pytest.raises(ValueError):
+        rs.RsSTAGGER(classification_function=3)
+
+
+def test_nearest_neighbor_distances():
+    x = array.array("d", [1.0, 2.0])
+    assert rs.get_distance(x, [4.0, -2.0]) == 25.0
+    assert rs.get_1_to_n_distances(x, [[1.0, 2.0], [4.0, -2.0], [0.0, 0.0]]).tolist() == [
+        0.0,
+        25.0,
+        5.0,
+    ]
+    assert rs.get_1_to_n_distances(x, []).tolist() == []
+    with pytest.raises(ValueError):
+        rs.get_distance(x, [1.0])
+    with pytest.raises(ValueError):
+        rs.get_1_to_n_distances(x, [[1.0, 2.0, 3.0]])
+
+
+@pytest.mark.parametrize(
+    "metric, p, expected",
+    [
+        ("sqeuclidean", 2.0, 25.0),
+        ("euclidean", 2.0, 5.0),
+        ("manhattan", 2.0, 7.0),
+        ("chebyshev", 2.0, 4.0),
+        ("minkowski", 1.0, 7.0),
+        ("minkowski", 2.0, 5.0),
+        ("minkowski", 3.0, (3**3 + 4**3) ** (1 / 3)),
+        ("minkowski", math.inf, 4.0),
+        # The two samples are orthogonal.
+        ("cosine", 2.0, 1.0),
+    ],
+)
+def test_distance_metrics(metric, p, expected):
+    a, b = [1.0, 2.0], [4.0, -2.0]
+    assert rs.get_distance(a, b, metric, p) == pytest.approx(expected)
+    assert rs.get_1_to_n_distances(a, [a, b], metric=metric, p=p).tolist() == pytest.approx(
+        [0.0, expected]
+    )
+
+
+def test_distance_metrics_reject_bad_arguments():
+    assert rs.get_distance([0.0, 0.0], [1.0, 2.0], "cosine") == 1.0
+    with pytest.raises(ValueError):
+        rs.get_distance([1.0], [2.0], "hamming")
+    with pytest.raises(ValueError):
+        rs.get_distance([1.0], [2.0], "minkowski", p=0.5)
+
+
+def test_parallel_distances_match_the_serial_ones():
+    rng = random.Random(42)
+    x = [rng.random() for _ in range(20)]
+    samples = [[rng.random() for _ in range(20)] for _ in range(3000)]
+    serial = rs.get_1_to_n_distances(x, samples, "manhattan").tolist()
+    for n_jobs in (-1, 2, 3):
+        assert rs.get_1_to_n_distances(x, samples, "manhattan", n_jobs=n_jobs).tolist() == serial
+    for n_jobs in (0, -2):
+        with pytest.raises(ValueError):
+            rs.get_1_to_n_distances(x, samples, n_jobs=n_jobs)
+
+
+def test_window_evicts_and_searches():
+    window = rs.RsWindow(3)
+    assert window.search({"a": 0.0}, 2) == ([], [])
+    assert window.append({"a": 0.0, "b": 0.0}, 0) is None
+    assert window.append({"a": 3.0, "b": 4.0}, 1) is None
+    assert window.append({"a": 1.0, "b": 0.0}, 2) is None
+    assert window.append({"a": 6.0, "b": 8.0}, 3) == 0
+    assert len(window) == 3
+    assert window.labels() == [1, 2, 3]
+    assert window[0] == ({"a": 3.0, "b": 4.0}, 1)
+    assert window[-1] == ({"a": 6.0, "b": 8.0}, 3)
+    with pytest.raises(IndexError):
+        window[3]
+
+    indices, distances = window.search({"a": 0.0, "b": 0.0}, 2)
+    assert indices == [1, 0]
+    assert distances == pytest.approx([1.0, 5.0])
+    # The missing features are read as 0 and the new ones are ignored.
+    assert window.search({"b": 8.0, "c": 1.0}, 5)[0] == [0, 2, 1]
+    window.clear()
+    assert len(window) == 0
+    with pytest.raises(ValueError):
+        rs.RsWindow(0)
+
+
+def test_window_search_matches_brute_force():
+    rng = random.Random(42)
+    window = rs.RsWindow(50, metric="chebyshev")
+    xs = []
+    for i in range(120):
+        xs.append({"a": rng.random(), "b": rng.random()})
+        window.append(xs[-1], i % 4)
+    recent = xs[-50:]
+    query = {"a": 0.5, "b": 0.5}
+    distances = [max(abs(x["a"] - 0.5), abs(x["b"] - 0.5)) for x in recent]
+    expected = sorted(range(50), key=lambda i: (distances[i], i))[:7]
+    assert window.search(query, 7) == (expected, [distances[i] for i in expected])
+
+
+@pytest.mark.parametrize("metric", ["euclidean", "sqeuclidean", "manhattan", "chebyshev"])
+def test_kd_tree_matches_brute_force(metric):
+    rng = random.Random(42)
+    tree = rs.RsKDTree(metric=metric)
+    window = rs.RsWindow(300, metric=metric)
+    for i in range(2000):
+        # Rounded so that there are ties, which should go to the oldest samples as well.
+        x = {"a": round(rng.random(), 2), "b": round(rng.gauss(0, 1), 2), "c": float(i % 3)}
+        tree.insert(x, i % 4)
+        window.append(x, i % 4)
+        if len(tree) > 300:
+            assert tree.remove_oldest() == (i - 300) % 4
+        if i % 97 == 0:
+            query = {"a": rng.random(), "b": rng.gauss(0, 1), "c": 1.0}
+            assert tree.query(query, 10) == window.search(query, 10)
+    assert tree.labels() == window.labels()
+    assert tree[0] == window[0]
+
+
+def test_kd_tree_removes_the_oldest_first():
+    tree = rs.RsKDTree()
+    assert tree.remove_oldest() is None
+    assert tree.query({"a": 0.0}, 3) == ([], [])
+    for i in range(5):
+        tree.insert({"a": float(i)}, str(i))
+    assert tree.remove_oldest() == "0"
+    assert tree.query({"a": 0.0}, 2) == ([0, 1], [1.0, 2.0])
+    assert tree.query({"a": 0.0}, 10)[0] == [0, 1, 2, 3]
+    for _ in range(4):
+        tree.remove_oldest()
+    assert len(tree) == 0
+    tree.insert({"a": 7.0}, "7")
+    assert tree.query({"a": 0.0}, 1) == ([0], [7.0])
+    with pytest.raises(ValueError):
+        rs.RsKDTree(metric="cosine")
+
+
+# The Cauchy projections of the Manhattan distance are heavy-tailed, hence wider buckets.
+@pytest.mark.parametrize(
+    "metric, bucket_width", [("euclidean", 4.0), ("manhattan", 8.0), ("cosine", 4.0)]
+)
+def test_lsh_index_finds_most_nearest_neighbors(metric, bucket_width):
+    rng = random.Random(42)
+    index = rs.RsLSHIndex(500, 8, 4, bucket_width=bucket_width, metric=metric, seed=1)
+    window = rs.RsWindow(500, metric=metric)
+    for i in range(1500):
+        x = {f"x{j}": rng.gauss(0, 1) for j in range(8)}
+        index.insert(x, i % 3)
+        window.append(x, i % 3)
+    assert index.labels() == window.labels()
+    assert index[-1] == window[-1]
+    found = found_with_probes = 0
+    for _ in range(50):
+        query = {f"x{j}": rng.gauss(0, 1) for j in range(8)}
+        expected = set(window.search(query, 10)[0])
+        indices, distances = index.query(query, 10)
+        assert distances == sorted(distances)
+        found += len(expected.intersection(indices))
+        found_with_probes += len(expected.intersection(index.query(query, 10, probes=4)[0]))
+    assert found_with_probes >= found
+    assert found_with_probes > 0.6 * 500
+
+
+def test_lsh_index_evicts_the_oldest_first():
+    index = rs.RsLSHIndex(3, n_tables=2, n_hashes=1, bucket_width=100.0, seed=1)
+    assert index.query({"a": 0.0}, 3) == ([], [])
+    assert index.insert({"a": 0.0}, "0") is None
+    for i in range(1, 5):
+        index.insert({"a": float(i)}, str(i))
+    assert index.labels() == ["2", "3", "4"]
+    # The buckets are wide enough to hold every sample, but not the evicted ones.
+    assert index.query({"a": 0.0}, 5) == ([0, 1, 2], [2.0, 3.0, 4.0])
+    assert index.insert({"a": 5.0}, "5") == "2"
+    with pytest.raises(ValueError):
+        rs.RsLSHIndex(3, metric="chebyshev")
+    with pytest.raises(ValueError):
+        rs.RsLSHIndex(3, bucket_width=0.0)
+    with pytest.raises(ValueError):
+        rs.RsLSHIndex(0)
+
+
+def test_adaptive_mean_and_var_forget_the_values_before_a_drift():
+    rng = random.Random(24)
+    mean, var = rs.RsAdaptiveMean(), rs.RsAdaptiveVar()
+    stream = [rng.gauss(0, 1) for _ in range(1_000)]
+    for x in stream:
+        mean.update(x)
+        var.update(x)
+    assert mean.n_detections == 0 and mean.width == 1_000
+    assert mean.get() == pytest.approx(statistics.fmean(stream))
+    assert var.get() == pytest.approx(statistics.pvariance(stream))
+
+    detected = []
+    for i in range(1_000):
+        x = rng.gauss(3, 1)
+        stream.append(x)
+        mean.update(x)
+        var.update(x)
+        if mean.drift_detected:
+            detected.append(i)
+    assert detected and detected[0] < 100
+    assert mean.n_detections == var.n_detections == len(detected)
+    # The window holds the latest values, hardly any from before the shift.
+    assert 1_000 <= mean.width == var.width < 1_100
+    window = stream[-mean.width :]
+    assert mean.get() == pytest.approx(statistics.fmean(window))
+    assert var.mean == pytest.approx(statistics.fmean(window))
+    assert var.get() == pytest.approx(statistics.pvariance(window))
+    assert var.get() < 1.5
+
+    with pytest.raises(ValueError, match="x should not be NaN"):
+        mean.update(float("nan"))
+    for kwargs in [{"delta": 0}, {"delta": 1}, {"clock": 0}]:
+        with pytest.raises(ValueError):
+            rs.RsAdaptiveVar(**kwargs)
+
+
+def test_adwin_detects_a_shift_of_the_mean():
+    rng = random.Random(42)
+    adwin = rs.RsADWIN()
+    detections = []
+    for i in range(2000):
+        drift, warning = adwin.update(rng.gauss(0 if i < 1000 else 1, 0.5))
+        assert drift == adwin.drift_detected
+        assert not warning
+        if drift:
+            detections.append(i)
+    assert detections and 1000 <= detections[0] < 1100
+    assert adwin.n_detections == len(detections)
+    assert adwin.width < 1500
+    assert abs(adwin.estimation - 1) < 0.1
+    with pytest.raises(ValueError):
+        rs.RsADWIN(delta=1.0)
+    with pytest.raises(ValueError):
+        adwin.update(math.nan)
+
+
+def test_page_hinkley_detects_the_shifts_in_its_mode():
+    rng = random.Random(42)
+    values = [rng.gauss(0 if i < 500 else 2, 0.5) for i in range(1000)]
+    detections = {}
+    for mode in ("up", "down", "both"):
+        detector = rs.RsPageHinkley(mode=mode)
+        detections[mode] = [i for i, x in enumerate(values) if detector.update(x)[0]]
+    assert detections["down"] == []
+    assert detections["up"] and 500 <= detections["up"][0] < 550
+    assert detections["both"][0] == detections["up"][0]
+    with pytest.raises(ValueError):
+        rs.RsPageHinkley(mode="sideways")
+
+
+@pytest.mark.parametrize("make", [rs.RsDDM, rs.RsEDDM])
+def test_error_rate_detectors_warn_then_detect_a_drift(make):
+    rng = random.Random(42)
+    detector = make()
+    warnings, drifts = [], []
+    for i in range(3000):
+        drift, warning = detector.update(rng.random() < (0.1 if i < 1500 else 0.5))
+        assert (drift, warning) == (detector.drift_detected, detector.warning_detected)
+        if warning:
+            warnings.append(i)
+        if drift:
+            drifts.append(i)
+    assert drifts and 1500 <= drifts[0] < 1700
+    assert any(1500 <= i < drifts[0] for i in warnings)
+    # The detector starts over after a drift, hence a steady error rate raises no more drift.
+    assert not [i for i in drifts if i > 2000]
+
+
+def test_error_rate_detectors_reject_bad_arguments():
+    with pytest.raises(ValueError):
+        rs.RsEDDM(alpha=0.8, beta=0.9)
+    with pytest.raises(TypeError):
+        rs.RsDDM().update("error")
+
+
+def test_kswin_detects_a_shift_of_the_distribution():
+    rng = random.Random(42)
+    kswin = rs.RsKSWIN(alpha=0.0001, seed=1)
+    detections = []
+    for i in range(2000):
+        drift, _ = kswin.update(rng.gauss(0, 1) if i < 1000 else rng.uniform(2, 3))
+        if drift:
+            detections.append(i)
+            # The window keeps its most recent values only.
+            assert len(kswin) == 30
+    assert detections and 1000 <= detections[0] < 1030
+    assert kswin.p_value <= 1 and 0 <= kswin.statistic <= 1
+    same = rs.RsKSWIN(alpha=0.0001, seed=1)
+    rng = random.Random(42)
+    for i in range(2000):
+        assert same.update(rng.gauss(0, 1) if i < 1000 else rng.uniform(2, 3))[0] == (
+            i in detections
+        )
+    with pytest.raises(ValueError):
+        rs.RsKSWIN(window_size=50, stat_size=30)
+
+
+def test_wasserstein_drift_compares_the_window_to_the_evicted_values():
+    drift = rs.RsWassersteinDrift(4, n_quantiles=4)
+    for x in [0.0, 1.0, 2.0, 3.0]:
+        drift.update(x)
+    assert drift.wasserstein == drift.energy == 0.0
+    for x in [1.0, 2.0, 3.0, 4.0]:
+        drift.update(x)
+    # Same values as scipy.stats.wasserstein_distance and energy_distance.
+    assert drift.wasserstein == pytest.approx(1)
+    assert drift.energy == pytest.approx(math.sqrt(0.5))
+    rng = random.Random(14)
+    drift = rs.RsWassersteinDrift(200)
+    for _ in range(1000):
+        drift.update(rng.gauss(0, 1))
+    assert drift.wasserstein < 0.2
+    for _ in range(200):
+        drift.update(rng.gauss(2, 1))
+    assert drift.wasserstein == pytest.approx(2, abs=0.2)
+    assert drift.energy > 1
+    drift.reset_reference()
+    assert drift.wasserstein == 0.0
+    with pytest.raises(ValueError):
+        rs.RsWassersteinDrift(0)
+    with pytest.raises(ValueError):
+        rs.RsWassersteinDrift(10, compression=math.nan)
+
+
+def test_multinomial_drift_compares_the_window_to_the_evicted_values():
+    rng = random.Random(29)
+    drift, window, reference = rs.RsMultinomialDrift(50), collections.deque(), collections.Counter()
+    assert drift.p_value == 1.0 and drift.df == 0
+    before, after = [0.5, 0.3, 0.2], [0.1, 0.3, 0.6]
+    for i in range(400):
+        x = rng.choices("abc", weights=before if i < 300 else after)[0]
+        drift.update(x)
+        window.append(x)
+        if len(window) > 50:
+            reference[window.popleft()] += 1
+        if i == 299:
+            assert not drift.drift_detected
+        if not reference:
+            continue
+        recent, n = collections.Counter(window), len(window) + sum(reference.values())
+        statistic = 0.0
+        for key in set(recent) | set(reference):
+            total = recent[key] + reference[key]
+            for count, size in [(recent[key], len(window)), (reference[key], n - len(window))]:
+                expected = size * total / n
+                statistic += (count - expected) ** 2 / expected
+        assert drift.statistic == pytest.approx(statistic)
+        assert drift.df == len(set(recent) | set(reference)) - 1
+        if drift.df == 2:
+            assert drift.p_value == pytest.approx(math.exp(-statistic / 2))
+    assert drift.drift_detected
+
+    drift.reset_reference()
+    assert (drift.statistic, drift.df, drift.p_value) == (0.0, 0, 1.0)
+    with pytest.raises(ValueError, match="window_size should be at least 1"):
+        rs.RsMultinomialDrift(0)
+    with pytest.raises(ValueError, match="alpha should be between 0 and 1"):
+        rs.RsMultinomialDrift(10, alpha=1.0)
+
+
+def test_histogram_cdf_and_bins():
+    hist = rs.RsHistogram()
+    assert hist.cdf(0.0) == 0.0
+    for x in range(4):
+        hist.update(float(x))
+    assert hist.iter_bins() == [(0.0, 0.0, 1), (1.0, 1.0, 1), (2.0, 2.0, 1), (3.0, 3.0, 1)]
+    assert [hist.cdf(x) for x in (-1, 0, 0.5, 1, 2.5, 3.5)] == [0, 0.25, 0.25, 0.5, 0.75, 1]
+
+    rng = random.Random(42)
+    values = [rng.gauss(-3, 1) for _ in range(1000)] + [rng.gauss(3, 1) for _ in range(1000)]
+    hist = rs.RsHistogram(max_bins=15)
+    for x in values:
+        hist.update(x)
+    bins = hist.iter_bins()
+    assert len(hist) == 15 and sum(count for _, _, count in bins) == hist.n == 2000
+    for (left, right, _), (next_left, _, _) in zip(bins, bins[1:]):
+        assert left <= right < next_left
+    assert abs(hist.cdf(0.0) - 0.5) < 0.02
+    assert abs(hist.cdf(-3.0) - sum(x <= -3 for x in values) / 2000) < 0.02
+
+
+def test_histogram_merge_pools_the_bins():
+    rng = random.Random(42)
+    left, right, whole = rs.RsHistogram(10), rs.RsHistogram(20), rs.RsHistogram(20)
+    for i in range(1000):
+        x = rng.expovariate(1)
+        (left if i % 2 else right).update(x)
+        whole.update(x)
+    left.merge(right)
+    assert left.n == 1000 and len(left) == 20
+    assert sum(count for _, _, count in left.iter_bins()) == 1000
+    for x in (0.1, 0.5, 1.0, 2.0):
+        assert abs(left.cdf(x) - whole.cdf(x)) < 0.03
+
+
+def test_decayed_top_k_follows_the_trending_keys():
+    rng = random.Random(27)
+    top_k, events, t = rs.RsDecayedTopK(capacity=10, halflife=4.0), [], 0.0
+    for i in range(300):
+        t += rng.expovariate(1)
+        # "a" is frequent at first, then "b" takes over.
+        key = rng.choice("aab" if i < 150 else "bbbc")
+        w = rng.choice([1.0, 2.0])
+        top_k.update(key, w=w, t=t)
+        events.append((key, w, t))
+    expected = collections.defaultdict(float)
+    for key, w, ti in events:
+        expected[key] += w * 2 ** (-(t - ti) / 4)
+    for key in "abc":
+        assert top_k.get(key) == pytest.approx(expected[key])
+    assert top_k.get("z") == 0.0
+    assert [key for key, _, _ in top_k.top()] == ["b", "c", "a"]
+    assert top_k.top(1) == [("b", pytest.approx(expected["b"]), 0.0)]
+
+    # Without a time, each update advances the clock by one.
+    per_update = rs.RsDecayedTopK(3, halflife=1.0)
+    for key in ["x", "y", "y"]:
+        per_update.update(key)
+    assert per_update.get("x") == pytest.approx(0.25)
+    assert per_update.get("y") == pytest.approx(1.5)
+    # Counts are rescaled before the forward decay overflows.
+    per_update.update("x", t=1e6)
+    assert per_update.get("x") == pytest.approx(1.0)
+    assert per_update.get("y") == 0.0
+
+
+def test_decayed_top_k_bounds_the_counts_of_the_evicted_keys():
+    rng = random.Random(28)
+    top_k, exact = rs.RsDecayedTopK(capacity=5, halflife=50.0), collections.Counter()
+    for t in range(1_000):
+        key = min(int(rng.expovariate(0.3)), 20)
+        top_k.update(key, t=float(t))
+        exact[key] += 2 ** (-(999 - t) / 50)
+    assert len(top_k) == 5
+    for key, count, error in top_k.top():
+        assert count - error <= exact[key] + 1e-9 <= count + 2e-9
+    heaviest = {key for key, _ in exact.most_common(3)}
+    assert heaviest <= {key for key, _, _ in top_k.top()}
+
+    with pytest.raises(ValueError, match="t should not decrease"):
+        top_k.update(0, t=10.0)
+    with pytest.raises(ValueError, match="w should be finite and non-negative"):
+        top_k.update(0, w=-1.0)
+    with pytest.raises(ValueError, match="capacity should be at least 1"):
+        rs.RsDecayedTopK(0, 1.0)
+    with pytest.raises(ValueError, match="halflife should be positive"):
+        rs.RsDecayedTopK(3, 0.0)
+
+
+def test_count_min_never_underestimates():
+    rng = random.Random(42)
+    sketch = rs.RsCountMin(272, 5, seed=1)
+    counts = {}
+    for _ in range(10_000):
+        key = int(rng.paretovariate(1)) if rng.random() < 0.5 else str(rng.randrange(1000))
+        counts[key] = counts.get(key, 0) + 1
+        sketch.update(key)
+    assert sketch.total == 10_000
+    errors = [sketch.query(key) - count for key, count in counts.items()]
+    assert min(errors) >= 0
+    assert sum(error > math.e / 272 * 10_000 for error in errors) < 0.01 * len(counts)
+    assert sketch.query("unseen") <= sketch.total
+
+
+def test_count_min_merge_adds_the_counters():
+    left, right, whole = (rs.RsCountMin(50, 4, seed=7) for _ in range(3))
+    for i in range(500):
+        (left if i % 3 else right).update(i % 37, 2.0)
+        whole.update(i % 37, 2.0)
+    left.merge(right)
+    assert left == whole
+    with pytest.raises(ValueError):
+        left.merge(rs.RsCountMin(50, 4, seed=8))
+
+
+def test_distinct_count_is_exact_up_to_the_threshold():
+    distinct = rs.RsDistinctCount(threshold=100, precision=12)
+    assert distinct.get() == 0 and distinct.is_exact
+    for i in range(50):
+        # An int and its string are different items, and repeats are counted once.
+        for item in [i, str(i), i]:
+            distinct.update(item)
+    assert distinct.get() == 100 and distinct.is_exact
+    distinct.update(50)
+    assert not distinct.is_exact
+    assert abs(distinct.get() - 101) <= 2
+
+    # Past the threshold, the count is that of a HyperLogLog of all the items.
+    hll = rs.RsHyperLogLog(precision=12)
+    for i in range(50):
+        hll.update(i)
+        hll.update(str(i))
+    for i in range(50, 20_000):
+        distinct.update(i)
+        hll.update(i)
+    assert distinct.get() == hll.get()
+    assert abs(distinct.get() - 20_050) < 0.05 * 20_050
+    with pytest.raises(ValueError, match="precision should be between 4 and 16"):
+        rs.RsDistinctCount(precision=20)
+
+
+def test_hyperloglog_estimates_and_merges_distinct_counts():
+    left, right, whole = (rs.RsHyperLogLog(precision=12) for _ in range(3))
+    assert whole.get() == 0
+    for i in range(50_000):
+        # The shards overlap on a third of the items, which the union counts once.
+        key = str(i) if i % 2 else i
+        (left if i % 3 else right).update(key)
+        if i % 3 == 1:
+            right.update(key)
+        whole.update(key)
+    # About 1.6% of relative standard error with 2^12 registers.
+    assert abs(whole.get() - 50_000) < 0.05 * 50_000
+    left.merge(right)
+    assert left == whole
+    with pytest.raises(ValueError):
+        left.merge(rs.RsHyperLogLog(precision=10))
+    with pytest.raises(ValueError):
+        rs.RsHyperLogLog(precision=20)
+
+
+def test_tdigest_quantiles_of_heavy_tailed_data():
+    rng = random.Random(42)
+    values = [rng.paretovariate(1.5) for _ in range(20_000)]
+    p2 = rs.RsQuantile(0.99)
+    tdigest = rs.RsQuantile(0.99, algorithm="tdigest")
+    left, right = rs.RsTDigest(), rs.RsTDigest()
+    for i, x in enumerate(values):
+        p2.update(x)
+        tdigest.update(x)
+        (left if i % 2 else right).update(x, 2.0)
+    exact = sorted(values)[int(0.99 * len(values))]
+    assert abs(tdigest.get() - exact) < 0.05 * exact
+    assert abs(tdigest.get() - exact) < abs(p2.get() - exact)
+    left.merge(right)
+    assert left.n == 40_000
+    for q in (0.01, 0.5, 0.99):
+        exact = sorted(values)[int(q * len(values))]
+        assert abs(left.quantile(q) - exact) < 0.05 * exact
+        assert abs(left.cdf(exact) - q) < 0.005
+    with pytest.raises(ValueError):
+        rs.RsQuantile(algorithm="gk")
+
+
+@pytest.mark.parametrize("eps", [0.5, 0.1, 0.01])
+def test_exp_histogram_counts_the_ones_of_the_window(eps):
+    rng = random.Random(42)
+    window_size = 10_000
+    histogram = rs.RsExpHistogram(window_size, eps)
+    bits = collections.deque(maxlen=window_size)
+    for i in range(50_000):
+        # The density of ones drifts so that the oldest bucket is often a large one.
+        bit = rng.random() < (0.1 if (i // 7_000) % 2 else 0.8)
+        histogram.update(bit)
+        bits.append(bit)
+        if i % 97 == 0:
+            assert abs(histogram.get() - sum(bits)) <= eps * sum(bits)
+    assert histogram.n_buckets < 2 * (1 / (2 * eps) + 2) * math.log2(window_size)
+    for _ in range(window_size):
+        histogram.update(False)
+    assert histogram.get() == 0 and histogram.n_buckets == 0
+    with pytest.raises(ValueError):
+        rs.RsExpHistogram(0, 0.1)
+    with pytest.raises(ValueError):
+        rs.RsExpHistogram(10, 0.0)
+
+
+def test_space_saving_tracks_the_heavy_hitters():
+    rng = random.Random(42)
+    keys = [int(rng.paretovariate(1.2)) for _ in range(20_000)]
+    counts = collections.Counter(keys)
+    left, right, whole = (rs.RsSpaceSaving(50) for _ in range(3))
+    for i, key in enumerate(keys):
+        (left if i % 2 else right).update(key)
+        whole.update(key)
+    assert len(whole) == whole.k == 50 and whole.total == len(keys)
+    for key, count in counts.items():
+        if count > len(keys) / 50:
+            assert whole.get(key) >= count
+        assert whole.get(key) - whole.error(key) <= count
+    expected = [key for key, _ in counts.most_common(5)]
+    assert [key for key, _ in whole.most_common(5)] == expected
+    left.merge(right)
+    assert left.total == len(keys)
+    assert [key for key, _ in left.most_common(5)] == expected
+    for key, count in left.most_common():
+        assert count - left.error(key) <= counts[key] <= count
+    with pytest.raises(ValueError):
+        rs.RsSpaceSaving(0)
+
+
+def test_extended_p2_quantile():
+    rng = random.Random(3)
+    values = [rng.expovariate(1.0) for _ in range(20_000)]
+    # The 0.9 quantile of a standard exponential is ln(10).
+    for n_markers in [5, 9, 21]:
+        stat = rs.RsQuantile(0.9, n_markers=n_markers)
+        for x in values:
+            stat.update(x)
+        assert stat.get() == pytest.approx(math.log(10), abs=0.1)
+        assert stat.n == len(values)
+    with pytest.raises(ValueError, match="odd"):
+        rs.RsQuantile(0.5, n_markers=6)
+    with pytest.raises(ValueError, match="odd"):
+        rs.RsQuantile(0.5, n_markers=3)
+
+
+def test_p2_quantile_edge_cases():
+    assert math.isnan(rs.RsQuantile().get())
+    assert math.isnan(rs.RsQuantile(0.9, n_markers=9).get())
+    assert math.isnan(rs.RsIQR(0.25, 0.75).get())
+    # Each unit of weight is a copy of the value, so a huge weight is refused at once.
+    for stat in [rs.RsQuantile(), rs.RsIQR(0.25, 0.75)]:
+        with pytest.raises(ValueError, match="at most"):
+            stat.update(1.0, 1e18)
+        assert stat.n == 0
+
+
+@pytest.mark.parametrize("binding", load_bindings(), ids=lambda b: b.__class__.__name__)
+def test_states_from_before_the_envelope_load(binding):
+    state = binding.__getstate__()
+    assert state[:4] == b"RVRS"
+    # The bare payloads written before the envelope have the layout of version 1.
+    if state[4:6] == b"\x01\x00":
+        assert type(binding)._from_state(state[6:]) == binding
+
+
+def test_quantile_states_from_before_the_algorithm_choice_load():
+    quantile = univariate(rs.RsQuantile(0.3))
+    state = quantile.__getstate__()
+    # Version 1 held the P² estimator alone, without the variant index of the algorithm.
+    assert state[:6] == b"RVRS\x02\x00"
+    old_state = b"RVRS\x01\x00" + state[10:]
+    assert rs.RsQuantile._from_state(old_state) == quantile
+    assert rs.RsQuantile._from_state(state[10:]) == quantile
+
+
+@pytest.mark.parametrize(
+    "make",
+    [
+        lambda: rs.RsRollingMean(5),
+        lambda: rs.RsRollingVar(5),
+        lambda: rs.RsRollingQuantile(0.5, 5),
+        lambda: rs.RsRollingIQR(0.25, 0.75, 5),
+        lambda: rs.RsRollingMin(5),
+        lambda: rs.RsRollingMax(5),
+        lambda: rs.RsRollingPeakToPeak(5),
+        lambda: rs.RsRollingMAD(5),
+        lambda: rs.RsRollingPercentileRank(5),
+    ],
+)
+def test_rolling_stats_only_take_a_weight_of_one(make):
+    stat, unweighted = make(), make()
+    for x in [3.0, 1.0, 4.0]:
+        stat.update(x, 1.0)
+        unweighted.update(x)
+    stat.update(1.0, w=1)
+    unweighted.update(1.0)
+    for w in [0.0, 2.0, 0.5, math.nan]:
+        with pytest.raises(ValueError, match="w should be 1"):
+            stat.update(5.0, w)
+        with pytest.raises(ValueError, match="w should be 1"):
+            stat.update(5.0, w=w)
+    assert stat == unweighted
+    assert stat.n == 4
+
+
+# The second argument of `update`, if any, is either a weight or a time.
+@pytest.mark.parametrize(
+    "make, second",
+    [
+        (lambda: rs.RsQuantile(0.3), "w"),
+        (lambda: rs.RsQuantile(0.3, algorithm="tdigest"), "w"),
+        (lambda: rs.RsEWMean(0.2), "w"),
+        (lambda: rs.RsEWVar(0.2), "w"),
+        (lambda: rs.RsIQR(0.25, 0.75), "w"),
+        (lambda: rs.RsKurtosis(False), "w"),
+        (lambda: rs.RsPeakToPeak(), "w"),
+        (lambda: rs.RsSkew(True), "w"),
+        (lambda: rs.RsMAD(), "w"),
+        (lambda: rs.RsRollingMean(5), None),
+        (lambda: rs.RsRollingVar(5), None),
+        (lambda: rs.RsRollingQuantile(0.5, 5), None),
+        (lambda: rs.RsRollingIQR(0.25, 0.75, 5), None),
+        (lambda: rs.RsRollingMin(5), None),
+        (lambda: rs.RsRollingMax(5), None),
+        (lambda: rs.RsRollingPeakToPeak(5), None),
+        (lambda: rs.RsRollingMAD(5), None),
+        (lambda: rs.RsPercentileRank(), None),
+        (lambda: rs.RsRollingPercentileRank(5), None),
+        (lambda: rs.RsAdaptiveMean(), None),
+        (lambda: rs.RsAdaptiveVar(), None),
+        (lambda: rs.RsShift(3), None),
+        (lambda: rs.RsAutoCorr(2), None),
+        (lambda: rs.RsTimeEWMean(2.0), "t"),
+        (lambda: rs.RsTimeEWVar(2.0), "t"),
+    ],
+)
+def test_n_counts_the_values_seen(make, second):
+    stat = make()
+    assert stat.n == 0
+    for i in range(12):
+        if second == "w":
+            stat.update(float(i), i % 3)
+        elif second == "t":
+            stat.update(float(i), 0.5 * i)
+        else:
+            stat.update(float(i))
+    assert stat.n == (sum(i % 3 for i in range(12)) if second == "w" else 12)
+    assert copy.deepcopy(stat).n == stat.n
+
+
+@pytest.mark.parametrize(
+    "make, old_n",
+    [
+        (lambda: rs.RsEWMean(0.2), 0),
+        (lambda: rs.RsEWVar(0.2), 0),
+        (lambda: rs.RsPeakToPeak(), 0),
+        (lambda: rs.RsRollingMean(5), 5),
+        (lambda: rs.RsRollingVar(5), 5),
+        (lambda: rs.RsRollingQuantile(0.5, 5), 5),
+        (lambda: rs.RsRollingIQR(0.25, 0.75, 5), 5),
+        (lambda: rs.RsRollingPercentileRank(5), 5),
+        (lambda: rs.RsShift(3), 4),
+        (lambda: rs.RsAutoCorr(2), 8),
+    ],
+)
+def test_states_from_before_n_was_stored_load(make, old_n):
+    stat = make()
+    for i in range(8):
+        stat.update(float(i * i % 7))
+    state = stat.__getstate__()
+    # Version 1 ended before the count, which is the last field of the state.
+    assert state[:6] == b"RVRS\x02\x00"
+    old = type(stat)._from_state(b"RVRS\x01\x00" + state[6:-8])
+    assert old.get() == stat.get()
+    assert old.n == old_n
+
+
+@pytest.mark.parametrize("make", [rs.RsTimeEWMean, rs.RsTimeEWVar])
+def test_timed_states_from_before_n_was_stored_load(make):
+    stat = make(2.0)
+    for i in range(8):
+        stat.update(float(i * i % 7), 0.5 * i)
+    state = stat.__getstate__()
+    assert state[:6] == b"RVRS\x02\x00"
+    old = make._from_state(b"RVRS\x01\x00" + state[6:-8])
+    assert old.get() == stat.get()
+    assert old.n == 0
+
+
+def test_stale_states_are_refused():
+    stat = rs.RsEWMean(0.2)
+    for x in range(5):
+        stat.update(float(x))
+    state = stat.__getstate__()
+    with pytest.raises(ValueError, match="failed to restore the state of RsEWMean"):
+        rs.RsEWMean._from_state(state[:-3])
+    with pytest.raises(ValueError, match="bytes left after the end of the state"):
+        rs.RsEWMean._from_state(state + b"\x00")
+    # The current payload labelled with the previous version is read as a shorter layout.
+    with pytest.raises(ValueError, match="8 bytes left"):
+        rs.RsEWMean._from_state(b"RVRS\x01\x00" + state[6:])
+    with pytest.raises(ValueError, match="only reads up to version 2"):
+        rs.RsEWMean._from_state(b"RVRS\x03\x00" + state[6:])
+    with pytest.raises(ValueError):
+        rs.RsEWMean(0.2).__setstate__(state + b"\x00")
+
+
+def test_sketches_hashed_by_an_older_release_are_refused():
+    def as_version_1(stat):
+        return b"RVRS\x01\x00" + stat.__getstate__()[6:]
+
+    exact, approximate = rs.RsDistinctCount(threshold=10), rs.RsDistinctCount(threshold=3)
+    for i in range(5):
+        exact.update(i)
+        approximate.update(i)
+    # The items of an exact count can be hashed again, unlike the registers of a sketch.
+    assert rs.RsDistinctCount._from_state(as_version_1(exact)) == exact
+    with pytest.raises(ValueError, match="older release"):
+        rs.RsDistinctCount._from_state(as_version_1(approximate))
+    for stat in [rs.RsHyperLogLog(), rs.RsCountMin(10, 2, seed=1)]:
+        stat.update("a")
+        with pytest.raises(ValueError, match="older release"):
+            type(stat)._from_state(as_version_1(stat))
+
+
+def test_sam_knn_adapts_to_a_drift():
+    rng = random.Random(42)
+    sam = rs.RsSAMKNN(n_neighbors=5, window_size=300, min_stm_size=20, seed=1)
+    assert sam.predict_one({"a": 0.0}) is None
+    errors, stm_sizes = [], []
+    for i in range(1200):
+        x = {"a": rng.random(), "b": rng.random()}
+        y = int(x["a"] > 0.5) if i < 600 else int(x["a"] <= 0.5)
+        errors.append(sam.predict_one(x) != y)
+        sam.learn_one(x, y)
+        stm_sizes.append(sam.stm_size)
+        assert sam.stm_size + sam.ltm_size <= 300
+    assert sum(errors[400:600]) < 30
+    assert sum(errors[1000:]) < 30
+    # The STM dropped the samples of the first concept once it drifted.
+    assert stm_sizes[599] > 100
+    assert min(stm_sizes[600:700]) < 50
+    assert sam.ltm_size > 0
+    assert len(sam.stm_labels) == sam.stm_size
+    assert len(sam.ltm_labels) == sam.ltm_size
+
+
+def test_sam_knn_without_ltm_is_a_sliding_knn():
+    sam = rs.RsSAMKNN(n_neighbors=1, window_size=10, stm_aprox_adaption=None, use_ltm=False)
+    for i in range(25):
+        sam.learn_one({"a": float(i)}, str(i))
+    assert sam.stm_size == 10
+    assert sam.ltm_size == 0
+    assert sam.stm_labels == [str(i) for i in range(15, 25)]
+    assert sam.predict_one({"a": 3.0}) == "15"
+    assert sam.predict_one({"a": 21.2}) == "21"
+
+
+@pytest.mark.parametrize("adaption", [True, False, None])
+def test_sam_knn_is_reproducible(adaption):
+    def run():
+        rng = random.Random(7)
+        sam = rs.RsSAMKNN(
+            n_neighbors=3, window_size=60, min_stm_size=10, stm_aprox_adaption=adaption, seed=3
+        )
+        predictions = []
+        for i in range(300):
+            x = {"a": rng.random(), "b": rng.random()}
+            predictions.append(sam.predict_one(x))
+            sam.learn_one(x, int(x["a"] + x["b"] > 1.0) if i < 150 else int(x["a"] > 0.3))
+        return predictions, sam
+
+    first, sam = run()
+    second, other = run()
+    assert first == second
+    assert sam == other
+
+
+def test_sam_knn_rejects_bad_arguments():
+    for kwargs in (
+        {"n_neighbors": 0},
+        {"window_size": 0},
+        {"ltm_size": 1.5},
+        {"ltm_size": float("nan")},
+        {"min_stm_size": 0},
+    ):
+        with pytest.raises(ValueError):
+            rs.RsSAMKNN(**kwargs)
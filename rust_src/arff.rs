@@ -0,0 +1,258 @@
+// Streaming of ARFF files, like `stream.iter_arff`.
+//
+// The header is read when the file is opened, and gives the names and types of the
+// attributes: numeric ones are read as floats, and the nominal, string and date ones as
+// strings. Missing values, `?`, are read as `None`. Rows in the sparse format, `{index
+// value, ...}`, give sparse dicts holding only the listed attributes.
+
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+
+use crate::csv::{open, Target};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Attribute {
+    Numeric,
+    Nominal(Vec<String>),
+    Text,
+}
+
+impl Attribute {
+    fn parse(kind: &str) -> Result<Self, String> {
+        if let Some(values) = kind.strip_prefix('{') {
+            let values = values
+                .strip_suffix('}')
+                .ok_or_else(|| format!("unterminated nominal type '{}'", kind))?;
+            let values = split_fields(values, ',')?;
+            if values.iter().all(|(value, _)| value.is_empty()) {
+                return Err("nominal types should have at least one value".to_string());
+            }
+            return Ok(Attribute::Nominal(
+                values.into_iter().map(|v| v.0).collect(),
+            ));
+        }
+        let name = kind.split_whitespace().next().unwrap_or("");
+        match name.to_lowercase().as_str() {
+            "numeric" | "real" | "integer" => Ok(Attribute::Numeric),
+            "string" | "date" => Ok(Attribute::Text),
+            _ => Err(format!("unsupported attribute type '{}'", kind)),
+        }
+    }
+
+    // Value of the attribute when a sparse row doesn't list it.
+    fn default(&self, py: Python) -> PyObject {
+        match self {
+            Attribute::Numeric => 0.0.into_py(py),
+            Attribute::Nominal(values) => values[0].clone().into_py(py),
+            Attribute::Text => py.None(),
+        }
+    }
+}
+
+// Splits on `separator`, outside of single or double quotes, and trims the fields. Returns
+// each field without its quotes, along with whether it was quoted.
+fn split_fields(line: &str, separator: char) -> Result<Vec<(String, bool)>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quote = None;
+    let mut quoted = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) if c == '\\' => field.extend(chars.next()),
+            Some(_) => field.push(c),
+            None if c == separator => {
+                fields.push((field.trim().to_string(), quoted));
+                field.clear();
+                quoted = false;
+            }
+            None if (c == '\'' || c == '"') && field.trim().is_empty() => {
+                field.clear();
+                quote = Some(c);
+                quoted = true;
+            }
+            None => field.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("unterminated quote in '{}'", line));
+    }
+    fields.push((field.trim().to_string(), quoted));
+    Ok(fields)
+}
+
+// Splits `@attribute name type`, where the name may be quoted.
+fn parse_attribute(line: &str) -> Result<(String, Attribute), String> {
+    let rest = line["@attribute".len()..].trim_start();
+    let (name, kind) = match rest.chars().next() {
+        Some(q @ ('\'' | '"')) => {
+            let end = rest[1..]
+                .find(q)
+                .ok_or_else(|| format!("unterminated quote in '{}'", line))?;
+            (rest[1..end + 1].to_string(), &rest[end + 2..])
+        }
+        _ => {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (rest[..end].to_string(), &rest[end..])
+        }
+    };
+    Ok((name, Attribute::parse(kind.trim())?))
+}
+
+/// Iterator over the rows of an ARFF file, made by `iter_arff`.
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsArffReader {
+    reader: Box<dyn BufRead + Send>,
+    line: String,
+    n_lines: usize,
+    names: Vec<Py<PyString>>,
+    attributes: Vec<Attribute>,
+    is_target: Vec<bool>,
+    target: Target,
+}
+
+impl RsArffReader {
+    fn error(&self, message: &str) -> PyErr {
+        PyValueError::new_err(format!("line {}: {}", self.n_lines, message))
+    }
+
+    // Next line which isn't blank or a comment, trimmed, or `None` at the end of the file.
+    fn next_line(&mut self) -> PyResult<Option<String>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.n_lines += 1;
+            let line = self.line.trim();
+            if !line.is_empty() && !line.starts_with('%') {
+                return Ok(Some(line.to_string()));
+            }
+        }
+    }
+
+    fn value(&self, py: Python, i: usize, (value, quoted): &(String, bool)) -> PyResult<PyObject> {
+        if value == "?" && !quoted {
+            return Ok(py.None());
+        }
+        match self.attributes[i] {
+            Attribute::Numeric => match value.parse::<f64>() {
+                Ok(value) => Ok(value.into_py(py)),
+                Err(_) => Err(self.error(&format!("'{}' is not a number", value))),
+            },
+            _ => Ok(value.into_py(py)),
+        }
+    }
+
+    // Values of the listed attributes of a row, by attribute.
+    fn parse_row(&self, line: &str) -> PyResult<Vec<Option<(String, bool)>>> {
+        let n = self.attributes.len();
+        if let Some(sparse) = line.strip_prefix('{') {
+            let sparse = sparse
+                .strip_suffix('}')
+                .ok_or_else(|| self.error("unterminated sparse row"))?;
+            let mut row = vec![None; n];
+            if sparse.trim().is_empty() {
+                return Ok(row);
+            }
+            for (entry, _) in split_fields(sparse, ',').map_err(|e| self.error(&e))? {
+                let (index, value) = entry.split_once(char::is_whitespace).ok_or_else(|| {
+                    self.error(&format!("'{}' is not an index and a value", entry))
+                })?;
+                let index: usize =
+                    index.parse().ok().filter(|&i| i < n).ok_or_else(|| {
+                        self.error(&format!("'{}' is not an attribute index", index))
+                    })?;
+                let mut value = split_fields(value, ',').map_err(|e| self.error(&e))?;
+                row[index] = value.pop();
+            }
+            return Ok(row);
+        }
+        let fields = split_fields(line, ',').map_err(|e| self.error(&e))?;
+        if fields.len() != n {
+            return Err(self.error(&format!(
+                "the row has {} values, but there are {} attributes",
+                fields.len(),
+                n
+            )));
+        }
+        Ok(fields.into_iter().map(Some).collect())
+    }
+}
+
+#[pymethods]
+impl RsArffReader {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(PyObject, PyObject)>> {
+        let line = match self.next_line()? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let row = self.parse_row(&line)?;
+        let x = PyDict::new(py);
+        for (i, value) in row.iter().enumerate() {
+            if let (Some(value), false) = (value, self.is_target[i]) {
+                x.set_item(&self.names[i], self.value(py, i, value)?)?;
+            }
+        }
+        let y = self.target.get(py, &self.names, |i| match &row[i] {
+            Some(value) => self.value(py, i, value),
+            None => Ok(self.attributes[i].default(py)),
+        })?;
+        Ok(Some((x.into(), y)))
+    }
+}
+
+/// Streams the rows of the ARFF file at `path` as `(x, y)` pairs, where `x` is a dict of the
+/// attributes and `y` the value of the `target` attribute, a dict of the values of several
+/// target attributes, or `None`.
+///
+/// In sparse rows, the targets which are not listed take the default value of the format:
+/// 0 for numeric attributes and the first value of nominal ones.
+#[pyfunction(target = "None")]
+pub fn iter_arff(py: Python, path: PathBuf, target: Option<&PyAny>) -> PyResult<RsArffReader> {
+    let mut reader = RsArffReader {
+        reader: open(&path)?,
+        line: String::new(),
+        n_lines: 0,
+        names: Vec::new(),
+        attributes: Vec::new(),
+        is_target: Vec::new(),
+        target: Target::None,
+    };
+    let mut names = Vec::new();
+    loop {
+        let line = reader
+            .next_line()?
+            .ok_or_else(|| PyValueError::new_err("the file has no @data section"))?;
+        let keyword = line.split_whitespace().next().unwrap_or("").to_lowercase();
+        match keyword.as_str() {
+            "@relation" => {}
+            "@attribute" => {
+                let (name, attribute) = parse_attribute(&line).map_err(|e| reader.error(&e))?;
+                names.push(name);
+                reader.attributes.push(attribute);
+            }
+            "@data" => break,
+            _ => return Err(reader.error(&format!("unexpected header line '{}'", line))),
+        }
+    }
+    reader.target = Target::extract(target, &names)?;
+    reader.is_target = vec![false; names.len()];
+    for i in reader.target.columns() {
+        reader.is_target[i] = true;
+    }
+    reader.names = names
+        .iter()
+        .map(|name| PyString::new(py, name).into())
+        .collect();
+    Ok(reader)
+}
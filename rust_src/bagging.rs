@@ -0,0 +1,404 @@
+// Online bagging of native classifiers with ADWIN drift detectors, like the bagging
+// ensembles of `river.ensemble`.
+//
+// Each member learns each sample a random number of times and owns an ADWIN detector which
+// monitors its error. Whenever a detector catches its error going up, the member with the
+// largest error is replaced by a fresh copy of the model. The members draw their weights
+// from their own generators, so the ensemble learns the same whether or not they are
+// updated in parallel.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::classifier::Classifier;
+use crate::drift::Adwin;
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+
+// Settings of the ADWIN detectors of the members, those of `river.drift.ADWIN`.
+const ADWIN_CLOCK: usize = 32;
+const ADWIN_GRACE_PERIOD: usize = 10;
+
+/// Number of times a member learns a sample.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Weighting {
+    /// Poisson(1), as in the online bagging of Oza and Russell.
+    Oza,
+    /// Poisson(w).
+    Bag(f64),
+    /// Once if the member misclassifies the sample, and otherwise with probability
+    /// `e / (1 - e)`, `e` being its error.
+    Me,
+    /// Once with probability 1/2.
+    Half,
+    /// 1 + Poisson(w).
+    Wt(f64),
+    /// Once with probability 1 - 1/e, as with a Poisson(1) weight capped to 1.
+    Subag,
+}
+
+impl Weighting {
+    fn parse(method: &str, w: f64) -> PyResult<Self> {
+        match method {
+            "bag" => Ok(Weighting::Bag(w)),
+            "me" => Ok(Weighting::Me),
+            "half" => Ok(Weighting::Half),
+            "wt" => Ok(Weighting::Wt(w)),
+            "subag" => Ok(Weighting::Subag),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown bagging_method '{}', expected 'bag', 'me', 'half', 'wt' or 'subag'",
+                method
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Weighting::Oza => "oza",
+            Weighting::Bag(_) => "bag",
+            Weighting::Me => "me",
+            Weighting::Half => "half",
+            Weighting::Wt(_) => "wt",
+            Weighting::Subag => "subag",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Member {
+    model: Classifier,
+    detector: Adwin,
+    rng: Rng,
+}
+
+impl HeapSize for Member {
+    fn heap_size(&self) -> usize {
+        self.model.heap_size() + self.detector.heap_size()
+    }
+}
+
+impl Member {
+    fn new(model: Classifier, delta: f64, seed: u64) -> Self {
+        Member {
+            model,
+            detector: Adwin::new(delta, ADWIN_CLOCK, ADWIN_GRACE_PERIOD)
+                .expect("adwin_delta is checked when the ensemble is built"),
+            rng: Rng::from_seed(seed),
+        }
+    }
+
+    fn weight(&mut self, weighting: Weighting, x: &HashMap<String, f64>, y: &Key) -> u64 {
+        match weighting {
+            Weighting::Oza => self.rng.poisson(1.0),
+            Weighting::Bag(w) => self.rng.poisson(w),
+            Weighting::Me => {
+                let error = self.detector.mean();
+                if self.model.predict_one(x).as_ref() != Some(y) {
+                    1
+                } else {
+                    (error < 1.0 && self.rng.next_f64() < error / (1.0 - error)) as u64
+                }
+            }
+            Weighting::Half => self.rng.below(2),
+            Weighting::Wt(w) => 1 + self.rng.poisson(w),
+            Weighting::Subag => (self.rng.poisson(1.0) > 0) as u64,
+        }
+    }
+
+    // Whether the detector caught the error of the member going up.
+    fn learn_one(
+        &mut self,
+        weighting: Weighting,
+        x: &HashMap<String, f64>,
+        y: &Key,
+    ) -> PyResult<bool> {
+        for _ in 0..self.weight(weighting, x, y) {
+            self.model.learn_one(x, y)?;
+        }
+        let prediction = match self.model.predict_one(x) {
+            Some(prediction) => prediction,
+            None => return Ok(false),
+        };
+        let error = self.detector.mean();
+        self.detector
+            .update(if prediction == *y { 0.0 } else { 1.0 });
+        Ok(self.detector.drift_detected() && self.detector.mean() > error)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Bagging {
+    template: Classifier,
+    members: Vec<Member>,
+    weighting: Weighting,
+    delta: f64,
+    parallel: bool,
+    n_replacements: usize,
+    rng: Rng,
+}
+
+impl HeapSize for Bagging {
+    fn heap_size(&self) -> usize {
+        self.template.heap_size() + self.members.heap_size()
+    }
+}
+
+impl Bagging {
+    fn new(
+        model: &PyAny,
+        n_models: usize,
+        weighting: Weighting,
+        delta: f64,
+        parallel: bool,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        if n_models == 0 {
+            return Err(PyValueError::new_err("n_models should be at least 1"));
+        }
+        if delta.is_nan() || delta <= 0.0 || delta >= 1.0 {
+            return Err(PyValueError::new_err(
+                "adwin_delta should be between 0 and 1",
+            ));
+        }
+        let template = Classifier::from_py(model)?;
+        let mut rng = Rng::new(seed);
+        let members = (0..n_models)
+            .map(|_| Member::new(template.clone(), delta, rng.next_u64()))
+            .collect();
+        Ok(Bagging {
+            template,
+            members,
+            weighting,
+            delta,
+            parallel,
+            n_replacements: 0,
+            rng,
+        })
+    }
+
+    fn learn_one(&mut self, x: &HashMap<String, f64>, y: &Key) -> PyResult<()> {
+        let weighting = self.weighting;
+        let changes = if self.parallel {
+            self.members
+                .par_iter_mut()
+                .map(|member| member.learn_one(weighting, x, y))
+                .collect::<PyResult<Vec<bool>>>()?
+        } else {
+            self.members
+                .iter_mut()
+                .map(|member| member.learn_one(weighting, x, y))
+                .collect::<PyResult<Vec<bool>>>()?
+        };
+        if changes.contains(&true) {
+            let mut worst = 0;
+            for (i, member) in self.members.iter().enumerate() {
+                if member.detector.mean() > self.members[worst].detector.mean() {
+                    worst = i;
+                }
+            }
+            self.members[worst] =
+                Member::new(self.template.clone(), self.delta, self.rng.next_u64());
+            self.n_replacements += 1;
+        }
+        Ok(())
+    }
+
+    // Average of the probabilities of the members, the labels in the order in which they are
+    // first met.
+    fn predict_proba_one(&self, x: &HashMap<String, f64>) -> Vec<(Key, f64)> {
+        let mut proba: Vec<(Key, f64)> = Vec::new();
+        for member in &self.members {
+            for (label, p) in member.model.predict_proba_one(x) {
+                match proba.iter_mut().find(|(l, _)| *l == label) {
+                    Some((_, total)) => *total += p,
+                    None => proba.push((label, p)),
+                }
+            }
+        }
+        let total: f64 = proba.iter().map(|(_, p)| p).sum();
+        if total > 0.0 {
+            proba.iter_mut().for_each(|(_, p)| *p /= total);
+        }
+        proba
+    }
+
+    fn predict_one(&self, x: &HashMap<String, f64>) -> Option<Key> {
+        let mut best: Option<(Key, f64)> = None;
+        for (label, p) in self.predict_proba_one(x) {
+            if best.as_ref().is_none_or(|(_, b)| p > *b) {
+                best = Some((label, p));
+            }
+        }
+        best.map(|(label, _)| label)
+    }
+}
+
+macro_rules! bagging {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl HeapSize for $name {
+                fn heap_size(&self) -> usize {
+                    self.bagging.heap_size()
+                }
+            }
+
+            #[pymethods]
+            impl $name {
+                /// Learns the sample with every member, on several threads and without the
+                /// GIL with `parallel=True`.
+                pub fn learn_one(
+                    &mut self,
+                    py: Python,
+                    x: HashMap<String, f64>,
+                    y: Key,
+                ) -> PyResult<()> {
+                    let bagging = &mut self.bagging;
+                    if bagging.parallel {
+                        py.allow_threads(|| bagging.learn_one(&x, &y))
+                    } else {
+                        bagging.learn_one(&x, &y)
+                    }
+                }
+                pub fn predict_one(&self, py: Python, x: HashMap<String, f64>) -> Option<PyObject> {
+                    let template = &self.bagging.template;
+                    self.bagging
+                        .predict_one(&x)
+                        .map(|label| template.label_to_py(py, label))
+                }
+                /// Average of the probabilities of the members.
+                pub fn predict_proba_one(
+                    &self,
+                    py: Python,
+                    x: HashMap<String, f64>,
+                ) -> PyResult<PyObject> {
+                    let template = &self.bagging.template;
+                    template.proba_to_py(py, self.bagging.predict_proba_one(&x))
+                }
+                /// Copies of the members.
+                #[getter]
+                pub fn models(&self, py: Python) -> PyResult<Vec<PyObject>> {
+                    self.bagging
+                        .members
+                        .iter()
+                        .map(|member| member.model.to_py(py))
+                        .collect()
+                }
+                /// Error of each member, over the window of its detector.
+                #[getter]
+                pub fn errors(&self) -> Vec<f64> {
+                    self.bagging
+                        .members
+                        .iter()
+                        .map(|member| member.detector.mean())
+                        .collect()
+                }
+                /// Number of members replaced after a drift.
+                #[getter]
+                pub fn n_replacements(&self) -> usize {
+                    self.bagging.n_replacements
+                }
+                #[getter]
+                pub fn n_models(&self) -> usize {
+                    self.bagging.members.len()
+                }
+            }
+        )*
+    };
+}
+
+bagging!(RsLeveragingBaggingClassifier, RsADWINBaggingClassifier);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLeveragingBaggingClassifier {
+    bagging: Bagging,
+}
+
+#[pymethods]
+impl RsLeveragingBaggingClassifier {
+    /// Leveraging bagging of `n_models` copies of `model`, a `RsLogisticRegression` or a
+    /// `RsOutputCodeClassifier`.
+    ///
+    /// The `bagging_method` sets the number of times a member learns a sample:
+    ///
+    /// - `"bag"`, a Poisson(`w`) number of times,
+    /// - `"me"`, once if the member misclassifies it, and otherwise with probability
+    ///   `e / (1 - e)`, `e` being its error,
+    /// - `"half"`, once with probability 1/2,
+    /// - `"wt"`, 1 plus a Poisson(`w`) number of times,
+    /// - `"subag"`, once with probability 1 - 1/e.
+    ///
+    /// Reference: Bifet, A., Holmes, G. and Pfahringer, B., 2010. Leveraging bagging for
+    /// evolving data streams. In Joint European Conference on Machine Learning and Knowledge
+    /// Discovery in Databases, pp.135-150.
+    #[new]
+    #[args(
+        n_models = "10",
+        w = "6.0",
+        adwin_delta = "0.002",
+        bagging_method = "\"bag\"",
+        seed = "None",
+        parallel = "false"
+    )]
+    pub fn new(
+        model: &PyAny,
+        n_models: usize,
+        w: f64,
+        adwin_delta: f64,
+        bagging_method: &str,
+        seed: Option<u64>,
+        parallel: bool,
+    ) -> PyResult<Self> {
+        if w.is_nan() || w <= 0.0 {
+            return Err(PyValueError::new_err("w should be positive"));
+        }
+        let weighting = Weighting::parse(bagging_method, w)?;
+        Ok(RsLeveragingBaggingClassifier {
+            bagging: Bagging::new(model, n_models, weighting, adwin_delta, parallel, seed)?,
+        })
+    }
+
+    #[getter]
+    pub fn bagging_method(&self) -> &'static str {
+        self.bagging.weighting.name()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsADWINBaggingClassifier {
+    bagging: Bagging,
+}
+
+#[pymethods]
+impl RsADWINBaggingClassifier {
+    /// Online bagging of `n_models` copies of `model`, a `RsLogisticRegression` or a
+    /// `RsOutputCodeClassifier`, each member learning a sample a Poisson(1) number of times.
+    ///
+    /// Reference: Bifet, A., Holmes, G., Pfahringer, B., Kirkby, R. and Gavalda, R., 2009.
+    /// New ensemble methods for evolving data streams. In Proceedings of the 15th ACM
+    /// SIGKDD International Conference on Knowledge Discovery and Data Mining, pp.139-148.
+    #[new]
+    #[args(
+        n_models = "10",
+        adwin_delta = "0.002",
+        seed = "None",
+        parallel = "false"
+    )]
+    pub fn new(
+        model: &PyAny,
+        n_models: usize,
+        adwin_delta: f64,
+        seed: Option<u64>,
+        parallel: bool,
+    ) -> PyResult<Self> {
+        Ok(RsADWINBaggingClassifier {
+            bagging: Bagging::new(model, n_models, Weighting::Oza, adwin_delta, parallel, seed)?,
+        })
+    }
+}
@@ -0,0 +1,219 @@
+// Micro-benchmarks of the univariate stats and of the distance kernel of the neighbors, to
+// compare the throughput of two builds.
+//
+// Each stat is updated with the same stream of uniform values, drawn from a seeded `Rng`,
+// and then read as many times. The timings are taken in Rust with the GIL released, so
+// they don't include the cost of crossing the Python boundary.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::bindings::{
+    RsEWMean, RsEWVar, RsIQR, RsKurtosis, RsPeakToPeak, RsQuantile, RsRollingIQR, RsRollingMAD,
+    RsRollingMax, RsRollingMean, RsRollingMin, RsRollingPeakToPeak, RsRollingQuantile,
+    RsRollingVar, RsSkew,
+};
+use crate::drift::{RsAdaptiveMean, RsAdaptiveVar};
+use crate::neighbors::sq_euclidean;
+use crate::rng::Rng;
+use crate::stats::{RsAutoCorr, RsMAD, RsPercentileRank, RsRollingPercentileRank, RsShift};
+use crate::univariate::AnyStat;
+
+const WINDOW_SIZE: usize = 1000;
+
+fn candidates() -> PyResult<Vec<(&'static str, AnyStat)>> {
+    Ok(vec![
+        (
+            "RsQuantile",
+            AnyStat::Quantile(RsQuantile::new(Some(0.5), 5, "p2", 100.0)?),
+        ),
+        (
+            "RsEWMean",
+            AnyStat::EWMean(RsEWMean::new(Some(0.5), None, None, None)?),
+        ),
+        (
+            "RsEWVar",
+            AnyStat::EWVar(RsEWVar::new(Some(0.5), None, None, None)?),
+        ),
+        ("RsIQR", AnyStat::Iqr(RsIQR::new(0.25, 0.75)?)),
+        ("RsKurtosis", AnyStat::Kurtosis(RsKurtosis::new(false))),
+        ("RsPeakToPeak", AnyStat::PeakToPeak(RsPeakToPeak::new())),
+        ("RsSkew", AnyStat::Skew(RsSkew::new(false))),
+        (
+            "RsRollingQuantile",
+            AnyStat::RollingQuantile(RsRollingQuantile::new(0.5, WINDOW_SIZE)?),
+        ),
+        (
+            "RsRollingIQR",
+            AnyStat::RollingIqr(RsRollingIQR::new(0.25, 0.75, WINDOW_SIZE)?),
+        ),
+        (
+            "RsRollingMean",
+            AnyStat::RollingMean(RsRollingMean::new(WINDOW_SIZE)?),
+        ),
+        (
+            "RsRollingVar",
+            AnyStat::RollingVar(RsRollingVar::new(WINDOW_SIZE, 1)?),
+        ),
+        (
+            "RsRollingMin",
+            AnyStat::RollingMin(RsRollingMin::new(WINDOW_SIZE)?),
+        ),
+        (
+            "RsRollingMax",
+            AnyStat::RollingMax(RsRollingMax::new(WINDOW_SIZE)?),
+        ),
+        (
+            "RsRollingPeakToPeak",
+            AnyStat::RollingPeakToPeak(RsRollingPeakToPeak::new(WINDOW_SIZE)?),
+        ),
+        ("RsMAD", AnyStat::Mad(RsMAD::new(100.0)?)),
+        (
+            "RsRollingMAD",
+            AnyStat::RollingMad(RsRollingMAD::new(WINDOW_SIZE)?),
+        ),
+        (
+            "RsPercentileRank",
+            AnyStat::PercentileRank(RsPercentileRank::new(100.0)?),
+        ),
+        (
+            "RsRollingPercentileRank",
+            AnyStat::RollingPercentileRank(RsRollingPercentileRank::new(WINDOW_SIZE)?),
+        ),
+        (
+            "RsAdaptiveMean",
+            AnyStat::AdaptiveMean(RsAdaptiveMean::new(0.002, 32, 10)?),
+        ),
+        (
+            "RsAdaptiveVar",
+            AnyStat::AdaptiveVar(RsAdaptiveVar::new(0.002, 32, 10)?),
+        ),
+        ("RsShift", AnyStat::Shift(RsShift::new(1, f64::NAN))),
+        ("RsAutoCorr", AnyStat::AutoCorr(RsAutoCorr::new(1)?)),
+    ])
+}
+
+struct Timing {
+    name: &'static str,
+    update_ns: f64,
+    get_ns: f64,
+}
+
+fn time(name: &'static str, mut stat: AnyStat, values: &[f64]) -> PyResult<Timing> {
+    let start = Instant::now();
+    for &x in values {
+        stat.update(black_box(x))?;
+    }
+    let update = start.elapsed();
+    let start = Instant::now();
+    for _ in values {
+        black_box(black_box(&stat).get());
+    }
+    let get = start.elapsed();
+    let n = values.len() as f64;
+    Ok(Timing {
+        name,
+        update_ns: update.as_nanos() as f64 / n,
+        get_ns: get.as_nanos() as f64 / n,
+    })
+}
+
+/// Times `update` and `get` of each univariate stat over `n` synthetic values.
+///
+/// Returns one dict per stat, holding its `name`, the number of values `n`, and the mean
+/// duration of an update and of a read, in nanoseconds, as `update_ns` and `get_ns`.
+#[pyfunction(n = "100_000", seed = "42")]
+pub fn bench(py: Python, n: usize, seed: u64) -> PyResult<Vec<PyObject>> {
+    if n == 0 {
+        return Err(PyValueError::new_err("n should be at least 1"));
+    }
+    let mut rng = Rng::from_seed(seed);
+    let values: Vec<f64> = (0..n).map(|_| rng.next_f64()).collect();
+    let candidates = candidates()?;
+    let timings = py.allow_threads(|| {
+        candidates
+            .into_iter()
+            .map(|(name, stat)| time(name, stat, &values))
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+    timings
+        .into_iter()
+        .map(|timing| {
+            let report = PyDict::new(py);
+            report.set_item("name", timing.name)?;
+            report.set_item("n", n)?;
+            report.set_item("update_ns", timing.update_ns)?;
+            report.set_item("get_ns", timing.get_ns)?;
+            Ok(report.into())
+        })
+        .collect()
+}
+
+type Kernel = fn(&[f64], &[f64]) -> f64;
+
+// The distance kernel before it was split into partial sums, as a baseline.
+fn scalar_sq_euclidean(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn time_distances(kernel: Kernel, x: &[f64], samples: &[f64]) -> f64 {
+    let start = Instant::now();
+    for row in samples.chunks_exact(x.len()) {
+        black_box(kernel(black_box(x), black_box(row)));
+    }
+    start.elapsed().as_nanos() as f64 / (samples.len() / x.len()) as f64
+}
+
+/// Times the squared Euclidean distances from a point to `n_samples` samples of
+/// `n_features` uniform values, with the kernel of the neighbors and with a plain scalar
+/// loop, each `repeat` times.
+///
+/// Returns one dict per kernel, `"chunked"` and `"scalar"`, holding its `name`,
+/// `n_samples`, `n_features` and the shortest mean duration of a distance in nanoseconds,
+/// as `distance_ns`.
+#[pyfunction(n_samples = "10_000", n_features = "100", repeat = "10", seed = "42")]
+pub fn bench_distances(
+    py: Python,
+    n_samples: usize,
+    n_features: usize,
+    repeat: usize,
+    seed: u64,
+) -> PyResult<Vec<PyObject>> {
+    if n_samples == 0 || n_features == 0 || repeat == 0 {
+        return Err(PyValueError::new_err(
+            "n_samples, n_features and repeat should be at least 1",
+        ));
+    }
+    let mut rng = Rng::from_seed(seed);
+    let x: Vec<f64> = (0..n_features).map(|_| rng.next_f64()).collect();
+    let samples: Vec<f64> = (0..n_samples * n_features)
+        .map(|_| rng.next_f64())
+        .collect();
+    let kernels: [(&str, Kernel); 2] = [("chunked", sq_euclidean), ("scalar", scalar_sq_euclidean)];
+    let timings: Vec<(&str, f64)> = py.allow_threads(|| {
+        kernels
+            .iter()
+            .map(|&(name, kernel)| {
+                let best = (0..repeat)
+                    .map(|_| time_distances(kernel, &x, &samples))
+                    .fold(f64::INFINITY, f64::min);
+                (name, best)
+            })
+            .collect()
+    });
+    timings
+        .into_iter()
+        .map(|(name, distance_ns)| {
+            let report = PyDict::new(py);
+            report.set_item("name", name)?;
+            report.set_item("n_samples", n_samples)?;
+            report.set_item("n_features", n_features)?;
+            report.set_item("distance_ns", distance_ns)?;
+            Ok(report.into())
+        })
+        .collect()
+}
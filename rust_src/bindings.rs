@@ -0,0 +1,1299 @@
+// Bindings of the watermill statistics, and of their native replacements.
+
+use std::collections::VecDeque;
+use std::io::Read;
+use std::os::raw::c_int;
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::{ffi, AsPyPointer};
+use serde::{Deserialize, Serialize};
+use watermill::{
+    ewmean::EWMean, ewvariance::EWVariance, kurtosis::Kurtosis, ptp::PeakToPeak, skew::Skew,
+    stats::Univariate,
+};
+
+use crate::memory::HeapSize;
+use crate::moments::{merge_central_moments, revert_central_moments, update_central_moments};
+use crate::quantile::{repetitions, P2Quantile, QuantileEstimator};
+use crate::rolling::{
+    check_q, OrderedWindow, RollingExtremum, RollingIQR, RollingMoments, RollingQuantile,
+};
+use crate::sketch::TDigest;
+use crate::state::Versioned;
+use crate::univariate::{Param, Params};
+use crate::view;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsQuantile {
+    pub quantile: QuantileEstimator,
+}
+
+impl HeapSize for RsQuantile {
+    fn heap_size(&self) -> usize {
+        self.quantile.heap_size()
+    }
+}
+
+impl Versioned for RsQuantile {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the state is the P² estimator alone.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        Ok(RsQuantile {
+            quantile: QuantileEstimator::P2(bincode::deserialize_from(payload)?),
+        })
+    }
+}
+
+impl Params for RsQuantile {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        let algorithm = match self.quantile {
+            QuantileEstimator::P2(_) => "p2",
+            QuantileEstimator::TDigest { .. } => "tdigest",
+        };
+        vec![
+            ("q", Param::Float(self.quantile.q())),
+            ("algorithm", Param::Str(algorithm)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsQuantile {
+    /// The `"p2"` algorithm keeps `n_markers` markers, and above the 5 of the classic P²
+    /// algorithm trades memory for accuracy. The `"tdigest"` one keeps a t-digest with the
+    /// given `compression` instead, which is more accurate on heavy-tailed data.
+    #[new]
+    #[args(
+        q = "None",
+        n_markers = "5",
+        algorithm = "\"p2\"",
+        compression = "100.0"
+    )]
+    pub fn new(
+        q: Option<f64>,
+        n_markers: usize,
+        algorithm: &str,
+        compression: f64,
+    ) -> PyResult<RsQuantile> {
+        let q = q.unwrap_or(0.5);
+        let quantile = match algorithm {
+            "p2" => {
+                QuantileEstimator::P2(P2Quantile::new(q, n_markers).map_err(PyValueError::new_err)?)
+            }
+            "tdigest" => {
+                check_q(q, "q").map_err(PyValueError::new_err)?;
+                if compression.is_nan() || compression < 1.0 {
+                    return Err(PyValueError::new_err("compression should be at least 1"));
+                }
+                QuantileEstimator::TDigest {
+                    q,
+                    digest: TDigest::new(compression),
+                }
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown algorithm '{}', expected 'p2' or 'tdigest'",
+                    algorithm
+                )))
+            }
+        };
+        Ok(RsQuantile { quantile })
+    }
+    /// Adds `x` with weight `w`, which should be a whole number with the `"p2"` algorithm.
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        self.quantile.update(x, w).map_err(PyValueError::new_err)
+    }
+    pub fn get(&self) -> f64 {
+        self.quantile.get()
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.quantile.n()
+    }
+}
+
+// The weight of a value counts it that many times, so that a weight of 0 skips it.
+pub(crate) fn check_w(w: f64) -> PyResult<()> {
+    if !w.is_finite() || w < 0.0 {
+        return Err(PyValueError::new_err("w should be finite and non-negative"));
+    }
+    Ok(())
+}
+
+// A window holds every value once, so the rolling stats only take a weight of 1, which lets
+// them be updated like the weighted ones.
+pub(crate) fn check_unit_w(w: f64) -> PyResult<()> {
+    if w != 1.0 {
+        return Err(PyValueError::new_err(
+            "w should be 1, since every value of a window counts once",
+        ));
+    }
+    Ok(())
+}
+
+// Smoothing factor from exactly one of its pandas parameterizations.
+fn ew_alpha(
+    alpha: Option<f64>,
+    span: Option<f64>,
+    halflife: Option<f64>,
+    com: Option<f64>,
+) -> PyResult<f64> {
+    match (alpha, span, halflife, com) {
+        (Some(alpha), None, None, None) => {
+            if !(0.0..=1.0).contains(&alpha) {
+                return Err(PyValueError::new_err("alpha should be between 0 and 1"));
+            }
+            Ok(alpha)
+        }
+        (None, Some(span), None, None) => {
+            if span.is_nan() || span < 1.0 {
+                return Err(PyValueError::new_err("span should be at least 1"));
+            }
+            Ok(2.0 / (span + 1.0))
+        }
+        (None, None, Some(halflife), None) => {
+            if halflife.is_nan() || halflife <= 0.0 {
+                return Err(PyValueError::new_err("halflife should be positive"));
+            }
+            Ok(1.0 - (-std::f64::consts::LN_2 / halflife).exp())
+        }
+        (None, None, None, Some(com)) => {
+            if com.is_nan() || com < 0.0 {
+                return Err(PyValueError::new_err("com should be non-negative"));
+            }
+            Ok(1.0 / (1.0 + com))
+        }
+        _ => Err(PyValueError::new_err(
+            "exactly one of alpha, span, halflife and com should be given",
+        )),
+    }
+}
+
+// A value of weight `w` moves the mean as much as `w` updates with it would, and like the
+// watermill update, the first nonzero value replaces the initial mean of 0.
+fn ew_update(ewmean: &mut EWMean<f64>, x: f64, w: f64) {
+    if w == 1.0 {
+        ewmean.update(x);
+    } else if w > 0.0 {
+        if ewmean.mean == 0.0 {
+            ewmean.mean = x;
+        } else {
+            let alpha = 1.0 - (1.0 - ewmean.alpha).powf(w);
+            ewmean.mean = alpha * x + (1.0 - alpha) * ewmean.mean;
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsEWMean {
+    ewmean: EWMean<f64>,
+    alpha: f64,
+    n: f64,
+}
+
+impl HeapSize for RsEWMean {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Versioned for RsEWMean {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the weight seen isn't stored, and counts from 0 again.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (ewmean, alpha) = bincode::deserialize_from(payload)?;
+        Ok(RsEWMean {
+            ewmean,
+            alpha,
+            n: 0.0,
+        })
+    }
+}
+
+impl Params for RsEWMean {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("alpha", Param::Float(self.alpha))]
+    }
+}
+
+#[pymethods]
+impl RsEWMean {
+    /// Either `alpha`, the weight of the latest value, or one of `span`, `halflife` and
+    /// `com`, which are converted to `alpha` as in pandas.
+    #[new]
+    #[args(alpha = "None", "*", span = "None", halflife = "None", com = "None")]
+    pub fn new(
+        alpha: Option<f64>,
+        span: Option<f64>,
+        halflife: Option<f64>,
+        com: Option<f64>,
+    ) -> PyResult<RsEWMean> {
+        let alpha = ew_alpha(alpha, span, halflife, com)?;
+        Ok(RsEWMean {
+            ewmean: EWMean::new(alpha),
+            alpha,
+            n: 0.0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        ew_update(&mut self.ewmean, x, w);
+        self.n += w;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.ewmean.get()
+    }
+    #[getter]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsEWVar {
+    ewvar: EWVariance<f64>,
+    alpha: f64,
+    n: f64,
+}
+
+impl HeapSize for RsEWVar {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Versioned for RsEWVar {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the weight seen isn't stored, and counts from 0 again.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (ewvar, alpha) = bincode::deserialize_from(payload)?;
+        Ok(RsEWVar {
+            ewvar,
+            alpha,
+            n: 0.0,
+        })
+    }
+}
+
+impl Params for RsEWVar {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("alpha", Param::Float(self.alpha))]
+    }
+}
+
+#[pymethods]
+impl RsEWVar {
+    /// Either `alpha`, the weight of the latest value, or one of `span`, `halflife` and
+    /// `com`, which are converted to `alpha` as in pandas.
+    #[new]
+    #[args(alpha = "None", "*", span = "None", halflife = "None", com = "None")]
+    pub fn new(
+        alpha: Option<f64>,
+        span: Option<f64>,
+        halflife: Option<f64>,
+        com: Option<f64>,
+    ) -> PyResult<RsEWVar> {
+        let alpha = ew_alpha(alpha, span, halflife, com)?;
+        Ok(RsEWVar {
+            ewvar: EWVariance::new(alpha),
+            alpha,
+            n: 0.0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        if w == 1.0 {
+            self.ewvar.update(x);
+        } else {
+            ew_update(&mut self.ewvar.mean, x, w);
+            ew_update(&mut self.ewvar.sq_mean, x * x, w);
+        }
+        self.n += w;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.ewvar.get()
+    }
+    #[getter]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+}
+
+// The two quantiles have the layout of the watermill `IQR`, which they replace.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsIQR {
+    quantiles: (P2Quantile, P2Quantile),
+    q_inf: f64,
+    q_sup: f64,
+}
+
+impl HeapSize for RsIQR {
+    fn heap_size(&self) -> usize {
+        self.quantiles.heap_size()
+    }
+}
+
+impl Params for RsIQR {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![
+            ("q_inf", Param::Float(self.q_inf)),
+            ("q_sup", Param::Float(self.q_sup)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsIQR {
+    #[new]
+    pub fn new(q_inf: f64, q_sup: f64) -> PyResult<RsIQR> {
+        check_q(q_inf, "q_inf").map_err(PyValueError::new_err)?;
+        check_q(q_sup, "q_sup").map_err(PyValueError::new_err)?;
+        if q_inf >= q_sup {
+            return Err(PyValueError::new_err("q_inf should be lower than q_sup"));
+        }
+        let quantile = |q| P2Quantile::new(q, 5).map_err(PyValueError::new_err);
+        Ok(RsIQR {
+            quantiles: (quantile(q_inf)?, quantile(q_sup)?),
+            q_inf,
+            q_sup,
+        })
+    }
+    /// Adds `x` with weight `w`, which should be a whole number.
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        for _ in 0..repetitions(w).map_err(PyValueError::new_err)? {
+            self.quantiles.0.update(x);
+            self.quantiles.1.update(x);
+        }
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.quantiles.1.get() - self.quantiles.0.get()
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.quantiles.0.n()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsKurtosis {
+    kurtosis: Kurtosis<f64>,
+    bias: bool,
+}
+
+impl HeapSize for RsKurtosis {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Params for RsKurtosis {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("bias", Param::Bool(self.bias))]
+    }
+}
+
+#[pymethods]
+impl RsKurtosis {
+    #[new]
+    pub fn new(bias: bool) -> RsKurtosis {
+        RsKurtosis {
+            kurtosis: Kurtosis::new(bias),
+            bias,
+        }
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        if w == 1.0 {
+            self.kurtosis.update(x);
+        } else {
+            update_central_moments(&mut self.kurtosis.central_moments, x, w);
+        }
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.kurtosis.get()
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.kurtosis.central_moments.count.count
+    }
+    /// Removes `x`, which should have been given to `update` before.
+    pub fn revert(&mut self, x: f64) -> PyResult<()> {
+        revert_central_moments(&mut self.kurtosis.central_moments, x).map_err(PyValueError::new_err)
+    }
+    /// Folds in the observations of `other`, e.g. a stat computed on another shard.
+    pub fn merge(&mut self, other: PyRef<RsKurtosis>) {
+        merge_central_moments(
+            &mut self.kurtosis.central_moments,
+            &other.kurtosis.central_moments,
+        );
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPeakToPeak {
+    ptp: PeakToPeak<f64>,
+    n: f64,
+}
+
+impl HeapSize for RsPeakToPeak {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Versioned for RsPeakToPeak {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the weight seen isn't stored, and counts from 0 again.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        Ok(RsPeakToPeak {
+            ptp: bincode::deserialize_from(payload)?,
+            n: 0.0,
+        })
+    }
+}
+
+impl Params for RsPeakToPeak {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        Vec::new()
+    }
+}
+
+#[pymethods]
+impl RsPeakToPeak {
+    #[new]
+    pub fn new() -> RsPeakToPeak {
+        RsPeakToPeak {
+            ptp: PeakToPeak::new(),
+            n: 0.0,
+        }
+    }
+    /// The extremes ignore the weights, except that a weight of 0 skips `x`.
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        if w > 0.0 {
+            self.ptp.update(x);
+        }
+        self.n += w;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.ptp.get()
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+    /// Folds in the extremes of `other`, e.g. a stat computed on another shard.
+    pub fn merge(&mut self, other: PyRef<RsPeakToPeak>) {
+        self.ptp.min.update(other.ptp.min.min);
+        self.ptp.max.update(other.ptp.max.max);
+        self.n += other.n;
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSkew {
+    skew: Skew<f64>,
+    bias: bool,
+}
+
+impl HeapSize for RsSkew {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Params for RsSkew {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("bias", Param::Bool(self.bias))]
+    }
+}
+
+#[pymethods]
+impl RsSkew {
+    #[new]
+    pub fn new(bias: bool) -> RsSkew {
+        RsSkew {
+            skew: Skew::new(bias),
+            bias,
+        }
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        if w == 1.0 {
+            self.skew.update(x);
+        } else {
+            update_central_moments(&mut self.skew.central_moments, x, w);
+        }
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.skew.get()
+    }
+    /// Total weight of the values seen so far.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.skew.central_moments.count.count
+    }
+    /// Removes `x`, which should have been given to `update` before.
+    pub fn revert(&mut self, x: f64) -> PyResult<()> {
+        revert_central_moments(&mut self.skew.central_moments, x).map_err(PyValueError::new_err)
+    }
+    /// Folds in the observations of `other`, e.g. a stat computed on another shard.
+    pub fn merge(&mut self, other: PyRef<RsSkew>) {
+        merge_central_moments(&mut self.skew.central_moments, &other.skew.central_moments);
+    }
+}
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingMean {
+    stat: RollingMoments,
+    n: u64,
+}
+
+impl HeapSize for RsRollingMean {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+impl Versioned for RsRollingMean {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those of the
+    // window.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let stat: RollingMoments = bincode::deserialize_from(payload)?;
+        let n = stat.len() as u64;
+        Ok(RsRollingMean { stat, n })
+    }
+}
+
+impl Params for RsRollingMean {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("window_size", Param::Int(self.stat.window_size()))]
+    }
+}
+
+#[pymethods]
+impl RsRollingMean {
+    #[new]
+    pub fn new(window_size: usize) -> PyResult<RsRollingMean> {
+        Ok(RsRollingMean {
+            stat: RollingMoments::new(window_size).map_err(PyValueError::new_err)?,
+            n: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.stat.update(x);
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.mean()
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    /// Copy of the window, from the oldest to the most recent value.
+    pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_iter(py, self.stat.iter().copied())
+    }
+    pub fn __len__(&self) -> usize {
+        self.stat.len()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingVar {
+    stat: RollingMoments,
+    ddof: usize,
+    n: u64,
+}
+
+impl HeapSize for RsRollingVar {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+impl Versioned for RsRollingVar {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those of the
+    // window.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (stat, ddof): (RollingMoments, usize) = bincode::deserialize_from(payload)?;
+        let n = stat.len() as u64;
+        Ok(RsRollingVar { stat, ddof, n })
+    }
+}
+
+impl Params for RsRollingVar {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![
+            ("window_size", Param::Int(self.stat.window_size())),
+            ("ddof", Param::Int(self.ddof)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsRollingVar {
+    #[new]
+    #[args(ddof = "1")]
+    pub fn new(window_size: usize, ddof: usize) -> PyResult<RsRollingVar> {
+        Ok(RsRollingVar {
+            stat: RollingMoments::new(window_size).map_err(PyValueError::new_err)?,
+            ddof,
+            n: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.stat.update(x);
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.variance(self.ddof)
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        self.stat.mean()
+    }
+    /// Copy of the window, from the oldest to the most recent value.
+    pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_iter(py, self.stat.iter().copied())
+    }
+    pub fn __len__(&self) -> usize {
+        self.stat.len()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingQuantile {
+    stat: RollingQuantile,
+    q: f64,
+    window_size: usize,
+    n: u64,
+    #[serde(skip)]
+    exports: usize,
+}
+
+impl HeapSize for RsRollingQuantile {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+impl view::Exporting for RsRollingQuantile {
+    fn exports(&self) -> usize {
+        self.exports
+    }
+}
+
+// The views belong to the original, so a copy starts without any.
+impl Clone for RsRollingQuantile {
+    fn clone(&self) -> Self {
+        RsRollingQuantile {
+            stat: self.stat.clone(),
+            q: self.q,
+            window_size: self.window_size,
+            n: self.n,
+            exports: 0,
+        }
+    }
+}
+
+impl Versioned for RsRollingQuantile {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those of the
+    // window.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (stat, q, window_size): (RollingQuantile, f64, usize) =
+            bincode::deserialize_from(payload)?;
+        let n = stat.window().len() as u64;
+        Ok(RsRollingQuantile {
+            stat,
+            q,
+            window_size,
+            n,
+            exports: 0,
+        })
+    }
+}
+
+impl RsRollingQuantile {
+    pub fn push_missing(&mut self) -> PyResult<()> {
+        view::check_not_exported(self.exports)?;
+        self.stat.window_mut().push_missing();
+        Ok(())
+    }
+}
+
+impl Params for RsRollingQuantile {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![
+            ("q", Param::Float(self.q)),
+            ("window_size", Param::Int(self.window_size)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsRollingQuantile {
+    #[new]
+    pub fn new(q: f64, window_size: usize) -> PyResult<RsRollingQuantile> {
+        Ok(RsRollingQuantile {
+            stat: RollingQuantile::new(q, window_size).map_err(PyValueError::new_err)?,
+            q,
+            window_size,
+            n: 0,
+            exports: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        view::check_not_exported(self.exports)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.stat.update(x);
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.get()
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    /// Number of values of the window strictly lower than `x`.
+    pub fn rank(&self, x: f64) -> usize {
+        self.stat.window().rank(x)
+    }
+    /// Quantiles `qs` of the window, in one call.
+    pub fn get_many(&self, qs: Vec<f64>) -> PyResult<Vec<f64>> {
+        self.stat
+            .window()
+            .quantiles(&qs)
+            .map_err(PyValueError::new_err)
+    }
+    /// Copy of the window, from the oldest to the most recent value, or in increasing
+    /// order if `sorted` is true.
+    #[args(sorted = "false")]
+    pub fn window<'py>(&self, py: Python<'py>, sorted: bool) -> &'py PyArray1<f64> {
+        let window = self.stat.window();
+        if sorted {
+            PyArray1::from_iter(py, window.iter_sorted().copied())
+        } else {
+            PyArray1::from_iter(py, window.iter().copied())
+        }
+    }
+    pub fn __len__(&self) -> usize {
+        self.stat.window().len()
+    }
+    /// Read-only view of the window, from the oldest to the most recent value.
+    unsafe fn __getbuffer__(
+        mut slf: PyRefMut<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let owner = slf.as_ptr();
+        view::fill_view(view, flags, owner, slf.stat.window_mut().values())?;
+        slf.exports += 1;
+        Ok(())
+    }
+    unsafe fn __releasebuffer__(mut slf: PyRefMut<Self>, view: *mut ffi::Py_buffer) {
+        view::release_view(view);
+        slf.exports -= 1;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingIQR {
+    stat: RollingIQR,
+    q_inf: f64,
+    q_sup: f64,
+    window_size: usize,
+    n: u64,
+    #[serde(skip)]
+    exports: usize,
+}
+
+impl HeapSize for RsRollingIQR {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+impl view::Exporting for RsRollingIQR {
+    fn exports(&self) -> usize {
+        self.exports
+    }
+}
+
+impl Clone for RsRollingIQR {
+    fn clone(&self) -> Self {
+        RsRollingIQR {
+            stat: self.stat.clone(),
+            q_inf: self.q_inf,
+            q_sup: self.q_sup,
+            window_size: self.window_size,
+            n: self.n,
+            exports: 0,
+        }
+    }
+}
+
+impl Versioned for RsRollingIQR {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those of the
+    // window.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (stat, q_inf, q_sup, window_size): (RollingIQR, f64, f64, usize) =
+            bincode::deserialize_from(payload)?;
+        let n = stat.window().len() as u64;
+        Ok(RsRollingIQR {
+            stat,
+            q_inf,
+            q_sup,
+            window_size,
+            n,
+            exports: 0,
+        })
+    }
+}
+
+impl RsRollingIQR {
+    pub fn push_missing(&mut self) -> PyResult<()> {
+        view::check_not_exported(self.exports)?;
+        self.stat.window_mut().push_missing();
+        Ok(())
+    }
+}
+
+impl Params for RsRollingIQR {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![
+            ("q_inf", Param::Float(self.q_inf)),
+            ("q_sup", Param::Float(self.q_sup)),
+            ("window_size", Param::Int(self.window_size)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsRollingIQR {
+    #[new]
+    pub fn new(q_inf: f64, q_sup: f64, window_size: usize) -> PyResult<RsRollingIQR> {
+        Ok(RsRollingIQR {
+            stat: RollingIQR::new(q_inf, q_sup, window_size).map_err(PyValueError::new_err)?,
+            q_inf,
+            q_sup,
+            window_size,
+            n: 0,
+            exports: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        view::check_not_exported(self.exports)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.stat.update(x);
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.get()
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    /// Number of values of the window strictly lower than `x`.
+    pub fn rank(&self, x: f64) -> usize {
+        self.stat.window().rank(x)
+    }
+    /// Quantiles `qs` of the window, in one call.
+    pub fn get_many(&self, qs: Vec<f64>) -> PyResult<Vec<f64>> {
+        self.stat
+            .window()
+            .quantiles(&qs)
+            .map_err(PyValueError::new_err)
+    }
+    /// Copy of the window, from the oldest to the most recent value, or in increasing
+    /// order if `sorted` is true.
+    #[args(sorted = "false")]
+    pub fn window<'py>(&self, py: Python<'py>, sorted: bool) -> &'py PyArray1<f64> {
+        let window = self.stat.window();
+        if sorted {
+            PyArray1::from_iter(py, window.iter_sorted().copied())
+        } else {
+            PyArray1::from_iter(py, window.iter().copied())
+        }
+    }
+    pub fn __len__(&self) -> usize {
+        self.stat.window().len()
+    }
+    /// Read-only view of the window, from the oldest to the most recent value.
+    unsafe fn __getbuffer__(
+        mut slf: PyRefMut<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let owner = slf.as_ptr();
+        view::fill_view(view, flags, owner, slf.stat.window_mut().values())?;
+        slf.exports += 1;
+        Ok(())
+    }
+    unsafe fn __releasebuffer__(mut slf: PyRefMut<Self>, view: *mut ffi::Py_buffer) {
+        view::release_view(view);
+        slf.exports -= 1;
+    }
+}
+
+// Pyclass of a rolling extremum, built with `$make` from the window size.
+macro_rules! rolling_extremum {
+    ($(#[$doc:meta])* $name:ident, $make:path) => {
+        $(#[$doc])*
+        #[derive(Clone, Serialize, Deserialize)]
+        #[pyclass(module = "river.stats._rust_stats")]
+        pub struct $name {
+            stat: RollingExtremum,
+            // Every value of the window, whereas the stat only keeps its candidates.
+            window: VecDeque<f64>,
+        }
+
+        impl HeapSize for $name {
+            fn heap_size(&self) -> usize {
+                self.stat.heap_size() + self.window.heap_size()
+            }
+        }
+
+        impl Versioned for $name {
+            const VERSION: u16 = 2;
+
+            // Up to version 1, only the candidates for the extremum are stored, so the window
+            // starts empty again, while the extremum carries on.
+            fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+                let stat: RollingExtremum = bincode::deserialize_from(payload)?;
+                let window = VecDeque::with_capacity(stat.window_size());
+                Ok($name { stat, window })
+            }
+        }
+
+        impl Params for $name {
+            fn params(&self) -> Vec<(&'static str, Param)> {
+                vec![("window_size", Param::Int(self.stat.window_size()))]
+            }
+        }
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            pub fn new(window_size: usize) -> PyResult<Self> {
+                Ok($name {
+                    stat: $make(window_size).map_err(PyValueError::new_err)?,
+                    window: VecDeque::with_capacity(window_size),
+                })
+            }
+            #[args(w = "1.0")]
+            pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+                check_unit_w(w)?;
+                if x.is_nan() {
+                    return Err(PyValueError::new_err("x should not be NaN"));
+                }
+                self.stat.update(x);
+                if self.window.len() == self.stat.window_size() {
+                    self.window.pop_front();
+                }
+                self.window.push_back(x);
+                Ok(())
+            }
+            pub fn get(&self) -> f64 {
+                self.stat.get()
+            }
+            /// Number of values seen so far, including those which have left the window.
+            #[getter]
+            pub fn n(&self) -> u64 {
+                self.stat.n()
+            }
+            /// Copy of the window, from the oldest to the most recent value.
+            pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+                PyArray1::from_iter(py, self.window.iter().copied())
+            }
+        }
+    };
+}
+
+rolling_extremum!(
+    /// Minimum of a sliding window, NaN while it is empty.
+    RsRollingMin,
+    RollingExtremum::min
+);
+
+rolling_extremum!(
+    /// Maximum of a sliding window, NaN while it is empty.
+    RsRollingMax,
+    RollingExtremum::max
+);
+
+/// Difference between the maximum and the minimum of a sliding window, NaN while it is
+/// empty.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingPeakToPeak {
+    min: RollingExtremum,
+    max: RollingExtremum,
+    window: VecDeque<f64>,
+}
+
+impl HeapSize for RsRollingPeakToPeak {
+    fn heap_size(&self) -> usize {
+        self.min.heap_size() + self.max.heap_size() + self.window.heap_size()
+    }
+}
+
+impl Versioned for RsRollingPeakToPeak {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, only the candidates for the extrema are stored, so the window starts
+    // empty again, while the extrema carry on.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (min, max): (RollingExtremum, RollingExtremum) = bincode::deserialize_from(payload)?;
+        let window = VecDeque::with_capacity(min.window_size());
+        Ok(RsRollingPeakToPeak { min, max, window })
+    }
+}
+
+impl Params for RsRollingPeakToPeak {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("window_size", Param::Int(self.min.window_size()))]
+    }
+}
+
+#[pymethods]
+impl RsRollingPeakToPeak {
+    #[new]
+    pub fn new(window_size: usize) -> PyResult<Self> {
+        Ok(RsRollingPeakToPeak {
+            min: RollingExtremum::min(window_size).map_err(PyValueError::new_err)?,
+            max: RollingExtremum::max(window_size).map_err(PyValueError::new_err)?,
+            window: VecDeque::with_capacity(window_size),
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.min.update(x);
+        self.max.update(x);
+        if self.window.len() == self.min.window_size() {
+            self.window.pop_front();
+        }
+        self.window.push_back(x);
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.max.get() - self.min.get()
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.min.n()
+    }
+    /// Copy of the window, from the oldest to the most recent value.
+    pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_iter(py, self.window.iter().copied())
+    }
+}
+
+/// Exact median absolute deviation from the median of a sliding window, NaN while it is
+/// empty.
+///
+/// The buffer protocol gives a read-only view of the window.
+#[derive(Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingMAD {
+    window: OrderedWindow,
+    window_size: usize,
+    n: u64,
+    #[serde(skip)]
+    exports: usize,
+}
+
+impl HeapSize for RsRollingMAD {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl view::Exporting for RsRollingMAD {
+    fn exports(&self) -> usize {
+        self.exports
+    }
+}
+
+impl Clone for RsRollingMAD {
+    fn clone(&self) -> Self {
+        RsRollingMAD {
+            window: self.window.clone(),
+            window_size: self.window_size,
+            n: self.n,
+            exports: 0,
+        }
+    }
+}
+
+impl RsRollingMAD {
+    pub fn push_missing(&mut self) -> PyResult<()> {
+        view::check_not_exported(self.exports)?;
+        self.window.push_missing();
+        Ok(())
+    }
+}
+
+impl Params for RsRollingMAD {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("window_size", Param::Int(self.window_size))]
+    }
+}
+
+#[pymethods]
+impl RsRollingMAD {
+    #[new]
+    pub fn new(window_size: usize) -> PyResult<Self> {
+        if window_size == 0 {
+            return Err(PyValueError::new_err("window_size should be at least 1"));
+        }
+        Ok(RsRollingMAD {
+            window: OrderedWindow::new(window_size),
+            window_size,
+            n: 0,
+            exports: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        view::check_not_exported(self.exports)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.window.push(x);
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.window.mad()
+    }
+    /// Median of the window, around which the deviations are taken.
+    pub fn median(&self) -> f64 {
+        self.window.quantiles(&[0.5]).map_or(f64::NAN, |m| m[0])
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    /// Copy of the window, from the oldest to the most recent value, or in increasing
+    /// order if `sorted` is true.
+    #[args(sorted = "false")]
+    pub fn window<'py>(&self, py: Python<'py>, sorted: bool) -> &'py PyArray1<f64> {
+        if sorted {
+            PyArray1::from_iter(py, self.window.iter_sorted().copied())
+        } else {
+            PyArray1::from_iter(py, self.window.iter().copied())
+        }
+    }
+    pub fn __len__(&self) -> usize {
+        self.window.len()
+    }
+    /// Read-only view of the window, from the oldest to the most recent value.
+    unsafe fn __getbuffer__(
+        mut slf: PyRefMut<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let owner = slf.as_ptr();
+        view::fill_view(view, flags, owner, slf.window.values())?;
+        slf.exports += 1;
+        Ok(())
+    }
+    unsafe fn __releasebuffer__(mut slf: PyRefMut<Self>, view: *mut ffi::Py_buffer) {
+        view::release_view(view);
+        slf.exports -= 1;
+    }
+}
@@ -0,0 +1,283 @@
+// Streaming calibration of the scores of a binary classifier.
+//
+// The calibrators only see the scores and the labels, so they can wrap any model. Both map
+// a score in [0, 1] to a probability, and leave it unchanged until they have learnt
+// something.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+use crate::special::sigmoid;
+
+// Scores are clipped away from 0 and 1 before taking their logit.
+const EPSILON: f64 = 1e-12;
+
+pub(crate) fn check_score(p: f64) -> PyResult<()> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err(PyValueError::new_err("p should be between 0 and 1"));
+    }
+    Ok(())
+}
+
+/// Consecutive scores sharing the same calibrated value.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Block {
+    x_min: f64,
+    x_max: f64,
+    sum_x: f64,
+    sum_y: f64,
+    weight: f64,
+}
+
+impl Block {
+    fn new(x: f64, y: f64) -> Self {
+        Block {
+            x_min: x,
+            x_max: x,
+            sum_x: x,
+            sum_y: y,
+            weight: 1.0,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.sum_y / self.weight
+    }
+
+    fn center(&self) -> f64 {
+        self.sum_x / self.weight
+    }
+
+    fn absorb(&mut self, other: &Block) {
+        self.x_min = self.x_min.min(other.x_min);
+        self.x_max = self.x_max.max(other.x_max);
+        self.sum_x += other.sum_x;
+        self.sum_y += other.sum_y;
+        self.weight += other.weight;
+    }
+}
+
+impl HeapSize for Block {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Isotonic regression of the labels on the scores, maintained online.
+///
+/// The fit is a sequence of blocks, sorted by score, whose values are non-decreasing. A new
+/// pair joins the block spanning its score, or becomes a block of its own, and the pool
+/// adjacent violators algorithm then merges it with its neighbours until the values are
+/// monotone again. Beyond `max_blocks`, the two adjacent blocks with the closest values are
+/// merged, which keeps the fit monotone. Scores are mapped by interpolating linearly
+/// between the centers of the blocks.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IsotonicCalibrator {
+    max_blocks: usize,
+    blocks: Vec<Block>,
+}
+
+impl HeapSize for IsotonicCalibrator {
+    fn heap_size(&self) -> usize {
+        self.blocks.heap_size()
+    }
+}
+
+impl IsotonicCalibrator {
+    pub fn new(max_blocks: usize) -> Self {
+        IsotonicCalibrator {
+            max_blocks,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn n_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn learn_one(&mut self, p: f64, y: bool) {
+        let y = if y { 1.0 } else { 0.0 };
+        let i = self.blocks.partition_point(|block| block.x_max < p);
+        let i = if i < self.blocks.len() && self.blocks[i].x_min <= p {
+            self.blocks[i].absorb(&Block::new(p, y));
+            i
+        } else {
+            self.blocks.insert(i, Block::new(p, y));
+            i
+        };
+        self.pool(i);
+        if self.blocks.len() > self.max_blocks {
+            let i = (1..self.blocks.len())
+                .min_by(|&a, &b| {
+                    let gap = |i: usize| self.blocks[i].value() - self.blocks[i - 1].value();
+                    gap(a).total_cmp(&gap(b))
+                })
+                .unwrap();
+            self.merge(i);
+        }
+    }
+
+    // Merges block `i` into block `i - 1`.
+    fn merge(&mut self, i: usize) {
+        let block = self.blocks.remove(i);
+        self.blocks[i - 1].absorb(&block);
+    }
+
+    // Restores the monotonicity around the block `i`, the only one which changed.
+    fn pool(&mut self, mut i: usize) {
+        loop {
+            if i > 0 && self.blocks[i - 1].value() > self.blocks[i].value() {
+                self.merge(i);
+                i -= 1;
+            } else if i + 1 < self.blocks.len()
+                && self.blocks[i].value() > self.blocks[i + 1].value()
+            {
+                self.merge(i + 1);
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn transform_one(&self, p: f64) -> f64 {
+        let (first, last) = match (self.blocks.first(), self.blocks.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return p,
+        };
+        if p <= first.center() {
+            return first.value();
+        }
+        if p >= last.center() {
+            return last.value();
+        }
+        let i = self.blocks.partition_point(|block| block.center() <= p);
+        let (left, right) = (&self.blocks[i - 1], &self.blocks[i]);
+        let t = (p - left.center()) / (right.center() - left.center());
+        left.value() + t * (right.value() - left.value())
+    }
+}
+
+/// Logistic regression of the labels on the logits of the scores, fitted with SGD.
+///
+/// It starts from the identity mapping, `a = 1` and `b = 0`, and moves the slope and the
+/// intercept against the gradient of the log loss with the learning rate `lr`.
+///
+/// Reference: Platt, J., 1999. Probabilistic outputs for support vector machines and
+/// comparisons to regularized likelihood methods. Advances in Large Margin Classifiers,
+/// 10(3), pp.61-74.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlattCalibrator {
+    lr: f64,
+    a: f64,
+    b: f64,
+}
+
+impl HeapSize for PlattCalibrator {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(EPSILON, 1.0 - EPSILON);
+    (p / (1.0 - p)).ln()
+}
+
+impl PlattCalibrator {
+    pub fn new(lr: f64) -> Self {
+        PlattCalibrator { lr, a: 1.0, b: 0.0 }
+    }
+
+    pub fn learn_one(&mut self, p: f64, y: bool) {
+        let z = logit(p);
+        let error = sigmoid(self.a * z + self.b) - if y { 1.0 } else { 0.0 };
+        self.a -= self.lr * error * z;
+        self.b -= self.lr * error;
+    }
+
+    pub fn transform_one(&self, p: f64) -> f64 {
+        sigmoid(self.a * logit(p) + self.b)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsIsotonicCalibrator {
+    pub(crate) calibrator: IsotonicCalibrator,
+}
+
+impl HeapSize for RsIsotonicCalibrator {
+    fn heap_size(&self) -> usize {
+        self.calibrator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsIsotonicCalibrator {
+    #[new]
+    #[args(max_blocks = "100")]
+    pub fn new(max_blocks: usize) -> PyResult<Self> {
+        if max_blocks < 2 {
+            return Err(PyValueError::new_err("max_blocks should be at least 2"));
+        }
+        Ok(RsIsotonicCalibrator {
+            calibrator: IsotonicCalibrator::new(max_blocks),
+        })
+    }
+    pub fn learn_one(&mut self, p: f64, y: bool) -> PyResult<()> {
+        check_score(p)?;
+        self.calibrator.learn_one(p, y);
+        Ok(())
+    }
+    pub fn transform_one(&self, p: f64) -> PyResult<f64> {
+        check_score(p)?;
+        Ok(self.calibrator.transform_one(p))
+    }
+    /// Number of constant pieces of the fit.
+    #[getter]
+    pub fn n_blocks(&self) -> usize {
+        self.calibrator.n_blocks()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPlattCalibrator {
+    pub(crate) calibrator: PlattCalibrator,
+}
+
+impl HeapSize for RsPlattCalibrator {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsPlattCalibrator {
+    #[new]
+    #[args(lr = "0.01")]
+    pub fn new(lr: f64) -> PyResult<Self> {
+        if lr.is_nan() || lr <= 0.0 {
+            return Err(PyValueError::new_err("lr should be positive"));
+        }
+        Ok(RsPlattCalibrator {
+            calibrator: PlattCalibrator::new(lr),
+        })
+    }
+    pub fn learn_one(&mut self, p: f64, y: bool) -> PyResult<()> {
+        check_score(p)?;
+        self.calibrator.learn_one(p, y);
+        Ok(())
+    }
+    pub fn transform_one(&self, p: f64) -> PyResult<f64> {
+        check_score(p)?;
+        Ok(self.calibrator.transform_one(p))
+    }
+    /// Slope and intercept of the logistic mapping of the logits.
+    #[getter]
+    pub fn coefficients(&self) -> (f64, f64) {
+        (self.calibrator.a, self.calibrator.b)
+    }
+}
@@ -0,0 +1,116 @@
+// Copies of the native classifiers, which the wrappers, like the samplers of `imblearn`,
+// train without going through Python.
+//
+// The labels are `Key`s. Those of a `RsLogisticRegression` are `False` and `True`, read as 0
+// and 1, and given back as bools.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::linear::{LogisticRegression, RsLogisticRegression};
+use crate::memory::HeapSize;
+use crate::multiclass::{OutputCodeClassifier, RsOutputCodeClassifier};
+
+/// Copy of a Rust-backed classifier.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Classifier {
+    Logistic(LogisticRegression),
+    OutputCode(OutputCodeClassifier),
+}
+
+impl HeapSize for Classifier {
+    fn heap_size(&self) -> usize {
+        match self {
+            Classifier::Logistic(model) => model.heap_size(),
+            Classifier::OutputCode(model) => model.heap_size(),
+        }
+    }
+}
+
+impl Classifier {
+    pub fn from_py(model: &PyAny) -> PyResult<Self> {
+        if let Ok(model) = model.extract::<PyRef<RsLogisticRegression>>() {
+            return Ok(Classifier::Logistic(model.model.clone()));
+        }
+        if let Ok(model) = model.extract::<PyRef<RsOutputCodeClassifier>>() {
+            return Ok(Classifier::OutputCode(model.model.clone()));
+        }
+        Err(PyTypeError::new_err(format!(
+            "{} is not a Rust-backed classifier",
+            model.get_type().name()?
+        )))
+    }
+
+    pub fn to_py(&self, py: Python) -> PyResult<PyObject> {
+        Ok(match self {
+            Classifier::Logistic(model) => Py::new(
+                py,
+                RsLogisticRegression {
+                    model: model.clone(),
+                },
+            )?
+            .into_py(py),
+            Classifier::OutputCode(model) => Py::new(
+                py,
+                RsOutputCodeClassifier {
+                    model: model.clone(),
+                },
+            )?
+            .into_py(py),
+        })
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>, y: &Key) -> PyResult<()> {
+        match self {
+            Classifier::Logistic(model) => match y {
+                Key::Int(0) => model.learn_one(x, false),
+                Key::Int(1) => model.learn_one(x, true),
+                _ => {
+                    return Err(PyValueError::new_err(
+                        "the labels of a RsLogisticRegression should be booleans",
+                    ))
+                }
+            },
+            Classifier::OutputCode(model) => model.learn_one(x, y.clone())?,
+        }
+        Ok(())
+    }
+
+    /// Probability of each label, of those seen so far for the multi-class classifiers.
+    pub fn predict_proba_one(&self, x: &HashMap<String, f64>) -> Vec<(Key, f64)> {
+        match self {
+            Classifier::Logistic(model) => {
+                let p = model.predict_proba(x);
+                vec![(Key::Int(0), 1.0 - p), (Key::Int(1), p)]
+            }
+            Classifier::OutputCode(model) => model.predict_proba(x),
+        }
+    }
+
+    pub fn predict_one(&self, x: &HashMap<String, f64>) -> Option<Key> {
+        match self {
+            Classifier::Logistic(model) => Some(Key::Int((model.predict_proba(x) >= 0.5) as i64)),
+            Classifier::OutputCode(model) => model.predict_one(x),
+        }
+    }
+
+    pub fn label_to_py(&self, py: Python, label: Key) -> PyObject {
+        match self {
+            Classifier::Logistic(_) => (label == Key::Int(1)).into_py(py),
+            Classifier::OutputCode(_) => label.into_py(py),
+        }
+    }
+
+    pub fn proba_to_py(&self, py: Python, proba: Vec<(Key, f64)>) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (label, p) in proba {
+            dict.set_item(self.label_to_py(py, label), p)?;
+        }
+        Ok(dict.into())
+    }
+}
@@ -0,0 +1,116 @@
+// Comparison of the bindings by their internal state rather than by their outputs.
+//
+// Both states are serialized into a CBOR value tree and walked side by side. Maps are
+// compared regardless of their order, since the `HashMap`s of the bindings don't iterate
+// in the same order from one instance to the other. NaN is considered equal to NaN, so
+// that a state holding NaN compares equal to its round-tripped copy.
+
+use ciborium::value::Value;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+
+#[derive(Clone, Copy)]
+struct Tolerance {
+    rtol: f64,
+    atol: f64,
+}
+
+impl Tolerance {
+    const EXACT: Tolerance = Tolerance {
+        rtol: 0.0,
+        atol: 0.0,
+    };
+
+    fn close(&self, a: f64, b: f64) -> bool {
+        if a.is_nan() || b.is_nan() {
+            return a.is_nan() && b.is_nan();
+        }
+        if a.is_infinite() || b.is_infinite() {
+            return a == b;
+        }
+        (a - b).abs() <= self.atol + self.rtol * b.abs()
+    }
+}
+
+fn same(a: &Value, b: &Value, tol: Tolerance) -> bool {
+    match (a, b) {
+        (Value::Float(a), Value::Float(b)) => tol.close(*a, *b),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b, tol))
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, a)| {
+                    b.iter()
+                        .find(|(other, _)| other == key)
+                        .is_some_and(|(_, b)| same(a, b, tol))
+                })
+        }
+        (Value::Tag(ta, a), Value::Tag(tb, b)) => ta == tb && same(a, b, tol),
+        (a, b) => a == b,
+    }
+}
+
+fn to_value<T: Serialize>(state: &T) -> PyResult<Value> {
+    Value::serialized(state)
+        .map_err(|e| PyValueError::new_err(format!("failed to compare the states: {}", e)))
+}
+
+fn state_eq<T: Serialize>(a: &T, b: &T, tol: Tolerance) -> PyResult<bool> {
+    Ok(same(&to_value(a)?, &to_value(b)?, tol))
+}
+
+pub fn richcmp<T: Serialize>(a: &T, b: &T, op: CompareOp) -> PyResult<Option<bool>> {
+    match op {
+        CompareOp::Eq => Ok(Some(state_eq(a, b, Tolerance::EXACT)?)),
+        CompareOp::Ne => Ok(Some(!state_eq(a, b, Tolerance::EXACT)?)),
+        _ => Ok(None),
+    }
+}
+
+pub fn allclose<T: Serialize>(a: &T, b: &T, rtol: f64, atol: f64) -> PyResult<bool> {
+    if !(rtol >= 0.0 && atol >= 0.0) {
+        return Err(PyValueError::new_err(
+            "rtol and atol should be non-negative",
+        ));
+    }
+    state_eq(a, b, Tolerance { rtol, atol })
+}
+
+macro_rules! comparable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                fn __richcmp__(&self, py: Python, other: &PyAny, op: CompareOp) -> PyResult<PyObject> {
+                    let other = match other.extract::<PyRef<Self>>() {
+                        Ok(other) => other,
+                        Err(_) => return Ok(py.NotImplemented()),
+                    };
+                    Ok(match richcmp(self, &*other, op)? {
+                        Some(result) => result.into_py(py),
+                        None => py.NotImplemented(),
+                    })
+                }
+                // Equal states don't make equal objects for good, since either can be
+                // updated, so the bindings are unhashable like the other mutable containers.
+                fn __hash__(&self) -> PyResult<isize> {
+                    Err(PyTypeError::new_err(format!(
+                        "unhashable type: '{}'",
+                        crate::state::name::<Self>()
+                    )))
+                }
+                /// Whether both states are equal, with the floats compared up to
+                /// `atol + rtol * |other|`.
+                #[args(rtol = "1e-5", atol = "1e-8")]
+                pub fn allclose(&self, other: PyRef<Self>, rtol: f64, atol: f64) -> PyResult<bool> {
+                    allclose(self, &*other, rtol, atol)
+                }
+            }
+        )*
+    };
+}
+
+for_each_binding!(comparable);
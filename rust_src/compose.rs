@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use numpy::PyArray1;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ingest::{extract_columns, Matrix, Values};
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::univariate::{AnyStat, StatRefMut};
+
+/// Several univariate stats updated together, with a single call from Python.
+///
+/// The pipeline owns copies of the stats it is given, so updating it leaves the original
+/// objects untouched.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsStatPipeline {
+    stats: Vec<AnyStat>,
+}
+
+impl HeapSize for RsStatPipeline {
+    fn heap_size(&self) -> usize {
+        self.stats.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsStatPipeline {
+    #[new]
+    pub fn new(stats: &PyList) -> PyResult<Self> {
+        let stats = stats
+            .iter()
+            .map(AnyStat::from_py)
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(RsStatPipeline { stats })
+    }
+    /// Updates every stat with `x` and returns their values, in order.
+    pub fn update(&mut self, x: f64) -> PyResult<Vec<f64>> {
+        for stat in self.stats.iter_mut() {
+            stat.update(x)?;
+        }
+        Ok(self.get())
+    }
+    pub fn get(&self) -> Vec<f64> {
+        self.stats.iter().map(AnyStat::get).collect()
+    }
+    /// Updates every stat with each value of a 1D array, pandas Series, Arrow array or list,
+    /// in order.
+    pub fn update_many(&mut self, py: Python, values: &PyAny) -> PyResult<()> {
+        let values = Values::extract(values)?;
+        let slices = values.slices();
+        let stats = &mut self.stats;
+        py.allow_threads(move || {
+            slices
+                .iter()
+                .flat_map(|slice| slice.iter())
+                .try_for_each(|&x| stats.iter_mut().try_for_each(|stat| stat.update(x)))
+        })
+    }
+    /// Copies of the stats held by the pipeline.
+    #[getter]
+    pub fn stats(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        self.stats.iter().map(|stat| stat.to_py(py)).collect()
+    }
+    pub fn __len__(&self) -> usize {
+        self.stats.len()
+    }
+}
+
+/// One univariate stat per key, e.g. per user or per device.
+///
+/// `stat_factory` is either a stat or a callable returning one. Each new key starts from a
+/// copy of that stat, so the factory is only called once.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsGroupedStat {
+    template: AnyStat,
+    #[serde(with = "crate::key::pairs")]
+    groups: HashMap<Key, AnyStat>,
+}
+
+impl HeapSize for RsGroupedStat {
+    fn heap_size(&self) -> usize {
+        self.template.heap_size() + self.groups.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsGroupedStat {
+    #[new]
+    pub fn new(stat_factory: &PyAny) -> PyResult<Self> {
+        let template = if stat_factory.is_callable() {
+            AnyStat::from_py(stat_factory.call0()?)?
+        } else {
+            AnyStat::from_py(stat_factory)?
+        };
+        Ok(RsGroupedStat {
+            template,
+            groups: HashMap::new(),
+        })
+    }
+    pub fn update(&mut self, key: Key, x: f64) -> PyResult<()> {
+        let template = &self.template;
+        self.groups
+            .entry(key)
+            .or_insert_with(|| template.clone())
+            .update(x)
+    }
+    pub fn get(&self, key: Key) -> PyResult<f64> {
+        match self.groups.get(&key) {
+            Some(stat) => Ok(stat.get()),
+            None => Err(PyKeyError::new_err(key)),
+        }
+    }
+    pub fn keys(&self) -> Vec<Key> {
+        self.groups.keys().cloned().collect()
+    }
+    pub fn __len__(&self) -> usize {
+        self.groups.len()
+    }
+    pub fn __contains__(&self, key: Key) -> bool {
+        self.groups.contains_key(&key)
+    }
+}
+
+/// One univariate stat per column of a 2D batch.
+///
+/// The number of columns is set by the first batch. With `parallel=True` the columns are
+/// updated on several threads, with the GIL released.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsColumnStats {
+    template: AnyStat,
+    columns: Vec<AnyStat>,
+    parallel: bool,
+}
+
+fn for_each_column<F>(stats: &mut [AnyStat], parallel: bool, update: F) -> PyResult<()>
+where
+    F: Fn(usize, &mut AnyStat) -> PyResult<()> + Sync,
+{
+    if parallel {
+        stats
+            .par_iter_mut()
+            .enumerate()
+            .try_for_each(|(j, stat)| update(j, stat))
+    } else {
+        stats
+            .iter_mut()
+            .enumerate()
+            .try_for_each(|(j, stat)| update(j, stat))
+    }
+}
+
+impl HeapSize for RsColumnStats {
+    fn heap_size(&self) -> usize {
+        self.template.heap_size() + self.columns.heap_size()
+    }
+}
+
+impl RsColumnStats {
+    fn check_width(&mut self, width: usize) -> PyResult<()> {
+        if self.columns.is_empty() {
+            self.columns = vec![self.template.clone(); width];
+        } else if self.columns.len() != width {
+            return Err(PyValueError::new_err(format!(
+                "expected {} columns, got {}",
+                self.columns.len(),
+                width
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl RsColumnStats {
+    #[new]
+    #[args(parallel = "false")]
+    pub fn new(stat: &PyAny, parallel: bool) -> PyResult<Self> {
+        Ok(RsColumnStats {
+            template: AnyStat::from_py(stat)?,
+            columns: Vec::new(),
+            parallel,
+        })
+    }
+    /// Updates each column stat with the matching value of a row.
+    pub fn update(&mut self, x: Vec<f64>) -> PyResult<()> {
+        self.check_width(x.len())?;
+        for (stat, x) in self.columns.iter_mut().zip(x) {
+            stat.update(x)?;
+        }
+        Ok(())
+    }
+    /// Updates each column stat with a column of `x`, in row order.
+    ///
+    /// `x` is a 2D NumPy array or buffer-protocol object, a pandas, Polars or PyArrow data
+    /// frame, or a list of rows.
+    pub fn update_many(&mut self, py: Python, x: &PyAny) -> PyResult<()> {
+        let parallel = self.parallel;
+        if let Some(columns) = extract_columns(x)? {
+            self.check_width(columns.len())?;
+            let slices: Vec<Vec<&[f64]>> = columns.iter().map(Values::slices).collect();
+            let stats = &mut self.columns;
+            return py.allow_threads(move || {
+                for_each_column(stats, parallel, |j, stat| {
+                    slices[j]
+                        .iter()
+                        .try_for_each(|slice| slice.iter().try_for_each(|&v| stat.update(v)))
+                })
+            });
+        }
+        let x = Matrix::extract(x)?;
+        self.check_width(x.n_cols)?;
+        let (values, n_cols) = (x.as_slice(), x.n_cols);
+        let stats = &mut self.columns;
+        py.allow_threads(move || {
+            for_each_column(stats, parallel, |j, stat| {
+                values
+                    .iter()
+                    .skip(j)
+                    .step_by(n_cols)
+                    .try_for_each(|&v| stat.update(v))
+            })
+        })
+    }
+    /// Current value of each column stat.
+    pub fn get<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_vec(py, self.columns.iter().map(AnyStat::get).collect())
+    }
+    #[getter]
+    pub fn n_columns(&self) -> usize {
+        self.columns.len()
+    }
+}
+
+/// Univariate stat fed with a stream that has missing values.
+///
+/// `None` and NaN are counted as missing rather than passed on to the stat. With
+/// `placeholders=True`, a rolling window still gives them a slot, so that it keeps covering
+/// the same number of steps, and the stat is computed over the values present in it.
+/// The rolling mean, variance and extrema can't leave a slot empty, and raise a
+/// `ValueError` on a missing value with `placeholders=True`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSkipMissing {
+    stat: AnyStat,
+    placeholders: bool,
+    n: usize,
+    n_missing: usize,
+}
+
+impl HeapSize for RsSkipMissing {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsSkipMissing {
+    #[new]
+    #[args(placeholders = "false")]
+    pub fn new(stat: &PyAny, placeholders: bool) -> PyResult<Self> {
+        Ok(RsSkipMissing {
+            stat: AnyStat::from_py(stat)?,
+            placeholders,
+            n: 0,
+            n_missing: 0,
+        })
+    }
+    pub fn update(&mut self, x: Option<f64>) -> PyResult<()> {
+        match x {
+            Some(x) if !x.is_nan() => self.stat.update(x)?,
+            _ => {
+                if self.placeholders {
+                    self.stat.push_missing()?;
+                }
+                self.n_missing += 1;
+            }
+        }
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.get()
+    }
+    /// Number of updates, missing values included.
+    #[getter]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+    #[getter]
+    pub fn n_missing(&self) -> usize {
+        self.n_missing
+    }
+    /// Copy of the wrapped stat.
+    #[getter]
+    pub fn stat(&self, py: Python) -> PyResult<PyObject> {
+        self.stat.to_py(py)
+    }
+}
+
+// Pyclass owning two stats that are updated together, whose value is derived from theirs.
+macro_rules! combinator {
+    ($(#[$doc:meta])* $name:ident, |$a:ident, $b:ident| $value:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Serialize, Deserialize)]
+        #[pyclass(module = "river.stats._rust_stats")]
+        pub struct $name {
+            a: AnyStat,
+            b: AnyStat,
+        }
+
+        impl HeapSize for $name {
+            fn heap_size(&self) -> usize {
+                self.a.heap_size() + self.b.heap_size()
+            }
+        }
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            pub fn new(stat_a: &PyAny, stat_b: &PyAny) -> PyResult<Self> {
+                Ok($name {
+                    a: AnyStat::from_py(stat_a)?,
+                    b: AnyStat::from_py(stat_b)?,
+                })
+            }
+            /// Updates both stats with `x` and returns the derived value.
+            pub fn update(&mut self, x: f64) -> PyResult<f64> {
+                self.a.update(x)?;
+                self.b.update(x)?;
+                Ok(self.get())
+            }
+            pub fn get(&self) -> f64 {
+                let ($a, $b) = (self.a.get(), self.b.get());
+                $value
+            }
+            /// Copies of both stats.
+            #[getter]
+            pub fn stats(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+                Ok((self.a.to_py(py)?, self.b.to_py(py)?))
+            }
+        }
+    };
+}
+
+combinator!(
+    /// Difference of two stats fed with the same values, e.g. a fast minus a slow EWMean.
+    RsDiff,
+    |a, b| a - b
+);
+
+combinator!(
+    /// Ratio of two stats fed with the same values, which is infinite or NaN when the
+    /// second one is 0.
+    RsRatio,
+    |a, b| a / b
+);
+
+/// Updates each stat of `stats` with every value of a 1D batch, in order.
+///
+/// The stats are updated in place on several threads, with the GIL released. A stat can
+/// only appear once in the list.
+#[pyfunction]
+pub fn update_all(py: Python, stats: &PyList, values: &PyAny) -> PyResult<()> {
+    let mut borrows = stats
+        .iter()
+        .map(StatRefMut::extract)
+        .collect::<PyResult<Vec<_>>>()?;
+    let mut stats: Vec<_> = borrows.iter_mut().map(StatRefMut::as_mut).collect();
+    let values = Values::extract(values)?;
+    let slices = values.slices();
+    py.allow_threads(move || {
+        stats.par_iter_mut().try_for_each(|stat| {
+            slices
+                .iter()
+                .try_for_each(|slice| slice.iter().try_for_each(|&x| stat.update(x)))
+        })
+    })
+}
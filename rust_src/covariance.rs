@@ -0,0 +1,974 @@
+use std::collections::HashMap;
+
+use numpy::PyArray2;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::check_w;
+use crate::ingest::{Matrix, Values};
+use crate::key::Key;
+use crate::linalg::{cholesky_inverse, sherman_morrison, symmetric_eigen};
+use crate::memory::HeapSize;
+use crate::moments::CoMoments;
+
+/// Pairwise covariances between the features of a stream of dicts.
+///
+/// Like `covariance.EmpiricalCovariance`, each pair of features is only updated with the
+/// samples in which both are present, so every entry holds its own count and means. The
+/// pair statistics are stored in `d * d` row-major arrays, of which only the upper triangle
+/// is used.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CovMatrix {
+    ddof: u32,
+    features: Vec<String>,
+    feature_index: HashMap<String, usize>,
+    n: Vec<f64>,
+    mean_x: Vec<f64>,
+    mean_y: Vec<f64>,
+    m2_x: Vec<f64>,
+    m2_y: Vec<f64>,
+    c: Vec<f64>,
+}
+
+impl HeapSize for CovMatrix {
+    fn heap_size(&self) -> usize {
+        self.features.heap_size()
+            + self.feature_index.heap_size()
+            + self.n.heap_size()
+            + self.mean_x.heap_size()
+            + self.mean_y.heap_size()
+            + self.m2_x.heap_size()
+            + self.m2_y.heap_size()
+            + self.c.heap_size()
+    }
+}
+
+impl CovMatrix {
+    pub fn new(ddof: u32) -> Self {
+        CovMatrix {
+            ddof,
+            features: Vec::new(),
+            feature_index: HashMap::new(),
+            n: Vec::new(),
+            mean_x: Vec::new(),
+            mean_y: Vec::new(),
+            m2_x: Vec::new(),
+            m2_y: Vec::new(),
+            c: Vec::new(),
+        }
+    }
+
+    /// Matrix whose `n` features are named after their position, as the columns of the
+    /// vectors passed to `update_dense`.
+    pub fn with_features(ddof: u32, n: usize) -> Self {
+        let mut cov = CovMatrix::new(ddof);
+        cov.grow((0..n).map(|j| j.to_string()).collect());
+        cov
+    }
+
+    pub fn n_features(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    fn grow(&mut self, new_features: Vec<String>) {
+        let old = self.n_features();
+        for name in new_features {
+            self.feature_index.insert(name.clone(), self.features.len());
+            self.features.push(name);
+        }
+        let d = self.n_features();
+        for v in [
+            &mut self.n,
+            &mut self.mean_x,
+            &mut self.mean_y,
+            &mut self.m2_x,
+            &mut self.m2_y,
+            &mut self.c,
+        ] {
+            let mut grown = vec![0.0; d * d];
+            for i in 0..old {
+                grown[i * d..i * d + old].copy_from_slice(&v[i * old..(i + 1) * old]);
+            }
+            *v = grown;
+        }
+    }
+
+    // Maps a dict to (index, value) pairs sorted by index, registering unseen features.
+    fn index(&mut self, x: &HashMap<String, f64>) -> Vec<(usize, f64)> {
+        let mut new_features: Vec<String> = x
+            .keys()
+            .filter(|k| !self.feature_index.contains_key(*k))
+            .cloned()
+            .collect();
+        if !new_features.is_empty() {
+            new_features.sort();
+            self.grow(new_features);
+        }
+        let mut pairs: Vec<(usize, f64)> =
+            x.iter().map(|(k, v)| (self.feature_index[k], *v)).collect();
+        pairs.sort_by_key(|&(i, _)| i);
+        pairs
+    }
+
+    pub fn update(&mut self, x: &HashMap<String, f64>) {
+        let x = self.index(x);
+        self.update_indexed(&x);
+    }
+
+    pub fn update_indexed(&mut self, x: &[(usize, f64)]) {
+        let d = self.n_features();
+        for (a, &(i, xi)) in x.iter().enumerate() {
+            for &(j, xj) in &x[a..] {
+                let k = i * d + j;
+                self.n[k] += 1.0;
+                let n = self.n[k];
+                let dx = xi - self.mean_x[k];
+                let dy = xj - self.mean_y[k];
+                self.mean_x[k] += dx / n;
+                self.mean_y[k] += dy / n;
+                self.m2_x[k] += dx * (xi - self.mean_x[k]);
+                self.m2_y[k] += dy * (xj - self.mean_y[k]);
+                self.c[k] += dx * (xj - self.mean_y[k]);
+            }
+        }
+    }
+
+    // The first vector names the features after their position, and the next ones should
+    // hold as many values.
+    fn check_dense(&mut self, n_values: usize) -> Result<(), String> {
+        if self.features.is_empty() {
+            self.grow((0..n_values).map(|j| j.to_string()).collect());
+        }
+        if n_values != self.n_features() {
+            return Err(format!(
+                "x has {} values, but the matrix has {} features",
+                n_values,
+                self.n_features()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Updates with a vector holding the value of every feature, in the order of `features`.
+    pub fn update_dense(&mut self, x: &[f64]) -> Result<(), String> {
+        self.check_dense(x.len())?;
+        let x: Vec<(usize, f64)> = x.iter().copied().enumerate().collect();
+        self.update_indexed(&x);
+        Ok(())
+    }
+
+    /// Updates with the rows of a row-major matrix with `n_cols` columns.
+    pub fn update_rows(&mut self, values: &[f64], n_cols: usize) -> Result<(), String> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.check_dense(n_cols)?;
+        let mut x: Vec<(usize, f64)> = (0..n_cols).map(|j| (j, 0.0)).collect();
+        for row in values.chunks_exact(n_cols) {
+            for (pair, &v) in x.iter_mut().zip(row) {
+                pair.1 = v;
+            }
+            self.update_indexed(&x);
+        }
+        Ok(())
+    }
+
+    /// Removes a vector passed to `update_dense` before.
+    pub fn revert_dense(&mut self, x: &[f64]) -> Result<(), String> {
+        let d = self.n_features();
+        if x.len() != d {
+            return Err(format!(
+                "x has {} values, but the matrix has {} features",
+                x.len(),
+                d
+            ));
+        }
+        if (0..d).any(|i| self.n[i * d + i] == 0.0) {
+            return Err("there is no observation to revert".to_string());
+        }
+        let x: Vec<(usize, f64)> = x.iter().copied().enumerate().collect();
+        self.revert_indexed(&x);
+        Ok(())
+    }
+
+    pub fn revert(&mut self, x: &HashMap<String, f64>) -> Result<(), String> {
+        let d = self.n_features();
+        let mut pairs = Vec::with_capacity(x.len());
+        for (name, &v) in x {
+            match self.feature_index.get(name) {
+                Some(&i) if self.n[i * d + i] > 0.0 => pairs.push((i, v)),
+                _ => return Err(format!("feature '{}' has no observation to revert", name)),
+            }
+        }
+        pairs.sort_by_key(|&(i, _)| i);
+        self.revert_indexed(&pairs);
+        Ok(())
+    }
+
+    pub fn revert_indexed(&mut self, x: &[(usize, f64)]) {
+        let d = self.n_features();
+        for (a, &(i, xi)) in x.iter().enumerate() {
+            for &(j, xj) in &x[a..] {
+                let k = i * d + j;
+                let n = self.n[k];
+                if n <= 1.0 {
+                    self.n[k] = 0.0;
+                    self.mean_x[k] = 0.0;
+                    self.mean_y[k] = 0.0;
+                    self.m2_x[k] = 0.0;
+                    self.m2_y[k] = 0.0;
+                    self.c[k] = 0.0;
+                    continue;
+                }
+                let prev_x = self.mean_x[k] - (xi - self.mean_x[k]) / (n - 1.0);
+                let prev_y = self.mean_y[k] - (xj - self.mean_y[k]) / (n - 1.0);
+                self.m2_x[k] -= (xi - prev_x) * (xi - self.mean_x[k]);
+                self.m2_y[k] -= (xj - prev_y) * (xj - self.mean_y[k]);
+                self.c[k] -= (xi - prev_x) * (xj - self.mean_y[k]);
+                self.mean_x[k] = prev_x;
+                self.mean_y[k] = prev_y;
+                self.n[k] = n - 1.0;
+            }
+        }
+    }
+
+    /// Folds in the samples of `other`, e.g. a matrix computed on another shard. The
+    /// features `other` has seen and this one hasn't are appended in its order.
+    pub fn merge(&mut self, other: &CovMatrix) {
+        let new_features: Vec<String> = other
+            .features
+            .iter()
+            .filter(|name| !self.feature_index.contains_key(*name))
+            .cloned()
+            .collect();
+        if !new_features.is_empty() {
+            self.grow(new_features);
+        }
+        let index: Vec<usize> = other
+            .features
+            .iter()
+            .map(|name| self.feature_index[name])
+            .collect();
+        let od = other.n_features();
+        for i in 0..od {
+            for j in i..od {
+                let b = i * od + j;
+                let nb = other.n[b];
+                if nb == 0.0 {
+                    continue;
+                }
+                // The pair may be the other way around in this matrix.
+                let k = self.upper(index[i], index[j]);
+                let (mean_x, mean_y, m2_x, m2_y) = if index[i] <= index[j] {
+                    (
+                        other.mean_x[b],
+                        other.mean_y[b],
+                        other.m2_x[b],
+                        other.m2_y[b],
+                    )
+                } else {
+                    (
+                        other.mean_y[b],
+                        other.mean_x[b],
+                        other.m2_y[b],
+                        other.m2_x[b],
+                    )
+                };
+                let na = self.n[k];
+                let n = na + nb;
+                let dx = mean_x - self.mean_x[k];
+                let dy = mean_y - self.mean_y[k];
+                self.mean_x[k] += dx * nb / n;
+                self.mean_y[k] += dy * nb / n;
+                self.m2_x[k] += m2_x + dx * dx * na * nb / n;
+                self.m2_y[k] += m2_y + dy * dy * na * nb / n;
+                self.c[k] += other.c[b] + dx * dy * na * nb / n;
+                self.n[k] = n;
+            }
+        }
+    }
+
+    fn upper(&self, i: usize, j: usize) -> usize {
+        let d = self.n_features();
+        if i <= j {
+            i * d + j
+        } else {
+            j * d + i
+        }
+    }
+
+    pub fn cov(&self, i: usize, j: usize) -> f64 {
+        let k = self.upper(i, j);
+        let ddof = self.ddof as f64;
+        if self.n[k] > ddof {
+            self.c[k] / (self.n[k] - ddof)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn corr(&self, i: usize, j: usize) -> f64 {
+        let k = self.upper(i, j);
+        let denom = (self.m2_x[k] * self.m2_y[k]).sqrt();
+        if denom > 0.0 {
+            self.c[k] / denom
+        } else {
+            0.0
+        }
+    }
+
+    pub fn covariance(&self) -> Vec<f64> {
+        let d = self.n_features();
+        (0..d * d).map(|k| self.cov(k / d, k % d)).collect()
+    }
+
+    pub fn correlation(&self) -> Vec<f64> {
+        let d = self.n_features();
+        (0..d * d).map(|k| self.corr(k / d, k % d)).collect()
+    }
+}
+
+pub fn to_pyarray2<'py>(
+    py: Python<'py>,
+    values: Vec<f64>,
+    d: usize,
+) -> PyResult<&'py PyArray2<f64>> {
+    numpy::PyArray1::from_vec(py, values).reshape([d, d])
+}
+
+/// Dense covariance estimate along with an incrementally maintained inverse.
+///
+/// Absent features are read as 0, which also makes it possible to add features on the fly:
+/// a new feature is treated as having been 0 so far. The inverse of the scatter matrix is
+/// initialized with the identity, as in `covariance.EmpiricalPrecision`, and kept up to date
+/// with the Sherman-Morrison formula. The Ledoit-Wolf shrinkage intensity is estimated from
+/// the running fourth moment of the centered observations.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PrecisionMatrix {
+    features: Vec<String>,
+    feature_index: HashMap<String, usize>,
+    n: f64,
+    mean: Vec<f64>,
+    scatter: Vec<f64>,
+    inv_scatter: Vec<f64>,
+    sum_norm4: f64,
+}
+
+impl HeapSize for PrecisionMatrix {
+    fn heap_size(&self) -> usize {
+        self.features.heap_size()
+            + self.feature_index.heap_size()
+            + self.mean.heap_size()
+            + self.scatter.heap_size()
+            + self.inv_scatter.heap_size()
+    }
+}
+
+impl PrecisionMatrix {
+    pub fn new() -> Self {
+        PrecisionMatrix {
+            features: Vec::new(),
+            feature_index: HashMap::new(),
+            n: 0.0,
+            mean: Vec::new(),
+            scatter: Vec::new(),
+            inv_scatter: Vec::new(),
+            sum_norm4: 0.0,
+        }
+    }
+
+    pub fn n_features(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    fn grow(&mut self, new_features: Vec<String>) {
+        let old = self.n_features();
+        for name in new_features {
+            self.feature_index.insert(name.clone(), self.features.len());
+            self.features.push(name);
+        }
+        let d = self.n_features();
+        self.mean.resize(d, 0.0);
+        let mut scatter = vec![0.0; d * d];
+        let mut inv = vec![0.0; d * d];
+        for i in 0..old {
+            scatter[i * d..i * d + old].copy_from_slice(&self.scatter[i * old..(i + 1) * old]);
+            inv[i * d..i * d + old].copy_from_slice(&self.inv_scatter[i * old..(i + 1) * old]);
+        }
+        for i in old..d {
+            inv[i * d + i] = 1.0;
+        }
+        self.scatter = scatter;
+        self.inv_scatter = inv;
+    }
+
+    pub fn as_vec(&self, x: &HashMap<String, f64>) -> Vec<f64> {
+        let mut v = vec![0.0; self.n_features()];
+        for (name, value) in x {
+            if let Some(&i) = self.feature_index.get(name) {
+                v[i] = *value;
+            }
+        }
+        v
+    }
+
+    pub fn update(&mut self, x: &HashMap<String, f64>) {
+        let mut new_features: Vec<String> = x
+            .keys()
+            .filter(|k| !self.feature_index.contains_key(*k))
+            .cloned()
+            .collect();
+        if !new_features.is_empty() {
+            new_features.sort();
+            self.grow(new_features);
+        }
+        let x = self.as_vec(x);
+        self.update_vec(&x);
+    }
+
+    pub fn update_vec(&mut self, x: &[f64]) {
+        let d = self.n_features();
+        // Nothing is learnt before the first feature, e.g. from an empty dict.
+        if d == 0 {
+            return;
+        }
+        self.n += 1.0;
+        let diff: Vec<f64> = x.iter().zip(&self.mean).map(|(a, b)| a - b).collect();
+        let c = (self.n - 1.0) / self.n;
+        for (row, di) in self.scatter.chunks_mut(d).zip(&diff) {
+            for (s, dj) in row.iter_mut().zip(&diff) {
+                *s += c * di * dj;
+            }
+        }
+        sherman_morrison(&mut self.inv_scatter, d, &diff, c);
+        for (m, di) in self.mean.iter_mut().zip(&diff) {
+            *m += di / self.n;
+        }
+        let norm2: f64 = x.iter().zip(&self.mean).map(|(a, b)| (a - b).powi(2)).sum();
+        self.sum_norm4 += norm2 * norm2;
+    }
+
+    /// Inverse of the sample covariance matrix, maintained with rank-1 updates.
+    pub fn precision(&self) -> Vec<f64> {
+        let scale = (self.n - 1.0).max(1.0);
+        self.inv_scatter.iter().map(|v| v * scale).collect()
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    /// Sample covariance matrix.
+    pub fn covariance(&self) -> Vec<f64> {
+        let n = (self.n - 1.0).max(1.0);
+        self.scatter.iter().map(|v| v / n).collect()
+    }
+
+    fn mle_covariance(&self) -> Vec<f64> {
+        let n = self.n.max(1.0);
+        self.scatter.iter().map(|v| v / n).collect()
+    }
+
+    /// Ledoit-Wolf shrinkage intensity, between 0 and 1.
+    pub fn shrinkage(&self) -> f64 {
+        let d = self.n_features();
+        if d == 0 || self.n < 2.0 {
+            return 0.0;
+        }
+        let s = self.mle_covariance();
+        let trace: f64 = (0..d).map(|i| s[i * d + i]).sum();
+        let mu = trace / d as f64;
+        let frob2: f64 = s.iter().map(|v| v * v).sum();
+        let delta = (frob2 - 2.0 * mu * trace + d as f64 * mu * mu) / d as f64;
+        if delta <= 0.0 {
+            return 0.0;
+        }
+        let beta = ((self.sum_norm4 / self.n - frob2) / (d as f64 * self.n)).max(0.0);
+        beta.min(delta) / delta
+    }
+
+    /// Covariance matrix shrunk towards a scaled identity matrix.
+    pub fn shrunk_covariance(&self, shrinkage: f64) -> Vec<f64> {
+        let d = self.n_features();
+        let mut s = self.mle_covariance();
+        let mu = (0..d).map(|i| s[i * d + i]).sum::<f64>() / d.max(1) as f64;
+        for v in s.iter_mut() {
+            *v *= 1.0 - shrinkage;
+        }
+        for i in 0..d {
+            s[i * d + i] += shrinkage * mu;
+        }
+        s
+    }
+
+    pub fn shrunk_precision(&self, shrinkage: f64) -> Option<Vec<f64>> {
+        cholesky_inverse(&self.shrunk_covariance(shrinkage), self.n_features())
+    }
+
+    /// Squared Mahalanobis distance of `x` to the mean, using the given precision matrix.
+    pub fn mahalanobis(&self, x: &[f64], precision: &[f64]) -> f64 {
+        let d = self.n_features();
+        if d == 0 {
+            return 0.0;
+        }
+        let diff: Vec<f64> = x.iter().zip(&self.mean).map(|(a, b)| a - b).collect();
+        precision
+            .chunks(d)
+            .zip(&diff)
+            .map(|(row, di)| di * row.iter().zip(&diff).map(|(p, dj)| p * dj).sum::<f64>())
+            .sum()
+    }
+}
+
+impl Default for PrecisionMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Whitening {
+    Zca,
+    Pca,
+}
+
+/// Whitening of feature dicts with the running covariance matrix.
+///
+/// The covariance matrix `V diag(l) V^T` is only factorized every `refresh_every` updates,
+/// and whenever a new feature shows up. PCA whitening projects the centered features on the
+/// eigenvectors and scales them by `1 / sqrt(l + eps)`; ZCA whitening then rotates them back
+/// to the original axes, which keeps the outputs close to the inputs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Whitener {
+    method: Whitening,
+    eps: f64,
+    refresh_every: u64,
+    cov: PrecisionMatrix,
+    since_refresh: u64,
+    whitening: Vec<f64>,
+}
+
+impl HeapSize for Whitener {
+    fn heap_size(&self) -> usize {
+        self.cov.heap_size() + self.whitening.heap_size()
+    }
+}
+
+impl Whitener {
+    pub fn new(method: Whitening, eps: f64, refresh_every: u64) -> Self {
+        Whitener {
+            method,
+            eps,
+            refresh_every,
+            cov: PrecisionMatrix::new(),
+            since_refresh: 0,
+            whitening: Vec::new(),
+        }
+    }
+
+    pub fn features(&self) -> &[String] {
+        self.cov.features()
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>) {
+        let d = self.cov.n_features();
+        self.cov.update(x);
+        self.since_refresh += 1;
+        if self.since_refresh >= self.refresh_every || self.cov.n_features() != d {
+            self.refresh();
+        }
+    }
+
+    /// Factorizes the covariance matrix again.
+    pub fn refresh(&mut self) {
+        let d = self.cov.n_features();
+        let (values, vectors) = symmetric_eigen(&self.cov.covariance(), d);
+        let scales: Vec<f64> = values
+            .iter()
+            .map(|l| 1.0 / (l.max(0.0) + self.eps).sqrt())
+            .collect();
+        let mut whitening = vec![0.0; d * d];
+        match self.method {
+            Whitening::Pca => {
+                for (k, scale) in scales.iter().enumerate() {
+                    for j in 0..d {
+                        whitening[k * d + j] = scale * vectors[k * d + j];
+                    }
+                }
+            }
+            Whitening::Zca => {
+                for (k, scale) in scales.iter().enumerate() {
+                    let v = &vectors[k * d..(k + 1) * d];
+                    for i in 0..d {
+                        for j in 0..d {
+                            whitening[i * d + j] += scale * v[i] * v[j];
+                        }
+                    }
+                }
+            }
+        }
+        self.whitening = whitening;
+        self.since_refresh = 0;
+    }
+
+    /// Whitened features: named after the inputs with ZCA, numbered by decreasing variance
+    /// with PCA. Unseen features are ignored.
+    pub fn transform_one(&self, x: &HashMap<String, f64>) -> HashMap<Key, f64> {
+        let d = self.cov.n_features();
+        let centered: Vec<f64> = self
+            .cov
+            .as_vec(x)
+            .iter()
+            .zip(self.cov.mean())
+            .map(|(a, b)| a - b)
+            .collect();
+        self.whitening
+            .chunks(d.max(1))
+            .enumerate()
+            .map(|(k, row)| {
+                let key = match self.method {
+                    Whitening::Zca => Key::Str(self.cov.features()[k].clone()),
+                    Whitening::Pca => Key::Int(k as i64),
+                };
+                (key, row.iter().zip(&centered).map(|(w, c)| w * c).sum())
+            })
+            .collect()
+    }
+}
+
+/// Streaming covariance of a pair of variables, as `stats.Cov`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCov {
+    moments: CoMoments,
+    ddof: u32,
+}
+
+impl HeapSize for RsCov {
+    fn heap_size(&self) -> usize {
+        self.moments.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsCov {
+    #[new]
+    #[args(ddof = "1")]
+    pub fn new(ddof: u32) -> RsCov {
+        RsCov {
+            moments: CoMoments::new(),
+            ddof,
+        }
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, y: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        self.moments.update(x, y, w);
+        Ok(())
+    }
+    #[args(w = "1.0")]
+    pub fn revert(&mut self, x: f64, y: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        self.moments.revert(x, y, w).map_err(PyValueError::new_err)
+    }
+    /// Folds in the pairs seen by `other`, e.g. on another shard.
+    pub fn merge(&mut self, other: PyRef<RsCov>) {
+        self.moments.merge(&other.moments);
+    }
+    pub fn get(&self) -> f64 {
+        self.moments.cov(self.ddof)
+    }
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.moments.n()
+    }
+    #[getter]
+    pub fn ddof(&self) -> u32 {
+        self.ddof
+    }
+}
+
+/// Streaming Pearson correlation of a pair of variables, as `stats.PearsonCorr`.
+///
+/// The delta degrees of freedom cancel out in the ratio, so `ddof` doesn't change the value.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPearsonCorr {
+    moments: CoMoments,
+    ddof: u32,
+}
+
+impl HeapSize for RsPearsonCorr {
+    fn heap_size(&self) -> usize {
+        self.moments.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsPearsonCorr {
+    #[new]
+    #[args(ddof = "1")]
+    pub fn new(ddof: u32) -> RsPearsonCorr {
+        RsPearsonCorr {
+            moments: CoMoments::new(),
+            ddof,
+        }
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, y: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        self.moments.update(x, y, w);
+        Ok(())
+    }
+    #[args(w = "1.0")]
+    pub fn revert(&mut self, x: f64, y: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        self.moments.revert(x, y, w).map_err(PyValueError::new_err)
+    }
+    /// Folds in the pairs seen by `other`, e.g. on another shard.
+    pub fn merge(&mut self, other: PyRef<RsPearsonCorr>) {
+        self.moments.merge(&other.moments);
+    }
+    pub fn get(&self) -> f64 {
+        self.moments.corr()
+    }
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.moments.n()
+    }
+    #[getter]
+    pub fn ddof(&self) -> u32 {
+        self.ddof
+    }
+}
+
+/// Streaming covariance matrix over feature dicts.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCovMatrix {
+    cov: CovMatrix,
+    ddof: u32,
+}
+
+impl HeapSize for RsCovMatrix {
+    fn heap_size(&self) -> usize {
+        self.cov.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsCovMatrix {
+    #[new]
+    #[args(ddof = "1", "*", n_features = "None")]
+    pub fn new(ddof: u32, n_features: Option<usize>) -> RsCovMatrix {
+        RsCovMatrix {
+            cov: match n_features {
+                Some(n) => CovMatrix::with_features(ddof, n),
+                None => CovMatrix::new(ddof),
+            },
+            ddof,
+        }
+    }
+    /// Updates with a dict of features, or with a 1D array holding a value for each feature
+    /// in the order of `features`. The features of the first array are named after their
+    /// position.
+    pub fn update(&mut self, x: &PyAny) -> PyResult<()> {
+        match x.downcast::<PyDict>() {
+            Ok(x) => {
+                self.cov.update(&x.extract()?);
+                Ok(())
+            }
+            Err(_) => {
+                let x = Values::extract(x)?.slices().concat();
+                self.cov.update_dense(&x).map_err(PyValueError::new_err)
+            }
+        }
+    }
+    /// Updates with each row of a 2D array, whose columns are the features.
+    pub fn update_many(&mut self, py: Python, x: &PyAny) -> PyResult<()> {
+        let x = Matrix::extract(x)?;
+        let (values, n_cols) = (x.as_slice(), x.n_cols);
+        let cov = &mut self.cov;
+        py.allow_threads(move || cov.update_rows(values, n_cols))
+            .map_err(PyValueError::new_err)
+    }
+    pub fn revert(&mut self, x: &PyAny) -> PyResult<()> {
+        match x.downcast::<PyDict>() {
+            Ok(x) => self.cov.revert(&x.extract()?),
+            Err(_) => self
+                .cov
+                .revert_dense(&Values::extract(x)?.slices().concat()),
+        }
+        .map_err(PyValueError::new_err)
+    }
+    /// Folds in the samples of `other`, e.g. a matrix computed on another shard. The
+    /// features `other` has seen and this one hasn't are appended in its order.
+    pub fn merge(&mut self, other: PyRef<RsCovMatrix>) {
+        self.cov.merge(&other.cov);
+    }
+    /// Covariance matrix, with rows and columns ordered as in `features`.
+    pub fn get<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        to_pyarray2(py, self.cov.covariance(), self.cov.n_features())
+    }
+    /// Pearson correlation matrix, with rows and columns ordered as in `features`.
+    pub fn correlation<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        to_pyarray2(py, self.cov.correlation(), self.cov.n_features())
+    }
+    #[getter]
+    pub fn features(&self) -> Vec<String> {
+        self.cov.features().to_vec()
+    }
+}
+
+/// Streaming precision matrix over feature dicts, with optional Ledoit-Wolf shrinkage.
+///
+/// `get` returns the precision matrix maintained with Sherman-Morrison updates, whereas the
+/// `shrunk_*` methods factorize the shrunk covariance matrix on demand. When `shrinkage` is
+/// `None`, the Ledoit-Wolf intensity is estimated from the stream.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPrecisionMatrix {
+    prec: PrecisionMatrix,
+    fixed_shrinkage: Option<f64>,
+}
+
+impl HeapSize for RsPrecisionMatrix {
+    fn heap_size(&self) -> usize {
+        self.prec.heap_size()
+    }
+}
+
+impl RsPrecisionMatrix {
+    fn effective_shrinkage(&self) -> f64 {
+        self.fixed_shrinkage
+            .unwrap_or_else(|| self.prec.shrinkage())
+    }
+}
+
+#[pymethods]
+impl RsPrecisionMatrix {
+    #[new]
+    #[args(shrinkage = "None")]
+    pub fn new(shrinkage: Option<f64>) -> PyResult<Self> {
+        if let Some(s) = shrinkage {
+            if !(0.0..=1.0).contains(&s) {
+                return Err(PyValueError::new_err("shrinkage should be between 0 and 1"));
+            }
+        }
+        Ok(RsPrecisionMatrix {
+            prec: PrecisionMatrix::new(),
+            fixed_shrinkage: shrinkage,
+        })
+    }
+    pub fn update(&mut self, x: HashMap<String, f64>) {
+        self.prec.update(&x);
+    }
+    pub fn get<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        to_pyarray2(py, self.prec.precision(), self.prec.n_features())
+    }
+    #[getter]
+    pub fn shrinkage(&self) -> f64 {
+        self.effective_shrinkage()
+    }
+    pub fn shrunk_covariance<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        let cov = self.prec.shrunk_covariance(self.effective_shrinkage());
+        to_pyarray2(py, cov, self.prec.n_features())
+    }
+    pub fn shrunk_precision<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        match self.prec.shrunk_precision(self.effective_shrinkage()) {
+            Some(prec) => to_pyarray2(py, prec, self.prec.n_features()),
+            None => Err(PyValueError::new_err(
+                "the shrunk covariance matrix is not positive definite",
+            )),
+        }
+    }
+    /// Squared Mahalanobis distance of `x` to the running mean.
+    #[args(shrunk = "false")]
+    pub fn mahalanobis(&self, x: HashMap<String, f64>, shrunk: bool) -> PyResult<f64> {
+        let x = self.prec.as_vec(&x);
+        let precision = if shrunk {
+            self.prec
+                .shrunk_precision(self.effective_shrinkage())
+                .ok_or_else(|| {
+                    PyValueError::new_err("the shrunk covariance matrix is not positive definite")
+                })?
+        } else {
+            self.prec.precision()
+        };
+        Ok(self.prec.mahalanobis(&x, &precision))
+    }
+    #[getter]
+    pub fn features(&self) -> Vec<String> {
+        self.prec.features().to_vec()
+    }
+}
+
+/// Streaming ZCA or PCA whitening of feature dicts.
+///
+/// `method` is either `"zca"` or `"pca"`, `eps` is added to the eigenvalues before taking
+/// their inverse square root, and the covariance matrix is factorized again every
+/// `refresh_every` calls to `learn_one`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsWhitener {
+    whitener: Whitener,
+}
+
+impl HeapSize for RsWhitener {
+    fn heap_size(&self) -> usize {
+        self.whitener.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsWhitener {
+    #[new]
+    #[args(method = "\"zca\"", eps = "1e-5", refresh_every = "100")]
+    pub fn new(method: &str, eps: f64, refresh_every: u64) -> PyResult<Self> {
+        let method = match method {
+            "zca" => Whitening::Zca,
+            "pca" => Whitening::Pca,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "method should be 'zca' or 'pca', got '{}'",
+                    method
+                )))
+            }
+        };
+        if !eps.is_finite() || eps < 0.0 {
+            return Err(PyValueError::new_err(
+                "eps should be finite and non-negative",
+            ));
+        }
+        if refresh_every == 0 {
+            return Err(PyValueError::new_err("refresh_every should be at least 1"));
+        }
+        Ok(RsWhitener {
+            whitener: Whitener::new(method, eps, refresh_every),
+        })
+    }
+    pub fn learn_one(&mut self, x: HashMap<String, f64>) {
+        self.whitener.learn_one(&x);
+    }
+    pub fn transform_one(&self, x: HashMap<String, f64>) -> HashMap<Key, f64> {
+        self.whitener.transform_one(&x)
+    }
+    /// Factorizes the covariance matrix now rather than at the next scheduled refresh.
+    pub fn refresh(&mut self) {
+        self.whitener.refresh();
+    }
+    #[getter]
+    pub fn features(&self) -> Vec<String> {
+        self.whitener.features().to_vec()
+    }
+}
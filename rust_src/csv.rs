@@ -0,0 +1,335 @@
+// Streaming of CSV files as (features, target) pairs, like `stream.iter_csv`.
+//
+// The records are split in Rust, and the converters given as the `int`, `float`, `str` and
+// `bool` builtins are applied without calling them, so that the only Python objects made
+// per row are the values and the dict holding them. Files are read as gzip-compressed when
+// they start with its magic bytes.
+//
+// The other text formats of `arff.rs` and `libsvm.rs` share the opening of the files, the
+// converters and the selection of the targets.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyLong, PyString};
+
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const BOM: &str = "\u{feff}";
+
+/// Splits the records of a CSV stream, with fields optionally enclosed in double quotes,
+/// which may then hold delimiters, line breaks and doubled quotes.
+struct Records {
+    reader: Box<dyn BufRead + Send>,
+    delimiter: u8,
+    line: Vec<u8>,
+    n_records: usize,
+}
+
+impl Records {
+    fn new(reader: Box<dyn BufRead + Send>, delimiter: u8) -> Self {
+        Records {
+            reader,
+            delimiter,
+            line: Vec::new(),
+            n_records: 0,
+        }
+    }
+
+    /// Next record, skipping the empty lines.
+    fn next(&mut self) -> Result<Option<Vec<String>>, String> {
+        loop {
+            let mut fields = Vec::new();
+            let mut field = Vec::new();
+            let mut quoted = false;
+            let mut read_any = false;
+            loop {
+                self.line.clear();
+                let n = self
+                    .reader
+                    .read_until(b'\n', &mut self.line)
+                    .map_err(|e| e.to_string())?;
+                if n == 0 {
+                    if quoted {
+                        return Err("unterminated quoted field at the end of the file".into());
+                    }
+                    break;
+                }
+                read_any = true;
+                let mut bytes = self.line.iter().copied().peekable();
+                while let Some(byte) = bytes.next() {
+                    match byte {
+                        b'"' if quoted => {
+                            if bytes.peek() == Some(&b'"') {
+                                bytes.next();
+                                field.push(b'"');
+                            } else {
+                                quoted = false;
+                            }
+                        }
+                        b'"' if field.is_empty() => quoted = true,
+                        b'\n' | b'\r' if !quoted => {}
+                        byte if byte == self.delimiter && !quoted => {
+                            fields.push(std::mem::take(&mut field));
+                        }
+                        byte => field.push(byte),
+                    }
+                }
+                if !quoted {
+                    break;
+                }
+            }
+            if !read_any {
+                return Ok(None);
+            }
+            fields.push(field);
+            if fields.len() == 1 && fields[0].is_empty() {
+                continue;
+            }
+            self.n_records += 1;
+            return fields
+                .into_iter()
+                .map(|field| {
+                    String::from_utf8(field)
+                        .map_err(|_| format!("record {} is not valid UTF-8", self.n_records))
+                })
+                .collect::<Result<_, _>>()
+                .map(Some);
+        }
+    }
+}
+
+pub(crate) enum Converter {
+    Str,
+    Int,
+    Float,
+    Bool,
+    Python(PyObject),
+}
+
+impl Converter {
+    pub(crate) fn new(py: Python, converter: &PyAny) -> Self {
+        let builtin = |ty: &PyAny| converter.is(ty);
+        if builtin(py.get_type::<PyString>()) {
+            Converter::Str
+        } else if builtin(py.get_type::<PyLong>()) {
+            Converter::Int
+        } else if builtin(py.get_type::<PyFloat>()) {
+            Converter::Float
+        } else if builtin(py.get_type::<PyBool>()) {
+            Converter::Bool
+        } else {
+            Converter::Python(converter.into())
+        }
+    }
+
+    // The builtins are called for the values which Rust doesn't parse, so that they are
+    // read as in Python, or raise the same errors.
+    pub(crate) fn convert(&self, py: Python, value: &str) -> PyResult<PyObject> {
+        let fallback = |ty: &PyAny| ty.call1((value,)).map(PyObject::from);
+        match self {
+            Converter::Str => Ok(value.into_py(py)),
+            Converter::Int => match value.parse::<i64>() {
+                Ok(value) => Ok(value.into_py(py)),
+                Err(_) => fallback(py.get_type::<PyLong>()),
+            },
+            Converter::Float => match value.parse::<f64>() {
+                Ok(value) => Ok(value.into_py(py)),
+                Err(_) => fallback(py.get_type::<PyFloat>()),
+            },
+            Converter::Bool => Ok((!value.is_empty()).into_py(py)),
+            Converter::Python(converter) => converter.call1(py, (value,)),
+        }
+    }
+}
+
+pub(crate) fn column(names: &[String], name: &str) -> PyResult<usize> {
+    names
+        .iter()
+        .position(|column| column == name)
+        .ok_or_else(|| PyValueError::new_err(format!("no column named '{}'", name)))
+}
+
+/// Column of the target, or columns of the targets, among `names`.
+pub(crate) enum Target {
+    None,
+    One(usize),
+    Many(Vec<usize>),
+}
+
+impl Target {
+    /// Reads a column name, a list of them, or `None`.
+    pub(crate) fn extract(target: Option<&PyAny>, names: &[String]) -> PyResult<Self> {
+        let target = match target {
+            None => return Ok(Target::None),
+            Some(target) => target,
+        };
+        match target.extract::<String>() {
+            Ok(name) => Ok(Target::One(column(names, &name)?)),
+            Err(_) => {
+                let targets: Vec<String> = target.extract()?;
+                let targets = targets.iter().map(|name| column(names, name));
+                Ok(Target::Many(targets.collect::<PyResult<_>>()?))
+            }
+        }
+    }
+
+    pub(crate) fn columns(&self) -> Vec<usize> {
+        match self {
+            Target::None => Vec::new(),
+            Target::One(i) => vec![*i],
+            Target::Many(targets) => targets.clone(),
+        }
+    }
+
+    /// `None`, the value of the target column, or a dict of the values of the targets.
+    pub(crate) fn get(
+        &self,
+        py: Python,
+        names: &[Py<PyString>],
+        mut value: impl FnMut(usize) -> PyResult<PyObject>,
+    ) -> PyResult<PyObject> {
+        match self {
+            Target::None => Ok(py.None()),
+            Target::One(i) => value(*i),
+            Target::Many(targets) => {
+                let y = PyDict::new(py);
+                for &i in targets {
+                    y.set_item(&names[i], value(i)?)?;
+                }
+                Ok(y.into())
+            }
+        }
+    }
+}
+
+/// Iterator over the rows of a CSV file, made by `iter_csv`.
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCsvReader {
+    records: Records,
+    names: Vec<Py<PyString>>,
+    converters: Vec<Converter>,
+    features: Vec<usize>,
+    target: Target,
+    drop_nones: bool,
+}
+
+impl RsCsvReader {
+    fn value(&self, py: Python, record: &[String], i: usize) -> PyResult<PyObject> {
+        match record.get(i) {
+            Some(value) => self.converters[i].convert(py, value),
+            None => Ok(py.None()),
+        }
+    }
+}
+
+#[pymethods]
+impl RsCsvReader {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(PyObject, PyObject)>> {
+        let record = match self.records.next().map_err(PyValueError::new_err)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        if record.len() > self.names.len() {
+            return Err(PyValueError::new_err(format!(
+                "row {} has {} fields, but the header has {}",
+                self.records.n_records - 1,
+                record.len(),
+                self.names.len()
+            )));
+        }
+        let x = PyDict::new(py);
+        for &i in &self.features {
+            let value = self.value(py, &record, i)?;
+            if !(self.drop_nones && value.is_none(py)) {
+                x.set_item(&self.names[i], value)?;
+            }
+        }
+        let y = self
+            .target
+            .get(py, &self.names, |i| self.value(py, &record, i))?;
+        Ok(Some((x.into(), y)))
+    }
+}
+
+pub(crate) fn open(path: &Path) -> PyResult<Box<dyn BufRead + Send>> {
+    let mut file = BufReader::new(File::open(path)?);
+    if file.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Streams the rows of the CSV file at `path` as `(x, y)` pairs, where `x` is a dict of the
+/// features and `y` the value of the `target` column, a dict of the values of several
+/// target columns, or `None`.
+///
+/// The values are strings unless a `converters` entry maps their column to a callable, and
+/// the columns in `drop` are left out. The missing trailing fields of a short row are
+/// `None`, and they are not included in `x` when `drop_nones` is set.
+#[pyfunction(
+    target = "None",
+    converters = "None",
+    drop = "None",
+    drop_nones = "false",
+    delimiter = "\",\""
+)]
+pub fn iter_csv(
+    py: Python,
+    path: PathBuf,
+    target: Option<&PyAny>,
+    converters: Option<HashMap<String, &PyAny>>,
+    drop: Option<Vec<String>>,
+    drop_nones: bool,
+    delimiter: &str,
+) -> PyResult<RsCsvReader> {
+    let delimiter = match delimiter.as_bytes() {
+        [delimiter] if *delimiter != b'"' => *delimiter,
+        _ => {
+            return Err(PyValueError::new_err(
+                "delimiter should be a single ASCII character, other than '\"'",
+            ))
+        }
+    };
+    let mut records = Records::new(open(&path)?, delimiter);
+    let mut header = records
+        .next()
+        .map_err(PyValueError::new_err)?
+        .ok_or_else(|| PyIOError::new_err(format!("{} is empty", path.display())))?;
+    if let Some(first) = header.first_mut() {
+        if let Some(name) = first.strip_prefix(BOM) {
+            *first = name.to_string();
+        }
+    }
+    let target = Target::extract(target, &header)?;
+    let mut converters_by_column: Vec<Converter> = header.iter().map(|_| Converter::Str).collect();
+    for (name, converter) in converters.unwrap_or_default() {
+        converters_by_column[column(&header, &name)?] = Converter::new(py, converter);
+    }
+    let mut excluded: HashSet<usize> = target.columns().into_iter().collect();
+    for name in drop.unwrap_or_default() {
+        excluded.insert(column(&header, &name)?);
+    }
+    Ok(RsCsvReader {
+        records,
+        names: header
+            .iter()
+            .map(|name| PyString::new(py, name).into())
+            .collect(),
+        converters: converters_by_column,
+        features: (0..header.len())
+            .filter(|i| !excluded.contains(i))
+            .collect(),
+        target,
+        drop_nones,
+    })
+}
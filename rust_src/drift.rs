@@ -0,0 +1,1554 @@
+use std::collections::{HashMap, VecDeque};
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::hypothesis::SortedWindow;
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+use crate::sketch::TDigest;
+use crate::special::{chi2_sf, ks_two_sample_sf};
+#[cfg(feature = "python")]
+use crate::univariate::{Param, Params};
+
+/// 1-Wasserstein and energy distances between two sorted samples, each point weighing
+/// equally within its own sample. Both are computed by integrating the difference of the
+/// empirical CDFs over the merged support.
+pub fn cdf_distances(a: &[f64], b: &[f64]) -> (f64, f64) {
+    if a.is_empty() || b.is_empty() {
+        return (0.0, 0.0);
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (mut i, mut j) = (0, 0);
+    let mut prev = a[0].min(b[0]);
+    let (mut w1, mut cramer) = (0.0, 0.0);
+    while i < a.len() || j < b.len() {
+        let next = match (a.get(i), b.get(j)) {
+            (Some(&x), Some(&y)) => x.min(y),
+            (Some(&x), None) => x,
+            (None, Some(&y)) => y,
+            (None, None) => unreachable!(),
+        };
+        let diff = i as f64 / n_a - j as f64 / n_b;
+        w1 += diff.abs() * (next - prev);
+        cramer += diff * diff * (next - prev);
+        while i < a.len() && a[i] == next {
+            i += 1;
+        }
+        while j < b.len() && b[j] == next {
+            j += 1;
+        }
+        prev = next;
+    }
+    (w1, (2.0 * cramer).sqrt())
+}
+
+/// Two-sample Kolmogorov-Smirnov statistic between two sorted samples, the largest
+/// difference between their empirical CDFs.
+pub fn ks_statistic(a: &[f64], b: &[f64]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (mut i, mut j) = (0, 0);
+    let mut statistic: f64 = 0.0;
+    while i < a.len() && j < b.len() {
+        let next = a[i].min(b[j]);
+        while i < a.len() && a[i] == next {
+            i += 1;
+        }
+        while j < b.len() && b[j] == next {
+            j += 1;
+        }
+        statistic = statistic.max((i as f64 / n_a - j as f64 / n_b).abs());
+    }
+    statistic
+}
+
+/// Distribution shift between a reference distribution and a window of recent values.
+///
+/// Values enter the recent window, which is kept exactly; the ones it evicts are summarized
+/// in a t-digest that acts as the reference. The reference is read at `n_quantiles` evenly
+/// spaced quantiles when distances are computed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WassersteinDrift {
+    window_size: usize,
+    n_quantiles: usize,
+    compression: f64,
+    recent: SortedWindow,
+    reference: TDigest,
+}
+
+impl HeapSize for WassersteinDrift {
+    fn heap_size(&self) -> usize {
+        self.recent.heap_size() + self.reference.heap_size()
+    }
+}
+
+impl WassersteinDrift {
+    pub fn new(window_size: usize, compression: f64, n_quantiles: usize) -> Result<Self, String> {
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        if n_quantiles == 0 {
+            return Err("n_quantiles should be at least 1".to_string());
+        }
+        if compression.is_nan() || compression < 1.0 {
+            return Err("compression should be at least 1".to_string());
+        }
+        Ok(WassersteinDrift {
+            window_size,
+            n_quantiles,
+            compression,
+            recent: SortedWindow::new(window_size),
+            reference: TDigest::new(compression),
+        })
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.recent.push(x);
+        if self.recent.len() > self.window_size {
+            let evicted = self.recent.pop().unwrap();
+            self.reference.update(evicted, 1.0);
+        }
+    }
+
+    pub fn reset_reference(&mut self) {
+        self.reference = TDigest::new(self.compression);
+    }
+
+    fn distances(&self) -> (f64, f64) {
+        if self.reference.is_empty() {
+            return (0.0, 0.0);
+        }
+        let m = self.n_quantiles as f64;
+        let grid: Vec<f64> = (0..self.n_quantiles)
+            .map(|i| (i as f64 + 0.5) / m)
+            .collect();
+        let reference = self.reference.quantiles(&grid);
+        cdf_distances(&reference, self.recent.sorted())
+    }
+
+    pub fn wasserstein(&self) -> f64 {
+        self.distances().0
+    }
+
+    pub fn energy(&self) -> f64 {
+        self.distances().1
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsWassersteinDrift {
+    drift: WassersteinDrift,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsWassersteinDrift {
+    fn heap_size(&self) -> usize {
+        self.drift.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsWassersteinDrift {
+    #[new]
+    #[args(compression = "100.0", n_quantiles = "100")]
+    pub fn new(window_size: usize, compression: f64, n_quantiles: usize) -> PyResult<Self> {
+        Ok(RsWassersteinDrift {
+            drift: WassersteinDrift::new(window_size, compression, n_quantiles)
+                .map_err(PyValueError::new_err)?,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.drift.update(x);
+        Ok(())
+    }
+    /// Forgets the reference distribution, e.g. once a drift has been acted upon.
+    pub fn reset_reference(&mut self) {
+        self.drift.reset_reference();
+    }
+    #[getter]
+    pub fn wasserstein(&self) -> f64 {
+        self.drift.wasserstein()
+    }
+    #[getter]
+    pub fn energy(&self) -> f64 {
+        self.drift.energy()
+    }
+}
+
+// Buckets kept per row before the two oldest are merged into the next row.
+const MAX_BUCKETS: usize = 5;
+// Smallest size of either side of a cut.
+const MIN_WINDOW_LENGTH: usize = 5;
+
+/// ADaptive WINdowing, which drops the oldest values whenever the mean of the window
+/// changes significantly.
+///
+/// The window is summarized in an exponential histogram: the buckets of row `i` each hold
+/// the sum and the sum of squared deviations of `2^i` values, so the window takes a space
+/// logarithmic in its width. Every `clock` updates, the window is checked for a cut into an
+/// older and a more recent part whose means differ by more than the Hoeffding-like bound
+/// set by `delta`, and the oldest buckets are dropped until there is none.
+///
+/// Reference: Bifet, A. and Gavalda, R., 2007. Learning from time-changing data with
+/// adaptive windowing. In Proceedings of the 2007 SIAM International Conference on Data
+/// Mining, pp.443-448.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Adwin {
+    delta: f64,
+    clock: usize,
+    grace_period: usize,
+    // Row `i` holds the buckets of `2^i` values, from the oldest to the most recent.
+    rows: Vec<VecDeque<(f64, f64)>>,
+    width: usize,
+    total: f64,
+    variance: f64,
+    tick: usize,
+    drift_detected: bool,
+    n_detections: usize,
+}
+
+impl HeapSize for Adwin {
+    fn heap_size(&self) -> usize {
+        self.rows.heap_size()
+    }
+}
+
+impl Adwin {
+    pub fn new(delta: f64, clock: usize, grace_period: usize) -> Result<Self, String> {
+        if delta.is_nan() || delta <= 0.0 || delta >= 1.0 {
+            return Err("delta should be between 0 and 1".to_string());
+        }
+        if clock == 0 {
+            return Err("clock should be at least 1".to_string());
+        }
+        Ok(Adwin {
+            delta,
+            clock,
+            grace_period,
+            rows: Vec::new(),
+            width: 0,
+            total: 0.0,
+            variance: 0.0,
+            tick: 0,
+            drift_detected: false,
+            n_detections: 0,
+        })
+    }
+
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.width == 0 {
+            return 0.0;
+        }
+        self.total / self.width as f64
+    }
+
+    /// Population variance of the window.
+    pub fn variance(&self) -> f64 {
+        if self.width == 0 {
+            return 0.0;
+        }
+        self.variance / self.width as f64
+    }
+
+    /// Whether the last update shrank the window.
+    pub fn drift_detected(&self) -> bool {
+        self.drift_detected
+    }
+
+    pub fn n_detections(&self) -> usize {
+        self.n_detections
+    }
+
+    /// Number of values seen, including those dropped from the window.
+    pub fn n(&self) -> usize {
+        self.tick
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if self.width > 0 {
+            let n = self.width as f64;
+            let delta = x - self.total / n;
+            self.variance += n * delta * delta / (n + 1.0);
+        }
+        self.width += 1;
+        self.total += x;
+        if self.rows.is_empty() {
+            self.rows.push(VecDeque::new());
+        }
+        self.rows[0].push_back((x, 0.0));
+        self.compress();
+
+        self.tick += 1;
+        self.drift_detected = false;
+        if self.tick.is_multiple_of(self.clock) && self.width > self.grace_period {
+            while self.find_cut() {
+                self.drop_oldest();
+                self.drift_detected = true;
+            }
+            if self.drift_detected {
+                self.n_detections += 1;
+            }
+        }
+    }
+
+    // Merges the two oldest buckets of every full row into the next one.
+    fn compress(&mut self) {
+        let mut i = 0;
+        while i < self.rows.len() && self.rows[i].len() > MAX_BUCKETS {
+            let (t1, v1) = self.rows[i].pop_front().unwrap();
+            let (t2, v2) = self.rows[i].pop_front().unwrap();
+            let n = (1u64 << i) as f64;
+            let diff = t1 / n - t2 / n;
+            let merged = (t1 + t2, v1 + v2 + n * diff * diff / 2.0);
+            if i + 1 == self.rows.len() {
+                self.rows.push(VecDeque::new());
+            }
+            self.rows[i + 1].push_back(merged);
+            i += 1;
+        }
+    }
+
+    // Whether some split of the window, from the oldest value on, has means too far apart.
+    fn find_cut(&self) -> bool {
+        if self.width < 2 * MIN_WINDOW_LENGTH {
+            return false;
+        }
+        let width = self.width as f64;
+        let variance = self.variance / width;
+        let dd = (2.0 * width.ln() / self.delta).ln();
+        let (mut n0, mut u0) = (0.0, 0.0);
+        for (i, row) in self.rows.iter().enumerate().rev() {
+            let size = (1u64 << i) as f64;
+            for &(total, _) in row {
+                n0 += size;
+                u0 += total;
+                let n1 = width - n0;
+                if n1 < MIN_WINDOW_LENGTH as f64 {
+                    return false;
+                }
+                if n0 < MIN_WINDOW_LENGTH as f64 {
+                    continue;
+                }
+                let u1 = self.total - u0;
+                let m = 1.0 / (n0 - MIN_WINDOW_LENGTH as f64 + 1.0)
+                    + 1.0 / (n1 - MIN_WINDOW_LENGTH as f64 + 1.0);
+                let epsilon = (2.0 * m * variance * dd).sqrt() + 2.0 / 3.0 * dd * m;
+                if (u0 / n0 - u1 / n1).abs() > epsilon {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn drop_oldest(&mut self) {
+        let i = self.rows.len() - 1;
+        let (total, variance) = self.rows[i].pop_front().unwrap();
+        if self.rows[i].is_empty() {
+            self.rows.pop();
+        }
+        let n = (1u64 << i) as f64;
+        self.width -= 1 << i;
+        self.total -= total;
+        if self.width == 0 {
+            self.total = 0.0;
+            self.variance = 0.0;
+            return;
+        }
+        let rest = self.width as f64;
+        let diff = total / n - self.total / rest;
+        self.variance -= variance + n * rest * diff * diff / (n + rest);
+        self.variance = self.variance.max(0.0);
+    }
+}
+
+/// Mean over a window that ADWIN shrinks whenever the mean drifts.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAdaptiveMean {
+    adwin: Adwin,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsAdaptiveMean {
+    fn heap_size(&self) -> usize {
+        self.adwin.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+impl Params for RsAdaptiveMean {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("delta", Param::Float(self.adwin.delta()))]
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsAdaptiveMean {
+    #[new]
+    #[args(delta = "0.002", clock = "32", grace_period = "10")]
+    pub fn new(delta: f64, clock: usize, grace_period: usize) -> PyResult<Self> {
+        Ok(RsAdaptiveMean {
+            adwin: Adwin::new(delta, clock, grace_period).map_err(PyValueError::new_err)?,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.adwin.update(x);
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.adwin.mean()
+    }
+    /// Number of values in the window.
+    #[getter]
+    pub fn width(&self) -> usize {
+        self.adwin.width()
+    }
+    /// Number of values seen so far, including those dropped from the window.
+    #[getter]
+    pub fn n(&self) -> usize {
+        self.adwin.n()
+    }
+    /// Whether the last update shrank the window.
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.adwin.drift_detected()
+    }
+    #[getter]
+    pub fn n_detections(&self) -> usize {
+        self.adwin.n_detections()
+    }
+}
+
+/// Variance over a window that ADWIN shrinks whenever the mean drifts.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAdaptiveVar {
+    adwin: Adwin,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsAdaptiveVar {
+    fn heap_size(&self) -> usize {
+        self.adwin.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+impl Params for RsAdaptiveVar {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("delta", Param::Float(self.adwin.delta()))]
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsAdaptiveVar {
+    #[new]
+    #[args(delta = "0.002", clock = "32", grace_period = "10")]
+    pub fn new(delta: f64, clock: usize, grace_period: usize) -> PyResult<Self> {
+        Ok(RsAdaptiveVar {
+            adwin: Adwin::new(delta, clock, grace_period).map_err(PyValueError::new_err)?,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.adwin.update(x);
+        Ok(())
+    }
+    /// Population variance of the window.
+    pub fn get(&self) -> f64 {
+        self.adwin.variance()
+    }
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        self.adwin.mean()
+    }
+    /// Number of values in the window.
+    #[getter]
+    pub fn width(&self) -> usize {
+        self.adwin.width()
+    }
+    /// Number of values seen so far, including those dropped from the window.
+    #[getter]
+    pub fn n(&self) -> usize {
+        self.adwin.n()
+    }
+    /// Whether the last update shrank the window.
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.adwin.drift_detected()
+    }
+    #[getter]
+    pub fn n_detections(&self) -> usize {
+        self.adwin.n_detections()
+    }
+}
+
+/// ADWIN drift detector over a stream of real values, such as the errors of a model.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsADWIN {
+    adwin: Adwin,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsADWIN {
+    fn heap_size(&self) -> usize {
+        self.adwin.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsADWIN {
+    #[new]
+    #[args(delta = "0.002", clock = "32", grace_period = "10")]
+    pub fn new(delta: f64, clock: usize, grace_period: usize) -> PyResult<Self> {
+        Ok(RsADWIN {
+            adwin: Adwin::new(delta, clock, grace_period).map_err(PyValueError::new_err)?,
+        })
+    }
+    /// Adds a value and returns whether it triggered a drift, and a warning. ADWIN has no
+    /// warning zone, so the warning is always false; it is returned for the same interface
+    /// as the detectors that have one.
+    pub fn update(&mut self, x: f64) -> PyResult<(bool, bool)> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.adwin.update(x);
+        Ok((self.adwin.drift_detected(), false))
+    }
+    /// Number of values in the window.
+    #[getter]
+    pub fn width(&self) -> usize {
+        self.adwin.width()
+    }
+    /// Mean of the window.
+    #[getter]
+    pub fn estimation(&self) -> f64 {
+        self.adwin.mean()
+    }
+    /// Population variance of the window.
+    #[getter]
+    pub fn variance(&self) -> f64 {
+        self.adwin.variance()
+    }
+    /// Whether the last update shrank the window.
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.adwin.drift_detected()
+    }
+    #[getter]
+    pub fn n_detections(&self) -> usize {
+        self.adwin.n_detections()
+    }
+}
+
+/// Shift of the distribution of a categorical stream, scored by a chi-square test of
+/// homogeneity between a reference and a window of recent values.
+///
+/// As in `WassersteinDrift`, the recent window holds the last `window_size` values, and the
+/// ones it evicts are counted in the reference.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MultinomialDrift {
+    window_size: usize,
+    alpha: f64,
+    recent: VecDeque<Key>,
+    #[serde(with = "crate::key::pairs")]
+    recent_counts: HashMap<Key, f64>,
+    #[serde(with = "crate::key::pairs")]
+    reference_counts: HashMap<Key, f64>,
+    n_reference: f64,
+}
+
+impl HeapSize for MultinomialDrift {
+    fn heap_size(&self) -> usize {
+        self.recent.heap_size() + self.recent_counts.heap_size() + self.reference_counts.heap_size()
+    }
+}
+
+impl MultinomialDrift {
+    pub fn new(window_size: usize, alpha: f64) -> Self {
+        MultinomialDrift {
+            window_size,
+            alpha,
+            recent: VecDeque::with_capacity(window_size + 1),
+            recent_counts: HashMap::new(),
+            reference_counts: HashMap::new(),
+            n_reference: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, x: Key) {
+        *self.recent_counts.entry(x.clone()).or_insert(0.0) += 1.0;
+        self.recent.push_back(x);
+        if self.recent.len() > self.window_size {
+            let evicted = self.recent.pop_front().unwrap();
+            let count = self.recent_counts.get_mut(&evicted).unwrap();
+            *count -= 1.0;
+            if *count == 0.0 {
+                self.recent_counts.remove(&evicted);
+            }
+            *self.reference_counts.entry(evicted).or_insert(0.0) += 1.0;
+            self.n_reference += 1.0;
+        }
+    }
+
+    pub fn reset_reference(&mut self) {
+        self.reference_counts.clear();
+        self.n_reference = 0.0;
+    }
+
+    // Categories seen in either sample, with their reference and recent counts.
+    fn categories(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let reference = self
+            .reference_counts
+            .iter()
+            .map(|(x, &n)| (n, self.recent_counts.get(x).cloned().unwrap_or(0.0)));
+        let recent_only = self
+            .recent_counts
+            .iter()
+            .filter(|(x, _)| !self.reference_counts.contains_key(x))
+            .map(|(_, &n)| (0.0, n));
+        reference.chain(recent_only)
+    }
+
+    pub fn df(&self) -> usize {
+        if self.n_reference == 0.0 {
+            return 0;
+        }
+        self.categories().count().saturating_sub(1)
+    }
+
+    pub fn statistic(&self) -> f64 {
+        let n_recent = self.recent.len() as f64;
+        if self.n_reference == 0.0 || n_recent == 0.0 {
+            return 0.0;
+        }
+        let n = self.n_reference + n_recent;
+        self.categories()
+            .map(|(reference, recent)| {
+                let total = reference + recent;
+                let expected_reference = self.n_reference * total / n;
+                let expected_recent = n_recent * total / n;
+                (reference - expected_reference).powi(2) / expected_reference
+                    + (recent - expected_recent).powi(2) / expected_recent
+            })
+            .sum()
+    }
+
+    pub fn p_value(&self) -> f64 {
+        let df = self.df();
+        if df == 0 {
+            return 1.0;
+        }
+        chi2_sf(self.statistic(), df as f64)
+    }
+
+    pub fn drift_detected(&self) -> bool {
+        self.p_value() < self.alpha
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsMultinomialDrift {
+    drift: MultinomialDrift,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsMultinomialDrift {
+    fn heap_size(&self) -> usize {
+        self.drift.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsMultinomialDrift {
+    /// A drift is flagged when the p-value of the test falls below `alpha`.
+    #[new]
+    #[args(alpha = "0.01")]
+    pub fn new(window_size: usize, alpha: f64) -> PyResult<Self> {
+        if window_size == 0 {
+            return Err(PyValueError::new_err("window_size should be at least 1"));
+        }
+        if alpha.is_nan() || alpha <= 0.0 || alpha >= 1.0 {
+            return Err(PyValueError::new_err("alpha should be between 0 and 1"));
+        }
+        Ok(RsMultinomialDrift {
+            drift: MultinomialDrift::new(window_size, alpha),
+        })
+    }
+    pub fn update(&mut self, x: Key) {
+        self.drift.update(x);
+    }
+    /// Forgets the reference distribution, e.g. once a drift has been acted upon.
+    pub fn reset_reference(&mut self) {
+        self.drift.reset_reference();
+    }
+    #[getter]
+    pub fn statistic(&self) -> f64 {
+        self.drift.statistic()
+    }
+    #[getter]
+    pub fn df(&self) -> usize {
+        self.drift.df()
+    }
+    #[getter]
+    pub fn p_value(&self) -> f64 {
+        self.drift.p_value()
+    }
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.drift.drift_detected()
+    }
+}
+
+/// Direction of the changes of the mean that `PageHinkley` detects.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PageHinkleyMode {
+    Up,
+    Down,
+    Both,
+}
+
+impl PageHinkleyMode {
+    pub fn new(name: &str) -> Result<Self, String> {
+        match name {
+            "up" => Ok(PageHinkleyMode::Up),
+            "down" => Ok(PageHinkleyMode::Down),
+            "both" => Ok(PageHinkleyMode::Both),
+            _ => Err(format!(
+                "unknown mode '{}', expected 'up', 'down' or 'both'",
+                name
+            )),
+        }
+    }
+}
+
+/// Page-Hinkley test, which accumulates the deviations of the values from their running
+/// mean, faded by `alpha` and offset by the tolerated change `delta`, and flags a drift when
+/// the cumulative sum moves more than `threshold` away from its extremum.
+///
+/// As in river, the detector starts over with the update following a drift.
+///
+/// Reference: Page, E.S., 1954. Continuous inspection schemes. Biometrika, 41(1/2),
+/// pp.100-115.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PageHinkley {
+    min_instances: usize,
+    delta: f64,
+    threshold: f64,
+    alpha: f64,
+    mode: PageHinkleyMode,
+    n: usize,
+    mean: f64,
+    sum_increase: f64,
+    sum_decrease: f64,
+    min_increase: f64,
+    max_decrease: f64,
+    drift_detected: bool,
+}
+
+impl HeapSize for PageHinkley {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl PageHinkley {
+    pub fn new(
+        min_instances: usize,
+        delta: f64,
+        threshold: f64,
+        alpha: f64,
+        mode: PageHinkleyMode,
+    ) -> Result<Self, String> {
+        if alpha.is_nan() || alpha <= 0.0 || alpha > 1.0 {
+            return Err("alpha should be in (0, 1]".to_string());
+        }
+        Ok(PageHinkley::start(
+            min_instances,
+            delta,
+            threshold,
+            alpha,
+            mode,
+        ))
+    }
+
+    // Detector that has seen no value yet, with parameters which are already checked.
+    fn start(
+        min_instances: usize,
+        delta: f64,
+        threshold: f64,
+        alpha: f64,
+        mode: PageHinkleyMode,
+    ) -> Self {
+        PageHinkley {
+            min_instances,
+            delta,
+            threshold,
+            alpha,
+            mode,
+            n: 0,
+            mean: 0.0,
+            sum_increase: 0.0,
+            sum_decrease: 0.0,
+            min_increase: f64::INFINITY,
+            max_decrease: f64::NEG_INFINITY,
+            drift_detected: false,
+        }
+    }
+
+    pub fn drift_detected(&self) -> bool {
+        self.drift_detected
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if self.drift_detected {
+            *self = PageHinkley::start(
+                self.min_instances,
+                self.delta,
+                self.threshold,
+                self.alpha,
+                self.mode,
+            );
+        }
+        self.n += 1;
+        self.mean += (x - self.mean) / self.n as f64;
+        let deviation = x - self.mean;
+        self.sum_increase = self.alpha * self.sum_increase + deviation - self.delta;
+        self.sum_decrease = self.alpha * self.sum_decrease + deviation + self.delta;
+        self.min_increase = self.min_increase.min(self.sum_increase);
+        self.max_decrease = self.max_decrease.max(self.sum_decrease);
+        if self.n >= self.min_instances {
+            let increase = self.sum_increase - self.min_increase > self.threshold;
+            let decrease = self.max_decrease - self.sum_decrease > self.threshold;
+            self.drift_detected = match self.mode {
+                PageHinkleyMode::Up => increase,
+                PageHinkleyMode::Down => decrease,
+                PageHinkleyMode::Both => increase || decrease,
+            };
+        }
+    }
+}
+
+/// Drift Detection Method, which watches the error rate `p` of a model and its standard
+/// deviation `s`, and flags a warning, then a drift, when `p + s` exceeds its lowest value
+/// `p_min + s_min` by `warning_threshold`, then `drift_threshold`, times `s_min`.
+///
+/// As in river, the detector starts over with the update following a drift.
+///
+/// Reference: Gama, J., Medas, P., Castillo, G. and Rodrigues, P., 2004. Learning with drift
+/// detection. In Brazilian symposium on artificial intelligence, pp.286-295.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ddm {
+    warm_start: usize,
+    warning_threshold: f64,
+    drift_threshold: f64,
+    n: usize,
+    n_errors: usize,
+    p_min: f64,
+    s_min: f64,
+    drift_detected: bool,
+    warning_detected: bool,
+}
+
+impl HeapSize for Ddm {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Ddm {
+    pub fn new(
+        warm_start: usize,
+        warning_threshold: f64,
+        drift_threshold: f64,
+    ) -> Result<Self, String> {
+        if warning_threshold.is_nan() || drift_threshold.is_nan() {
+            return Err("the thresholds should not be NaN".to_string());
+        }
+        Ok(Ddm::start(warm_start, warning_threshold, drift_threshold))
+    }
+
+    // Detector that has seen no value yet, with parameters which are already checked.
+    fn start(warm_start: usize, warning_threshold: f64, drift_threshold: f64) -> Self {
+        Ddm {
+            warm_start,
+            warning_threshold,
+            drift_threshold,
+            n: 0,
+            n_errors: 0,
+            p_min: f64::INFINITY,
+            s_min: f64::INFINITY,
+            drift_detected: false,
+            warning_detected: false,
+        }
+    }
+
+    pub fn drift_detected(&self) -> bool {
+        self.drift_detected
+    }
+
+    pub fn warning_detected(&self) -> bool {
+        self.warning_detected
+    }
+
+    /// Adds whether the model made an error.
+    pub fn update(&mut self, error: bool) {
+        if self.drift_detected {
+            *self = Ddm::start(
+                self.warm_start,
+                self.warning_threshold,
+                self.drift_threshold,
+            );
+        }
+        self.n += 1;
+        self.n_errors += error as usize;
+        let n = self.n as f64;
+        let p = self.n_errors as f64 / n;
+        let s = (p * (1.0 - p) / n).sqrt();
+        if self.n > self.warm_start {
+            if p + s <= self.p_min + self.s_min {
+                self.p_min = p;
+                self.s_min = s;
+            }
+            self.warning_detected = p + s > self.p_min + self.warning_threshold * self.s_min;
+            if p + s > self.p_min + self.drift_threshold * self.s_min {
+                self.drift_detected = true;
+                self.warning_detected = false;
+            }
+        }
+    }
+}
+
+/// Early Drift Detection Method, which watches the distance between consecutive errors of a
+/// model, and flags a warning, then a drift, when its mean plus twice its standard deviation
+/// falls below `alpha`, then `beta`, times its highest value.
+///
+/// As in river, the detector starts over with the update following a drift.
+///
+/// Reference: Baena-Garcia, M., del Campo-Avila, J., Fidalgo, R., Bifet, A., Gavalda, R. and
+/// Morales-Bueno, R., 2006. Early drift detection method. In Fourth international workshop
+/// on knowledge discovery from data streams, pp.77-86.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Eddm {
+    warm_start: usize,
+    alpha: f64,
+    beta: f64,
+    n: usize,
+    last_error: usize,
+    n_errors: usize,
+    // Mean and sum of squared deviations of the distances between errors.
+    mean_distance: f64,
+    m2_distance: f64,
+    max_level: f64,
+    drift_detected: bool,
+    warning_detected: bool,
+}
+
+impl HeapSize for Eddm {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Eddm {
+    pub fn new(warm_start: usize, alpha: f64, beta: f64) -> Result<Self, String> {
+        if alpha.is_nan() || beta.is_nan() || alpha < beta {
+            return Err("alpha should be at least beta".to_string());
+        }
+        Ok(Eddm::start(warm_start, alpha, beta))
+    }
+
+    // Detector that has seen no value yet, with parameters which are already checked.
+    fn start(warm_start: usize, alpha: f64, beta: f64) -> Self {
+        Eddm {
+            warm_start,
+            alpha,
+            beta,
+            n: 0,
+            last_error: 0,
+            n_errors: 0,
+            mean_distance: 0.0,
+            m2_distance: 0.0,
+            max_level: f64::NEG_INFINITY,
+            drift_detected: false,
+            warning_detected: false,
+        }
+    }
+
+    pub fn drift_detected(&self) -> bool {
+        self.drift_detected
+    }
+
+    pub fn warning_detected(&self) -> bool {
+        self.warning_detected
+    }
+
+    /// Adds whether the model made an error.
+    pub fn update(&mut self, error: bool) {
+        if self.drift_detected {
+            *self = Eddm::start(self.warm_start, self.alpha, self.beta);
+        }
+        self.n += 1;
+        if !error {
+            return;
+        }
+        self.n_errors += 1;
+        let distance = (self.n - self.last_error) as f64;
+        let delta = distance - self.mean_distance;
+        self.mean_distance += delta / self.n_errors as f64;
+        self.m2_distance += delta * (distance - self.mean_distance);
+        self.last_error = self.n;
+        if self.n <= self.warm_start {
+            return;
+        }
+        // The sample standard deviation, which is 0 until there are two distances.
+        let std = if self.n_errors > 1 {
+            (self.m2_distance / (self.n_errors - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        let level = self.mean_distance + 2.0 * std;
+        if level > self.max_level {
+            self.max_level = level;
+        } else if self.n_errors > self.warm_start {
+            let ratio = level / self.max_level;
+            if ratio < self.beta {
+                self.drift_detected = true;
+            } else {
+                self.warning_detected = ratio < self.alpha;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPageHinkley {
+    detector: PageHinkley,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsPageHinkley {
+    fn heap_size(&self) -> usize {
+        self.detector.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsPageHinkley {
+    /// Detects the increases of the mean with the `"up"` mode, its decreases with the
+    /// `"down"` one, and both with `"both"`.
+    #[new]
+    #[args(
+        min_instances = "30",
+        delta = "0.005",
+        threshold = "50.0",
+        alpha = "0.9999",
+        mode = "\"both\""
+    )]
+    pub fn new(
+        min_instances: usize,
+        delta: f64,
+        threshold: f64,
+        alpha: f64,
+        mode: &str,
+    ) -> PyResult<Self> {
+        let mode = PageHinkleyMode::new(mode).map_err(PyValueError::new_err)?;
+        Ok(RsPageHinkley {
+            detector: PageHinkley::new(min_instances, delta, threshold, alpha, mode)
+                .map_err(PyValueError::new_err)?,
+        })
+    }
+    /// Adds a value and returns whether it triggered a drift, and a warning, which is always
+    /// false as the test has no warning zone.
+    pub fn update(&mut self, x: f64) -> PyResult<(bool, bool)> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.detector.update(x);
+        Ok((self.detector.drift_detected(), false))
+    }
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.detector.drift_detected()
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsDDM {
+    detector: Ddm,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsDDM {
+    fn heap_size(&self) -> usize {
+        self.detector.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsDDM {
+    #[new]
+    #[args(warm_start = "30", warning_threshold = "2.0", drift_threshold = "3.0")]
+    pub fn new(warm_start: usize, warning_threshold: f64, drift_threshold: f64) -> PyResult<Self> {
+        Ok(RsDDM {
+            detector: Ddm::new(warm_start, warning_threshold, drift_threshold)
+                .map_err(PyValueError::new_err)?,
+        })
+    }
+    /// Adds whether the model made an error, and returns whether it triggered a drift, and
+    /// a warning.
+    pub fn update(&mut self, x: bool) -> (bool, bool) {
+        self.detector.update(x);
+        (
+            self.detector.drift_detected(),
+            self.detector.warning_detected(),
+        )
+    }
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.detector.drift_detected()
+    }
+    #[getter]
+    pub fn warning_detected(&self) -> bool {
+        self.detector.warning_detected()
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsEDDM {
+    detector: Eddm,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsEDDM {
+    fn heap_size(&self) -> usize {
+        self.detector.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsEDDM {
+    #[new]
+    #[args(warm_start = "30", alpha = "0.95", beta = "0.9")]
+    pub fn new(warm_start: usize, alpha: f64, beta: f64) -> PyResult<Self> {
+        Ok(RsEDDM {
+            detector: Eddm::new(warm_start, alpha, beta).map_err(PyValueError::new_err)?,
+        })
+    }
+    /// Adds whether the model made an error, and returns whether it triggered a drift, and
+    /// a warning.
+    pub fn update(&mut self, x: bool) -> (bool, bool) {
+        self.detector.update(x);
+        (
+            self.detector.drift_detected(),
+            self.detector.warning_detected(),
+        )
+    }
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.detector.drift_detected()
+    }
+    #[getter]
+    pub fn warning_detected(&self) -> bool {
+        self.detector.warning_detected()
+    }
+}
+
+/// Kolmogorov-Smirnov WINdowing, which compares the `stat_size` most recent values of a
+/// window of `window_size` values with as many values sampled from the older part of the
+/// window, and flags a drift when a two-sample Kolmogorov-Smirnov test rejects that both
+/// come from the same distribution at level `alpha`, with a statistic above 0.1.
+///
+/// The most recent values are kept sorted as they slide, so that only the sample is sorted
+/// on each update, and the p-value is exact since both samples have the same size. After a
+/// drift, the window keeps its most recent values only.
+///
+/// Reference: Raab, C., Heusinger, M. and Schleif, F.M., 2020. Reactive soft prototype
+/// computing for concept drift streams. Neurocomputing, 416, pp.340-351.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Kswin {
+    alpha: f64,
+    window_size: usize,
+    stat_size: usize,
+    window: VecDeque<f64>,
+    recent: SortedWindow,
+    statistic: f64,
+    p_value: f64,
+    drift_detected: bool,
+    rng: Rng,
+}
+
+impl HeapSize for Kswin {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size() + self.recent.heap_size()
+    }
+}
+
+impl Kswin {
+    pub fn new(
+        alpha: f64,
+        window_size: usize,
+        stat_size: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        if alpha.is_nan() || alpha <= 0.0 || alpha >= 1.0 {
+            return Err("alpha should be between 0 and 1".to_string());
+        }
+        if stat_size == 0 {
+            return Err("stat_size should be at least 1".to_string());
+        }
+        // The older part of the window should hold enough values to sample from.
+        if window_size < 2 * stat_size {
+            return Err("window_size should be at least twice stat_size".to_string());
+        }
+        Ok(Kswin {
+            alpha,
+            window_size,
+            stat_size,
+            window: VecDeque::with_capacity(window_size + 1),
+            recent: SortedWindow::new(stat_size),
+            statistic: 0.0,
+            p_value: 1.0,
+            drift_detected: false,
+            rng: Rng::new(seed),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn statistic(&self) -> f64 {
+        self.statistic
+    }
+
+    pub fn p_value(&self) -> f64 {
+        self.p_value
+    }
+
+    pub fn drift_detected(&self) -> bool {
+        self.drift_detected
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.window.push_back(x);
+        if self.window.len() > self.window_size {
+            self.window.pop_front();
+        }
+        self.recent.push(x);
+        if self.recent.len() > self.stat_size {
+            self.recent.pop();
+        }
+        self.drift_detected = false;
+        if self.window.len() < self.window_size {
+            return;
+        }
+
+        // Partial Fisher-Yates shuffle of the positions of the older values.
+        let mut positions: Vec<usize> = (0..self.window_size - self.stat_size).collect();
+        for i in 0..self.stat_size {
+            let j = i + self.rng.below((positions.len() - i) as u64) as usize;
+            positions.swap(i, j);
+        }
+        let mut sample: Vec<f64> = positions[..self.stat_size]
+            .iter()
+            .map(|&i| self.window[i])
+            .collect();
+        sample.sort_by(f64::total_cmp);
+
+        self.statistic = ks_statistic(&sample, self.recent.sorted());
+        let h = (self.statistic * self.stat_size as f64).round() as usize;
+        self.p_value = ks_two_sample_sf(self.stat_size, h);
+        if self.p_value <= self.alpha && self.statistic > 0.1 {
+            self.drift_detected = true;
+            let n_older = self.window.len() - self.stat_size;
+            self.window.drain(..n_older);
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsKSWIN {
+    detector: Kswin,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsKSWIN {
+    fn heap_size(&self) -> usize {
+        self.detector.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsKSWIN {
+    #[new]
+    #[args(alpha = "0.005", window_size = "100", stat_size = "30", seed = "None")]
+    pub fn new(
+        alpha: f64,
+        window_size: usize,
+        stat_size: usize,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        Ok(RsKSWIN {
+            detector: Kswin::new(alpha, window_size, stat_size, seed)
+                .map_err(PyValueError::new_err)?,
+        })
+    }
+    /// Adds a value and returns whether it triggered a drift, and a warning, which is always
+    /// false as the test has no warning zone.
+    pub fn update(&mut self, x: f64) -> PyResult<(bool, bool)> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.detector.update(x);
+        Ok((self.detector.drift_detected(), false))
+    }
+    /// Statistic of the last test.
+    #[getter]
+    pub fn statistic(&self) -> f64 {
+        self.detector.statistic()
+    }
+    /// P-value of the last test.
+    #[getter]
+    pub fn p_value(&self) -> f64 {
+        self.detector.p_value()
+    }
+    #[getter]
+    pub fn drift_detected(&self) -> bool {
+        self.detector.drift_detected()
+    }
+    pub fn __len__(&self) -> usize {
+        self.detector.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normals(n: usize, mean: f64, seed: u64) -> Vec<f64> {
+        let mut rng = Rng::from_seed(seed);
+        (0..n).map(|_| mean + rng.normal()).collect()
+    }
+
+    #[test]
+    fn distances_between_two_samples() {
+        let (w1, energy) = cdf_distances(&[0.0], &[1.0]);
+        assert_eq!(w1, 1.0);
+        assert!((energy - 2f64.sqrt()).abs() < 1e-12);
+        assert_eq!(
+            cdf_distances(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]),
+            (0.0, 0.0)
+        );
+        assert_eq!(cdf_distances(&[], &[1.0]), (0.0, 0.0));
+        // Shifting a sample moves it by as much in the 1-Wasserstein distance.
+        let (w1, _) = cdf_distances(&[0.0, 1.0, 2.0], &[0.5, 1.5, 2.5]);
+        assert!((w1 - 0.5).abs() < 1e-12);
+
+        assert_eq!(ks_statistic(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), 1.0);
+        assert_eq!(ks_statistic(&[1.0, 2.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(ks_statistic(&[1.0, 2.0, 3.0, 4.0], &[3.0, 4.0]), 0.5);
+    }
+
+    #[test]
+    fn wasserstein_drift_follows_a_shift() {
+        let mut drift = WassersteinDrift::new(200, 100.0, 50).unwrap();
+        assert_eq!(drift.wasserstein(), 0.0);
+        for x in normals(2000, 0.0, 1) {
+            drift.update(x);
+        }
+        let stationary = drift.wasserstein();
+        assert!(stationary < 0.3);
+        for x in normals(200, 3.0, 2) {
+            drift.update(x);
+        }
+        assert!((drift.wasserstein() - 3.0).abs() < 0.3);
+        assert!(drift.energy() > 0.0);
+        drift.reset_reference();
+        assert_eq!(drift.wasserstein(), 0.0);
+    }
+
+    #[test]
+    fn adwin_keeps_a_stationary_window() {
+        let mut adwin = Adwin::new(0.002, 32, 10).unwrap();
+        let values = normals(1000, 5.0, 3);
+        for &x in &values {
+            adwin.update(x);
+        }
+        assert_eq!(adwin.n_detections(), 0);
+        assert_eq!(adwin.width(), 1000);
+        let mean = values.iter().sum::<f64>() / 1000.0;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 1000.0;
+        assert!((adwin.mean() - mean).abs() < 1e-9);
+        assert!((adwin.variance() - variance).abs() < 1e-9);
+    }
+
+    #[test]
+    fn adwin_drops_the_values_before_a_shift() {
+        let mut adwin = Adwin::new(0.002, 32, 10).unwrap();
+        for x in normals(1000, 0.0, 4) {
+            adwin.update(x);
+        }
+        for x in normals(1000, 3.0, 5) {
+            adwin.update(x);
+        }
+        assert!(adwin.n_detections() >= 1);
+        assert!(adwin.width() < 1100);
+        assert!((adwin.mean() - 3.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn multinomial_drift_tests_the_contingency_table() {
+        let mut drift = MultinomialDrift::new(2, 0.05);
+        for x in ["a", "a", "b", "b"] {
+            drift.update(Key::Str(x.to_string()));
+        }
+        // The reference holds two a and the window two b, so every cell is 1 off.
+        assert_eq!(drift.df(), 1);
+        assert_eq!(drift.statistic(), 4.0);
+        assert!((drift.p_value() - chi2_sf(4.0, 1.0)).abs() < 1e-12);
+        assert!(drift.drift_detected());
+        drift.reset_reference();
+        assert_eq!(drift.df(), 0);
+        assert_eq!(drift.p_value(), 1.0);
+    }
+
+    #[test]
+    fn page_hinkley_follows_its_mode() {
+        assert!(PageHinkleyMode::new("sideways").is_err());
+        for (mode, shift, expected) in [
+            ("up", 5.0, true),
+            ("up", -5.0, false),
+            ("down", -5.0, true),
+            ("both", -5.0, true),
+        ] {
+            let mode = PageHinkleyMode::new(mode).unwrap();
+            let mut detector = PageHinkley::new(30, 0.005, 50.0, 0.9999, mode).unwrap();
+            let mut detected = false;
+            for x in normals(500, 0.0, 6)
+                .into_iter()
+                .chain(normals(500, shift, 7))
+            {
+                detector.update(x);
+                detected |= detector.drift_detected();
+            }
+            assert_eq!(detected, expected, "{:?} {}", mode, shift);
+        }
+    }
+
+    #[test]
+    fn error_rate_detectors_flag_a_rising_error_rate() {
+        // An error every 10 steps, then every other step.
+        let errors: Vec<bool> = (1..=3000)
+            .map(|i| i % if i <= 1500 { 10 } else { 2 } == 0)
+            .collect();
+        let mut ddm = Ddm::new(30, 2.0, 3.0).unwrap();
+        let mut eddm = Eddm::new(30, 0.95, 0.9).unwrap();
+        let (mut ddm_at, mut eddm_at) = (None, None);
+        for (i, &error) in errors.iter().enumerate() {
+            ddm.update(error);
+            eddm.update(error);
+            if ddm.drift_detected() && ddm_at.is_none() {
+                ddm_at = Some(i);
+            }
+            if eddm.drift_detected() && eddm_at.is_none() {
+                eddm_at = Some(i);
+            }
+        }
+        assert!((1500..1600).contains(&ddm_at.unwrap()));
+        assert!((1500..2000).contains(&eddm_at.unwrap()));
+    }
+
+    #[test]
+    fn kswin_detects_a_shift_of_the_distribution() {
+        let mut kswin = Kswin::new(0.005, 100, 30, Some(9)).unwrap();
+        let mut detections = Vec::new();
+        for (i, x) in normals(500, 0.0, 10)
+            .into_iter()
+            .chain(normals(200, 4.0, 11))
+            .enumerate()
+        {
+            kswin.update(x);
+            if kswin.drift_detected() {
+                detections.push(i);
+            }
+        }
+        assert!(detections.iter().any(|&i| (500..530).contains(&i)));
+        assert!(kswin.len() <= 100);
+        assert!((0.0..=1.0).contains(&kswin.p_value()));
+    }
+
+    #[test]
+    fn constructors_reject_invalid_parameters() {
+        assert!(WassersteinDrift::new(0, 100.0, 50).is_err());
+        assert!(WassersteinDrift::new(200, 100.0, 0).is_err());
+        assert!(WassersteinDrift::new(200, f64::NAN, 50).is_err());
+        assert!(Adwin::new(0.0, 32, 10).is_err());
+        assert!(Adwin::new(f64::NAN, 32, 10).is_err());
+        assert!(Adwin::new(0.002, 0, 10).is_err());
+        let mode = PageHinkleyMode::Both;
+        assert!(PageHinkley::new(30, 0.005, 50.0, 0.0, mode).is_err());
+        assert!(PageHinkley::new(30, 0.005, 50.0, f64::NAN, mode).is_err());
+        assert!(Ddm::new(30, f64::NAN, 3.0).is_err());
+        assert!(Eddm::new(30, 0.9, 0.95).is_err());
+        assert!(Eddm::new(30, f64::NAN, 0.9).is_err());
+        assert!(Kswin::new(1.0, 100, 30, None).is_err());
+        assert!(Kswin::new(0.005, 100, 0, None).is_err());
+        // A window shorter than the recent values would underflow its older part.
+        assert!(Kswin::new(0.005, 20, 30, None).is_err());
+        assert!(Kswin::new(0.005, 59, 30, None).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+// Self-describing encodings of the states, for document databases and services that don't
+// speak bincode.
+//
+// Besides the versioned bincode state of `__getstate__`, a state can be written as CBOR,
+// MessagePack or JSON, in which case it is a map holding the class name, the layout
+// version and the fields of the binding. `to_dict` returns the JSON document as Python
+// objects. JSON has no literal for the non-finite floats, which are written as "NaN",
+// "inf" and "-inf".
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::nonfinite::{Finite, Lenient};
+use crate::state::{decode, encode, name, Versioned};
+
+#[derive(Clone, Copy)]
+enum Format {
+    Bincode,
+    Cbor,
+    MessagePack,
+    Json,
+}
+
+impl Format {
+    fn parse(format: &str) -> PyResult<Self> {
+        match format {
+            "bincode" => Ok(Format::Bincode),
+            "cbor" => Ok(Format::Cbor),
+            "msgpack" => Ok(Format::MessagePack),
+            "json" => Ok(Format::Json),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown format {:?}, expected one of 'bincode', 'cbor', 'msgpack' or 'json'",
+                format
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    class: &'a str,
+    version: u16,
+    state: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    class: String,
+    version: u16,
+}
+
+#[derive(Deserialize)]
+struct Content<T> {
+    state: T,
+}
+
+fn encode_error<E: std::fmt::Display>(e: E) -> PyErr {
+    PyValueError::new_err(format!("failed to encode the state: {}", e))
+}
+
+fn decode_error<E: std::fmt::Display>(e: E) -> PyErr {
+    PyValueError::new_err(format!("failed to decode the state: {}", e))
+}
+
+fn to_bytes<T: Versioned>(state: &T, format: &str) -> PyResult<Vec<u8>> {
+    let envelope = Envelope {
+        class: name::<T>(),
+        version: T::VERSION,
+        state,
+    };
+    match Format::parse(format)? {
+        Format::Bincode => Ok(encode(state)),
+        Format::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&envelope, &mut bytes).map_err(encode_error)?;
+            Ok(bytes)
+        }
+        Format::MessagePack => rmp_serde::to_vec_named(&envelope).map_err(encode_error),
+        Format::Json => serde_json::to_vec(&Finite(&envelope)).map_err(encode_error),
+    }
+}
+
+fn parse<D: DeserializeOwned>(bytes: &[u8], format: Format) -> PyResult<D> {
+    match format {
+        Format::Bincode => bincode::deserialize(bytes).map_err(decode_error),
+        Format::Cbor => ciborium::de::from_reader(bytes).map_err(decode_error),
+        Format::MessagePack => rmp_serde::from_slice(bytes).map_err(decode_error),
+        Format::Json => {
+            let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+            let value = D::deserialize(Lenient(&mut deserializer)).map_err(decode_error)?;
+            deserializer.end().map_err(decode_error)?;
+            Ok(value)
+        }
+    }
+}
+
+fn from_bytes<T: Versioned>(bytes: &[u8], format: &str) -> PyResult<T> {
+    let format = Format::parse(format)?;
+    if let Format::Bincode = format {
+        return decode(bytes);
+    }
+    let header: Header = parse(bytes, format)?;
+    if header.class != name::<T>() {
+        return Err(PyValueError::new_err(format!(
+            "the state is that of {}, not {}",
+            header.class,
+            name::<T>()
+        )));
+    }
+    if header.version != T::VERSION {
+        return Err(PyValueError::new_err(format!(
+            "the state of {} has version {}, but only version {} can be read from this format",
+            header.class,
+            header.version,
+            T::VERSION
+        )));
+    }
+    let content: Content<T> = parse(bytes, format)?;
+    Ok(content.state)
+}
+
+fn json_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => n.into_py(py),
+            (_, Some(n)) => n.into_py(py),
+            _ => n.as_f64().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| json_to_py(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items).into_py(py)
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, field) in fields {
+                dict.set_item(key, json_to_py(py, field)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+fn py_to_json(obj: &PyAny) -> PyResult<Value> {
+    if obj.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = obj.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if obj.is_instance_of::<PyLong>()? {
+        match obj.extract::<i64>() {
+            Ok(n) => Ok(Value::from(n)),
+            Err(_) => Ok(Value::from(obj.extract::<u64>()?)),
+        }
+    } else if let Ok(x) = obj.downcast::<PyFloat>() {
+        // The non-finite floats are spelled as in the JSON encoding.
+        Ok(serde_json::to_value(Finite(&x.value())).map_err(decode_error)?)
+    } else if let Ok(s) = obj.downcast::<PyString>() {
+        Ok(Value::String(s.to_str()?.to_string()))
+    } else if let Ok(items) = obj.downcast::<PyList>() {
+        items
+            .iter()
+            .map(py_to_json)
+            .collect::<PyResult<_>>()
+            .map(Value::Array)
+    } else if let Ok(items) = obj.downcast::<PyTuple>() {
+        items
+            .iter()
+            .map(py_to_json)
+            .collect::<PyResult<_>>()
+            .map(Value::Array)
+    } else if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut fields = Map::new();
+        for (key, field) in dict {
+            let key = key.downcast::<PyString>().map_err(|_| {
+                decode_error(format!(
+                    "the keys of a state dict should be str, not {}",
+                    key
+                ))
+            })?;
+            fields.insert(key.to_str()?.to_string(), py_to_json(field)?);
+        }
+        Ok(Value::Object(fields))
+    } else {
+        Err(decode_error(format!(
+            "{} can't be part of a state dict",
+            obj.get_type().name()?
+        )))
+    }
+}
+
+// The dicts go through the JSON encoding, so that they hold the same document as
+// `to_bytes(format="json")`, with the same checks of the class and the version.
+fn to_dict<T: Versioned>(py: Python, state: &T) -> PyResult<PyObject> {
+    let value: Value = serde_json::from_slice(&to_bytes(state, "json")?).map_err(encode_error)?;
+    json_to_py(py, &value)
+}
+
+fn from_dict<T: Versioned>(dict: &PyDict) -> PyResult<T> {
+    let bytes = serde_json::to_vec(&py_to_json(dict)?).map_err(decode_error)?;
+    from_bytes(&bytes, "json")
+}
+
+macro_rules! encodable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                /// Encodes the state as "bincode", "cbor", "msgpack" or "json".
+                #[args(format = "\"bincode\"")]
+                pub fn to_bytes<'py>(&self, py: Python<'py>, format: &str) -> PyResult<&'py PyBytes> {
+                    Ok(PyBytes::new(py, &to_bytes(self, format)?))
+                }
+                /// Decodes a state produced by `to_bytes` with the same format.
+                #[staticmethod]
+                #[args(format = "\"bincode\"")]
+                pub fn from_bytes(data: &[u8], format: &str) -> PyResult<Self> {
+                    from_bytes(data, format)
+                }
+                /// State as a dict of plain Python values, holding the class name, the layout
+                /// version and the fields of the binding.
+                pub fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+                    to_dict(py, self)
+                }
+                /// Rebuilds an instance from the dict returned by `to_dict`.
+                #[staticmethod]
+                pub fn from_dict(data: &PyDict) -> PyResult<Self> {
+                    from_dict(data)
+                }
+            }
+        )*
+    };
+}
+
+for_each_binding!(encodable);
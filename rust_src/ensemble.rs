@@ -0,0 +1,175 @@
+// Aggregation of the outputs of the members of an ensemble, in one call, so that large
+// ensembles don't spend their time in Python dict arithmetic.
+//
+// Each function takes optional `weights`, one per member, in any of the layouts read by
+// `Values`. Given a `temperature`, the weights are instead the performances of the members,
+// turned into weights by a softmax of `weights / temperature`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::ingest::Values;
+use crate::special::log_sum_exp;
+
+fn member_weights(
+    weights: Option<&PyAny>,
+    temperature: Option<f64>,
+    n: usize,
+) -> PyResult<Vec<f64>> {
+    let weights = match weights {
+        Some(weights) => Values::extract(weights)?.slices().concat(),
+        None if temperature.is_some() => {
+            return Err(PyValueError::new_err(
+                "a temperature requires the performances to be given as weights",
+            ))
+        }
+        None => return Ok(vec![1.0; n]),
+    };
+    if weights.len() != n {
+        return Err(PyValueError::new_err(format!(
+            "got {} weights for {} members",
+            weights.len(),
+            n
+        )));
+    }
+    match temperature {
+        Some(t) if t.is_nan() || t <= 0.0 => {
+            Err(PyValueError::new_err("temperature should be positive"))
+        }
+        Some(t) => {
+            let scaled: Vec<f64> = weights.iter().map(|w| w / t).collect();
+            let lse = log_sum_exp(&scaled);
+            Ok(scaled.into_iter().map(|w| (w - lse).exp()).collect())
+        }
+        None if weights.iter().any(|&w| w.is_nan() || w < 0.0) => {
+            Err(PyValueError::new_err("weights should be non-negative"))
+        }
+        None => Ok(weights),
+    }
+}
+
+// Running totals per label, in the order in which the labels are first seen. The labels are
+// looked up through a Python dict, so that any hashable works, and `True` stays a `bool`.
+struct Tally<'py> {
+    index: &'py PyDict,
+    labels: Vec<&'py PyAny>,
+    totals: Vec<f64>,
+}
+
+impl<'py> Tally<'py> {
+    fn new(py: Python<'py>) -> Self {
+        Tally {
+            index: PyDict::new(py),
+            labels: Vec::new(),
+            totals: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, label: &'py PyAny, amount: f64) -> PyResult<()> {
+        match self.index.get_item(label) {
+            Some(i) => self.totals[i.extract::<usize>()?] += amount,
+            None => {
+                self.index.set_item(label, self.labels.len())?;
+                self.labels.push(label);
+                self.totals.push(amount);
+            }
+        }
+        Ok(())
+    }
+
+    // The first of the labels with the largest total.
+    fn best(&self) -> Option<&'py PyAny> {
+        let mut best: Option<usize> = None;
+        for (i, &total) in self.totals.iter().enumerate() {
+            if best.is_none_or(|b| total > self.totals[b]) {
+                best = Some(i);
+            }
+        }
+        best.map(|i| self.labels[i])
+    }
+}
+
+fn members(outputs: &PyAny) -> PyResult<Vec<&PyAny>> {
+    outputs.iter()?.collect()
+}
+
+/// Label with the most votes among the predictions of the members, weighted by `weights`.
+///
+/// A prediction which is a dict of probabilities votes for its most likely label, and `None`
+/// abstains. Ties go to the label seen first, and there is no winner without votes.
+#[pyfunction(weights = "None", temperature = "None")]
+pub fn majority_vote<'py>(
+    py: Python<'py>,
+    predictions: &'py PyAny,
+    weights: Option<&PyAny>,
+    temperature: Option<f64>,
+) -> PyResult<Option<&'py PyAny>> {
+    let predictions = members(predictions)?;
+    let weights = member_weights(weights, temperature, predictions.len())?;
+    let mut tally = Tally::new(py);
+    for (prediction, weight) in predictions.into_iter().zip(weights) {
+        if prediction.is_none() {
+            continue;
+        }
+        let label = match prediction.downcast::<PyDict>() {
+            Ok(proba) => {
+                let mut votes = Tally::new(py);
+                for (label, p) in proba {
+                    votes.add(label, p.extract()?)?;
+                }
+                match votes.best() {
+                    Some(label) => label,
+                    None => continue,
+                }
+            }
+            Err(_) => prediction,
+        };
+        tally.add(label, weight)?;
+    }
+    Ok(tally.best())
+}
+
+/// Weighted average of the probability dicts of the members, normalized to sum to 1.
+///
+/// A label missing from a dict has a probability of 0 there, and the labels are in the order
+/// in which they are first seen.
+#[pyfunction(weights = "None", temperature = "None")]
+pub fn average_proba<'py>(
+    py: Python<'py>,
+    probas: &'py PyAny,
+    weights: Option<&PyAny>,
+    temperature: Option<f64>,
+) -> PyResult<&'py PyDict> {
+    let probas = members(probas)?;
+    let weights = member_weights(weights, temperature, probas.len())?;
+    let mut tally = Tally::new(py);
+    for (proba, weight) in probas.into_iter().zip(weights) {
+        for (label, p) in proba.downcast::<PyDict>()? {
+            tally.add(label, weight * p.extract::<f64>()?)?;
+        }
+    }
+    let total: f64 = tally.totals.iter().sum();
+    let dict = PyDict::new(py);
+    for (label, value) in tally.labels.into_iter().zip(tally.totals) {
+        dict.set_item(label, if total > 0.0 { value / total } else { value })?;
+    }
+    Ok(dict)
+}
+
+/// Weighted average of the regression outputs of the members, 0 if there are none.
+#[pyfunction(weights = "None", temperature = "None")]
+pub fn average(
+    predictions: &PyAny,
+    weights: Option<&PyAny>,
+    temperature: Option<f64>,
+) -> PyResult<f64> {
+    let predictions = Values::extract(predictions)?.slices().concat();
+    let weights = member_weights(weights, temperature, predictions.len())?;
+    let total: f64 = weights.iter().sum();
+    if total == 0.0 {
+        return Ok(0.0);
+    }
+    let sum: f64 = predictions.iter().zip(&weights).map(|(y, w)| y * w).sum();
+    Ok(sum / total)
+}
@@ -0,0 +1,357 @@
+// Progressive validation, where each sample is first used to evaluate the model and then
+// to train it.
+//
+// With a Rust-backed model and metric, the whole loop runs in Rust with the GIL released;
+// otherwise it calls the Python methods, as `evaluate.progressive_val_score` does.
+//
+// When the labels are delayed, each prediction is kept until its label arrives, and the
+// metric and the model are updated then, in order of arrival.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::DerefMut;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::calibration::{RsIsotonicCalibrator, RsPlattCalibrator};
+use crate::ingest::Values;
+use crate::metrics::{RsAccuracy, RsLogLoss};
+
+// The variants hold either the borrows of the Python objects, or plain mutable references
+// to them, which can be sent to the thread running without the GIL.
+enum Model<I, P> {
+    Isotonic(I),
+    Platt(P),
+}
+
+impl<'py> Model<PyRefMut<'py, RsIsotonicCalibrator>, PyRefMut<'py, RsPlattCalibrator>> {
+    fn extract(model: &'py PyAny) -> PyResult<Option<Self>> {
+        if let Ok(cell) = model.downcast::<PyCell<RsIsotonicCalibrator>>() {
+            return Ok(Some(Model::Isotonic(cell.try_borrow_mut()?)));
+        }
+        if let Ok(cell) = model.downcast::<PyCell<RsPlattCalibrator>>() {
+            return Ok(Some(Model::Platt(cell.try_borrow_mut()?)));
+        }
+        Ok(None)
+    }
+}
+
+impl<I, P> Model<I, P>
+where
+    I: DerefMut<Target = RsIsotonicCalibrator>,
+    P: DerefMut<Target = RsPlattCalibrator>,
+{
+    fn as_mut(&mut self) -> Model<&mut RsIsotonicCalibrator, &mut RsPlattCalibrator> {
+        match self {
+            Model::Isotonic(model) => Model::Isotonic(&mut **model),
+            Model::Platt(model) => Model::Platt(&mut **model),
+        }
+    }
+
+    fn predict_proba(&self, p: f64) -> PyResult<f64> {
+        match self {
+            Model::Isotonic(model) => model.transform_one(p),
+            Model::Platt(model) => model.transform_one(p),
+        }
+    }
+
+    fn learn(&mut self, p: f64, y: bool) -> PyResult<()> {
+        match self {
+            Model::Isotonic(model) => model.learn_one(p, y),
+            Model::Platt(model) => model.learn_one(p, y),
+        }
+    }
+}
+
+enum Metric<L, A> {
+    LogLoss(L),
+    Accuracy(A),
+}
+
+impl<'py> Metric<PyRefMut<'py, RsLogLoss>, PyRefMut<'py, RsAccuracy>> {
+    fn extract(metric: &'py PyAny) -> PyResult<Option<Self>> {
+        if let Ok(cell) = metric.downcast::<PyCell<RsLogLoss>>() {
+            return Ok(Some(Metric::LogLoss(cell.try_borrow_mut()?)));
+        }
+        if let Ok(cell) = metric.downcast::<PyCell<RsAccuracy>>() {
+            return Ok(Some(Metric::Accuracy(cell.try_borrow_mut()?)));
+        }
+        Ok(None)
+    }
+}
+
+impl<L, A> Metric<L, A>
+where
+    L: DerefMut<Target = RsLogLoss>,
+    A: DerefMut<Target = RsAccuracy>,
+{
+    fn as_mut(&mut self) -> Metric<&mut RsLogLoss, &mut RsAccuracy> {
+        match self {
+            Metric::LogLoss(metric) => Metric::LogLoss(&mut **metric),
+            Metric::Accuracy(metric) => Metric::Accuracy(&mut **metric),
+        }
+    }
+
+    // Hard predictions threshold the probability of the positive class at 0.5.
+    fn update(&mut self, y: bool, p: f64) {
+        match self {
+            Metric::LogLoss(metric) => metric.metric.update(y, p),
+            Metric::Accuracy(metric) => metric.metric.update((p >= 0.5) == y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Event {
+    Predict(usize),
+    Learn(usize),
+}
+
+// Label waiting in the queue, which pops the earliest arrival first, and the earliest
+// sample among those arriving at the same time.
+struct Pending {
+    arrival: f64,
+    index: usize,
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .arrival
+            .total_cmp(&self.arrival)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+/// Order of the predictions and of the arrivals of the labels.
+///
+/// The labels which are available at the moment of a sample, `arrival <= moment`, are
+/// learnt before it is predicted, so that a label arriving at the moment of its own sample
+/// is learnt right after the prediction. The labels still pending at the end are learnt
+/// last.
+fn schedule(moments: &[f64], arrivals: &[f64]) -> Result<Vec<Event>, String> {
+    if moments.iter().any(|t| t.is_nan()) || moments.windows(2).any(|w| w[0] > w[1]) {
+        return Err("moments should be non-decreasing".to_string());
+    }
+    if moments
+        .iter()
+        .zip(arrivals)
+        .any(|(t, a)| a.is_nan() || a < t)
+    {
+        return Err("labels should not arrive before their sample".to_string());
+    }
+    let mut queue = BinaryHeap::new();
+    let mut events = Vec::with_capacity(2 * moments.len());
+    for (index, (&moment, &arrival)) in moments.iter().zip(arrivals).enumerate() {
+        while queue
+            .peek()
+            .is_some_and(|pending: &Pending| pending.arrival <= moment)
+        {
+            events.push(Event::Learn(queue.pop().unwrap().index));
+        }
+        events.push(Event::Predict(index));
+        queue.push(Pending { arrival, index });
+    }
+    while let Some(pending) = queue.pop() {
+        events.push(Event::Learn(pending.index));
+    }
+    Ok(events)
+}
+
+type NativeModel<'a> = Model<&'a mut RsIsotonicCalibrator, &'a mut RsPlattCalibrator>;
+type NativeMetric<'a> = Metric<&'a mut RsLogLoss, &'a mut RsAccuracy>;
+
+// Calibrators map a score to a probability, so `x` holds one score per sample.
+fn native_loop(
+    mut model: NativeModel,
+    mut metric: NativeMetric,
+    x: &[f64],
+    y: &[f64],
+) -> PyResult<()> {
+    for (&xi, &yi) in x.iter().zip(y) {
+        let yi = yi != 0.0;
+        let p = model.predict_proba(xi)?;
+        metric.update(yi, p);
+        model.learn(xi, yi)?;
+    }
+    Ok(())
+}
+
+fn native_delayed_loop(
+    mut model: NativeModel,
+    mut metric: NativeMetric,
+    x: &[f64],
+    y: &[f64],
+    events: &[Event],
+) -> PyResult<()> {
+    let mut predictions = vec![f64::NAN; x.len()];
+    for &event in events {
+        match event {
+            Event::Predict(i) => predictions[i] = model.predict_proba(x[i])?,
+            Event::Learn(i) => {
+                let yi = y[i] != 0.0;
+                metric.update(yi, predictions[i]);
+                model.learn(x[i], yi)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn python_loop(model: &PyAny, x: &PyAny, y: &PyAny, metric: &PyAny) -> PyResult<()> {
+    let predict = predict_method(metric)?;
+    for (xi, yi) in x.iter()?.zip(y.iter()?) {
+        let (xi, yi) = (xi?, yi?);
+        let y_pred = model.call_method1(predict, (xi,))?;
+        update_metric(metric, yi, y_pred)?;
+        model.call_method1("learn_one", (xi, yi))?;
+    }
+    Ok(())
+}
+
+fn predict_method(metric: &PyAny) -> PyResult<&'static str> {
+    let requires_labels: bool = metric.getattr("requires_labels")?.extract()?;
+    Ok(if requires_labels {
+        "predict_one"
+    } else {
+        "predict_proba_one"
+    })
+}
+
+// Missing predictions, `None` or an empty dict, are not scored.
+fn update_metric(metric: &PyAny, y: &PyAny, y_pred: &PyAny) -> PyResult<()> {
+    let empty = y_pred.downcast::<PyDict>().is_ok_and(|d| d.is_empty());
+    if !y_pred.is_none() && !empty {
+        metric.call_method1("update", (y, y_pred))?;
+    }
+    Ok(())
+}
+
+fn python_delayed_loop(
+    model: &PyAny,
+    x: Vec<&PyAny>,
+    y: Vec<&PyAny>,
+    metric: &PyAny,
+    events: &[Event],
+) -> PyResult<()> {
+    let predict = predict_method(metric)?;
+    let mut predictions = vec![None; x.len()];
+    for &event in events {
+        match event {
+            Event::Predict(i) => predictions[i] = Some(model.call_method1(predict, (x[i],))?),
+            Event::Learn(i) => {
+                if let Some(y_pred) = predictions[i].take() {
+                    update_metric(metric, y[i], y_pred)?;
+                }
+                model.call_method1("learn_one", (x[i], y[i]))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Progressive validation of `model` on the samples of `x` and the targets `y`, which
+/// updates `metric` and returns it.
+///
+/// When the model is a `RsIsotonicCalibrator` or a `RsPlattCalibrator` and the metric a
+/// `RsLogLoss` or a `RsAccuracy`, `x` holds the scores and `y` the labels, in any 1D layout
+/// read by the batch methods, and the loop runs in Rust. Any other model and metric are
+/// driven through their `predict_one` or `predict_proba_one`, `learn_one` and `update`
+/// methods.
+///
+/// Given `arrivals`, the label of each sample only becomes available at that time, and the
+/// model is evaluated and trained on it then. The samples occur at their `moments`, which
+/// default to their positions in the stream, so that `arrivals` can count delays in
+/// samples.
+#[pyfunction(arrivals = "None", moments = "None")]
+pub fn evaluate_progressive<'py>(
+    py: Python<'py>,
+    model: &'py PyAny,
+    x: &'py PyAny,
+    y: &'py PyAny,
+    metric: &'py PyAny,
+    arrivals: Option<&PyAny>,
+    moments: Option<&PyAny>,
+) -> PyResult<&'py PyAny> {
+    if moments.is_some() && arrivals.is_none() {
+        return Err(PyValueError::new_err(
+            "moments are only used along with arrivals",
+        ));
+    }
+    let arrivals = arrivals
+        .map(|arrivals| Ok::<_, PyErr>(Values::extract(arrivals)?.slices().concat()))
+        .transpose()?;
+    // The metric is only borrowed along with a native model, as the Python loop updates it.
+    let native = match Model::extract(model)? {
+        Some(model) => Metric::extract(metric)?.map(|metric| (model, metric)),
+        None => None,
+    };
+    match native {
+        Some((mut model, mut metric)) => {
+            let x = Values::extract(x)?.slices().concat();
+            let y = Values::extract(y)?.slices().concat();
+            check_lengths("y", y.len(), x.len())?;
+            let (model, metric) = (model.as_mut(), metric.as_mut());
+            match arrivals {
+                Some(arrivals) => {
+                    let events = delayed_events(py, moments, &arrivals, x.len())?;
+                    py.allow_threads(|| native_delayed_loop(model, metric, &x, &y, &events))?;
+                }
+                None => py.allow_threads(|| native_loop(model, metric, &x, &y))?,
+            }
+        }
+        None => match arrivals {
+            Some(arrivals) => {
+                let x = x.iter()?.collect::<PyResult<Vec<_>>>()?;
+                let y = y.iter()?.collect::<PyResult<Vec<_>>>()?;
+                check_lengths("y", y.len(), x.len())?;
+                let events = delayed_events(py, moments, &arrivals, x.len())?;
+                python_delayed_loop(model, x, y, metric, &events)?;
+            }
+            None => python_loop(model, x, y, metric)?,
+        },
+    }
+    Ok(metric)
+}
+
+fn check_lengths(name: &str, len: usize, n: usize) -> PyResult<()> {
+    if len != n {
+        return Err(PyValueError::new_err(format!(
+            "x and {} should have the same length, got {} and {}",
+            name, n, len
+        )));
+    }
+    Ok(())
+}
+
+fn delayed_events(
+    py: Python,
+    moments: Option<&PyAny>,
+    arrivals: &[f64],
+    n: usize,
+) -> PyResult<Vec<Event>> {
+    check_lengths("arrivals", arrivals.len(), n)?;
+    let moments = match moments {
+        Some(moments) => Values::extract(moments)?.slices().concat(),
+        None => (0..n).map(|i| i as f64).collect(),
+    };
+    check_lengths("moments", moments.len(), n)?;
+    py.allow_threads(|| schedule(&moments, arrivals))
+        .map_err(PyValueError::new_err)
+}
@@ -0,0 +1,97 @@
+// Updates of matrix factorizations, exposed so that the Python code can maintain a
+// Cholesky or QR factor in `O(n^2)` per observation rather than refactorizing in `O(n^3)`.
+//
+// The matrices are taken in any layout read by `Matrix`, must be square, and are returned
+// as new NumPy arrays. The triangular factors are not checked to be triangular: the
+// entries on the other side of the diagonal are ignored.
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::covariance::to_pyarray2;
+use crate::ingest::{Matrix, Values};
+use crate::linalg;
+
+// Reads a square matrix and its size.
+fn square(a: &PyAny) -> PyResult<(Vec<f64>, usize)> {
+    let a = Matrix::extract(a)?;
+    let n = a.n_cols;
+    if a.as_slice().len() != n * n {
+        return Err(PyValueError::new_err("expected a square matrix"));
+    }
+    Ok((a.as_slice().to_vec(), n))
+}
+
+fn vector(x: &PyAny, n: usize) -> PyResult<Vec<f64>> {
+    let x = Values::extract(x)?.slices().concat();
+    if x.len() != n {
+        return Err(PyValueError::new_err(format!(
+            "expected {} values, got {}",
+            n,
+            x.len()
+        )));
+    }
+    Ok(x)
+}
+
+fn lower(mut l: Vec<f64>, n: usize) -> Vec<f64> {
+    for i in 0..n {
+        l[i * n + i + 1..(i + 1) * n].fill(0.0);
+    }
+    l
+}
+
+fn upper(mut r: Vec<f64>, n: usize) -> Vec<f64> {
+    for i in 0..n {
+        r[i * n..i * n + i].fill(0.0);
+    }
+    r
+}
+
+/// Cholesky factor of `L L^T + x x^T`, or of `L L^T - x x^T` with `downdate`, given the
+/// lower triangular factor `L`.
+#[pyfunction(downdate = "false")]
+pub fn cholesky_update(py: Python, l: &PyAny, x: &PyAny, downdate: bool) -> PyResult<PyObject> {
+    let (l, n) = square(l)?;
+    let x = vector(x, n)?;
+    let mut l = lower(l, n);
+    if (0..n).any(|i| l[i * n + i] <= 0.0) {
+        return Err(PyValueError::new_err(
+            "the diagonal of L should be positive",
+        ));
+    }
+    let updated = py.allow_threads(|| linalg::cholesky_rank1_update(&mut l, n, &x, downdate));
+    updated.map_err(PyValueError::new_err)?;
+    Ok(to_pyarray2(py, l, n)?.into_py(py))
+}
+
+/// Upper triangular factor `R` of the QR factorization of a matrix extended with the row
+/// `row`, given the factor of the matrix. Start from a matrix of zeros.
+///
+/// For online least squares, append the target to each row of features: the top-left block
+/// of `R` and the last column then give the coefficients through `solve_triangular`.
+#[pyfunction]
+pub fn qr_add_row(py: Python, r: &PyAny, row: &PyAny) -> PyResult<PyObject> {
+    let (r, n) = square(r)?;
+    let row = vector(row, n)?;
+    let mut r = upper(r, n);
+    py.allow_threads(|| linalg::qr_add_row(&mut r, n, &row));
+    Ok(to_pyarray2(py, r, n)?.into_py(py))
+}
+
+/// Solution of `A x = b` for a triangular `A`, upper unless `lower` is set.
+#[pyfunction(lower = "false")]
+pub fn solve_triangular(py: Python, a: &PyAny, b: &PyAny, lower: bool) -> PyResult<PyObject> {
+    let (a, n) = square(a)?;
+    let b = vector(b, n)?;
+    if (0..n).any(|i| a[i * n + i] == 0.0) {
+        return Err(PyValueError::new_err("the matrix is singular"));
+    }
+    let x = if lower {
+        linalg::forward_substitution(&self::lower(a, n), n, &b)
+    } else {
+        linalg::back_substitution(&upper(a, n), n, &b)
+    };
+    Ok(PyArray1::from_vec(py, x).into_py(py))
+}
@@ -0,0 +1,50 @@
+// Sharing of the stats between threads.
+//
+// The methods of the pyclasses hold the GIL, which runs them one at a time, except for the
+// batch updates, which release it while they hold the state mutably. pyo3 checks the borrows
+// at runtime, so a call from another thread during such an update fails with a
+// `RuntimeError` rather than racing. Serving threads should hence read from a
+// `RsFrozenStat` snapshot, which can never be updated, rather than from a stat that is being
+// updated. This relies on the GIL: pyo3 0.16 doesn't support free-threaded Python.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+use crate::univariate::AnyStat;
+
+/// Read-only snapshot of a univariate stat.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsFrozenStat {
+    stat: AnyStat,
+}
+
+impl HeapSize for RsFrozenStat {
+    fn heap_size(&self) -> usize {
+        self.stat.heap_size()
+    }
+}
+
+impl RsFrozenStat {
+    pub fn from_stat(stat: AnyStat) -> Self {
+        RsFrozenStat { stat }
+    }
+}
+
+#[pymethods]
+impl RsFrozenStat {
+    #[new]
+    pub fn new(stat: &PyAny) -> PyResult<Self> {
+        Ok(RsFrozenStat {
+            stat: AnyStat::from_py(stat)?,
+        })
+    }
+    pub fn get(&self) -> f64 {
+        self.stat.get()
+    }
+    /// Mutable copy of the stat, which can be updated again.
+    pub fn thaw(&self, py: Python) -> PyResult<PyObject> {
+        self.stat.to_py(py)
+    }
+}
@@ -0,0 +1,685 @@
+use std::collections::{HashMap, VecDeque};
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+use watermill::{stats::Univariate, variance::Variance};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::special::{beta_inc, chi2_sf, normal_cdf, student_t_two_sided};
+
+/// Welch's unequal variances t-test between two streams.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WelchTTest {
+    a: Variance<f64>,
+    b: Variance<f64>,
+}
+
+impl WelchTTest {
+    pub fn new() -> Self {
+        WelchTTest {
+            a: Variance::new(1),
+            b: Variance::new(1),
+        }
+    }
+
+    pub fn update_a(&mut self, x: f64) {
+        self.a.update(x);
+    }
+
+    pub fn update_b(&mut self, x: f64) {
+        self.b.update(x);
+    }
+
+    // Returns the squared standard errors of both means, if they are defined.
+    fn standard_errors(&self) -> Option<(f64, f64)> {
+        let n_a = self.a.mean.n.get();
+        let n_b = self.b.mean.n.get();
+        if n_a < 2.0 || n_b < 2.0 {
+            return None;
+        }
+        let se_a = self.a.get() / n_a;
+        let se_b = self.b.get() / n_b;
+        if se_a + se_b == 0.0 {
+            return None;
+        }
+        Some((se_a, se_b))
+    }
+
+    pub fn statistic(&self) -> f64 {
+        match self.standard_errors() {
+            Some((se_a, se_b)) => (self.a.mean.get() - self.b.mean.get()) / (se_a + se_b).sqrt(),
+            None => f64::NAN,
+        }
+    }
+
+    /// Welch-Satterthwaite degrees of freedom.
+    pub fn df(&self) -> f64 {
+        match self.standard_errors() {
+            Some((se_a, se_b)) => {
+                let n_a = self.a.mean.n.get();
+                let n_b = self.b.mean.n.get();
+                (se_a + se_b).powi(2) / (se_a.powi(2) / (n_a - 1.0) + se_b.powi(2) / (n_b - 1.0))
+            }
+            None => f64::NAN,
+        }
+    }
+
+    /// Two-sided p-value.
+    pub fn p_value(&self) -> f64 {
+        let t = self.statistic();
+        if t.is_nan() {
+            return f64::NAN;
+        }
+        student_t_two_sided(t, self.df())
+    }
+}
+
+impl Default for WelchTTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsWelchTTest {
+    test: WelchTTest,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsWelchTTest {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsWelchTTest {
+    #[new]
+    pub fn new() -> RsWelchTTest {
+        RsWelchTTest {
+            test: WelchTTest::new(),
+        }
+    }
+    pub fn update_a(&mut self, x: f64) {
+        self.test.update_a(x);
+    }
+    pub fn update_b(&mut self, x: f64) {
+        self.test.update_b(x);
+    }
+    #[getter]
+    pub fn statistic(&self) -> f64 {
+        self.test.statistic()
+    }
+    #[getter]
+    pub fn df(&self) -> f64 {
+        self.test.df()
+    }
+    #[getter]
+    pub fn p_value(&self) -> f64 {
+        self.test.p_value()
+    }
+}
+
+#[cfg(feature = "python")]
+impl Default for RsWelchTTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pearson's chi-square test of independence over an incrementally built contingency table.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChiSquareTest {
+    #[serde(with = "crate::key::pairs")]
+    table: HashMap<(Key, Key), f64>,
+    #[serde(with = "crate::key::pairs")]
+    rows: HashMap<Key, f64>,
+    #[serde(with = "crate::key::pairs")]
+    cols: HashMap<Key, f64>,
+    n: f64,
+}
+
+impl HeapSize for ChiSquareTest {
+    fn heap_size(&self) -> usize {
+        self.table.heap_size() + self.rows.heap_size() + self.cols.heap_size()
+    }
+}
+
+impl ChiSquareTest {
+    pub fn new() -> Self {
+        ChiSquareTest {
+            table: HashMap::new(),
+            rows: HashMap::new(),
+            cols: HashMap::new(),
+            n: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, x: Key, y: Key, w: f64) {
+        *self.rows.entry(x.clone()).or_insert(0.0) += w;
+        *self.cols.entry(y.clone()).or_insert(0.0) += w;
+        *self.table.entry((x, y)).or_insert(0.0) += w;
+        self.n += w;
+    }
+
+    pub fn statistic(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        let mut chi2 = 0.0;
+        for (x, row_total) in &self.rows {
+            for (y, col_total) in &self.cols {
+                let expected = row_total * col_total / self.n;
+                if expected == 0.0 {
+                    continue;
+                }
+                let observed = self
+                    .table
+                    .get(&(x.clone(), y.clone()))
+                    .cloned()
+                    .unwrap_or(0.0);
+                chi2 += (observed - expected).powi(2) / expected;
+            }
+        }
+        chi2
+    }
+
+    pub fn df(&self) -> usize {
+        self.rows.len().saturating_sub(1) * self.cols.len().saturating_sub(1)
+    }
+
+    pub fn p_value(&self) -> f64 {
+        let df = self.df();
+        if df == 0 {
+            return 1.0;
+        }
+        chi2_sf(self.statistic(), df as f64)
+    }
+}
+
+impl Default for ChiSquareTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsChiSquareTest {
+    test: ChiSquareTest,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsChiSquareTest {
+    fn heap_size(&self) -> usize {
+        self.test.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsChiSquareTest {
+    #[new]
+    pub fn new() -> RsChiSquareTest {
+        RsChiSquareTest {
+            test: ChiSquareTest::new(),
+        }
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: Key, y: Key, w: f64) {
+        self.test.update(x, y, w);
+    }
+    #[getter]
+    pub fn statistic(&self) -> f64 {
+        self.test.statistic()
+    }
+    #[getter]
+    pub fn df(&self) -> usize {
+        self.test.df()
+    }
+    #[getter]
+    pub fn p_value(&self) -> f64 {
+        self.test.p_value()
+    }
+}
+
+#[cfg(feature = "python")]
+impl Default for RsChiSquareTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FIFO window that also keeps its values sorted, to answer rank queries by bisection.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SortedWindow {
+    fifo: VecDeque<f64>,
+    sorted: Vec<f64>,
+}
+
+impl HeapSize for SortedWindow {
+    fn heap_size(&self) -> usize {
+        self.fifo.heap_size() + self.sorted.heap_size()
+    }
+}
+
+impl SortedWindow {
+    pub fn new(capacity: usize) -> Self {
+        SortedWindow {
+            fifo: VecDeque::with_capacity(capacity + 1),
+            sorted: Vec::with_capacity(capacity + 1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fifo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fifo.is_empty()
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.fifo.push_back(x);
+        let pos = self.sorted.partition_point(|&v| v < x);
+        self.sorted.insert(pos, x);
+    }
+
+    /// Takes a slot for a missing value, which is stored as NaN and left out of the
+    /// sorted values.
+    pub fn push_missing(&mut self) {
+        self.fifo.push_back(f64::NAN);
+    }
+
+    pub fn pop(&mut self) -> Option<f64> {
+        let x = self.fifo.pop_front()?;
+        if !x.is_nan() {
+            let pos = self.sorted.partition_point(|&v| v < x);
+            self.sorted.remove(pos);
+        }
+        Some(x)
+    }
+
+    pub fn sorted(&self) -> &[f64] {
+        &self.sorted
+    }
+
+    /// The values, from the oldest to the most recent.
+    pub fn values(&mut self) -> &[f64] {
+        self.fifo.make_contiguous()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.fifo.iter()
+    }
+
+    /// Number of values strictly lower than `x`, and number of values equal to `x`.
+    pub fn rank(&self, x: f64) -> (usize, usize) {
+        let lower = self.sorted.partition_point(|&v| v < x);
+        let lower_or_equal = self.sorted.partition_point(|&v| v <= x);
+        (lower, lower_or_equal - lower)
+    }
+}
+
+/// Mann-Whitney U test between a reference window and the most recent window of a stream.
+///
+/// New values enter the recent window, values leaving it enter the reference window, and
+/// the U statistic of the reference window against the recent one is kept up to date with
+/// a rank query on every insertion and eviction.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MannWhitneyU {
+    reference_size: usize,
+    recent_size: usize,
+    reference: SortedWindow,
+    recent: SortedWindow,
+    u: f64,
+}
+
+impl HeapSize for MannWhitneyU {
+    fn heap_size(&self) -> usize {
+        self.reference.heap_size() + self.recent.heap_size()
+    }
+}
+
+impl MannWhitneyU {
+    pub fn new(reference_size: usize, recent_size: usize) -> Self {
+        MannWhitneyU {
+            reference_size,
+            recent_size,
+            reference: SortedWindow::new(reference_size),
+            recent: SortedWindow::new(recent_size),
+            u: 0.0,
+        }
+    }
+
+    // Contribution of a reference value against the recent window.
+    fn reference_score(&self, x: f64) -> f64 {
+        let (lower, equal) = self.recent.rank(x);
+        lower as f64 + 0.5 * equal as f64
+    }
+
+    // Contribution of a recent value against the reference window.
+    fn recent_score(&self, x: f64) -> f64 {
+        let (lower, equal) = self.reference.rank(x);
+        (self.reference.len() - lower - equal) as f64 + 0.5 * equal as f64
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.u += self.recent_score(x);
+        self.recent.push(x);
+        if self.recent.len() > self.recent_size {
+            let moved = self.recent.pop().unwrap();
+            self.u -= self.recent_score(moved);
+            self.u += self.reference_score(moved);
+            self.reference.push(moved);
+        }
+        if self.reference.len() > self.reference_size {
+            let dropped = self.reference.pop().unwrap();
+            self.u -= self.reference_score(dropped);
+        }
+    }
+
+    pub fn statistic(&self) -> f64 {
+        self.u
+    }
+
+    pub fn z_score(&self) -> f64 {
+        let n_a = self.reference.len() as f64;
+        let n_b = self.recent.len() as f64;
+        if n_a == 0.0 || n_b == 0.0 {
+            return 0.0;
+        }
+        let mu = n_a * n_b / 2.0;
+        let sigma = (n_a * n_b * (n_a + n_b + 1.0) / 12.0).sqrt();
+        (self.u - mu) / sigma
+    }
+
+    /// Two-sided p-value, using the normal approximation of the U distribution.
+    pub fn p_value(&self) -> f64 {
+        2.0 * (1.0 - normal_cdf(self.z_score().abs()))
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsMannWhitneyU {
+    test: MannWhitneyU,
+    reference_size: usize,
+    recent_size: usize,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsMannWhitneyU {
+    fn heap_size(&self) -> usize {
+        self.test.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsMannWhitneyU {
+    #[new]
+    pub fn new(reference_size: usize, recent_size: usize) -> PyResult<Self> {
+        if reference_size == 0 || recent_size == 0 {
+            return Err(PyValueError::new_err("window sizes should be at least 1"));
+        }
+        Ok(RsMannWhitneyU {
+            test: MannWhitneyU::new(reference_size, recent_size),
+            reference_size,
+            recent_size,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.test.update(x);
+        Ok(())
+    }
+    #[getter]
+    pub fn statistic(&self) -> f64 {
+        self.test.statistic()
+    }
+    #[getter]
+    pub fn z_score(&self) -> f64 {
+        self.test.z_score()
+    }
+    #[getter]
+    pub fn p_value(&self) -> f64 {
+        self.test.p_value()
+    }
+}
+
+/// Paired comparison of the per-sample losses of two models, `a` and `b`, through their
+/// differences `a - b`: a paired t-test, a sign test, and a Wilcoxon signed-rank test.
+///
+/// The ranks of the absolute differences are kept up to date on each insertion: the values
+/// above the new one move up a rank, and those tied with it half a rank. Zero differences
+/// count as ties in the sign test, and are left out of the Wilcoxon test.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PairedComparison {
+    differences: Variance<f64>,
+    wins_a: u64,
+    wins_b: u64,
+    ties: u64,
+    // Sorted absolute values of the nonzero differences, and of the positive ones.
+    magnitudes: Vec<f64>,
+    positive_magnitudes: Vec<f64>,
+    // Sum of the ranks of the positive differences.
+    w_plus: f64,
+    // Sum of `t^3 - t` over the groups of `t` tied magnitudes.
+    tie_correction: f64,
+}
+
+impl HeapSize for PairedComparison {
+    fn heap_size(&self) -> usize {
+        self.magnitudes.heap_size() + self.positive_magnitudes.heap_size()
+    }
+}
+
+impl PairedComparison {
+    pub fn new() -> Self {
+        PairedComparison {
+            differences: Variance::new(1),
+            wins_a: 0,
+            wins_b: 0,
+            ties: 0,
+            magnitudes: Vec::new(),
+            positive_magnitudes: Vec::new(),
+            w_plus: 0.0,
+            tie_correction: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, loss_a: f64, loss_b: f64) {
+        let d = loss_a - loss_b;
+        self.differences.update(d);
+        if d == 0.0 {
+            self.ties += 1;
+            return;
+        }
+        if d < 0.0 {
+            self.wins_a += 1;
+        } else {
+            self.wins_b += 1;
+        }
+        let x = d.abs();
+        let lower = self.magnitudes.partition_point(|&v| v < x);
+        let equal = self.magnitudes.partition_point(|&v| v <= x) - lower;
+        let positive_lower = self.positive_magnitudes.partition_point(|&v| v < x);
+        let positive_lower_or_equal = self.positive_magnitudes.partition_point(|&v| v <= x);
+        let positive_equal = positive_lower_or_equal - positive_lower;
+        let positive_above = self.positive_magnitudes.len() - positive_lower_or_equal;
+        self.w_plus += positive_above as f64 + 0.5 * positive_equal as f64;
+        if d > 0.0 {
+            self.w_plus += lower as f64 + 1.0 + 0.5 * equal as f64;
+            self.positive_magnitudes.insert(positive_lower, x);
+        }
+        self.magnitudes.insert(lower, x);
+        self.tie_correction += 3.0 * (equal * (equal + 1)) as f64;
+    }
+
+    pub fn n(&self) -> u64 {
+        self.differences.mean.n.get() as u64
+    }
+
+    pub fn mean_difference(&self) -> f64 {
+        self.differences.mean.get()
+    }
+
+    pub fn t_statistic(&self) -> f64 {
+        let n = self.differences.mean.n.get();
+        if n < 2.0 {
+            return f64::NAN;
+        }
+        let se = (self.differences.get() / n).sqrt();
+        if se == 0.0 {
+            return f64::NAN;
+        }
+        self.differences.mean.get() / se
+    }
+
+    pub fn t_p_value(&self) -> f64 {
+        let t = self.t_statistic();
+        if t.is_nan() {
+            return f64::NAN;
+        }
+        student_t_two_sided(t, self.differences.mean.n.get() - 1.0)
+    }
+
+    /// Two-sided exact binomial p-value of the wins of each model, ties left out.
+    pub fn sign_p_value(&self) -> f64 {
+        let n = (self.wins_a + self.wins_b) as f64;
+        let k = self.wins_a.min(self.wins_b) as f64;
+        if k == n {
+            return 1.0;
+        }
+        // P(X <= k) for X ~ Binomial(n, 1/2).
+        (2.0 * beta_inc(n - k, k + 1.0, 0.5)).min(1.0)
+    }
+
+    pub fn wilcoxon_statistic(&self) -> f64 {
+        self.w_plus
+    }
+
+    pub fn wilcoxon_z_score(&self) -> f64 {
+        let n = self.magnitudes.len() as f64;
+        let mu = n * (n + 1.0) / 4.0;
+        let var = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - self.tie_correction / 48.0;
+        if var <= 0.0 {
+            return 0.0;
+        }
+        (self.w_plus - mu) / var.sqrt()
+    }
+
+    /// Two-sided p-value, using the normal approximation of the signed-rank distribution.
+    pub fn wilcoxon_p_value(&self) -> f64 {
+        2.0 * (1.0 - normal_cdf(self.wilcoxon_z_score().abs()))
+    }
+}
+
+impl Default for PairedComparison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPairedComparison {
+    test: PairedComparison,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsPairedComparison {
+    fn heap_size(&self) -> usize {
+        self.test.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsPairedComparison {
+    #[new]
+    pub fn new() -> RsPairedComparison {
+        RsPairedComparison {
+            test: PairedComparison::new(),
+        }
+    }
+    pub fn update(&mut self, loss_a: f64, loss_b: f64) -> PyResult<()> {
+        if loss_a.is_nan() || loss_b.is_nan() {
+            return Err(PyValueError::new_err("losses should not be NaN"));
+        }
+        self.test.update(loss_a, loss_b);
+        Ok(())
+    }
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.test.n()
+    }
+    #[getter]
+    pub fn mean_difference(&self) -> f64 {
+        self.test.mean_difference()
+    }
+    /// Samples where `a` had the lower loss, where `b` had, and ties.
+    #[getter]
+    pub fn wins(&self) -> (u64, u64, u64) {
+        (self.test.wins_a, self.test.wins_b, self.test.ties)
+    }
+    #[getter]
+    pub fn t_statistic(&self) -> f64 {
+        self.test.t_statistic()
+    }
+    #[getter]
+    pub fn t_p_value(&self) -> f64 {
+        self.test.t_p_value()
+    }
+    #[getter]
+    pub fn sign_p_value(&self) -> f64 {
+        self.test.sign_p_value()
+    }
+    #[getter]
+    pub fn wilcoxon_statistic(&self) -> f64 {
+        self.test.wilcoxon_statistic()
+    }
+    #[getter]
+    pub fn wilcoxon_z_score(&self) -> f64 {
+        self.test.wilcoxon_z_score()
+    }
+    #[getter]
+    pub fn wilcoxon_p_value(&self) -> f64 {
+        self.test.wilcoxon_p_value()
+    }
+    /// All the statistics and p-values, in a dict.
+    pub fn summary(&self, py: Python) -> PyResult<PyObject> {
+        let summary = PyDict::new(py);
+        summary.set_item("n", self.n())?;
+        summary.set_item("mean_difference", self.mean_difference())?;
+        summary.set_item("wins", self.wins())?;
+        summary.set_item("t_statistic", self.t_statistic())?;
+        summary.set_item("t_p_value", self.t_p_value())?;
+        summary.set_item("sign_p_value", self.sign_p_value())?;
+        summary.set_item("wilcoxon_statistic", self.wilcoxon_statistic())?;
+        summary.set_item("wilcoxon_p_value", self.wilcoxon_p_value())?;
+        Ok(summary.into())
+    }
+}
+
+#[cfg(feature = "python")]
+impl Default for RsPairedComparison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
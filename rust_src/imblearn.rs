@@ -0,0 +1,395 @@
+// Resampling of imbalanced streams around native classifiers, like `river.imblearn`.
+//
+// The classes are compared through the ratio of their desired share to their count so far.
+// The pivot is the class furthest from its desired share, in the direction which the
+// sampler can't correct, and the other classes are sampled relative to it. The hard sampler
+// instead replays the examples on which the classifier did the worst.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::mem::size_of;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::classifier::Classifier;
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+
+// Probabilities are clipped away from 0 before taking their log.
+const EPSILON: f64 = 1e-15;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Strategy {
+    Under,
+    Over,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Sampler {
+    classifier: Classifier,
+    strategy: Strategy,
+    labels: Vec<Key>,
+    desired: Vec<f64>,
+    counts: Vec<f64>,
+    pivot: Option<usize>,
+    rng: Rng,
+}
+
+impl HeapSize for Sampler {
+    fn heap_size(&self) -> usize {
+        self.classifier.heap_size()
+            + self.labels.heap_size()
+            + self.desired.heap_size()
+            + self.counts.heap_size()
+    }
+}
+
+impl Sampler {
+    fn new(
+        classifier: &PyAny,
+        desired_dist: &PyDict,
+        strategy: Strategy,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let mut labels = Vec::with_capacity(desired_dist.len());
+        let mut desired = Vec::with_capacity(desired_dist.len());
+        for (label, share) in desired_dist {
+            let share: f64 = share.extract()?;
+            if share.is_nan() || share < 0.0 {
+                return Err(PyValueError::new_err(
+                    "the shares of desired_dist should be non-negative",
+                ));
+            }
+            labels.push(label.extract()?);
+            desired.push(share);
+        }
+        if labels.is_empty() {
+            return Err(PyValueError::new_err("desired_dist should not be empty"));
+        }
+        let n = labels.len();
+        Ok(Sampler {
+            classifier: Classifier::from_py(classifier)?,
+            strategy,
+            labels,
+            desired,
+            counts: vec![0.0; n],
+            pivot: None,
+            rng: Rng::new(seed),
+        })
+    }
+
+    fn ratio(&self, i: usize) -> f64 {
+        self.desired[i] / self.counts[i]
+    }
+
+    // The seen class with the largest ratio when undersampling, the smallest positive one
+    // when oversampling, the first one in the order of `desired_dist` on ties.
+    fn find_pivot(&self) -> Option<usize> {
+        let mut pivot: Option<usize> = None;
+        let candidates = (0..self.labels.len()).filter(|&i| {
+            self.counts[i] > 0.0 && (self.strategy == Strategy::Under || self.desired[i] > 0.0)
+        });
+        for i in candidates {
+            let better = match pivot {
+                None => true,
+                Some(p) if self.strategy == Strategy::Under => self.ratio(i) > self.ratio(p),
+                Some(p) => self.ratio(i) < self.ratio(p),
+            };
+            if better {
+                pivot = Some(i);
+            }
+        }
+        pivot
+    }
+
+    fn learn_one(&mut self, x: &HashMap<String, f64>, y: Key) -> PyResult<()> {
+        let i = self
+            .labels
+            .iter()
+            .position(|label| *label == y)
+            .ok_or_else(|| PyValueError::new_err("the label is not in desired_dist"))?;
+        self.counts[i] += 1.0;
+        if self.pivot != Some(i) {
+            self.pivot = self.find_pivot();
+        }
+        let pivot = self.pivot.unwrap_or(i);
+        if pivot == i {
+            return self.classifier.learn_one(x, &y);
+        }
+        // Likelihood ratio of the pivot.
+        let m = self.ratio(pivot);
+        match self.strategy {
+            Strategy::Under => {
+                if self.rng.next_f64() < self.ratio(i) / m {
+                    self.classifier.learn_one(x, &y)?;
+                }
+            }
+            Strategy::Over => {
+                for _ in 0..self.rng.poisson(self.ratio(i) / m) {
+                    self.classifier.learn_one(x, &y)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! sampler {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl HeapSize for $name {
+                fn heap_size(&self) -> usize {
+                    self.sampler.heap_size()
+                }
+            }
+
+            #[pymethods]
+            impl $name {
+                /// `y` is one of the labels of `desired_dist`.
+                pub fn learn_one(&mut self, x: HashMap<String, f64>, y: Key) -> PyResult<()> {
+                    self.sampler.learn_one(&x, y)
+                }
+                pub fn predict_one(&self, py: Python, x: HashMap<String, f64>) -> Option<PyObject> {
+                    let classifier = &self.sampler.classifier;
+                    classifier
+                        .predict_one(&x)
+                        .map(|label| classifier.label_to_py(py, label))
+                }
+                pub fn predict_proba_one(
+                    &self,
+                    py: Python,
+                    x: HashMap<String, f64>,
+                ) -> PyResult<PyObject> {
+                    let classifier = &self.sampler.classifier;
+                    classifier.proba_to_py(py, classifier.predict_proba_one(&x))
+                }
+                /// Copy of the wrapped classifier.
+                #[getter]
+                pub fn classifier(&self, py: Python) -> PyResult<PyObject> {
+                    self.sampler.classifier.to_py(py)
+                }
+            }
+        )*
+    };
+}
+
+sampler!(RsRandomUnderSampler, RsRandomOverSampler);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRandomUnderSampler {
+    sampler: Sampler,
+}
+
+#[pymethods]
+impl RsRandomUnderSampler {
+    /// Trains a copy of `classifier`, a `RsLogisticRegression` or a
+    /// `RsOutputCodeClassifier`, on a subsample of the stream whose class distribution
+    /// tends to `desired_dist`. The samples of the pivot, the class the most
+    /// under-represented relative to `desired_dist`, are all learnt, and those of another
+    /// class with the probability which brings it down to the same ratio.
+    #[new]
+    #[args(seed = "None")]
+    pub fn new(classifier: &PyAny, desired_dist: &PyDict, seed: Option<u64>) -> PyResult<Self> {
+        Ok(RsRandomUnderSampler {
+            sampler: Sampler::new(classifier, desired_dist, Strategy::Under, seed)?,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRandomOverSampler {
+    sampler: Sampler,
+}
+
+#[pymethods]
+impl RsRandomOverSampler {
+    /// Trains a copy of `classifier`, a `RsLogisticRegression` or a
+    /// `RsOutputCodeClassifier`, on a resampling of the stream whose class distribution
+    /// tends to `desired_dist`. The samples of the pivot, the class the most
+    /// over-represented relative to `desired_dist`, are learnt once, and those of another
+    /// class a Poisson number of times, whose mean brings it up to the same ratio.
+    #[new]
+    #[args(seed = "None")]
+    pub fn new(classifier: &PyAny, desired_dist: &PyDict, seed: Option<u64>) -> PyResult<Self> {
+        Ok(RsRandomOverSampler {
+            sampler: Sampler::new(classifier, desired_dist, Strategy::Over, seed)?,
+        })
+    }
+}
+
+// Example of the buffer, which pops the easiest one first, and the oldest one among those
+// as hard.
+#[derive(Clone, Serialize, Deserialize)]
+struct HardExample {
+    loss: f64,
+    index: u64,
+    x: HashMap<String, f64>,
+    y: Key,
+}
+
+impl Ord for HardExample {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .loss
+            .total_cmp(&self.loss)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for HardExample {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HardExample {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HardExample {}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct HardSampler {
+    classifier: Classifier,
+    size: usize,
+    p: f64,
+    buffer: BinaryHeap<HardExample>,
+    n: u64,
+    rng: Rng,
+}
+
+impl HeapSize for HardSampler {
+    fn heap_size(&self) -> usize {
+        self.classifier.heap_size()
+            + self.buffer.capacity() * size_of::<HardExample>()
+            + self
+                .buffer
+                .iter()
+                .map(|example| example.x.heap_size() + example.y.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl HardSampler {
+    fn learn_one(&mut self, x: &HashMap<String, f64>, y: Key) -> PyResult<()> {
+        let p = self
+            .classifier
+            .predict_proba_one(x)
+            .into_iter()
+            .find(|(label, _)| *label == y)
+            .map_or(0.0, |(_, p)| p);
+        let example = HardExample {
+            loss: -p.max(EPSILON).ln(),
+            index: self.n,
+            x: x.clone(),
+            y: y.clone(),
+        };
+        self.n += 1;
+        if self.buffer.len() < self.size {
+            self.buffer.push(example);
+        } else if self.buffer.peek().is_some_and(|easiest| example < *easiest) {
+            self.buffer.pop();
+            self.buffer.push(example);
+        }
+        if self.rng.next_f64() < self.p {
+            let hard = self.buffer.as_slice();
+            let example = &hard[self.rng.below(hard.len() as u64) as usize];
+            self.classifier.learn_one(&example.x, &example.y)
+        } else {
+            self.classifier.learn_one(x, &y)
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsHardSamplingClassifier {
+    sampler: HardSampler,
+}
+
+impl HeapSize for RsHardSamplingClassifier {
+    fn heap_size(&self) -> usize {
+        self.sampler.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsHardSamplingClassifier {
+    /// Trains a copy of `classifier`, a `RsLogisticRegression` or a
+    /// `RsOutputCodeClassifier`, which replays the hard examples of the stream.
+    ///
+    /// A buffer holds the `size` examples of largest log loss, as measured when they came.
+    /// Each sample enters the buffer if it is harder than the easiest one there, which it
+    /// then replaces. With probability `p`, the classifier then learns an example drawn from
+    /// the buffer, and the sample otherwise.
+    #[new]
+    #[args(seed = "None")]
+    pub fn new(classifier: &PyAny, size: usize, p: f64, seed: Option<u64>) -> PyResult<Self> {
+        if size == 0 {
+            return Err(PyValueError::new_err("size should be at least 1"));
+        }
+        if !(0.0..=1.0).contains(&p) {
+            return Err(PyValueError::new_err("p should be between 0 and 1"));
+        }
+        Ok(RsHardSamplingClassifier {
+            sampler: HardSampler {
+                classifier: Classifier::from_py(classifier)?,
+                size,
+                p,
+                buffer: BinaryHeap::with_capacity(size),
+                n: 0,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: Key) -> PyResult<()> {
+        self.sampler.learn_one(&x, y)
+    }
+
+    pub fn predict_one(&self, py: Python, x: HashMap<String, f64>) -> Option<PyObject> {
+        let classifier = &self.sampler.classifier;
+        classifier
+            .predict_one(&x)
+            .map(|label| classifier.label_to_py(py, label))
+    }
+
+    pub fn predict_proba_one(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let classifier = &self.sampler.classifier;
+        classifier.proba_to_py(py, classifier.predict_proba_one(&x))
+    }
+
+    /// Copy of the wrapped classifier.
+    #[getter]
+    pub fn classifier(&self, py: Python) -> PyResult<PyObject> {
+        self.sampler.classifier.to_py(py)
+    }
+
+    /// Examples of the buffer, as `(loss, x, y)` triples, from the hardest to the easiest.
+    #[getter]
+    pub fn buffer(&self, py: Python) -> Vec<(f64, HashMap<String, f64>, PyObject)> {
+        let classifier = &self.sampler.classifier;
+        // The heap pops the easiest first, so its sorted vector starts with the hardest.
+        self.sampler
+            .buffer
+            .clone()
+            .into_sorted_vec()
+            .into_iter()
+            .map(|example| {
+                (
+                    example.loss,
+                    example.x,
+                    classifier.label_to_py(py, example.y),
+                )
+            })
+            .collect()
+    }
+}
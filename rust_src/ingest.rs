@@ -0,0 +1,209 @@
+// Conversion of the batch arguments into contiguous `f64` slices.
+//
+// Arrow arrays (and thus Polars series) are read in place through the address of their
+// data buffer, NumPy arrays and other buffer-protocol objects through the buffer protocol,
+// pandas objects through their NumPy array, and anything else is copied from a Python
+// sequence.
+
+use std::mem::align_of;
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+enum Chunk {
+    // The owner keeps the memory behind the pointer alive.
+    Arrow {
+        _owner: PyObject,
+        ptr: *const f64,
+        len: usize,
+    },
+    Buffer(PyBuffer<f64>),
+    Owned(Vec<f64>),
+}
+
+impl Chunk {
+    fn as_slice(&self) -> &[f64] {
+        match self {
+            Chunk::Arrow { ptr, len, .. } => unsafe { std::slice::from_raw_parts(*ptr, *len) },
+            Chunk::Buffer(buffer) => unsafe {
+                std::slice::from_raw_parts(buffer.buf_ptr() as *const f64, buffer.item_count())
+            },
+            Chunk::Owned(values) => values,
+        }
+    }
+}
+
+// Null entries are read as NaN.
+fn arrow_chunk(array: &PyAny) -> PyResult<Chunk> {
+    let array = if array.getattr("type")?.str()?.to_str()? == "double" {
+        array
+    } else {
+        array.call_method1("cast", ("double",))?
+    };
+    let len = array.len()?;
+    let offset: usize = array.getattr("offset")?.extract()?;
+    let null_count: usize = array.getattr("null_count")?.extract()?;
+    let buffers: Vec<Option<&PyAny>> = array.call_method0("buffers")?.extract()?;
+    let data = match buffers.get(1) {
+        Some(Some(data)) if len > 0 => *data,
+        _ => return Ok(Chunk::Owned(Vec::new())),
+    };
+    let address: usize = data.getattr("address")?.extract()?;
+    let ptr = (address as *const f64).wrapping_add(offset);
+    if null_count == 0 && address.is_multiple_of(align_of::<f64>()) {
+        return Ok(Chunk::Arrow {
+            _owner: data.into(),
+            ptr,
+            len,
+        });
+    }
+    let validity = match buffers[0] {
+        Some(validity) if null_count > 0 => {
+            Some(validity.getattr("address")?.extract::<usize>()? as *const u8)
+        }
+        _ => None,
+    };
+    let values = (0..len)
+        .map(|i| unsafe {
+            let valid = validity.is_none_or(|bits| {
+                let bit = offset + i;
+                *bits.add(bit / 8) >> (bit % 8) & 1 == 1
+            });
+            if valid {
+                ptr.add(i).read_unaligned()
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
+    Ok(Chunk::Owned(values))
+}
+
+// Reads a buffer-protocol object, converting other numeric formats than `f64` along the
+// way. Returns `None` when the object doesn't expose a numeric buffer.
+fn buffer_chunk(obj: &PyAny) -> PyResult<Option<(Chunk, Vec<usize>)>> {
+    let py = obj.py();
+    if let Ok(buffer) = PyBuffer::<f64>::get(obj) {
+        let shape = buffer.shape().to_vec();
+        return Ok(Some(if buffer.is_c_contiguous() {
+            (Chunk::Buffer(buffer), shape)
+        } else {
+            (Chunk::Owned(buffer.to_vec(py)?), shape)
+        }));
+    }
+    macro_rules! convert {
+        ($($ty:ty),*) => {
+            $(
+                if let Ok(buffer) = PyBuffer::<$ty>::get(obj) {
+                    let values = buffer.to_vec(py)?.into_iter().map(|v| v as f64).collect();
+                    return Ok(Some((Chunk::Owned(values), buffer.shape().to_vec())));
+                }
+            )*
+        };
+    }
+    convert!(f32, i64, i32, i16, i8, u64, u32, u16, u8);
+    Ok(None)
+}
+
+// pandas objects, with missing values read as NaN.
+fn to_numpy(obj: &PyAny) -> PyResult<&PyAny> {
+    let kwargs = PyDict::new(obj.py());
+    kwargs.set_item("dtype", "float64")?;
+    kwargs.set_item("na_value", f64::NAN)?;
+    obj.call_method("to_numpy", (), Some(kwargs))
+}
+
+/// One-dimensional batch of values.
+pub struct Values {
+    chunks: Vec<Chunk>,
+}
+
+impl Values {
+    pub fn extract(obj: &PyAny) -> PyResult<Self> {
+        let chunks = if obj.hasattr("num_chunks")? && obj.hasattr("chunks")? {
+            obj.getattr("chunks")?
+                .iter()?
+                .map(|chunk| arrow_chunk(chunk?))
+                .collect::<PyResult<_>>()?
+        } else if obj.hasattr("buffers")? && obj.hasattr("null_count")? {
+            vec![arrow_chunk(obj)?]
+        } else if obj.hasattr("to_arrow")? {
+            return Values::extract(obj.call_method0("to_arrow")?);
+        } else if let Some((chunk, shape)) = buffer_chunk(obj)? {
+            if shape.len() != 1 {
+                return Err(PyValueError::new_err(format!(
+                    "expected a 1D array, got {} dimensions",
+                    shape.len()
+                )));
+            }
+            vec![chunk]
+        } else if obj.hasattr("to_numpy")? {
+            return Values::extract(to_numpy(obj)?);
+        } else {
+            vec![Chunk::Owned(obj.extract()?)]
+        };
+        Ok(Values { chunks })
+    }
+
+    /// The values, as contiguous pieces in order.
+    pub fn slices(&self) -> Vec<&[f64]> {
+        self.chunks.iter().map(Chunk::as_slice).collect()
+    }
+}
+
+/// Two-dimensional batch of values, stored row-major.
+pub struct Matrix {
+    values: Chunk,
+    pub n_cols: usize,
+}
+
+impl Matrix {
+    /// Reads a 2D NumPy array, pandas data frame or buffer-protocol object.
+    pub fn extract(obj: &PyAny) -> PyResult<Self> {
+        let (values, shape) = match buffer_chunk(obj)? {
+            Some(buffer) => buffer,
+            None if obj.hasattr("to_numpy")? => return Matrix::extract(to_numpy(obj)?),
+            None => {
+                let rows: Vec<Vec<f64>> = obj.extract()?;
+                let n_cols = rows.first().map_or(0, Vec::len);
+                if rows.iter().any(|row| row.len() != n_cols) {
+                    return Err(PyValueError::new_err("rows should have the same length"));
+                }
+                let shape = vec![rows.len(), n_cols];
+                (Chunk::Owned(rows.concat()), shape)
+            }
+        };
+        if shape.len() != 2 {
+            return Err(PyValueError::new_err(format!(
+                "expected a 2D array, got {} dimensions",
+                shape.len()
+            )));
+        }
+        Ok(Matrix {
+            values,
+            n_cols: shape[1],
+        })
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        self.values.as_slice()
+    }
+}
+
+/// Columns of a PyArrow table or record batch, or of a Polars data frame.
+pub fn extract_columns(obj: &PyAny) -> PyResult<Option<Vec<Values>>> {
+    let columns = if obj.hasattr("column_names")? && obj.hasattr("columns")? {
+        obj.getattr("columns")?
+    } else if obj.hasattr("get_columns")? {
+        obj.call_method0("get_columns")?
+    } else {
+        return Ok(None);
+    };
+    columns
+        .iter()?
+        .map(|column| Values::extract(column?))
+        .collect::<PyResult<_>>()
+        .map(Some)
+}
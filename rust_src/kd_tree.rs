@@ -0,0 +1,478 @@
+// Incremental k-d tree over a stream of labeled samples, for nearest neighbor queries that
+// don't scan every stored sample in low and medium dimensions.
+//
+// The samples are inserted as new leaves and removed, oldest first, by marking their node
+// as deleted, so that the splits stay valid. The tree is rebuilt, balanced and without the
+// deleted nodes, once as many samples were removed as remain, or once as many were inserted
+// as it held at the previous rebuild, which keeps both operations amortized logarithmic.
+//
+// Reference: Bentley, J.L., 1975. Multidimensional binary search trees used for associative
+// searching. Communications of the ACM, 18(9), pp.509-517.
+
+use std::cmp::Ordering;
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIndexError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+#[cfg(feature = "python")]
+use crate::neighbors::FeatureOrder;
+use crate::neighbors::Metric;
+
+// Below this size the tree is left as inserted.
+const MIN_REBUILD_SIZE: usize = 16;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Node {
+    // Insertion number of the sample, which orders the samples by age.
+    id: u64,
+    label: Key,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+    deleted: bool,
+}
+
+impl HeapSize for Node {
+    fn heap_size(&self) -> usize {
+        self.label.heap_size()
+    }
+}
+
+// Candidate neighbor, ordered by distance and then by age, so that the heap of the `k` best
+// ones has the farthest, or else the most recent, on top.
+#[derive(PartialEq)]
+struct Candidate {
+    distance: f64,
+    id: u64,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// k-d tree of labeled samples, queried for the nearest ones by a metric other than the
+/// cosine one, for which the distance to a splitting hyperplane bounds nothing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KdTree {
+    metric: Metric,
+    n_features: usize,
+    // The values of the sample of the `i`-th node are the `i`-th row.
+    values: Vec<f64>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+    // Nodes of the samples that are not removed, oldest first.
+    live: VecDeque<usize>,
+    next_id: u64,
+    n_inserted_since_rebuild: usize,
+    size_at_rebuild: usize,
+}
+
+impl HeapSize for KdTree {
+    fn heap_size(&self) -> usize {
+        self.values.heap_size() + self.nodes.heap_size() + self.live.heap_size()
+    }
+}
+
+impl KdTree {
+    pub fn new(metric: Metric) -> Result<Self, String> {
+        if metric == Metric::Cosine {
+            return Err("a k-d tree can't prune with the cosine metric".to_string());
+        }
+        Ok(KdTree {
+            metric,
+            n_features: 0,
+            values: Vec::new(),
+            nodes: Vec::new(),
+            root: None,
+            live: VecDeque::new(),
+            next_id: 0,
+            n_inserted_since_rebuild: 0,
+            size_at_rebuild: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    fn row(&self, node: usize) -> &[f64] {
+        &self.values[node * self.n_features..(node + 1) * self.n_features]
+    }
+
+    /// Values of the `i`-th oldest sample.
+    pub fn sample(&self, i: usize) -> &[f64] {
+        self.row(self.live[i])
+    }
+
+    /// Label of the `i`-th oldest sample.
+    pub fn label(&self, i: usize) -> &Key {
+        &self.nodes[self.live[i]].label
+    }
+
+    /// Inserts a sample, which should have as many values as the first one.
+    pub fn insert(&mut self, x: &[f64], y: Key) {
+        if self.nodes.is_empty() {
+            self.n_features = x.len();
+        }
+        debug_assert_eq!(x.len(), self.n_features);
+        let node = self.nodes.len();
+        let mut axis = 0;
+        let mut parent = self.root;
+        while let Some(p) = parent {
+            let p_axis = self.nodes[p].axis;
+            let goes_left = x.get(p_axis) < self.row(p).get(p_axis);
+            let child = if goes_left {
+                &mut self.nodes[p].left
+            } else {
+                &mut self.nodes[p].right
+            };
+            match *child {
+                Some(c) => parent = Some(c),
+                None => {
+                    *child = Some(node);
+                    axis = (p_axis + 1) % self.n_features.max(1);
+                    break;
+                }
+            }
+        }
+        if self.root.is_none() {
+            self.root = Some(node);
+        }
+        self.values.extend_from_slice(x);
+        self.nodes.push(Node {
+            id: self.next_id,
+            label: y,
+            axis,
+            left: None,
+            right: None,
+            deleted: false,
+        });
+        self.live.push_back(node);
+        self.next_id += 1;
+        self.n_inserted_since_rebuild += 1;
+        if self.n_inserted_since_rebuild > self.size_at_rebuild.max(MIN_REBUILD_SIZE) {
+            self.rebuild();
+        }
+    }
+
+    /// Removes the oldest sample, and returns its label.
+    pub fn remove_oldest(&mut self) -> Option<Key> {
+        let node = self.live.pop_front()?;
+        self.nodes[node].deleted = true;
+        let label = self.nodes[node].label.clone();
+        if self.live.is_empty() {
+            self.values.clear();
+            self.nodes.clear();
+            self.root = None;
+            self.n_inserted_since_rebuild = 0;
+            self.size_at_rebuild = 0;
+        } else if self.nodes.len() - self.live.len() > self.live.len().max(MIN_REBUILD_SIZE) {
+            self.rebuild();
+        }
+        Some(label)
+    }
+
+    // Balanced tree of the samples that are not removed, split at the median along the
+    // axis with the widest spread.
+    fn rebuild(&mut self) {
+        let f = self.n_features;
+        let mut values = Vec::with_capacity(self.live.len() * f);
+        let mut nodes = Vec::with_capacity(self.live.len());
+        for &node in &self.live {
+            values.extend_from_slice(self.row(node));
+            let mut node = self.nodes[node].clone();
+            node.left = None;
+            node.right = None;
+            nodes.push(node);
+        }
+        self.values = values;
+        self.nodes = nodes;
+        self.live = (0..self.nodes.len()).collect();
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        self.root = self.build(&mut order);
+        self.n_inserted_since_rebuild = 0;
+        self.size_at_rebuild = self.live.len();
+    }
+
+    fn build(&mut self, order: &mut [usize]) -> Option<usize> {
+        if order.is_empty() {
+            return None;
+        }
+        let axis = (0..self.n_features)
+            .map(|axis| {
+                let (lo, hi) =
+                    order
+                        .iter()
+                        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &i| {
+                            let v = self.values[i * self.n_features + axis];
+                            (lo.min(v), hi.max(v))
+                        });
+                (axis, hi - lo)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(axis, _)| axis);
+        let f = self.n_features;
+        let values = &self.values;
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&i, &j| {
+            values
+                .get(i * f + axis)
+                .unwrap_or(&0.0)
+                .total_cmp(values.get(j * f + axis).unwrap_or(&0.0))
+        });
+        // The samples equal to the median along the axis go right, as they do on insertion.
+        let split = values.get(order[mid] * f + axis).copied();
+        let (left, rest) = order.split_at_mut(mid);
+        let (median, right) = rest.split_first_mut().unwrap();
+        let node = *median;
+        let mut right: Vec<usize> = right.to_vec();
+        let mut left: Vec<usize> = left.to_vec();
+        let ties: Vec<usize> = left
+            .iter()
+            .copied()
+            .filter(|&i| values.get(i * f + axis).copied() == split)
+            .collect();
+        left.retain(|&i| values.get(i * f + axis).copied() != split);
+        right.extend(ties);
+        self.nodes[node].axis = axis;
+        self.nodes[node].left = self.build(&mut left);
+        self.nodes[node].right = self.build(&mut right);
+        Some(node)
+    }
+
+    // Lower bound of the distance to any point on the other side of a split, `diff` away
+    // along its axis.
+    fn axis_bound(&self, diff: f64) -> f64 {
+        match self.metric {
+            Metric::SqEuclidean => diff * diff,
+            _ => diff.abs(),
+        }
+    }
+
+    /// Indices of the `k` samples nearest to `x`, from the nearest to the farthest, along with
+    /// their distances. The index of the oldest sample is 0, and the ties go to the oldest.
+    pub fn query(&self, x: &[f64], k: usize) -> Vec<(usize, f64)> {
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut stack: Vec<(usize, f64)> = self.root.map(|root| (root, 0.0)).into_iter().collect();
+        while let Some((node, bound)) = stack.pop() {
+            if best.len() == k && best.peek().is_some_and(|worst| bound > worst.distance) {
+                continue;
+            }
+            let n = &self.nodes[node];
+            if !n.deleted {
+                let candidate = Candidate {
+                    distance: self.metric.distance(x, self.row(node)),
+                    id: n.id,
+                    node,
+                };
+                if best.len() < k {
+                    best.push(candidate);
+                } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                    best.pop();
+                    best.push(candidate);
+                }
+            }
+            let diff = x.get(n.axis).copied().unwrap_or(0.0)
+                - self.row(node).get(n.axis).copied().unwrap_or(0.0);
+            let (near, far) = if diff < 0.0 {
+                (n.left, n.right)
+            } else {
+                (n.right, n.left)
+            };
+            if let Some(far) = far {
+                stack.push((far, bound.max(self.axis_bound(diff))));
+            }
+            if let Some(near) = near {
+                stack.push((near, bound));
+            }
+        }
+        let first_id = self.live.front().map_or(0, |&node| self.nodes[node].id);
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|c| ((self.nodes[c.node].id - first_id) as usize, c.distance))
+            .collect()
+    }
+}
+
+/// k-d tree of labeled samples, searched for the nearest neighbors in Rust.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsKDTree {
+    tree: KdTree,
+    features: FeatureOrder,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsKDTree {
+    fn heap_size(&self) -> usize {
+        self.tree.heap_size() + self.features.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsKDTree {
+    /// Compares the samples by one of the metrics of `get_distance` but the cosine one.
+    #[new]
+    #[args(metric = "\"euclidean\"", p = "2.0")]
+    pub fn new(metric: &str, p: f64) -> PyResult<Self> {
+        let metric = Metric::new(metric, p).map_err(PyValueError::new_err)?;
+        Ok(RsKDTree {
+            tree: KdTree::new(metric).map_err(PyValueError::new_err)?,
+            features: FeatureOrder::default(),
+        })
+    }
+
+    /// Inserts a dict of features and its `int` or `str` label.
+    pub fn insert(&mut self, x: HashMap<String, f64>, y: Key) {
+        let x = self.features.learn(&x);
+        self.tree.insert(&x, y);
+    }
+
+    /// Removes the oldest sample, and returns its label, or `None` when the tree is empty.
+    pub fn remove_oldest(&mut self) -> Option<Key> {
+        self.tree.remove_oldest()
+    }
+
+    /// Indices of the `k` samples nearest to `x`, the oldest sample being 0, and their
+    /// distances, from the nearest to the farthest.
+    pub fn query(&self, py: Python, x: HashMap<String, f64>, k: usize) -> (Vec<usize>, Vec<f64>) {
+        let x = self.features.read(&x);
+        py.allow_threads(|| self.tree.query(&x, k))
+            .into_iter()
+            .unzip()
+    }
+
+    /// Labels of the samples, from the oldest to the most recent.
+    pub fn labels(&self) -> Vec<Key> {
+        (0..self.tree.len())
+            .map(|i| self.tree.label(i).clone())
+            .collect()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Features and label of the `i`-th oldest sample.
+    pub fn __getitem__(&self, i: isize) -> PyResult<(HashMap<String, f64>, Key)> {
+        let n = self.tree.len() as isize;
+        let i = if i < 0 { i + n } else { i };
+        if !(0..n).contains(&i) {
+            return Err(PyIndexError::new_err("tree index out of range"));
+        }
+        let i = i as usize;
+        let x = self
+            .features
+            .names()
+            .iter()
+            .cloned()
+            .zip(self.tree.sample(i).iter().copied())
+            .collect();
+        Ok((x, self.tree.label(i).clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neighbors::Window;
+    use crate::rng::Rng;
+
+    fn points(n: usize, n_features: usize, seed: u64) -> Vec<Vec<f64>> {
+        let mut rng = Rng::from_seed(seed);
+        // Rounded values give ties, along the axes and between the distances.
+        (0..n)
+            .map(|_| {
+                (0..n_features)
+                    .map(|_| (rng.normal() * 4.0).round())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cosine_metric_is_refused() {
+        assert!(KdTree::new(Metric::Cosine).is_err());
+    }
+
+    #[test]
+    fn queries_match_a_window_scan() {
+        for metric in [
+            Metric::SqEuclidean,
+            Metric::Euclidean,
+            Metric::Manhattan,
+            Metric::Chebyshev,
+            Metric::Minkowski(3.0),
+        ] {
+            let mut tree = KdTree::new(metric).unwrap();
+            let mut window = Window::new(60).unwrap();
+            assert!(tree.query(&[0.0, 0.0, 0.0], 3).is_empty());
+            for (i, x) in points(300, 3, 1).iter().enumerate() {
+                tree.insert(x, Key::Int(i as i64));
+                window.append(x, Key::Int(i as i64));
+                if tree.len() > 60 {
+                    tree.remove_oldest();
+                }
+                if i % 7 == 0 {
+                    for q in points(3, 3, i as u64) {
+                        for k in [1, 5, 100] {
+                            assert_eq!(tree.query(&q, k), window.search(&q, k, metric));
+                        }
+                    }
+                }
+            }
+            assert_eq!(tree.len(), 60);
+            for i in 0..60 {
+                assert_eq!(tree.sample(i), window.row(i));
+                assert_eq!(tree.label(i), window.label(i));
+            }
+        }
+    }
+
+    #[test]
+    fn samples_are_removed_oldest_first() {
+        let mut tree = KdTree::new(Metric::Euclidean).unwrap();
+        assert_eq!(tree.remove_oldest(), None);
+        for i in 0..40 {
+            tree.insert(&[i as f64], Key::Int(i));
+        }
+        for i in 0..39 {
+            assert_eq!(tree.remove_oldest(), Some(Key::Int(i)));
+        }
+        assert_eq!(tree.query(&[0.0], 2), vec![(0, 39.0)]);
+        tree.remove_oldest();
+        assert!(tree.is_empty());
+        tree.insert(&[1.0, 2.0], Key::Int(0));
+        assert_eq!(tree.query(&[1.0, 2.0], 1), vec![(0, 0.0)]);
+    }
+}
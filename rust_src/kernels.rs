@@ -0,0 +1,90 @@
+// Element-wise link functions, exposed so that the Python models can hand them a whole
+// vector of scores at once.
+//
+// Each function takes either a 1D batch, in any of the layouts read by `Values`, and returns
+// a NumPy array, or a dict, and returns a dict with the same keys in the same order.
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::ingest::Values;
+use crate::special::{log_sum_exp, sigmoid as logistic};
+
+// The keys of a dict input, if any, along with the values.
+fn extract(x: &PyAny) -> PyResult<(Option<Vec<PyObject>>, Vec<f64>)> {
+    if let Ok(dict) = x.downcast::<PyDict>() {
+        let mut keys = Vec::with_capacity(dict.len());
+        let mut values = Vec::with_capacity(dict.len());
+        for (key, value) in dict {
+            keys.push(key.into());
+            values.push(value.extract()?);
+        }
+        return Ok((Some(keys), values));
+    }
+    let values = Values::extract(x)?;
+    Ok((None, values.slices().concat()))
+}
+
+fn output(py: Python, keys: Option<Vec<PyObject>>, values: Vec<f64>) -> PyResult<PyObject> {
+    match keys {
+        Some(keys) => {
+            let dict = PyDict::new(py);
+            for (key, value) in keys.into_iter().zip(values) {
+                dict.set_item(key, value)?;
+            }
+            Ok(dict.into())
+        }
+        None => Ok(PyArray1::from_vec(py, values).into_py(py)),
+    }
+}
+
+fn map(py: Python, x: &PyAny, f: impl Fn(f64) -> f64) -> PyResult<PyObject> {
+    let (keys, mut values) = extract(x)?;
+    values.iter_mut().for_each(|v| *v = f(*v));
+    output(py, keys, values)
+}
+
+/// Logistic function of each value.
+#[pyfunction]
+pub fn sigmoid(py: Python, x: &PyAny) -> PyResult<PyObject> {
+    map(py, x, logistic)
+}
+
+/// Exponentials of the values normalized to sum to 1, computed after subtracting their
+/// log-sum-exp so that large scores don't overflow.
+#[pyfunction]
+pub fn softmax(py: Python, x: &PyAny) -> PyResult<PyObject> {
+    let (keys, mut values) = extract(x)?;
+    let lse = log_sum_exp(&values);
+    if lse.is_infinite() {
+        // All the values are -inf, or some are +inf: split the mass between the largest.
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let n_max = values.iter().filter(|&&v| v == max).count() as f64;
+        values
+            .iter_mut()
+            .for_each(|v| *v = if *v == max { 1.0 / n_max } else { 0.0 });
+    } else {
+        values.iter_mut().for_each(|v| *v = (*v - lse).exp());
+    }
+    output(py, keys, values)
+}
+
+/// Logarithm of the sum of the exponentials of the values, -inf if there are none.
+#[pyfunction]
+pub fn logsumexp(x: &PyAny) -> PyResult<f64> {
+    let (_, values) = extract(x)?;
+    Ok(log_sum_exp(&values))
+}
+
+/// Each value bounded to `[lower, upper]`.
+#[pyfunction]
+pub fn clip(py: Python, x: &PyAny, lower: f64, upper: f64) -> PyResult<PyObject> {
+    if lower.is_nan() || upper.is_nan() || lower > upper {
+        return Err(PyValueError::new_err(
+            "lower should not be greater than upper",
+        ));
+    }
+    map(py, x, |v| v.clamp(lower, upper))
+}
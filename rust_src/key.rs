@@ -0,0 +1,113 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::memory::HeapSize;
+use crate::rng::splitmix64;
+
+/// Hashable key of the categorical structures, extracted from a Python `int` or `str` by the
+/// bindings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", derive(FromPyObject))]
+pub enum Key {
+    Int(i64),
+    Str(String),
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for Key {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Key::Int(i) => i.into_py(py),
+            Key::Str(s) => s.into_py(py),
+        }
+    }
+}
+
+impl Key {
+    /// Hash of the key which is the same on every platform and release, unlike the one of
+    /// `std`, so that the sketches filled with it can be saved and merged.
+    ///
+    /// FNV-1a over a tag byte and the little-endian integer or the UTF-8 string, whose low
+    /// bits are then spread over the whole word by the splitmix64 finalizer.
+    pub fn stable_hash(&self) -> u64 {
+        let fnv = |hash: u64, bytes: &[u8]| {
+            bytes.iter().fold(hash, |hash, &b| {
+                (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+        };
+        let mut hash = match self {
+            Key::Int(i) => fnv(fnv(0xcbf2_9ce4_8422_2325, &[0]), &i.to_le_bytes()),
+            Key::Str(s) => fnv(fnv(0xcbf2_9ce4_8422_2325, &[1]), s.as_bytes()),
+        };
+        splitmix64(&mut hash)
+    }
+}
+
+impl HeapSize for Key {
+    fn heap_size(&self) -> usize {
+        match self {
+            Key::Int(_) => 0,
+            Key::Str(s) => s.heap_size(),
+        }
+    }
+}
+
+/// Serde adapter of the maps keyed by `Key`, for `#[serde(with = "crate::key::pairs")]`.
+///
+/// JSON only has string keys, so the human-readable formats get a list of `[key, value]`
+/// pairs. The binary formats keep the map, and so the layout of the states they already
+/// hold.
+pub mod pairs {
+    use super::*;
+
+    pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_seq(map)
+        } else {
+            serializer.collect_map(map)
+        }
+    }
+
+    pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: Deserialize<'de> + FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            Ok(Vec::<(K, V)>::deserialize(deserializer)?
+                .into_iter()
+                .collect())
+        } else {
+            M::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash_is_pinned() {
+        // Sketches saved by an older release rely on these values.
+        assert_eq!(Key::Int(0).stable_hash(), 12111055192015656419);
+        assert_eq!(Key::Int(-1).stable_hash(), 18300783835812226594);
+        assert_eq!(
+            Key::Str("river".to_string()).stable_hash(),
+            15719202739302463507
+        );
+        assert_ne!(
+            Key::Int(1).stable_hash(),
+            Key::Str("1".to_string()).stable_hash()
+        );
+    }
+}
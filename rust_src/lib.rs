@@ -1,338 +1,341 @@
-// The purpose of this is to make the binding between watermill.rs and Python.
-use bincode::{deserialize, serialize};
-use pyo3::prelude::*;
-use pyo3::types::PyBytes;
-use serde::{Deserialize, Serialize};
-use watermill::{
-    ewmean::EWMean, ewvariance::EWVariance, iqr::RollingIQR, iqr::IQR, kurtosis::Kurtosis,
-    ptp::PeakToPeak, quantile::Quantile, quantile::RollingQuantile, skew::Skew, stats::Univariate,
-};
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsQuantile {
-    pub quantile: Quantile<f64>,
-}
-
-#[pymethods]
-impl RsQuantile {
-    #[new]
-    pub fn new(q: Option<f64>) -> RsQuantile {
-        match q {
-            Some(q) => {
-                return RsQuantile {
-                    quantile: Quantile::new(q).expect("q should between 0 and 1"),
-                }
-            }
-            None => RsQuantile {
-                quantile: Quantile::default(),
-            },
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.quantile.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.quantile.get()
-    }
-
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsEWMean {
-    ewmean: EWMean<f64>,
-    alpha: f64,
-}
-#[pymethods]
-impl RsEWMean {
-    #[new]
-    pub fn new(alpha: f64) -> RsEWMean {
-        RsEWMean {
-            ewmean: EWMean::new(alpha),
-            alpha,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.ewmean.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.ewmean.get()
-    }
-
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(f64,)> {
-        Ok((self.alpha,))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsEWVar {
-    ewvar: EWVariance<f64>,
-    alpha: f64,
-}
-#[pymethods]
-impl RsEWVar {
-    #[new]
-    pub fn new(alpha: f64) -> RsEWVar {
-        RsEWVar {
-            ewvar: EWVariance::new(alpha),
-            alpha,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.ewvar.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.ewvar.get()
-    }
-
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(f64,)> {
-        Ok((self.alpha,))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsIQR {
-    iqr: IQR<f64>,
-    q_inf: f64,
-    q_sup: f64,
-}
-
-#[pymethods]
-impl RsIQR {
-    #[new]
-    pub fn new(q_inf: f64, q_sup: f64) -> RsIQR {
-        RsIQR {
-            iqr: IQR::new(q_inf, q_sup).expect("TODO"),
-            q_inf,
-            q_sup,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.iqr.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.iqr.get()
-    }
-
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(f64, f64)> {
-        Ok((self.q_inf, self.q_sup))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsKurtosis {
-    kurtosis: Kurtosis<f64>,
-    bias: bool,
-}
-#[pymethods]
-impl RsKurtosis {
-    #[new]
-    pub fn new(bias: bool) -> RsKurtosis {
-        RsKurtosis {
-            kurtosis: Kurtosis::new(bias),
-            bias,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.kurtosis.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.kurtosis.get()
-    }
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(bool,)> {
-        Ok((self.bias,))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsPeakToPeak {
-    ptp: PeakToPeak<f64>,
-}
-
-#[pymethods]
-impl RsPeakToPeak {
-    #[new]
-    pub fn new() -> RsPeakToPeak {
-        RsPeakToPeak {
-            ptp: PeakToPeak::new(),
-        }
-    }
-
-    pub fn update(&mut self, x: f64) {
-        self.ptp.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.ptp.get()
-    }
-
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-}
+//! Online statistics, sketches and drift detectors, with the Python bindings of river on top.
+//!
+//! The algorithms are plain Rust and don't depend on Python, so that a Rust program can run
+//! exactly the same ones as river. The bindings, the `river.stats._rust_stats` extension
+//! module, are built with the `python` feature, which is on by default. Without it, the
+//! crate is a pure-Rust library:
+//!
+//! ```toml
+//! river = { version = "0.1", default-features = false }
+//! ```
+//!
+//! The structures are updated one value at a time and can be read at any time:
+//!
+//! ```
+//! use river::drift::Adwin;
+//! use river::rolling::RollingQuantile;
+//!
+//! let mut median = RollingQuantile::new(0.5, 3).unwrap();
+//! let mut adwin = Adwin::new(0.002, 32, 10).unwrap();
+//! for x in [1.0, 5.0, 2.0, 4.0] {
+//!     median.update(x);
+//!     adwin.update(x);
+//! }
+//! assert_eq!(median.get(), 4.0);
+//! assert_eq!(adwin.width(), 4);
+//! ```
+//!
+//! The states are serde-serializable, so they can be checkpointed or moved between
+//! processes, and `memory::HeapSize` reports the memory they hold.
 
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsSkew {
-    skew: Skew<f64>,
-    bias: bool,
+// Every pyclass of the module, for the methods that all of them implement the same way.
+#[cfg(feature = "python")]
+macro_rules! for_each_binding {
+    ($mac:ident) => {
+        $mac!(
+            crate::bindings::RsQuantile,
+            crate::bindings::RsEWMean,
+            crate::bindings::RsEWVar,
+            crate::bindings::RsIQR,
+            crate::bindings::RsKurtosis,
+            crate::bindings::RsPeakToPeak,
+            crate::bindings::RsSkew,
+            crate::bindings::RsRollingQuantile,
+            crate::bindings::RsRollingIQR,
+            crate::bindings::RsRollingMean,
+            crate::bindings::RsRollingVar,
+            crate::bindings::RsRollingMin,
+            crate::bindings::RsRollingMax,
+            crate::bindings::RsRollingPeakToPeak,
+            crate::bindings::RsRollingMAD,
+            crate::proba::RsGaussianMixture,
+            crate::proba::RsKernelDensity,
+            crate::stats::RsECDF,
+            crate::stats::RsTDigest,
+            crate::stats::RsCircularStats,
+            crate::stats::RsRollingCircularStats,
+            crate::stats::RsPercentileRank,
+            crate::stats::RsMAD,
+            crate::stats::RsRollingPercentileRank,
+            crate::stats::RsShift,
+            crate::stats::RsAutoCorr,
+            crate::stats::RsTimeEWMean,
+            crate::stats::RsTimeEWVar,
+            crate::stats::RsDecayedTopK,
+            crate::stats::RsSpaceSaving,
+            crate::stats::RsDistinctCount,
+            crate::stats::RsHyperLogLog,
+            crate::stats::RsHistogram,
+            crate::stats::RsCountMin,
+            crate::stats::RsExpHistogram,
+            crate::stats::RsCounter,
+            crate::stats::RsMode,
+            crate::compose::RsStatPipeline,
+            crate::compose::RsGroupedStat,
+            crate::compose::RsColumnStats,
+            crate::compose::RsDiff,
+            crate::compose::RsRatio,
+            crate::compose::RsSkipMissing,
+            crate::covariance::RsCov,
+            crate::covariance::RsPearsonCorr,
+            crate::covariance::RsCovMatrix,
+            crate::covariance::RsPrecisionMatrix,
+            crate::covariance::RsWhitener,
+            crate::hypothesis::RsWelchTTest,
+            crate::hypothesis::RsChiSquareTest,
+            crate::metrics::RsLogLoss,
+            crate::metrics::RsAccuracy,
+            crate::hypothesis::RsMannWhitneyU,
+            crate::hypothesis::RsPairedComparison,
+            crate::drift::RsWassersteinDrift,
+            crate::drift::RsAdaptiveMean,
+            crate::drift::RsAdaptiveVar,
+            crate::drift::RsADWIN,
+            crate::drift::RsMultinomialDrift,
+            crate::drift::RsPageHinkley,
+            crate::drift::RsDDM,
+            crate::drift::RsEDDM,
+            crate::drift::RsKSWIN,
+            crate::rng::RsRng,
+            crate::frozen::RsFrozenStat,
+            crate::calibration::RsIsotonicCalibrator,
+            crate::calibration::RsPlattCalibrator,
+            crate::selection::RsSuccessiveHalving,
+            crate::selection::RsBanditSelection,
+            crate::linear::RsLogisticRegression,
+            crate::linear::RsLinearRegression,
+            crate::multiclass::RsOutputCodeClassifier,
+            crate::multioutput::RsClassifierChain,
+            crate::multioutput::RsRegressorChain,
+            crate::imblearn::RsRandomUnderSampler,
+            crate::imblearn::RsRandomOverSampler,
+            crate::imblearn::RsHardSamplingClassifier,
+            crate::bagging::RsLeveragingBaggingClassifier,
+            crate::bagging::RsADWINBaggingClassifier,
+            crate::synth::RsSEA,
+            crate::synth::RsHyperplane,
+            crate::synth::RsAgrawal,
+            crate::synth::RsRandomRBF,
+            crate::synth::RsRandomTree,
+            crate::synth::RsLED,
+            crate::synth::RsSTAGGER,
+            crate::synth::RsConceptDriftStream,
+            crate::sam_knn::RsSAMKNN,
+            crate::neighbors::RsWindow,
+            crate::kd_tree::RsKDTree,
+            crate::lsh::RsLSHIndex,
+        );
+    };
 }
-#[pymethods]
-impl RsSkew {
-    #[new]
-    pub fn new(bias: bool) -> RsSkew {
-        RsSkew {
-            skew: Skew::new(bias),
-            bias,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.skew.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.skew.get()
-    }
 
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(bool,)> {
-        Ok((self.bias,))
-    }
-}
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsRollingQuantile {
-    stat: RollingQuantile<f64>,
-    q: f64,
-    window_size: usize,
-}
+pub mod drift;
+pub mod hypothesis;
+pub mod kd_tree;
+pub mod key;
+pub mod linalg;
+pub mod lsh;
+pub mod memory;
+pub mod moments;
+pub mod neighbors;
+pub mod nonfinite;
+pub mod quantile;
+pub mod rng;
+pub mod rolling;
+pub mod sam_knn;
+pub mod sketch;
+pub mod special;
 
-#[pymethods]
-impl RsRollingQuantile {
-    #[new]
-    pub fn new(q: f64, window_size: usize) -> RsRollingQuantile {
-        RsRollingQuantile {
-            stat: RollingQuantile::new(q, window_size).unwrap(),
-            q,
-            window_size,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.stat.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.stat.get()
-    }
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(f64, usize)> {
-        Ok((self.q, self.window_size))
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-#[pyclass(module = "river.stats._rust_stats")]
-pub struct RsRollingIQR {
-    stat: RollingIQR<f64>,
-    q_inf: f64,
-    q_sup: f64,
-    window_size: usize,
-}
-
-#[pymethods]
-impl RsRollingIQR {
-    #[new]
-    pub fn new(q_inf: f64, q_sup: f64, window_size: usize) -> RsRollingIQR {
-        RsRollingIQR {
-            stat: RollingIQR::new(q_inf, q_sup, window_size).unwrap(),
-            q_inf,
-            q_sup,
-            window_size,
-        }
-    }
-    pub fn update(&mut self, x: f64) {
-        self.stat.update(x);
-    }
-    pub fn get(&self) -> f64 {
-        self.stat.get()
-    }
-    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
-        Ok(())
-    }
-    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
-    }
-    pub fn __getnewargs__(&self) -> PyResult<(f64, f64, usize)> {
-        Ok((self.q_inf, self.q_sup, self.window_size))
-    }
-}
+#[cfg(feature = "python")]
+mod arff;
+#[cfg(feature = "python")]
+mod bagging;
+#[cfg(feature = "python")]
+mod bench;
+#[cfg(feature = "python")]
+mod bindings;
+#[cfg(feature = "python")]
+mod calibration;
+#[cfg(feature = "python")]
+mod classifier;
+#[cfg(feature = "python")]
+mod compare;
+#[cfg(feature = "python")]
+mod compose;
+#[cfg(feature = "python")]
+mod covariance;
+#[cfg(feature = "python")]
+mod csv;
+#[cfg(feature = "python")]
+mod encoding;
+#[cfg(feature = "python")]
+mod ensemble;
+#[cfg(feature = "python")]
+mod evaluate;
+#[cfg(feature = "python")]
+mod factorization;
+#[cfg(feature = "python")]
+mod frozen;
+#[cfg(feature = "python")]
+mod imblearn;
+#[cfg(feature = "python")]
+mod ingest;
+#[cfg(feature = "python")]
+mod kernels;
+#[cfg(feature = "python")]
+mod libsvm;
+#[cfg(feature = "python")]
+mod linear;
+#[cfg(feature = "python")]
+mod merge;
+#[cfg(feature = "python")]
+mod metrics;
+#[cfg(feature = "python")]
+mod multiclass;
+#[cfg(feature = "python")]
+mod multioutput;
+#[cfg(feature = "python")]
+mod persist;
+#[cfg(feature = "python")]
+mod proba;
+#[cfg(feature = "python")]
+mod selection;
+#[cfg(feature = "python")]
+mod state;
+#[cfg(feature = "python")]
+mod stats;
+#[cfg(feature = "python")]
+mod stream;
+#[cfg(feature = "python")]
+mod synth;
+#[cfg(feature = "python")]
+mod univariate;
+#[cfg(feature = "python")]
+mod view;
 
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
 
 /// A Python module implemented in Rust.
+#[cfg(feature = "python")]
 #[pymodule]
 fn _rust_stats(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<RsQuantile>()?;
-    m.add_class::<RsEWMean>()?;
-    m.add_class::<RsEWVar>()?;
-    m.add_class::<RsIQR>()?;
-    m.add_class::<RsKurtosis>()?;
-    m.add_class::<RsPeakToPeak>()?;
-    m.add_class::<RsSkew>()?;
-    m.add_class::<RsRollingQuantile>()?;
-    m.add_class::<RsRollingIQR>()?;
+    m.add_class::<bindings::RsQuantile>()?;
+    m.add_class::<bindings::RsEWMean>()?;
+    m.add_class::<bindings::RsEWVar>()?;
+    m.add_class::<bindings::RsIQR>()?;
+    m.add_class::<bindings::RsKurtosis>()?;
+    m.add_class::<bindings::RsPeakToPeak>()?;
+    m.add_class::<bindings::RsSkew>()?;
+    m.add_class::<bindings::RsRollingQuantile>()?;
+    m.add_class::<bindings::RsRollingIQR>()?;
+    m.add_class::<bindings::RsRollingMean>()?;
+    m.add_class::<bindings::RsRollingVar>()?;
+    m.add_class::<bindings::RsRollingMin>()?;
+    m.add_class::<bindings::RsRollingMax>()?;
+    m.add_class::<bindings::RsRollingPeakToPeak>()?;
+    m.add_class::<bindings::RsRollingMAD>()?;
+    m.add_class::<proba::RsGaussianMixture>()?;
+    m.add_class::<proba::RsKernelDensity>()?;
+    m.add_class::<stats::RsECDF>()?;
+    m.add_class::<stats::RsTDigest>()?;
+    m.add_class::<stats::RsCircularStats>()?;
+    m.add_class::<stats::RsRollingCircularStats>()?;
+    m.add_class::<stats::RsPercentileRank>()?;
+    m.add_class::<stats::RsMAD>()?;
+    m.add_class::<stats::RsRollingPercentileRank>()?;
+    m.add_class::<stats::RsShift>()?;
+    m.add_class::<stats::RsAutoCorr>()?;
+    m.add_class::<stats::RsTimeEWMean>()?;
+    m.add_class::<stats::RsTimeEWVar>()?;
+    m.add_class::<stats::RsDecayedTopK>()?;
+    m.add_class::<stats::RsSpaceSaving>()?;
+    m.add_class::<stats::RsDistinctCount>()?;
+    m.add_class::<stats::RsHyperLogLog>()?;
+    m.add_class::<stats::RsHistogram>()?;
+    m.add_class::<stats::RsCountMin>()?;
+    m.add_class::<stats::RsExpHistogram>()?;
+    m.add_class::<stats::RsCounter>()?;
+    m.add_class::<stats::RsMode>()?;
+    m.add_class::<compose::RsStatPipeline>()?;
+    m.add_class::<compose::RsGroupedStat>()?;
+    m.add_class::<compose::RsColumnStats>()?;
+    m.add_class::<compose::RsDiff>()?;
+    m.add_class::<compose::RsRatio>()?;
+    m.add_class::<compose::RsSkipMissing>()?;
+    m.add_class::<covariance::RsCov>()?;
+    m.add_class::<covariance::RsPearsonCorr>()?;
+    m.add_class::<covariance::RsCovMatrix>()?;
+    m.add_class::<covariance::RsPrecisionMatrix>()?;
+    m.add_class::<covariance::RsWhitener>()?;
+    m.add_class::<hypothesis::RsWelchTTest>()?;
+    m.add_class::<hypothesis::RsChiSquareTest>()?;
+    m.add_class::<metrics::RsLogLoss>()?;
+    m.add_class::<metrics::RsAccuracy>()?;
+    m.add_class::<hypothesis::RsMannWhitneyU>()?;
+    m.add_class::<hypothesis::RsPairedComparison>()?;
+    m.add_class::<drift::RsWassersteinDrift>()?;
+    m.add_class::<drift::RsAdaptiveMean>()?;
+    m.add_class::<drift::RsAdaptiveVar>()?;
+    m.add_class::<drift::RsADWIN>()?;
+    m.add_class::<drift::RsMultinomialDrift>()?;
+    m.add_class::<drift::RsPageHinkley>()?;
+    m.add_class::<drift::RsDDM>()?;
+    m.add_class::<drift::RsEDDM>()?;
+    m.add_class::<drift::RsKSWIN>()?;
+    m.add_class::<frozen::RsFrozenStat>()?;
+    m.add_class::<rng::RsRng>()?;
+    m.add_class::<calibration::RsIsotonicCalibrator>()?;
+    m.add_class::<calibration::RsPlattCalibrator>()?;
+    m.add_function(wrap_pyfunction!(compose::update_all, m)?)?;
+    m.add_function(wrap_pyfunction!(rng::set_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(bench::bench, m)?)?;
+    m.add_function(wrap_pyfunction!(bench::bench_distances, m)?)?;
+    m.add_function(wrap_pyfunction!(kernels::sigmoid, m)?)?;
+    m.add_function(wrap_pyfunction!(kernels::softmax, m)?)?;
+    m.add_function(wrap_pyfunction!(kernels::logsumexp, m)?)?;
+    m.add_function(wrap_pyfunction!(kernels::clip, m)?)?;
+    m.add_function(wrap_pyfunction!(ensemble::majority_vote, m)?)?;
+    m.add_function(wrap_pyfunction!(ensemble::average_proba, m)?)?;
+    m.add_function(wrap_pyfunction!(ensemble::average, m)?)?;
+    m.add_function(wrap_pyfunction!(factorization::cholesky_update, m)?)?;
+    m.add_function(wrap_pyfunction!(factorization::qr_add_row, m)?)?;
+    m.add_function(wrap_pyfunction!(factorization::solve_triangular, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate::evaluate_progressive, m)?)?;
+    m.add_class::<csv::RsCsvReader>()?;
+    m.add_function(wrap_pyfunction!(csv::iter_csv, m)?)?;
+    m.add_class::<arff::RsArffReader>()?;
+    m.add_function(wrap_pyfunction!(arff::iter_arff, m)?)?;
+    m.add_class::<libsvm::RsLibsvmReader>()?;
+    m.add_function(wrap_pyfunction!(libsvm::iter_libsvm, m)?)?;
+    m.add_class::<stream::RsShuffle>()?;
+    m.add_function(wrap_pyfunction!(stream::shuffle, m)?)?;
+    m.add_class::<stream::RsAnomalyInjector>()?;
+    m.add_function(wrap_pyfunction!(stream::inject_anomalies, m)?)?;
+    m.add_class::<selection::RsSuccessiveHalving>()?;
+    m.add_class::<selection::RsBanditSelection>()?;
+    m.add_class::<linear::RsLogisticRegression>()?;
+    m.add_class::<linear::RsLinearRegression>()?;
+    m.add_class::<multiclass::RsOutputCodeClassifier>()?;
+    m.add_class::<multioutput::RsClassifierChain>()?;
+    m.add_class::<multioutput::RsRegressorChain>()?;
+    m.add_class::<imblearn::RsRandomUnderSampler>()?;
+    m.add_class::<imblearn::RsRandomOverSampler>()?;
+    m.add_class::<imblearn::RsHardSamplingClassifier>()?;
+    m.add_class::<bagging::RsLeveragingBaggingClassifier>()?;
+    m.add_class::<bagging::RsADWINBaggingClassifier>()?;
+    m.add_class::<synth::RsSEA>()?;
+    m.add_class::<synth::RsHyperplane>()?;
+    m.add_class::<synth::RsAgrawal>()?;
+    m.add_class::<synth::RsRandomRBF>()?;
+    m.add_class::<synth::RsRandomTree>()?;
+    m.add_class::<synth::RsLED>()?;
+    m.add_class::<synth::RsSTAGGER>()?;
+    m.add_class::<synth::RsConceptDriftStream>()?;
+    m.add_class::<sam_knn::RsSAMKNN>()?;
+    m.add_class::<neighbors::RsWindow>()?;
+    m.add_class::<kd_tree::RsKDTree>()?;
+    m.add_class::<lsh::RsLSHIndex>()?;
+    m.add_function(wrap_pyfunction!(neighbors::get_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(neighbors::get_1_to_n_distances, m)?)?;
     Ok(())
 }
@@ -0,0 +1,88 @@
+// Streaming of LIBSVM (SVMLight) files, like `stream.iter_libsvm`.
+//
+// Each line holds a label followed by `index:value` pairs, and may end with a `#` comment.
+// The pairs become a sparse dict keyed by the indices, as strings, and the `qid:` pairs of
+// the ranking variant of the format are skipped.
+
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat};
+
+use crate::csv::{open, Converter};
+
+/// Iterator over the samples of a LIBSVM file, made by `iter_libsvm`.
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLibsvmReader {
+    reader: Box<dyn BufRead + Send>,
+    target_type: Converter,
+    line: String,
+    n_lines: usize,
+}
+
+impl RsLibsvmReader {
+    fn error(&self, message: &str) -> PyErr {
+        PyValueError::new_err(format!("line {}: {}", self.n_lines, message))
+    }
+}
+
+#[pymethods]
+impl RsLibsvmReader {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(PyObject, PyObject)>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.n_lines += 1;
+            let content = match self.line.split_once('#') {
+                Some((content, _)) => content,
+                None => &self.line,
+            };
+            let mut tokens = content.split_whitespace();
+            let label = match tokens.next() {
+                Some(label) => label,
+                None => continue,
+            };
+            let x = PyDict::new(py);
+            for token in tokens {
+                let (index, value) = token.split_once(':').ok_or_else(|| {
+                    self.error(&format!("'{}' is not an index:value pair", token))
+                })?;
+                if index == "qid" {
+                    continue;
+                }
+                let value: f64 = value
+                    .parse()
+                    .map_err(|_| self.error(&format!("'{}' is not a number", value)))?;
+                x.set_item(index, value)?;
+            }
+            let y = self.target_type.convert(py, label)?;
+            return Ok(Some((x.into(), y)));
+        }
+    }
+}
+
+/// Streams the samples of the LIBSVM file at `path` as `(x, y)` pairs, where `x` is a
+/// sparse dict of the features and `y` the label converted by `target_type`, which defaults
+/// to `float`.
+#[pyfunction(target_type = "None")]
+pub fn iter_libsvm(
+    py: Python,
+    path: PathBuf,
+    target_type: Option<&PyAny>,
+) -> PyResult<RsLibsvmReader> {
+    let target_type = target_type.unwrap_or_else(|| py.get_type::<PyFloat>());
+    Ok(RsLibsvmReader {
+        reader: open(&path)?,
+        target_type: Converter::new(py, target_type),
+        line: String::new(),
+        n_lines: 0,
+    })
+}
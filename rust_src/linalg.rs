@@ -0,0 +1,211 @@
+// Small dense linear algebra helpers shared by the multivariate bindings.
+// Matrices are stored row-major in flat `Vec<f64>`s of size `n * n`.
+
+/// Cholesky factorization of a symmetric positive definite matrix.
+/// Returns the lower triangular factor, or `None` if the matrix is not positive definite.
+pub fn cholesky(a: &[f64], n: usize) -> Option<Vec<f64>> {
+    let mut l = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..=i {
+            let mut sum = a[i * n + j];
+            for k in 0..j {
+                sum -= l[i * n + k] * l[j * n + k];
+            }
+            if i == j {
+                if sum <= 0.0 || !sum.is_finite() {
+                    return None;
+                }
+                l[i * n + i] = sum.sqrt();
+            } else {
+                l[i * n + j] = sum / l[j * n + j];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Solves `L y = b` for a lower triangular `L`.
+pub fn forward_substitution(l: &[f64], n: usize, b: &[f64]) -> Vec<f64> {
+    let mut y = vec![0.0; n];
+    for i in 0..n {
+        let mut sum = b[i];
+        for k in 0..i {
+            sum -= l[i * n + k] * y[k];
+        }
+        y[i] = sum / l[i * n + i];
+    }
+    y
+}
+
+/// Log-determinant of `L L^T` given the Cholesky factor `L`.
+pub fn log_det_cholesky(l: &[f64], n: usize) -> f64 {
+    (0..n).map(|i| l[i * n + i].ln()).sum::<f64>() * 2.0
+}
+
+/// Inverse of a symmetric positive definite matrix, through its Cholesky factorization.
+pub fn cholesky_inverse(a: &[f64], n: usize) -> Option<Vec<f64>> {
+    let l = cholesky(a, n)?;
+    let mut inv = vec![0.0; n * n];
+    let mut e = vec![0.0; n];
+    for col in 0..n {
+        e.iter_mut().for_each(|v| *v = 0.0);
+        e[col] = 1.0;
+        let y = forward_substitution(&l, n, &e);
+        // Back substitution with L^T.
+        let mut x = vec![0.0; n];
+        for i in (0..n).rev() {
+            let mut sum = y[i];
+            for k in i + 1..n {
+                sum -= l[k * n + i] * x[k];
+            }
+            x[i] = sum / l[i * n + i];
+        }
+        for i in 0..n {
+            inv[i * n + col] = x[i];
+        }
+    }
+    Some(inv)
+}
+
+/// Sherman-Morrison update of the inverse of a symmetric matrix `A` into the inverse of
+/// `A + c u u^T`.
+pub fn sherman_morrison(inv: &mut [f64], n: usize, u: &[f64], c: f64) {
+    if n == 0 {
+        return;
+    }
+    let z: Vec<f64> = inv
+        .chunks(n)
+        .map(|row| row.iter().zip(u).map(|(a, b)| a * b).sum())
+        .collect();
+    let denom = 1.0 + c * u.iter().zip(&z).map(|(a, b)| a * b).sum::<f64>();
+    if denom == 0.0 {
+        return;
+    }
+    for (row, zi) in inv.chunks_mut(n).zip(&z) {
+        for (a, zj) in row.iter_mut().zip(&z) {
+            *a -= c * zi * zj / denom;
+        }
+    }
+}
+
+/// Eigendecomposition of a symmetric matrix with the cyclic Jacobi method.
+///
+/// Returns the eigenvalues in decreasing order, and the matching unit eigenvectors as the
+/// rows of a row-major matrix.
+pub fn symmetric_eigen(a: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = a.to_vec();
+    // Columns of `v` are the eigenvectors while rotating.
+    let mut v = vec![0.0; n * n];
+    for i in 0..n {
+        v[i * n + i] = 1.0;
+    }
+    let norm: f64 = a.iter().map(|x| x * x).sum();
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i * n + j].powi(2))
+            .sum();
+        if off <= f64::EPSILON * f64::EPSILON * norm {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[k * n + p], a[k * n + q]);
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p * n + k], a[q * n + k]);
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k * n + p], v[k * n + q]);
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[j * n + j].total_cmp(&a[i * n + i]));
+    let values = order.iter().map(|&i| a[i * n + i]).collect();
+    let vectors = order
+        .iter()
+        .flat_map(|&i| (0..n).map(|k| v[k * n + i]).collect::<Vec<_>>())
+        .collect();
+    (values, vectors)
+}
+
+/// Solves `R x = b` for an upper triangular `R`.
+pub fn back_substitution(r: &[f64], n: usize, b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let mut sum = b[i];
+        for k in i + 1..n {
+            sum -= r[i * n + k] * x[k];
+        }
+        x[i] = sum / r[i * n + i];
+    }
+    x
+}
+
+/// Turns the lower triangular Cholesky factor `L` of `A` into that of `A + x x^T`, or of
+/// `A - x x^T` when `downdate` is set, in `O(n^2)` operations.
+///
+/// A downdate fails when `A - x x^T` is not positive definite, in which case `l` is left in
+/// an unspecified state.
+pub fn cholesky_rank1_update(
+    l: &mut [f64],
+    n: usize,
+    x: &[f64],
+    downdate: bool,
+) -> Result<(), String> {
+    let sign = if downdate { -1.0 } else { 1.0 };
+    let mut x = x.to_vec();
+    for k in 0..n {
+        let lkk = l[k * n + k];
+        let r2 = lkk * lkk + sign * x[k] * x[k];
+        if r2.is_nan() || r2 <= 0.0 {
+            return Err("the downdated matrix is not positive definite".to_string());
+        }
+        let r = r2.sqrt();
+        let c = r / lkk;
+        let s = x[k] / lkk;
+        l[k * n + k] = r;
+        for i in k + 1..n {
+            l[i * n + k] = (l[i * n + k] + sign * s * x[i]) / c;
+            x[i] = c * x[i] - s * l[i * n + k];
+        }
+    }
+    Ok(())
+}
+
+/// Adds the row `a` to the matrix whose QR factorization has the upper triangular factor
+/// `R`, with Givens rotations. Only `R` is maintained, which is all least squares need once
+/// the targets are appended as an extra column.
+pub fn qr_add_row(r: &mut [f64], n: usize, a: &[f64]) {
+    let mut a = a.to_vec();
+    for k in 0..n {
+        let h = r[k * n + k].hypot(a[k]);
+        if h == 0.0 {
+            continue;
+        }
+        let c = r[k * n + k] / h;
+        let s = a[k] / h;
+        for j in k..n {
+            let rkj = r[k * n + j];
+            r[k * n + j] = c * rkj + s * a[j];
+            a[j] = c * a[j] - s * rkj;
+        }
+    }
+}
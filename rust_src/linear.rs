@@ -0,0 +1,201 @@
+// Linear models of dicts of features, fitted with SGD.
+//
+// They are the native models which the wrappers, like `RsOutputCodeClassifier` or the
+// chains, train on their sub-problems without going through Python.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+use crate::special::sigmoid;
+
+// Weights of the features, and intercept, of a linear model.
+#[derive(Clone, Serialize, Deserialize)]
+struct Linear {
+    lr: f64,
+    l2: f64,
+    weights: HashMap<String, f64>,
+    intercept: f64,
+}
+
+impl Linear {
+    fn new(lr: f64, l2: f64) -> Self {
+        Linear {
+            lr,
+            l2,
+            weights: HashMap::new(),
+            intercept: 0.0,
+        }
+    }
+
+    fn raw(&self, x: &HashMap<String, f64>) -> f64 {
+        let z: f64 = x
+            .iter()
+            .filter_map(|(name, xi)| self.weights.get(name).map(|w| w * xi))
+            .sum();
+        z + self.intercept
+    }
+
+    // SGD step against the gradient `error * x` of the loss.
+    fn step(&mut self, x: &HashMap<String, f64>, error: f64) {
+        for (name, xi) in x {
+            let w = self.weights.entry(name.clone()).or_insert(0.0);
+            *w -= self.lr * (error * xi + self.l2 * *w);
+        }
+        self.intercept -= self.lr * error;
+    }
+}
+
+/// Logistic regression trained on the log loss with SGD, with an L2 penalty of `l2` on the
+/// weights. The features are weighted as they come, unseen ones starting at 0.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LogisticRegression {
+    linear: Linear,
+}
+
+impl HeapSize for LogisticRegression {
+    fn heap_size(&self) -> usize {
+        self.linear.weights.heap_size()
+    }
+}
+
+impl LogisticRegression {
+    pub fn new(lr: f64, l2: f64) -> Self {
+        LogisticRegression {
+            linear: Linear::new(lr, l2),
+        }
+    }
+
+    /// Probability of the positive class.
+    pub fn predict_proba(&self, x: &HashMap<String, f64>) -> f64 {
+        sigmoid(self.linear.raw(x))
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>, y: bool) {
+        let error = self.predict_proba(x) - if y { 1.0 } else { 0.0 };
+        self.linear.step(x, error);
+    }
+}
+
+/// Linear regression trained on the squared loss with SGD, with an L2 penalty of `l2` on the
+/// weights.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LinearRegression {
+    linear: Linear,
+}
+
+impl HeapSize for LinearRegression {
+    fn heap_size(&self) -> usize {
+        self.linear.weights.heap_size()
+    }
+}
+
+impl LinearRegression {
+    pub fn new(lr: f64, l2: f64) -> Self {
+        LinearRegression {
+            linear: Linear::new(lr, l2),
+        }
+    }
+
+    pub fn predict(&self, x: &HashMap<String, f64>) -> f64 {
+        self.linear.raw(x)
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>, y: f64) {
+        let error = self.predict(x) - y;
+        self.linear.step(x, error);
+    }
+}
+
+fn check_rates(lr: f64, l2: f64) -> PyResult<()> {
+    if lr.is_nan() || lr <= 0.0 {
+        return Err(PyValueError::new_err("lr should be positive"));
+    }
+    if l2.is_nan() || l2 < 0.0 {
+        return Err(PyValueError::new_err("l2 should be non-negative"));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLogisticRegression {
+    pub(crate) model: LogisticRegression,
+}
+
+impl HeapSize for RsLogisticRegression {
+    fn heap_size(&self) -> usize {
+        self.model.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsLogisticRegression {
+    #[new]
+    #[args(lr = "0.01", l2 = "0.0")]
+    pub fn new(lr: f64, l2: f64) -> PyResult<Self> {
+        check_rates(lr, l2)?;
+        Ok(RsLogisticRegression {
+            model: LogisticRegression::new(lr, l2),
+        })
+    }
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: bool) {
+        self.model.learn_one(&x, y);
+    }
+    pub fn predict_proba_one(&self, x: HashMap<String, f64>) -> HashMap<bool, f64> {
+        let p = self.model.predict_proba(&x);
+        HashMap::from([(false, 1.0 - p), (true, p)])
+    }
+    pub fn predict_one(&self, x: HashMap<String, f64>) -> bool {
+        self.model.predict_proba(&x) >= 0.5
+    }
+    #[getter]
+    pub fn weights(&self) -> HashMap<String, f64> {
+        self.model.linear.weights.clone()
+    }
+    #[getter]
+    pub fn intercept(&self) -> f64 {
+        self.model.linear.intercept
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLinearRegression {
+    pub(crate) model: LinearRegression,
+}
+
+impl HeapSize for RsLinearRegression {
+    fn heap_size(&self) -> usize {
+        self.model.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsLinearRegression {
+    #[new]
+    #[args(lr = "0.01", l2 = "0.0")]
+    pub fn new(lr: f64, l2: f64) -> PyResult<Self> {
+        check_rates(lr, l2)?;
+        Ok(RsLinearRegression {
+            model: LinearRegression::new(lr, l2),
+        })
+    }
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: f64) {
+        self.model.learn_one(&x, y);
+    }
+    pub fn predict_one(&self, x: HashMap<String, f64>) -> f64 {
+        self.model.predict(&x)
+    }
+    #[getter]
+    pub fn weights(&self) -> HashMap<String, f64> {
+        self.model.linear.weights.clone()
+    }
+    #[getter]
+    pub fn intercept(&self) -> f64 {
+        self.model.linear.intercept
+    }
+}
@@ -0,0 +1,355 @@
+// Locality-sensitive hashing over a sliding window of labeled samples, for approximate
+// nearest neighbor queries in dimensions where exact search has to scan every sample.
+//
+// Each of the tables hashes a sample with a few random projections, so that the samples
+// sharing a bucket with a query in some table are likely to be near it. A query also probes
+// the buckets of the codes one step away from its own, the nearest boundaries first, which
+// finds as many neighbors with fewer tables.
+//
+// References:
+// - Datar, M., Immorlica, N., Indyk, P. and Mirrokni, V.S., 2004. Locality-sensitive hashing
+//   scheme based on p-stable distributions. In Proceedings of the twentieth annual symposium
+//   on Computational geometry, pp.253-262.
+// - Charikar, M.S., 2002. Similarity estimation techniques from rounding algorithms. In
+//   Proceedings of the thiry-fourth annual ACM symposium on Theory of computing, pp.380-388.
+// - Lv, Q., Josephson, W., Wang, Z., Charikar, M. and Li, K., 2007. Multi-probe LSH:
+//   efficient indexing for high-dimensional similarity search. In Proceedings of the 33rd
+//   international conference on Very large data bases, pp.950-961.
+
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIndexError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+#[cfg(feature = "python")]
+use crate::neighbors::FeatureOrder;
+use crate::neighbors::{Metric, Window};
+use crate::rng::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Family {
+    // Buckets of width `bucket_width` along Gaussian projections for the L2 distances, and
+    // along Cauchy ones for the L1 distance.
+    PStable { cauchy: bool },
+    // Side of random hyperplanes through the origin, for the cosine distance.
+    Hyperplane,
+}
+
+// A hash that can step to a neighboring code, as `(distance to the boundary, table, hash,
+// neighboring value)`.
+type Step = (f64, usize, usize, i64);
+
+// Bucket of a code, hashed rather than kept whole so that the tables stay small.
+fn bucket(code: &[i64]) -> u64 {
+    code.iter().fold(0xcbf2_9ce4_8422_2325, |h: u64, &c| {
+        (h ^ c as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Approximate nearest neighbors among the `window_size` most recent samples.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LshIndex {
+    window: Window,
+    metric: Metric,
+    family: Family,
+    n_tables: usize,
+    n_hashes: usize,
+    bucket_width: f64,
+    // One row of `n_features` values per hash of each table, drawn with the first sample.
+    projections: Vec<f64>,
+    offsets: Vec<f64>,
+    tables: Vec<HashMap<u64, VecDeque<u64>>>,
+    // Number of samples inserted so far, which is the id of the next one.
+    n_inserted: u64,
+    rng: Rng,
+}
+
+impl HeapSize for LshIndex {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+            + self.projections.heap_size()
+            + self.offsets.heap_size()
+            + self.tables.heap_size()
+    }
+}
+
+impl LshIndex {
+    /// Index of the samples by `n_tables` tables of `n_hashes` hashes each, for the Euclidean,
+    /// squared Euclidean, Manhattan or cosine `metric`. The buckets of the first three are
+    /// `bucket_width` wide, in the unit of the distance.
+    pub fn new(
+        window_size: usize,
+        n_tables: usize,
+        n_hashes: usize,
+        bucket_width: f64,
+        metric: Metric,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        let family = match metric {
+            Metric::Euclidean | Metric::SqEuclidean => Family::PStable { cauchy: false },
+            Metric::Manhattan => Family::PStable { cauchy: true },
+            Metric::Cosine => Family::Hyperplane,
+            _ => {
+                return Err(
+                    "the metric should be 'euclidean', 'sqeuclidean', 'manhattan' or 'cosine'"
+                        .to_string(),
+                )
+            }
+        };
+        if n_tables == 0 || n_hashes == 0 {
+            return Err("n_tables and n_hashes should be at least 1".to_string());
+        }
+        if bucket_width.is_nan() || bucket_width <= 0.0 || bucket_width.is_infinite() {
+            return Err("bucket_width should be positive and finite".to_string());
+        }
+        Ok(LshIndex {
+            window: Window::new(window_size)?,
+            metric,
+            family,
+            n_tables,
+            n_hashes,
+            bucket_width,
+            projections: Vec::new(),
+            offsets: Vec::new(),
+            tables: vec![HashMap::new(); n_tables],
+            n_inserted: 0,
+            rng: Rng::new(seed),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    fn draw_projections(&mut self, n_features: usize) {
+        let n = self.n_tables * self.n_hashes;
+        self.projections = (0..n * n_features)
+            .map(|_| match self.family {
+                Family::PStable { cauchy: true } => (PI * (self.rng.next_f64() - 0.5)).tan(),
+                _ => self.rng.normal(),
+            })
+            .collect();
+        self.offsets = (0..n)
+            .map(|_| self.rng.next_f64() * self.bucket_width)
+            .collect();
+    }
+
+    // Code of `x` in each table, along with its steps from the nearest boundary.
+    fn codes(&self, x: &[f64]) -> (Vec<Vec<i64>>, Vec<Step>) {
+        let n_features = x.len();
+        let mut codes = vec![vec![0; self.n_hashes]; self.n_tables];
+        let mut steps = Vec::new();
+        for (t, code) in codes.iter_mut().enumerate() {
+            for (j, value) in code.iter_mut().enumerate() {
+                let h = t * self.n_hashes + j;
+                let projection = &self.projections[h * n_features..(h + 1) * n_features];
+                let dot: f64 = projection.iter().zip(x).map(|(a, v)| a * v).sum();
+                match self.family {
+                    Family::PStable { .. } => {
+                        let position = (dot + self.offsets[h]) / self.bucket_width;
+                        let floor = position.floor();
+                        *value = floor as i64;
+                        steps.push((position - floor, t, j, *value - 1));
+                        steps.push((floor + 1.0 - position, t, j, *value + 1));
+                    }
+                    Family::Hyperplane => {
+                        *value = (dot >= 0.0) as i64;
+                        steps.push((dot.abs(), t, j, 1 - *value));
+                    }
+                }
+            }
+        }
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+        (codes, steps)
+    }
+
+    /// Inserts a sample, which should have as many values as the first one, and returns the
+    /// label of the one it evicts once the window is full.
+    pub fn insert(&mut self, x: &[f64], y: Key) -> Option<Key> {
+        if self.n_inserted == 0 {
+            self.draw_projections(x.len());
+        }
+        if self.window.len() == self.window.window_size() {
+            let oldest = self.n_inserted - self.window.len() as u64;
+            let (codes, _) = self.codes(self.window.row(0));
+            for (table, code) in self.tables.iter_mut().zip(&codes) {
+                let key = bucket(code);
+                if let Some(ids) = table.get_mut(&key) {
+                    // The ids of a bucket are in the order of insertion.
+                    if ids.front() == Some(&oldest) {
+                        ids.pop_front();
+                    }
+                    if ids.is_empty() {
+                        table.remove(&key);
+                    }
+                }
+            }
+        }
+        let (codes, _) = self.codes(x);
+        for (table, code) in self.tables.iter_mut().zip(&codes) {
+            table
+                .entry(bucket(code))
+                .or_default()
+                .push_back(self.n_inserted);
+        }
+        self.n_inserted += 1;
+        self.window.append(x, y)
+    }
+
+    /// Indices of at most `k` samples near `x`, among those sharing its bucket or one of the
+    /// `probes` nearest buckets in any table, from the nearest to the farthest, along with
+    /// their distances. The index of the oldest sample is 0, and the ties go to the oldest.
+    pub fn query(&self, x: &[f64], k: usize, probes: usize) -> Vec<(usize, f64)> {
+        if self.is_empty() || k == 0 {
+            return Vec::new();
+        }
+        let (codes, steps) = self.codes(x);
+        let mut ids: Vec<u64> = Vec::new();
+        let mut probe = |table: usize, code: &[i64]| {
+            if let Some(bucket_ids) = self.tables[table].get(&bucket(code)) {
+                ids.extend(bucket_ids);
+            }
+        };
+        for (t, code) in codes.iter().enumerate() {
+            probe(t, code);
+        }
+        let mut n_probed = vec![0; self.n_tables];
+        for &(_, t, j, value) in &steps {
+            if n_probed[t] < probes {
+                n_probed[t] += 1;
+                let mut code = codes[t].clone();
+                code[j] = value;
+                probe(t, &code);
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        let first = self.n_inserted - self.window.len() as u64;
+        let mut candidates: Vec<(usize, f64)> = ids
+            .into_iter()
+            .map(|id| {
+                let i = (id - first) as usize;
+                (i, self.metric.distance(x, self.window.row(i)))
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(k);
+        candidates
+    }
+}
+
+/// Approximate nearest neighbors among a sliding window of labeled samples.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLSHIndex {
+    index: LshIndex,
+    features: FeatureOrder,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsLSHIndex {
+    fn heap_size(&self) -> usize {
+        self.index.heap_size() + self.features.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsLSHIndex {
+    /// Keeps the `window_size` most recent samples in `n_tables` tables of `n_hashes` hashes
+    /// each. The `"euclidean"`, `"sqeuclidean"` and `"manhattan"` metrics hash the samples
+    /// into buckets `bucket_width` wide along random projections, and the `"cosine"` one by
+    /// their side of random hyperplanes.
+    #[new]
+    #[args(
+        n_tables = "10",
+        n_hashes = "8",
+        bucket_width = "4.0",
+        metric = "\"euclidean\"",
+        seed = "None"
+    )]
+    pub fn new(
+        window_size: usize,
+        n_tables: usize,
+        n_hashes: usize,
+        bucket_width: f64,
+        metric: &str,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let metric = Metric::new(metric, 2.0).map_err(PyValueError::new_err)?;
+        let index = LshIndex::new(window_size, n_tables, n_hashes, bucket_width, metric, seed)
+            .map_err(PyValueError::new_err)?;
+        Ok(RsLSHIndex {
+            index,
+            features: FeatureOrder::default(),
+        })
+    }
+
+    /// Inserts a dict of features and its `int` or `str` label, and returns the label of the
+    /// evicted sample once the window is full.
+    pub fn insert(&mut self, x: HashMap<String, f64>, y: Key) -> Option<Key> {
+        let x = self.features.learn(&x);
+        self.index.insert(&x, y)
+    }
+
+    /// Indices of at most `k` samples near `x`, the oldest sample being 0, and their
+    /// distances, from the nearest to the farthest. Beside the bucket of `x`, the `probes`
+    /// buckets with the nearest boundaries are searched in each table.
+    #[args(probes = "0")]
+    pub fn query(
+        &self,
+        py: Python,
+        x: HashMap<String, f64>,
+        k: usize,
+        probes: usize,
+    ) -> (Vec<usize>, Vec<f64>) {
+        let x = self.features.read(&x);
+        py.allow_threads(|| self.index.query(&x, k, probes))
+            .into_iter()
+            .unzip()
+    }
+
+    /// Labels of the samples, from the oldest to the most recent.
+    pub fn labels(&self) -> Vec<Key> {
+        let window = self.index.window();
+        (0..window.len()).map(|i| window.label(i).clone()).collect()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Features and label of the `i`-th oldest sample.
+    pub fn __getitem__(&self, i: isize) -> PyResult<(HashMap<String, f64>, Key)> {
+        let window = self.index.window();
+        let n = window.len() as isize;
+        let i = if i < 0 { i + n } else { i };
+        if !(0..n).contains(&i) {
+            return Err(PyIndexError::new_err("index out of range"));
+        }
+        let i = i as usize;
+        let x = self
+            .features
+            .names()
+            .iter()
+            .cloned()
+            .zip(window.row(i).iter().copied())
+            .collect();
+        Ok((x, window.label(i).clone()))
+    }
+}
@@ -0,0 +1,105 @@
+// Memory accounting of the bindings, so that `sys.getsizeof` and the memory usage reported
+// by river include the windows, sketches and buffers held on the Rust side.
+//
+// `HeapSize` counts the bytes allocated on the heap by a value, which is its capacity
+// rather than its length for the collections. `__sizeof__` adds the size of the Python
+// object itself.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::mem::size_of;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::PyCell;
+use watermill::variance::Variance;
+
+/// Number of bytes a value owns on the heap, not counting its own size.
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($ty:ty),* $(,)?) => {
+        $(impl HeapSize for $ty {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+no_heap!(usize, u64, f64, Variance<f64>);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for HashMap<K, V> {
+    fn heap_size(&self) -> usize {
+        // The hashbrown table stores one control byte per bucket next to the entries.
+        self.capacity() * (size_of::<(K, V)>() + 1)
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+// Only the elements are counted, not the spare room of the B-tree nodes.
+impl<T: HeapSize> HeapSize for BTreeSet<T> {
+    fn heap_size(&self) -> usize {
+        self.len() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.len() * size_of::<(K, V)>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(feature = "python")]
+macro_rules! measurable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                pub fn __sizeof__(&self) -> usize {
+                    size_of::<PyCell<Self>>() + self.heap_size()
+                }
+                /// Number of bytes held on the heap by the windows, sketches and buffers.
+                pub fn memory_usage(&self) -> usize {
+                    self.heap_size()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "python")]
+for_each_binding!(measurable);
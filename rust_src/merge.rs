@@ -0,0 +1,38 @@
+// In-place addition of the stats and sketches which can be merged, so that `a += b` folds
+// the observations of `b` into `a` as `a.merge(b)` does. The result is the same as if `a`
+// had seen both streams, up to the approximation of the sketches.
+
+use pyo3::prelude::*;
+
+use crate::bindings::{RsKurtosis, RsPeakToPeak, RsSkew};
+use crate::covariance::{RsCov, RsCovMatrix, RsPearsonCorr};
+use crate::stats::{RsCountMin, RsHistogram, RsHyperLogLog, RsMAD, RsSpaceSaving, RsTDigest};
+use crate::univariate::IntoPyResult;
+
+macro_rules! mergeable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                pub fn __iadd__(&mut self, other: PyRef<$ty>) -> PyResult<()> {
+                    self.merge(other).into_result()
+                }
+            }
+        )*
+    };
+}
+
+mergeable!(
+    RsKurtosis,
+    RsSkew,
+    RsPeakToPeak,
+    RsCov,
+    RsPearsonCorr,
+    RsCovMatrix,
+    RsHistogram,
+    RsTDigest,
+    RsMAD,
+    RsCountMin,
+    RsHyperLogLog,
+    RsSpaceSaving,
+);
@@ -0,0 +1,149 @@
+// Online metrics of binary classifiers, which `evaluate_progressive` updates without going
+// through Python when the model is Rust-backed too.
+//
+// They follow the interface of the `river.metrics` classes: `update(y_true, y_pred)`,
+// `get`, and the `bigger_is_better` and `requires_labels` properties.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+
+// Probabilities are clipped away from 0 and 1 before taking their log.
+const EPSILON: f64 = 1e-15;
+
+/// Mean binary cross-entropy.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct LogLoss {
+    sum: f64,
+    n: f64,
+}
+
+impl LogLoss {
+    pub fn update(&mut self, y_true: bool, p: f64) {
+        let p = p.clamp(EPSILON, 1.0 - EPSILON);
+        self.sum -= if y_true { p.ln() } else { (1.0 - p).ln() };
+        self.n += 1.0;
+    }
+
+    pub fn get(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        self.sum / self.n
+    }
+}
+
+/// Share of correct predictions.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Accuracy {
+    correct: f64,
+    n: f64,
+}
+
+impl Accuracy {
+    pub fn update(&mut self, correct: bool) {
+        if correct {
+            self.correct += 1.0;
+        }
+        self.n += 1.0;
+    }
+
+    pub fn get(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        self.correct / self.n
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLogLoss {
+    pub(crate) metric: LogLoss,
+}
+
+impl HeapSize for RsLogLoss {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsLogLoss {
+    #[new]
+    pub fn new() -> Self {
+        RsLogLoss {
+            metric: LogLoss::default(),
+        }
+    }
+    /// `y_pred` is the probability of the positive class.
+    pub fn update(&mut self, y_true: bool, y_pred: f64) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&y_pred) {
+            return Err(PyValueError::new_err("y_pred should be between 0 and 1"));
+        }
+        self.metric.update(y_true, y_pred);
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.metric.get()
+    }
+    #[getter]
+    pub fn bigger_is_better(&self) -> bool {
+        false
+    }
+    #[getter]
+    pub fn requires_labels(&self) -> bool {
+        false
+    }
+}
+
+impl Default for RsLogLoss {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAccuracy {
+    pub(crate) metric: Accuracy,
+}
+
+impl HeapSize for RsAccuracy {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsAccuracy {
+    #[new]
+    pub fn new() -> Self {
+        RsAccuracy {
+            metric: Accuracy::default(),
+        }
+    }
+    pub fn update(&mut self, y_true: Key, y_pred: Key) {
+        self.metric.update(y_true == y_pred);
+    }
+    pub fn get(&self) -> f64 {
+        self.metric.get()
+    }
+    #[getter]
+    pub fn bigger_is_better(&self) -> bool {
+        true
+    }
+    #[getter]
+    pub fn requires_labels(&self) -> bool {
+        true
+    }
+}
+
+impl Default for RsAccuracy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,181 @@
+// Combination of the central moments of two disjoint samples, so that the shape statistics
+// computed on separate shards can be aggregated without exchanging the raw data, addition of
+// a weighted value, as the merge of a sample of copies of it, and removal of a single value,
+// which undoes its merge. The co-moments of a pair of variables are combined the same way.
+//
+// Reference: Pébay, P., 2008. Formulas for robust, one-pass parallel computation of
+// covariances and arbitrary-order statistical moments. Sandia Report SAND2008-6212.
+
+use serde::{Deserialize, Serialize};
+use watermill::moments::CentralMoments;
+
+use crate::memory::HeapSize;
+
+/// Folds the moments of `b` into `a`.
+pub fn merge_central_moments(a: &mut CentralMoments<f64>, b: &CentralMoments<f64>) {
+    let (na, nb) = (a.count.count, b.count.count);
+    if nb == 0.0 {
+        return;
+    }
+    if na == 0.0 {
+        *a = *b;
+        return;
+    }
+    let n = na + nb;
+    let delta = b.sum_delta - a.sum_delta;
+    let delta2 = delta * delta;
+    let (m2a, m3a, m4a) = (a.m2, a.m3, a.m4);
+    let (m2b, m3b, m4b) = (b.m2, b.m3, b.m4);
+
+    a.m4 = m4a
+        + m4b
+        + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+        + 6.0 * delta2 * (na * na * m2b + nb * nb * m2a) / (n * n)
+        + 4.0 * delta * (na * m3b - nb * m3a) / n;
+    a.m3 = m3a
+        + m3b
+        + delta2 * delta * na * nb * (na - nb) / (n * n)
+        + 3.0 * delta * (na * m2b - nb * m2a) / n;
+    a.m2 = m2a + m2b + delta2 * na * nb / n;
+    a.sum_delta += delta * nb / n;
+    a.count.count = n;
+}
+
+/// Folds in a value `x` of weight `w`, as `w` copies of it.
+pub fn update_central_moments(m: &mut CentralMoments<f64>, x: f64, w: f64) {
+    let mut b = CentralMoments::new();
+    b.count.count = w;
+    b.sum_delta = x;
+    merge_central_moments(m, &b);
+}
+
+/// Removes a value `x` seen before from the moments, by solving the merge of the remaining
+/// values with `x` for the former.
+pub fn revert_central_moments(m: &mut CentralMoments<f64>, x: f64) -> Result<(), String> {
+    let n = m.count.count;
+    if n < 1.0 {
+        return Err("cannot revert more values than were seen".to_string());
+    }
+    if n == 1.0 {
+        *m = CentralMoments::new();
+        return Ok(());
+    }
+    let na = n - 1.0;
+    let mean_a = (n * m.sum_delta - x) / na;
+    let delta = x - mean_a;
+    let delta2 = delta * delta;
+
+    let m2a = m.m2 - delta2 * na / n;
+    let m3a = m.m3 - delta2 * delta * na * (na - 1.0) / (n * n) + 3.0 * delta * m2a / n;
+    let m4a = m.m4
+        - delta2 * delta2 * na * (na * na - na + 1.0) / (n * n * n)
+        - 6.0 * delta2 * m2a / (n * n)
+        + 4.0 * delta * m3a / n;
+    m.m2 = m2a;
+    m.m3 = m3a;
+    m.m4 = m4a;
+    m.sum_delta = mean_a;
+    m.count.count = na;
+    Ok(())
+}
+
+/// Weighted means, sums of squared deviations and co-moment of a pair of variables, from which
+/// their covariance and Pearson correlation are derived.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct CoMoments {
+    n: f64,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    c: f64,
+}
+
+impl HeapSize for CoMoments {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl CoMoments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total weight of the pairs seen.
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+
+    /// Folds in the pair `(x, y)` with weight `w`, as `w` copies of it.
+    pub fn update(&mut self, x: f64, y: f64, w: f64) {
+        self.merge(&CoMoments {
+            n: w,
+            mean_x: x,
+            mean_y: y,
+            ..CoMoments::default()
+        });
+    }
+
+    /// Removes a pair `(x, y)` of weight `w` seen before, by solving the merge of the
+    /// remaining pairs with it for the former.
+    pub fn revert(&mut self, x: f64, y: f64, w: f64) -> Result<(), String> {
+        let n = self.n;
+        if w > n {
+            return Err("cannot revert more weight than was seen".to_string());
+        }
+        let na = n - w;
+        if na == 0.0 {
+            *self = CoMoments::default();
+            return Ok(());
+        }
+        let mean_x = (n * self.mean_x - w * x) / na;
+        let mean_y = (n * self.mean_y - w * y) / na;
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        self.m2_x -= dx * dx * na * w / n;
+        self.m2_y -= dy * dy * na * w / n;
+        self.c -= dx * dy * na * w / n;
+        self.mean_x = mean_x;
+        self.mean_y = mean_y;
+        self.n = na;
+        Ok(())
+    }
+
+    /// Folds in the pairs summarized by `other`.
+    pub fn merge(&mut self, other: &CoMoments) {
+        let (na, nb) = (self.n, other.n);
+        if nb == 0.0 {
+            return;
+        }
+        let n = na + nb;
+        let dx = other.mean_x - self.mean_x;
+        let dy = other.mean_y - self.mean_y;
+        self.mean_x += dx * nb / n;
+        self.mean_y += dy * nb / n;
+        self.m2_x += other.m2_x + dx * dx * na * nb / n;
+        self.m2_y += other.m2_y + dy * dy * na * nb / n;
+        self.c += other.c + dx * dy * na * nb / n;
+        self.n = n;
+    }
+
+    /// Covariance with `ddof` delta degrees of freedom, 0 until more than `ddof` pairs are
+    /// seen.
+    pub fn cov(&self, ddof: u32) -> f64 {
+        let ddof = ddof as f64;
+        if self.n > ddof {
+            self.c / (self.n - ddof)
+        } else {
+            0.0
+        }
+    }
+
+    /// Pearson correlation, 0 while either variable is constant.
+    pub fn corr(&self) -> f64 {
+        let denom = (self.m2_x * self.m2_y).sqrt();
+        if denom > 0.0 {
+            self.c / denom
+        } else {
+            0.0
+        }
+    }
+}
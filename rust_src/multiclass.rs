@@ -0,0 +1,276 @@
+// Reductions of multi-class problems to binary ones over native classifiers, like
+// `river.multiclass`.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::linear::{LogisticRegression, RsLogisticRegression};
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+use crate::special::log_sum_exp;
+
+// Probabilities are clipped away from 0 and 1 before taking their log.
+const EPSILON: f64 = 1e-12;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Coding {
+    Random,
+    Exact,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Decoding {
+    Soft,
+    Hamming,
+}
+
+/// Error-correcting output codes.
+///
+/// Each class is given a code of `code_size` bits when it is first seen, and the `i`-th
+/// classifier learns the `i`-th bit of the code of the label. A prediction is the class
+/// whose code is the closest to the outputs of the classifiers.
+///
+/// Reference: Dietterich, T.G. and Bakiri, G., 1994. Solving multiclass learning problems
+/// via error-correcting output codes. Journal of Artificial Intelligence Research, 2,
+/// pp.263-286.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OutputCodeClassifier {
+    classifiers: Vec<LogisticRegression>,
+    // In the order in which the classes are first seen, which breaks the ties.
+    codes: Vec<(Key, Vec<bool>)>,
+    coding: Coding,
+    decoding: Decoding,
+    rng: Rng,
+}
+
+impl HeapSize for OutputCodeClassifier {
+    fn heap_size(&self) -> usize {
+        self.classifiers.heap_size()
+            + self
+                .codes
+                .iter()
+                .map(|(label, code)| label.heap_size() + code.capacity())
+                .sum::<usize>()
+    }
+}
+
+impl OutputCodeClassifier {
+    fn new_code(&mut self) -> PyResult<Vec<bool>> {
+        let code_size = self.classifiers.len();
+        if self.coding == Coding::Exact
+            && code_size < 64
+            && self.codes.len() as u64 >= 1 << code_size
+        {
+            return Err(PyValueError::new_err(format!(
+                "the {} distinct codes of {} bits are all taken",
+                1u64 << code_size,
+                code_size
+            )));
+        }
+        loop {
+            let code: Vec<bool> = (0..code_size).map(|_| self.rng.below(2) == 1).collect();
+            if self.coding == Coding::Random || self.codes.iter().all(|(_, c)| *c != code) {
+                return Ok(code);
+            }
+        }
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>, y: Key) -> PyResult<()> {
+        let i = match self.codes.iter().position(|(label, _)| *label == y) {
+            Some(i) => i,
+            None => {
+                let code = self.new_code()?;
+                self.codes.push((y, code));
+                self.codes.len() - 1
+            }
+        };
+        let code = &self.codes[i].1;
+        for (classifier, &bit) in self.classifiers.iter_mut().zip(code) {
+            classifier.learn_one(x, bit);
+        }
+        Ok(())
+    }
+
+    /// Hamming distance, which is 0 with the soft decoding, and soft distance of the outputs
+    /// to the code of each class.
+    pub fn distances(&self, x: &HashMap<String, f64>) -> Vec<(usize, f64)> {
+        let outputs: Vec<f64> = self
+            .classifiers
+            .iter()
+            .map(|classifier| classifier.predict_proba(x))
+            .collect();
+        self.codes
+            .iter()
+            .map(|(_, code)| {
+                let mut hamming = 0;
+                let mut soft = 0.0;
+                for (&bit, &p) in code.iter().zip(&outputs) {
+                    let target = if bit { 1.0 } else { 0.0 };
+                    soft += (target - p).abs();
+                    if self.decoding == Decoding::Hamming && bit != (p >= 0.5) {
+                        hamming += 1;
+                    }
+                }
+                (hamming, soft)
+            })
+            .collect()
+    }
+
+    /// Probability of each class, in the order in which they were first seen, that the
+    /// classifiers output its code, treating their bits as independent.
+    pub fn predict_proba(&self, x: &HashMap<String, f64>) -> Vec<(Key, f64)> {
+        let outputs: Vec<f64> = self
+            .classifiers
+            .iter()
+            .map(|classifier| classifier.predict_proba(x).clamp(EPSILON, 1.0 - EPSILON))
+            .collect();
+        let log_likelihoods: Vec<f64> = self
+            .codes
+            .iter()
+            .map(|(_, code)| {
+                code.iter()
+                    .zip(&outputs)
+                    .map(|(&bit, &p)| if bit { p.ln() } else { (1.0 - p).ln() })
+                    .sum()
+            })
+            .collect();
+        let lse = log_sum_exp(&log_likelihoods);
+        self.codes
+            .iter()
+            .zip(log_likelihoods)
+            .map(|((label, _), ll)| (label.clone(), (ll - lse).exp()))
+            .collect()
+    }
+
+    pub fn predict_one(&self, x: &HashMap<String, f64>) -> Option<Key> {
+        let distances = self.distances(x);
+        let mut best: Option<usize> = None;
+        for (i, d) in distances.iter().enumerate() {
+            if best.is_none_or(|b| {
+                let b = distances[b];
+                d.0 < b.0 || (d.0 == b.0 && d.1 < b.1)
+            }) {
+                best = Some(i);
+            }
+        }
+        best.map(|i| self.codes[i].0.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsOutputCodeClassifier {
+    pub(crate) model: OutputCodeClassifier,
+}
+
+impl HeapSize for RsOutputCodeClassifier {
+    fn heap_size(&self) -> usize {
+        self.model.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsOutputCodeClassifier {
+    /// Trains `code_size` copies of `classifier`, a `RsLogisticRegression`, one per bit of
+    /// the codes. The codes are drawn at random, independently with the `"random"` coding
+    /// method, and distinct from those of the other classes with the `"exact"` one.
+    ///
+    /// The `"soft"` decoding picks the class minimizing the sum of the absolute differences
+    /// between its bits and the probabilities of the classifiers, and the `"hamming"` one
+    /// the class with the fewest bits unlike the predictions of the classifiers, the ties
+    /// being broken by the soft distance.
+    #[new]
+    #[args(coding_method = "\"random\"", decoding = "\"soft\"", seed = "None")]
+    pub fn new(
+        classifier: PyRef<RsLogisticRegression>,
+        code_size: usize,
+        coding_method: &str,
+        decoding: &str,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        if code_size == 0 {
+            return Err(PyValueError::new_err("code_size should be at least 1"));
+        }
+        let coding = match coding_method {
+            "random" => Coding::Random,
+            "exact" => Coding::Exact,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown coding_method '{}', expected 'random' or 'exact'",
+                    coding_method
+                )))
+            }
+        };
+        let decoding = match decoding {
+            "soft" => Decoding::Soft,
+            "hamming" => Decoding::Hamming,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown decoding '{}', expected 'soft' or 'hamming'",
+                    decoding
+                )))
+            }
+        };
+        Ok(RsOutputCodeClassifier {
+            model: OutputCodeClassifier {
+                classifiers: vec![classifier.model.clone(); code_size],
+                codes: Vec::new(),
+                coding,
+                decoding,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+
+    /// `y` is an `int` or a `str` label.
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: Key) -> PyResult<()> {
+        self.model.learn_one(&x, y)
+    }
+
+    /// Label whose code is the closest to the outputs, or `None` before any is seen.
+    pub fn predict_one(&self, x: HashMap<String, f64>) -> Option<Key> {
+        self.model.predict_one(&x)
+    }
+
+    /// Probability of each class that the classifiers output its code.
+    pub fn predict_proba_one(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (label, p) in self.model.predict_proba(&x) {
+            dict.set_item(label.into_py(py), p)?;
+        }
+        Ok(dict.into())
+    }
+
+    /// Distance of the outputs to the code of each class, the Hamming one with the
+    /// `"hamming"` decoding, and the soft one otherwise.
+    pub fn distances(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let hamming = self.model.decoding == Decoding::Hamming;
+        let dict = PyDict::new(py);
+        for ((label, _), (h, soft)) in self.model.codes.iter().zip(self.model.distances(&x)) {
+            let distance = if hamming { h as f64 } else { soft };
+            dict.set_item(label.clone().into_py(py), distance)?;
+        }
+        Ok(dict.into())
+    }
+
+    /// Code of each class seen so far, as a list of 0s and 1s.
+    #[getter]
+    pub fn code_book(&self, py: Python) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (label, code) in &self.model.codes {
+            let bits: Vec<u8> = code.iter().map(|&bit| bit as u8).collect();
+            dict.set_item(label.clone().into_py(py), bits)?;
+        }
+        Ok(dict.into())
+    }
+
+    #[getter]
+    pub fn code_size(&self) -> usize {
+        self.model.classifiers.len()
+    }
+}
@@ -0,0 +1,245 @@
+// Chains of native models over the outputs of multi-output problems, like
+// `river.multioutput`.
+//
+// The model of each output sees the features along with the outputs before it in the chain,
+// the true ones when learning and the predicted ones when predicting, so that a whole chain
+// is trained or queried in one call.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::linear::{
+    LinearRegression, LogisticRegression, RsLinearRegression, RsLogisticRegression,
+};
+use crate::memory::HeapSize;
+
+/// Native model of one output of a chain, whose outputs are fed to the next ones as floats.
+trait Link: Clone {
+    fn learn(&mut self, x: &HashMap<String, f64>, y: f64);
+    fn predict(&self, x: &HashMap<String, f64>) -> f64;
+}
+
+impl Link for LogisticRegression {
+    fn learn(&mut self, x: &HashMap<String, f64>, y: f64) {
+        self.learn_one(x, y != 0.0);
+    }
+
+    fn predict(&self, x: &HashMap<String, f64>) -> f64 {
+        if self.predict_proba(x) >= 0.5 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Link for LinearRegression {
+    fn learn(&mut self, x: &HashMap<String, f64>, y: f64) {
+        self.learn_one(x, y);
+    }
+
+    fn predict(&self, x: &HashMap<String, f64>) -> f64 {
+        LinearRegression::predict(self, x)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Chain<M> {
+    template: M,
+    // Empty until the order is given or read from the first target.
+    order: Vec<String>,
+    models: Vec<M>,
+}
+
+impl<M: HeapSize> HeapSize for Chain<M> {
+    fn heap_size(&self) -> usize {
+        self.template.heap_size() + self.order.heap_size() + self.models.heap_size()
+    }
+}
+
+impl<M: Link> Chain<M> {
+    fn new(template: M, order: Option<Vec<String>>) -> Self {
+        let mut chain = Chain {
+            template,
+            order: Vec::new(),
+            models: Vec::new(),
+        };
+        if let Some(order) = order {
+            chain.set_order(order);
+        }
+        chain
+    }
+
+    fn set_order(&mut self, order: Vec<String>) {
+        self.models = vec![self.template.clone(); order.len()];
+        self.order = order;
+    }
+
+    fn learn_one(&mut self, x: &HashMap<String, f64>, y: &HashMap<String, f64>) -> PyResult<()> {
+        let targets = self
+            .order
+            .iter()
+            .map(|name| {
+                y.get(name)
+                    .copied()
+                    .ok_or_else(|| PyValueError::new_err(format!("y lacks the output '{}'", name)))
+            })
+            .collect::<PyResult<Vec<f64>>>()?;
+        let mut x = x.clone();
+        for ((model, name), target) in self.models.iter_mut().zip(&self.order).zip(targets) {
+            model.learn(&x, target);
+            x.insert(name.clone(), target);
+        }
+        Ok(())
+    }
+
+    // Walks down the chain, `f` returning the output fed to the next models and the one
+    // reported.
+    fn predict_with<T>(
+        &self,
+        x: &HashMap<String, f64>,
+        f: impl Fn(&M, &HashMap<String, f64>) -> (f64, T),
+    ) -> Vec<T> {
+        let mut x = x.clone();
+        let mut outputs = Vec::with_capacity(self.models.len());
+        for (model, name) in self.models.iter().zip(&self.order) {
+            let (fed, output) = f(model, &x);
+            x.insert(name.clone(), fed);
+            outputs.push(output);
+        }
+        outputs
+    }
+}
+
+// The names and values of the outputs, the former in the order of the dict, which is that of
+// the chain when it isn't given.
+fn extract_outputs(y: &PyDict) -> PyResult<(Vec<String>, HashMap<String, f64>)> {
+    let mut names = Vec::with_capacity(y.len());
+    let mut values = HashMap::with_capacity(y.len());
+    for (name, value) in y {
+        let name: String = name.extract()?;
+        values.insert(name.clone(), value.extract()?);
+        names.push(name);
+    }
+    Ok((names, values))
+}
+
+fn to_dict<T: IntoPy<PyObject>>(
+    py: Python,
+    names: &[String],
+    values: Vec<T>,
+) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    for (name, value) in names.iter().zip(values) {
+        dict.set_item(name, value.into_py(py))?;
+    }
+    Ok(dict.into())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsClassifierChain {
+    chain: Chain<LogisticRegression>,
+}
+
+impl HeapSize for RsClassifierChain {
+    fn heap_size(&self) -> usize {
+        self.chain.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsClassifierChain {
+    /// Trains a copy of `model`, a `RsLogisticRegression`, per output, in the `order` of
+    /// the outputs, or that of the keys of the first target when it isn't given.
+    #[new]
+    #[args(order = "None")]
+    pub fn new(model: PyRef<RsLogisticRegression>, order: Option<Vec<String>>) -> Self {
+        RsClassifierChain {
+            chain: Chain::new(model.model.clone(), order),
+        }
+    }
+
+    /// `y` maps the name of each output to its label.
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: &PyDict) -> PyResult<()> {
+        let (names, y) = extract_outputs(y)?;
+        if self.chain.order.is_empty() {
+            self.chain.set_order(names);
+        }
+        self.chain.learn_one(&x, &y)
+    }
+
+    pub fn predict_one(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let labels = self.chain.predict_with(&x, |model, x| {
+            let label = model.predict(x);
+            (label, label != 0.0)
+        });
+        to_dict(py, &self.chain.order, labels)
+    }
+
+    /// Probabilities of the labels of each output, given the labels predicted before it.
+    pub fn predict_proba_one(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let probas = self.chain.predict_with(&x, |model, x| {
+            let p = model.predict_proba(x);
+            let proba = HashMap::from([(false, 1.0 - p), (true, p)]);
+            (if p >= 0.5 { 1.0 } else { 0.0 }, proba)
+        });
+        to_dict(py, &self.chain.order, probas)
+    }
+
+    #[getter]
+    pub fn order(&self) -> Vec<String> {
+        self.chain.order.clone()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRegressorChain {
+    chain: Chain<LinearRegression>,
+}
+
+impl HeapSize for RsRegressorChain {
+    fn heap_size(&self) -> usize {
+        self.chain.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsRegressorChain {
+    /// Trains a copy of `model`, a `RsLinearRegression`, per output, in the `order` of the
+    /// outputs, or that of the keys of the first target when it isn't given.
+    #[new]
+    #[args(order = "None")]
+    pub fn new(model: PyRef<RsLinearRegression>, order: Option<Vec<String>>) -> Self {
+        RsRegressorChain {
+            chain: Chain::new(model.model.clone(), order),
+        }
+    }
+
+    /// `y` maps the name of each output to its value.
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: &PyDict) -> PyResult<()> {
+        let (names, y) = extract_outputs(y)?;
+        if self.chain.order.is_empty() {
+            self.chain.set_order(names);
+        }
+        self.chain.learn_one(&x, &y)
+    }
+
+    pub fn predict_one(&self, py: Python, x: HashMap<String, f64>) -> PyResult<PyObject> {
+        let values = self.chain.predict_with(&x, |model, x| {
+            let value = model.predict(x);
+            (value, value)
+        });
+        to_dict(py, &self.chain.order, values)
+    }
+
+    #[getter]
+    pub fn order(&self) -> Vec<String> {
+        self.chain.order.clone()
+    }
+}
@@ -0,0 +1,785 @@
+// Brute-force nearest neighbors over labeled samples stored row-major, which back the
+// neighbor-based estimators.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use numpy::PyArray1;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIndexError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "python")]
+use crate::ingest::{Matrix, Values};
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+
+// Lower bound of the distances when weighting the votes by their inverse.
+const MIN_DISTANCE: f64 = 1e-9;
+
+// Number of partial sums of `sq_euclidean`, enough to fill two 256-bit registers.
+const LANES: usize = 8;
+
+/// Squared Euclidean distance between two samples.
+///
+/// The features are summed in chunks of `LANES` into as many independent partial sums, which
+/// the compiler turns into SIMD instructions, unlike a single running sum whose additions
+/// have to happen in order.
+pub fn sq_euclidean(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    let (a, b) = (&a[..n], &b[..n]);
+    let (chunks_a, chunks_b) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let tail: f64 = chunks_a
+        .remainder()
+        .iter()
+        .zip(chunks_b.remainder())
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum();
+    let mut sums = [0.0; LANES];
+    for (chunk_a, chunk_b) in chunks_a.zip(chunks_b) {
+        for i in 0..LANES {
+            let d = chunk_a[i] - chunk_b[i];
+            sums[i] += d * d;
+        }
+    }
+    sums.iter().sum::<f64>() + tail
+}
+
+/// Distance between two samples.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Metric {
+    SqEuclidean,
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    /// One minus the cosine similarity, and 1 when either sample is all zeros.
+    Cosine,
+    /// `(sum |a_i - b_i|^p)^(1/p)`, with `p` at least 1.
+    Minkowski(f64),
+}
+
+impl Metric {
+    /// Metric named `"sqeuclidean"`, `"euclidean"`, `"manhattan"`, `"chebyshev"`, `"cosine"`
+    /// or `"minkowski"`, the last one of order `p`.
+    pub fn new(name: &str, p: f64) -> Result<Self, String> {
+        Ok(match name {
+            "sqeuclidean" => Metric::SqEuclidean,
+            "euclidean" => Metric::Euclidean,
+            "manhattan" => Metric::Manhattan,
+            "chebyshev" => Metric::Chebyshev,
+            "cosine" => Metric::Cosine,
+            "minkowski" if p.is_nan() || p < 1.0 => {
+                return Err(format!("p should be at least 1, got {}", p))
+            }
+            "minkowski" if p == f64::INFINITY => Metric::Chebyshev,
+            "minkowski" => Metric::Minkowski(p),
+            _ => {
+                return Err(format!(
+                    "unknown metric '{}', expected 'sqeuclidean', 'euclidean', 'manhattan', \
+                     'chebyshev', 'cosine' or 'minkowski'",
+                    name
+                ))
+            }
+        })
+    }
+
+    pub fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        let diffs = a.iter().zip(b).map(|(x, y)| (x - y).abs());
+        match *self {
+            Metric::SqEuclidean => sq_euclidean(a, b),
+            Metric::Euclidean => sq_euclidean(a, b).sqrt(),
+            Metric::Manhattan => diffs.sum(),
+            Metric::Chebyshev => diffs.fold(0.0, f64::max),
+            Metric::Cosine => {
+                let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+                for (x, y) in a.iter().zip(b) {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+                }
+            }
+            Metric::Minkowski(p) => diffs.map(|d| d.powf(p)).sum::<f64>().powf(1.0 / p),
+        }
+    }
+}
+
+/// Distances from `x` to each row of `samples`, which holds rows of `x.len()` values.
+pub fn one_to_n_distances(x: &[f64], samples: &[f64], metric: Metric) -> Vec<f64> {
+    if x.is_empty() {
+        return Vec::new();
+    }
+    samples
+        .chunks_exact(x.len())
+        .map(|row| metric.distance(x, row))
+        .collect()
+}
+
+/// Same as `one_to_n_distances`, with the rows split among the threads of the current rayon
+/// pool.
+pub fn par_one_to_n_distances(x: &[f64], samples: &[f64], metric: Metric) -> Vec<f64> {
+    if x.is_empty() {
+        return Vec::new();
+    }
+    samples
+        .par_chunks_exact(x.len())
+        // Batches of rows amortize the scheduling over short rows.
+        .with_min_len(256)
+        .map(|row| metric.distance(x, row))
+        .collect()
+}
+
+/// Indices of the `k` smallest values, from the smallest to the largest, ties going to the
+/// lowest index. All of them when there are fewer than `k`.
+pub fn k_smallest(k: usize, values: &[f64]) -> Vec<usize> {
+    let k = k.min(values.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    let cmp = |&i: &usize, &j: &usize| values[i].total_cmp(&values[j]).then(i.cmp(&j));
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    indices.select_nth_unstable_by(k - 1, cmp);
+    indices.truncate(k);
+    indices.sort_unstable_by(cmp);
+    indices
+}
+
+/// Label with the most votes among `neighbors`, given as pairs of a label and its squared
+/// distance. With `weighted`, each neighbor votes with the inverse of its distance, and
+/// otherwise with 1. Ties go to the lowest label, and there is none without neighbors.
+pub fn vote<'a>(neighbors: impl Iterator<Item = (&'a Key, f64)>, weighted: bool) -> Option<Key> {
+    let mut votes: BTreeMap<&Key, f64> = BTreeMap::new();
+    for (label, d) in neighbors {
+        let weight = if weighted {
+            1.0 / d.sqrt().max(MIN_DISTANCE)
+        } else {
+            1.0
+        };
+        *votes.entry(label).or_insert(0.0) += weight;
+    }
+    let mut best: Option<(&Key, f64)> = None;
+    for (label, v) in votes {
+        if best.is_none_or(|(_, max)| v > max) {
+            best = Some((label, v));
+        }
+    }
+    best.map(|(label, _)| label.clone())
+}
+
+/// Centers of `k` clusters of `samples`, which holds rows of `n_features` values, found with
+/// Lloyd's algorithm from a k-means++ seeding. The samples themselves when there are at most
+/// `k` of them.
+///
+/// Reference: Arthur, D. and Vassilvitskii, S., 2007. k-means++: The advantages of careful
+/// seeding. In Proceedings of the eighteenth annual ACM-SIAM symposium on Discrete
+/// algorithms, pp.1027-1035.
+pub fn kmeans(samples: &[f64], n_features: usize, k: usize, rng: &mut Rng) -> Vec<f64> {
+    if n_features == 0 {
+        return Vec::new();
+    }
+    let rows: Vec<&[f64]> = samples.chunks_exact(n_features).collect();
+    if rows.len() <= k {
+        return samples.to_vec();
+    }
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let first = rng.below(rows.len() as u64) as usize;
+    let mut centers = rows[first].to_vec();
+    let mut closest: Vec<f64> = rows
+        .iter()
+        .map(|row| sq_euclidean(row, rows[first]))
+        .collect();
+    for _ in 1..k {
+        let total: f64 = closest.iter().sum();
+        let next = if total > 0.0 {
+            let mut target = rng.next_f64() * total;
+            closest
+                .iter()
+                .position(|&d| {
+                    target -= d;
+                    target < 0.0
+                })
+                .unwrap_or(rows.len() - 1)
+        } else {
+            rng.below(rows.len() as u64) as usize
+        };
+        centers.extend_from_slice(rows[next]);
+        for (d, row) in closest.iter_mut().zip(&rows) {
+            *d = d.min(sq_euclidean(row, rows[next]));
+        }
+    }
+
+    let mut assignments = vec![usize::MAX; rows.len()];
+    for _ in 0..300 {
+        let mut changed = false;
+        for (assignment, row) in assignments.iter_mut().zip(&rows) {
+            let nearest = centers
+                .chunks_exact(n_features)
+                .map(|center| sq_euclidean(row, center))
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .map(|(c, _)| c)
+                .unwrap_or(0);
+            if *assignment != nearest {
+                *assignment = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![0.0; k * n_features];
+        let mut counts = vec![0usize; k];
+        for (&c, row) in assignments.iter().zip(&rows) {
+            counts[c] += 1;
+            for (sum, x) in sums[c * n_features..(c + 1) * n_features]
+                .iter_mut()
+                .zip(*row)
+            {
+                *sum += x;
+            }
+        }
+        // An empty cluster keeps its center.
+        for (c, &count) in counts.iter().enumerate() {
+            if count > 0 {
+                for j in c * n_features..(c + 1) * n_features {
+                    centers[j] = sums[j] / count as f64;
+                }
+            }
+        }
+    }
+    centers
+}
+
+/// Samples of `n_features` values each, stored row-major, along with their labels.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LabeledSamples {
+    n_features: usize,
+    values: Vec<f64>,
+    labels: Vec<Key>,
+}
+
+impl HeapSize for LabeledSamples {
+    fn heap_size(&self) -> usize {
+        self.values.heap_size() + self.labels.heap_size()
+    }
+}
+
+impl LabeledSamples {
+    pub fn new(n_features: usize) -> Self {
+        LabeledSamples {
+            n_features,
+            ..Default::default()
+        }
+    }
+
+    pub fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.values[i * self.n_features..(i + 1) * self.n_features]
+    }
+
+    pub fn label(&self, i: usize) -> &Key {
+        &self.labels[i]
+    }
+
+    pub fn labels(&self) -> &[Key] {
+        &self.labels
+    }
+
+    /// Appends a sample, which should have `n_features` values.
+    pub fn push(&mut self, x: &[f64], y: Key) {
+        debug_assert_eq!(x.len(), self.n_features);
+        self.values.extend_from_slice(x);
+        self.labels.push(y);
+    }
+
+    pub fn append(&mut self, other: &mut LabeledSamples) {
+        self.values.append(&mut other.values);
+        self.labels.append(&mut other.labels);
+    }
+
+    /// Removes the `n` oldest samples, or all of them when there are fewer, and returns them.
+    pub fn drain_front(&mut self, n: usize) -> LabeledSamples {
+        let n = n.min(self.len());
+        LabeledSamples {
+            n_features: self.n_features,
+            values: self.values.drain(..n * self.n_features).collect(),
+            labels: self.labels.drain(..n).collect(),
+        }
+    }
+
+    /// Removes the samples at the given indices.
+    pub fn remove(&mut self, indices: &[usize]) {
+        let mut keep = vec![true; self.len()];
+        for &i in indices {
+            keep[i] = false;
+        }
+        let f = self.n_features;
+        let mut i = 0;
+        self.values.retain(|_| {
+            i += 1;
+            keep[(i - 1) / f]
+        });
+        let mut i = 0;
+        self.labels.retain(|_| {
+            i += 1;
+            keep[i - 1]
+        });
+    }
+
+    /// Squared Euclidean distances from `x` to each sample.
+    pub fn distances(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.len())
+            .map(|i| sq_euclidean(x, self.row(i)))
+            .collect()
+    }
+
+    /// Label of the `k` samples nearest to a point, given its squared `distances` to each
+    /// sample, by a vote weighted or not.
+    pub fn k_nearest_label(&self, distances: &[f64], k: usize, weighted: bool) -> Option<Key> {
+        let nearest = k_smallest(k, distances);
+        vote(
+            nearest.iter().map(|&i| (&self.labels[i], distances[i])),
+            weighted,
+        )
+    }
+}
+
+/// The `window_size` most recent samples, stored contiguously in a ring along with their
+/// labels, the oldest being overwritten by each new one once the window is full.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Window {
+    window_size: usize,
+    n_features: usize,
+    values: Vec<f64>,
+    labels: Vec<Key>,
+    // Slot of the oldest sample, which is 0 until the window is full.
+    oldest: usize,
+}
+
+impl HeapSize for Window {
+    fn heap_size(&self) -> usize {
+        self.values.heap_size() + self.labels.heap_size()
+    }
+}
+
+impl Window {
+    pub fn new(window_size: usize) -> Result<Self, String> {
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        Ok(Window {
+            window_size,
+            n_features: 0,
+            values: Vec::new(),
+            labels: Vec::new(),
+            oldest: 0,
+        })
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    fn slot(&self, i: usize) -> usize {
+        (self.oldest + i) % self.len()
+    }
+
+    /// Values of the `i`-th oldest sample.
+    pub fn row(&self, i: usize) -> &[f64] {
+        let slot = self.slot(i);
+        &self.values[slot * self.n_features..(slot + 1) * self.n_features]
+    }
+
+    /// Label of the `i`-th oldest sample.
+    pub fn label(&self, i: usize) -> &Key {
+        &self.labels[self.slot(i)]
+    }
+
+    /// Appends a sample, which should have as many values as the first one, and returns the
+    /// label of the one it evicts.
+    pub fn append(&mut self, x: &[f64], y: Key) -> Option<Key> {
+        if self.is_empty() {
+            self.n_features = x.len();
+        }
+        debug_assert_eq!(x.len(), self.n_features);
+        if self.len() < self.window_size {
+            self.values.extend_from_slice(x);
+            self.labels.push(y);
+            return None;
+        }
+        let slot = self.oldest;
+        self.values[slot * self.n_features..(slot + 1) * self.n_features].copy_from_slice(x);
+        self.oldest = (slot + 1) % self.window_size;
+        Some(std::mem::replace(&mut self.labels[slot], y))
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.labels.clear();
+        self.oldest = 0;
+    }
+
+    /// Indices of the `k` samples nearest to `x`, from the nearest to the farthest, along with
+    /// their distances. The index of the oldest sample is 0, and the ties go to the oldest.
+    pub fn search(&self, x: &[f64], k: usize, metric: Metric) -> Vec<(usize, f64)> {
+        let distances: Vec<f64> = (0..self.len())
+            .map(|i| metric.distance(x, self.row(i)))
+            .collect();
+        k_smallest(k, &distances)
+            .into_iter()
+            .map(|i| (i, distances[i]))
+            .collect()
+    }
+}
+
+/// Order in which the values of the feature dicts are stored, fixed by the first dict. The
+/// features missing from a later dict are read as 0, and its new ones are ignored.
+#[cfg(feature = "python")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct FeatureOrder(Option<Vec<String>>);
+
+#[cfg(feature = "python")]
+impl HeapSize for FeatureOrder {
+    fn heap_size(&self) -> usize {
+        self.0.as_ref().map_or(0, |names| names.heap_size())
+    }
+}
+
+#[cfg(feature = "python")]
+impl FeatureOrder {
+    /// Values of `x`, which fixes the order when it is the first dict.
+    pub fn learn(&mut self, x: &HashMap<String, f64>) -> Vec<f64> {
+        if self.0.is_none() {
+            let mut names: Vec<String> = x.keys().cloned().collect();
+            names.sort();
+            self.0 = Some(names);
+        }
+        self.read(x)
+    }
+
+    pub fn read(&self, x: &HashMap<String, f64>) -> Vec<f64> {
+        self.names()
+            .iter()
+            .map(|name| x.get(name).copied().unwrap_or(0.0))
+            .collect()
+    }
+
+    pub fn names(&self) -> &[String] {
+        self.0.as_deref().unwrap_or_default()
+    }
+}
+
+/// Sliding window of labeled samples, searched for the nearest neighbors in Rust.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsWindow {
+    window: Window,
+    metric: Metric,
+    features: FeatureOrder,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsWindow {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size() + self.features.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsWindow {
+    /// Keeps the `window_size` most recent samples, compared by one of the metrics of
+    /// `get_distance`.
+    #[new]
+    #[args(metric = "\"euclidean\"", p = "2.0")]
+    pub fn new(window_size: usize, metric: &str, p: f64) -> PyResult<Self> {
+        Ok(RsWindow {
+            window: Window::new(window_size).map_err(PyValueError::new_err)?,
+            metric: Metric::new(metric, p).map_err(PyValueError::new_err)?,
+            features: FeatureOrder::default(),
+        })
+    }
+
+    /// Appends a dict of features and its `int` or `str` label, and returns the label of the
+    /// evicted sample once the window is full.
+    pub fn append(&mut self, x: HashMap<String, f64>, y: Key) -> Option<Key> {
+        let x = self.features.learn(&x);
+        self.window.append(&x, y)
+    }
+
+    /// Indices of the `k` samples nearest to `x`, the oldest sample being 0, and their
+    /// distances, from the nearest to the farthest.
+    pub fn search(&self, py: Python, x: HashMap<String, f64>, k: usize) -> (Vec<usize>, Vec<f64>) {
+        let x = self.features.read(&x);
+        py.allow_threads(|| self.window.search(&x, k, self.metric))
+            .into_iter()
+            .unzip()
+    }
+
+    /// Labels of the samples, from the oldest to the most recent.
+    pub fn labels(&self) -> Vec<Key> {
+        (0..self.window.len())
+            .map(|i| self.window.label(i).clone())
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.window.clear();
+    }
+
+    #[getter]
+    pub fn window_size(&self) -> usize {
+        self.window.window_size()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Features and label of the `i`-th oldest sample.
+    pub fn __getitem__(&self, i: isize) -> PyResult<(HashMap<String, f64>, Key)> {
+        let n = self.window.len() as isize;
+        let i = if i < 0 { i + n } else { i };
+        if !(0..n).contains(&i) {
+            return Err(PyIndexError::new_err("window index out of range"));
+        }
+        let i = i as usize;
+        let x = self
+            .features
+            .names()
+            .iter()
+            .cloned()
+            .zip(self.window.row(i).iter().copied())
+            .collect();
+        Ok((x, self.window.label(i).clone()))
+    }
+}
+
+/// Distance between two 1D arrays of the same length, by one of the metrics of `Metric`,
+/// squared Euclidean by default.
+#[cfg(feature = "python")]
+#[pyfunction(metric = "\"sqeuclidean\"", p = "2.0")]
+pub fn get_distance(a: &PyAny, b: &PyAny, metric: &str, p: f64) -> PyResult<f64> {
+    let metric = Metric::new(metric, p).map_err(PyValueError::new_err)?;
+    let a = Values::extract(a)?.slices().concat();
+    let b = Values::extract(b)?.slices().concat();
+    if a.len() != b.len() {
+        return Err(PyValueError::new_err(format!(
+            "the arrays should have the same length, got {} and {}",
+            a.len(),
+            b.len()
+        )));
+    }
+    Ok(metric.distance(&a, &b))
+}
+
+/// Distances from the 1D array `x` to each row of the 2D array `samples`, which should have
+/// as many columns as `x` has values, by the same metrics as `get_distance`.
+///
+/// The distances are computed with the GIL released, on one thread with `n_jobs=1`, on as
+/// many threads as there are cores with `n_jobs=-1`, and on `n_jobs` threads otherwise.
+#[cfg(feature = "python")]
+#[pyfunction(metric = "\"sqeuclidean\"", p = "2.0", n_jobs = "1")]
+pub fn get_1_to_n_distances<'py>(
+    py: Python<'py>,
+    x: &PyAny,
+    samples: &PyAny,
+    metric: &str,
+    p: f64,
+    n_jobs: isize,
+) -> PyResult<&'py PyArray1<f64>> {
+    let metric = Metric::new(metric, p).map_err(PyValueError::new_err)?;
+    let pool = match n_jobs {
+        1 | -1 => None,
+        n if n > 1 => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n as usize)
+                .build()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+        ),
+        _ => return Err(PyValueError::new_err("n_jobs should be -1 or at least 1")),
+    };
+    let x = Values::extract(x)?.slices().concat();
+    let samples = Matrix::extract(samples)?;
+    // An empty list of rows has no columns to check.
+    if samples.n_cols != x.len() && !samples.as_slice().is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "samples should have {} columns like x, got {}",
+            x.len(),
+            samples.n_cols
+        )));
+    }
+    let samples = samples.as_slice();
+    let distances = py.allow_threads(|| match (n_jobs, pool) {
+        (1, _) => one_to_n_distances(&x, samples, metric),
+        (_, Some(pool)) => pool.install(|| par_one_to_n_distances(&x, samples, metric)),
+        (_, None) => par_one_to_n_distances(&x, samples, metric),
+    });
+    Ok(PyArray1::from_vec(py, distances))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sq_euclidean_matches_a_scalar_loop() {
+        let mut rng = Rng::from_seed(3);
+        // Lengths around the multiples of `LANES`, with and without a remainder.
+        for n in [0, 1, 7, 8, 9, 17] {
+            let a: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+            let b: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+            let mut expected = 0.0;
+            for i in 0..n {
+                expected += (a[i] - b[i]) * (a[i] - b[i]);
+            }
+            assert!((sq_euclidean(&a, &b) - expected).abs() < 1e-12, "n={}", n);
+        }
+        // The longer sample is cut to the length of the shorter one.
+        assert_eq!(sq_euclidean(&[1.0, 2.0, 3.0], &[0.0, 0.0]), 5.0);
+    }
+
+    #[test]
+    fn distances_of_each_metric() {
+        let (a, b) = ([0.0, 0.0, 1.0], [3.0, 4.0, 1.0]);
+        let distance = |name: &str, p: f64| Metric::new(name, p).unwrap().distance(&a, &b);
+        assert_eq!(distance("sqeuclidean", 2.0), 25.0);
+        assert_eq!(distance("euclidean", 2.0), 5.0);
+        assert_eq!(distance("manhattan", 2.0), 7.0);
+        assert_eq!(distance("chebyshev", 2.0), 4.0);
+        assert!((distance("minkowski", 3.0) - 91f64.cbrt()).abs() < 1e-12);
+        assert_eq!(
+            Metric::new("minkowski", f64::INFINITY),
+            Ok(Metric::Chebyshev)
+        );
+        assert!((distance("cosine", 2.0) - (1.0 - 1.0 / 26f64.sqrt())).abs() < 1e-12);
+        assert_eq!(Metric::Cosine.distance(&[0.0, 0.0], &[1.0, 2.0]), 1.0);
+        assert!(Metric::new("minkowski", 0.5).is_err());
+        assert!(Metric::new("minkowski", f64::NAN).is_err());
+        assert!(Metric::new("hamming", 2.0).is_err());
+    }
+
+    #[test]
+    fn parallel_distances_match_the_serial_ones() {
+        let mut rng = Rng::from_seed(1);
+        let samples: Vec<f64> = (0..5 * 1000).map(|_| rng.normal()).collect();
+        let x = [0.5, -1.0, 0.0, 2.0, 1.0];
+        let serial = one_to_n_distances(&x, &samples, Metric::Manhattan);
+        assert_eq!(serial.len(), 1000);
+        assert_eq!(
+            serial,
+            par_one_to_n_distances(&x, &samples, Metric::Manhattan)
+        );
+        assert!(one_to_n_distances(&[], &samples, Metric::Manhattan).is_empty());
+    }
+
+    #[test]
+    fn k_smallest_breaks_the_ties_by_index() {
+        let values = [3.0, 1.0, 2.0, 1.0, 0.5];
+        assert_eq!(k_smallest(3, &values), vec![4, 1, 3]);
+        assert_eq!(k_smallest(10, &values), vec![4, 1, 3, 2, 0]);
+        assert!(k_smallest(0, &values).is_empty());
+    }
+
+    #[test]
+    fn votes_of_the_neighbors() {
+        let (a, b) = (Key::Int(1), Key::Int(0));
+        let neighbors = [(&a, 1.0), (&a, 1.0), (&b, 0.01)];
+        assert_eq!(vote(neighbors.iter().copied(), false), Some(a.clone()));
+        // 1 / 0.1 outweighs twice 1 / 1.
+        assert_eq!(vote(neighbors.iter().copied(), true), Some(b.clone()));
+        assert_eq!(vote([(&a, 1.0), (&b, 1.0)].into_iter(), false), Some(b));
+        assert_eq!(vote(std::iter::empty(), true), None);
+    }
+
+    #[test]
+    fn kmeans_finds_separated_clusters() {
+        let mut rng = Rng::from_seed(2);
+        let mut samples = Vec::new();
+        for center in [-10.0, 0.0, 10.0] {
+            for _ in 0..50 {
+                samples.extend([center + rng.normal() * 0.1, rng.normal() * 0.1]);
+            }
+        }
+        let centers = kmeans(&samples, 2, 3, &mut rng);
+        let mut xs: Vec<f64> = centers.chunks_exact(2).map(|c| c[0]).collect();
+        xs.sort_by(f64::total_cmp);
+        for (x, expected) in xs.iter().zip([-10.0, 0.0, 10.0]) {
+            assert!((x - expected).abs() < 0.1);
+        }
+        assert_eq!(kmeans(&samples[..4], 2, 3, &mut rng), samples[..4].to_vec());
+    }
+
+    #[test]
+    fn labeled_samples_are_removed_and_drained() {
+        let mut samples = LabeledSamples::new(2);
+        for i in 0..5 {
+            samples.push(&[i as f64, -(i as f64)], Key::Int(i));
+        }
+        samples.remove(&[1, 3]);
+        assert_eq!(samples.labels(), &[Key::Int(0), Key::Int(2), Key::Int(4)]);
+        assert_eq!(samples.row(1), &[2.0, -2.0]);
+        let mut front = samples.drain_front(2);
+        assert_eq!(front.labels(), &[Key::Int(0), Key::Int(2)]);
+        assert_eq!(samples.len(), 1);
+        front.append(&mut samples);
+        assert!(samples.is_empty());
+        assert_eq!(front.distances(&[4.0, -4.0]), vec![32.0, 8.0, 0.0]);
+        assert_eq!(
+            front.k_nearest_label(&[32.0, 8.0, 0.0], 1, false),
+            Some(Key::Int(4))
+        );
+    }
+
+    #[test]
+    fn window_overwrites_the_oldest_sample() {
+        assert!(Window::new(0).is_err());
+        let mut window = Window::new(3).unwrap();
+        for i in 0..3 {
+            assert_eq!(window.append(&[i as f64], Key::Int(i)), None);
+        }
+        assert_eq!(window.append(&[3.0], Key::Int(3)), Some(Key::Int(0)));
+        assert_eq!(window.row(0), &[1.0]);
+        assert_eq!(window.label(2), &Key::Int(3));
+        assert_eq!(
+            window.search(&[2.2], 2, Metric::Euclidean),
+            vec![(1, 0.20000000000000018), (2, 0.7999999999999998)]
+        );
+        window.clear();
+        assert!(window.is_empty());
+    }
+}
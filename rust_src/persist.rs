@@ -0,0 +1,64 @@
+// Checkpointing of the bindings straight to files, without going through pickle.
+//
+// The state is the same versioned payload as `__getstate__`, streamed into the file, and
+// optionally gzip-compressed. `load` recognizes compressed files by their magic bytes.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use pyo3::prelude::*;
+
+use crate::state::{read_state, write_state, Versioned};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn save<T: Versioned>(state: &T, path: &Path, compress: bool) -> PyResult<()> {
+    let file = BufWriter::new(File::create(path)?);
+    if compress {
+        let encoder = GzEncoder::new(file, Compression::default());
+        write_state(encoder, state)?.finish()?.flush()?;
+    } else {
+        write_state(file, state)?.flush()?;
+    }
+    Ok(())
+}
+
+fn load<T: Versioned>(path: &Path) -> PyResult<T> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut magic = Vec::with_capacity(GZIP_MAGIC.len());
+    file.by_ref()
+        .take(GZIP_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let file = magic.as_slice().chain(file);
+    if magic == GZIP_MAGIC {
+        read_state(GzDecoder::new(file))
+    } else {
+        read_state(file)
+    }
+}
+
+macro_rules! persistable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                /// Writes the state to `path`, gzip-compressed if `compress` is true.
+                #[args(compress = "false")]
+                pub fn save(&self, path: PathBuf, compress: bool) -> PyResult<()> {
+                    save(self, &path, compress)
+                }
+                /// Reads a state written by `save`.
+                #[staticmethod]
+                pub fn load(path: PathBuf) -> PyResult<Self> {
+                    load(&path)
+                }
+            }
+        )*
+    };
+}
+
+for_each_binding!(persistable);
@@ -0,0 +1,491 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use watermill::{stats::Univariate, variance::Variance};
+
+use crate::linalg::{cholesky, forward_substitution, log_det_cholesky};
+use crate::memory::HeapSize;
+use crate::special::{log_sum_exp, normal_cdf, normal_pdf};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CovarianceType {
+    Full,
+    Diag,
+}
+
+impl CovarianceType {
+    pub fn parse(name: &str) -> Result<CovarianceType, String> {
+        match name {
+            "full" => Ok(CovarianceType::Full),
+            "diag" => Ok(CovarianceType::Diag),
+            _ => Err(format!(
+                "covariance_type should be 'full' or 'diag', got '{}'",
+                name
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Component {
+    weight: f64,
+    mean: Vec<f64>,
+    // Weighted sum of squared deviations, n * n for full covariances and n for diagonal ones.
+    scatter: Vec<f64>,
+}
+
+impl HeapSize for Component {
+    fn heap_size(&self) -> usize {
+        self.mean.heap_size() + self.scatter.heap_size()
+    }
+}
+
+/// Gaussian mixture fitted with incremental EM.
+///
+/// The first `n_components` observations seed the component means. Every subsequent
+/// observation is softly assigned to the components and their sufficient statistics are
+/// updated with the responsibilities. Each covariance is shrunk towards the running
+/// per-feature variance of the whole stream with a pseudo-count of one, which keeps freshly
+/// seeded components from collapsing onto a single point.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GaussianMixture {
+    n_components: usize,
+    covariance_type: CovarianceType,
+    reg_covar: f64,
+    features: Vec<String>,
+    feature_index: HashMap<String, usize>,
+    global_var: Vec<Variance<f64>>,
+    components: Vec<Component>,
+}
+
+impl HeapSize for GaussianMixture {
+    fn heap_size(&self) -> usize {
+        self.features.heap_size()
+            + self.feature_index.heap_size()
+            + self.global_var.heap_size()
+            + self.components.heap_size()
+    }
+}
+
+impl GaussianMixture {
+    pub fn new(n_components: usize, covariance_type: CovarianceType, reg_covar: f64) -> Self {
+        GaussianMixture {
+            n_components,
+            covariance_type,
+            reg_covar,
+            features: Vec::new(),
+            feature_index: HashMap::new(),
+            global_var: Vec::new(),
+            components: Vec::with_capacity(n_components),
+        }
+    }
+
+    fn n_features(&self) -> usize {
+        self.features.len()
+    }
+
+    // The feature layout is fixed by the first observation, absent features are read as 0
+    // and unknown ones are refused, since the fitted components know nothing of them.
+    fn index_features(&mut self, x: &HashMap<String, f64>) -> Result<Vec<f64>, String> {
+        if self.features.is_empty() {
+            let mut names: Vec<String> = x.keys().cloned().collect();
+            names.sort();
+            for (i, name) in names.iter().enumerate() {
+                self.feature_index.insert(name.clone(), i);
+            }
+            self.global_var = vec![Variance::new(0); names.len()];
+            self.features = names;
+        } else if let Some(name) = x.keys().find(|k| !self.feature_index.contains_key(*k)) {
+            return Err(format!(
+                "feature '{}' isn't one of those of the first observation",
+                name
+            ));
+        }
+        Ok(self.as_vec(x))
+    }
+
+    fn as_vec(&self, x: &HashMap<String, f64>) -> Vec<f64> {
+        let mut v = vec![0.0; self.n_features()];
+        for (name, value) in x {
+            if let Some(&i) = self.feature_index.get(name) {
+                v[i] = *value;
+            }
+        }
+        v
+    }
+
+    fn prior_var(&self, i: usize) -> f64 {
+        let var = self.global_var[i].get();
+        if var > 0.0 {
+            var
+        } else {
+            1.0
+        }
+    }
+
+    fn log_density(&self, c: &Component, x: &[f64]) -> f64 {
+        let n = self.n_features();
+        let norm = n as f64 * (2.0 * PI).ln();
+        match self.covariance_type {
+            CovarianceType::Diag => {
+                let mut log_det = 0.0;
+                let mut maha = 0.0;
+                for (i, (xi, mi)) in x.iter().zip(&c.mean).enumerate() {
+                    let var =
+                        (c.scatter[i] + self.prior_var(i)) / (c.weight + 1.0) + self.reg_covar;
+                    log_det += var.ln();
+                    maha += (xi - mi).powi(2) / var;
+                }
+                -0.5 * (norm + log_det + maha)
+            }
+            CovarianceType::Full => {
+                let mut cov = vec![0.0; n * n];
+                for i in 0..n {
+                    for j in 0..n {
+                        cov[i * n + j] = c.scatter[i * n + j] / (c.weight + 1.0);
+                    }
+                    cov[i * n + i] += self.prior_var(i) / (c.weight + 1.0) + self.reg_covar;
+                }
+                let l = match cholesky(&cov, n) {
+                    Some(l) => l,
+                    None => return f64::NEG_INFINITY,
+                };
+                let diff: Vec<f64> = x.iter().zip(&c.mean).map(|(a, b)| a - b).collect();
+                let z = forward_substitution(&l, n, &diff);
+                let maha: f64 = z.iter().map(|v| v * v).sum();
+                -0.5 * (norm + log_det_cholesky(&l, n) + maha)
+            }
+        }
+    }
+
+    // Returns the log of the weighted component densities along with their log-sum-exp.
+    fn weighted_log_densities(&self, x: &[f64]) -> (Vec<f64>, f64) {
+        let total: f64 = self.components.iter().map(|c| c.weight).sum();
+        let log_probs: Vec<f64> = self
+            .components
+            .iter()
+            .map(|c| (c.weight / total).ln() + self.log_density(c, x))
+            .collect();
+        let lse = log_sum_exp(&log_probs);
+        (log_probs, lse)
+    }
+
+    fn responsibilities(&self, x: &[f64]) -> Vec<f64> {
+        let (log_probs, lse) = self.weighted_log_densities(x);
+        if lse == f64::NEG_INFINITY {
+            return vec![1.0 / log_probs.len() as f64; log_probs.len()];
+        }
+        log_probs.iter().map(|lp| (lp - lse).exp()).collect()
+    }
+
+    pub fn learn_one(&mut self, x: &HashMap<String, f64>) -> Result<(), String> {
+        // A dict without any feature can't fix the layout, and is skipped until one does.
+        if self.features.is_empty() && x.is_empty() {
+            return Ok(());
+        }
+        let x = self.index_features(x)?;
+        let n = self.n_features();
+        for (var, xi) in self.global_var.iter_mut().zip(&x) {
+            var.update(*xi);
+        }
+        if self.components.len() < self.n_components {
+            let scatter_len = match self.covariance_type {
+                CovarianceType::Full => n * n,
+                CovarianceType::Diag => n,
+            };
+            self.components.push(Component {
+                weight: 1.0,
+                mean: x,
+                scatter: vec![0.0; scatter_len],
+            });
+            return Ok(());
+        }
+        let resp = self.responsibilities(&x);
+        let covariance_type = self.covariance_type;
+        for (c, r) in self.components.iter_mut().zip(resp) {
+            if r <= 0.0 {
+                continue;
+            }
+            c.weight += r;
+            let before: Vec<f64> = x.iter().zip(&c.mean).map(|(a, b)| a - b).collect();
+            for (m, d) in c.mean.iter_mut().zip(&before) {
+                *m += r / c.weight * d;
+            }
+            let after: Vec<f64> = x.iter().zip(&c.mean).map(|(a, b)| a - b).collect();
+            match covariance_type {
+                CovarianceType::Diag => {
+                    for ((s, b), a) in c.scatter.iter_mut().zip(&before).zip(&after) {
+                        *s += r * b * a;
+                    }
+                }
+                CovarianceType::Full => {
+                    for (row, b) in c.scatter.chunks_mut(n).zip(&before) {
+                        for (s, a) in row.iter_mut().zip(&after) {
+                            *s += r * b * a;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn predict_proba_one(&self, x: &HashMap<String, f64>) -> HashMap<usize, f64> {
+        if self.components.is_empty() {
+            return HashMap::new();
+        }
+        let x = self.as_vec(x);
+        self.responsibilities(&x).into_iter().enumerate().collect()
+    }
+
+    pub fn predict_one(&self, x: &HashMap<String, f64>) -> Option<usize> {
+        self.predict_proba_one(x)
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(k, _)| k)
+    }
+
+    pub fn score_one(&self, x: &HashMap<String, f64>) -> f64 {
+        if self.components.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+        let x = self.as_vec(x);
+        self.weighted_log_densities(&x).1
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        let total: f64 = self.components.iter().map(|c| c.weight).sum();
+        self.components.iter().map(|c| c.weight / total).collect()
+    }
+
+    pub fn means(&self) -> Vec<HashMap<String, f64>> {
+        self.components
+            .iter()
+            .map(|c| {
+                self.features
+                    .iter()
+                    .cloned()
+                    .zip(c.mean.iter().cloned())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsGaussianMixture {
+    gmm: GaussianMixture,
+    n_components: usize,
+    covariance_type: String,
+    reg_covar: f64,
+}
+
+impl HeapSize for RsGaussianMixture {
+    fn heap_size(&self) -> usize {
+        self.gmm.heap_size() + self.covariance_type.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsGaussianMixture {
+    #[new]
+    #[args(covariance_type = "\"full\"", reg_covar = "1e-6")]
+    pub fn new(n_components: usize, covariance_type: &str, reg_covar: f64) -> PyResult<Self> {
+        if n_components == 0 {
+            return Err(PyValueError::new_err("n_components should be at least 1"));
+        }
+        if reg_covar.is_nan() || reg_covar < 0.0 {
+            return Err(PyValueError::new_err("reg_covar should be non-negative"));
+        }
+        let cov_type = CovarianceType::parse(covariance_type).map_err(PyValueError::new_err)?;
+        Ok(RsGaussianMixture {
+            gmm: GaussianMixture::new(n_components, cov_type, reg_covar),
+            n_components,
+            covariance_type: covariance_type.to_string(),
+            reg_covar,
+        })
+    }
+    pub fn learn_one(&mut self, x: HashMap<String, f64>) -> PyResult<()> {
+        self.gmm.learn_one(&x).map_err(PyValueError::new_err)
+    }
+    pub fn predict_proba_one(&self, x: HashMap<String, f64>) -> HashMap<usize, f64> {
+        self.gmm.predict_proba_one(&x)
+    }
+    pub fn predict_one(&self, x: HashMap<String, f64>) -> Option<usize> {
+        self.gmm.predict_one(&x)
+    }
+    /// Log-density of `x` under the mixture.
+    pub fn score_one(&self, x: HashMap<String, f64>) -> f64 {
+        self.gmm.score_one(&x)
+    }
+    #[getter]
+    pub fn weights(&self) -> Vec<f64> {
+        self.gmm.weights()
+    }
+    #[getter]
+    pub fn means(&self) -> Vec<HashMap<String, f64>> {
+        self.gmm.means()
+    }
+}
+
+/// Univariate Gaussian kernel density estimator over a bounded set of kernel centers.
+///
+/// Each observation becomes a center of weight one. Once there are more than `max_centers`
+/// centers, the two closest neighbouring ones are merged into their weighted average. When
+/// no bandwidth is given, Silverman's rule of thumb is applied to the running variance.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KernelDensity {
+    bandwidth: Option<f64>,
+    max_centers: usize,
+    // Sorted by position.
+    centers: Vec<(f64, f64)>,
+    var: Variance<f64>,
+}
+
+impl HeapSize for KernelDensity {
+    fn heap_size(&self) -> usize {
+        self.centers.heap_size()
+    }
+}
+
+impl KernelDensity {
+    pub fn new(bandwidth: Option<f64>, max_centers: usize) -> Self {
+        KernelDensity {
+            bandwidth,
+            max_centers,
+            centers: Vec::with_capacity(max_centers + 1),
+            var: Variance::new(1),
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.var.update(x);
+        let pos = self.centers.partition_point(|&(c, _)| c < x);
+        if pos < self.centers.len() && self.centers[pos].0 == x {
+            self.centers[pos].1 += 1.0;
+        } else {
+            self.centers.insert(pos, (x, 1.0));
+        }
+        if self.centers.len() > self.max_centers {
+            let i = (0..self.centers.len() - 1)
+                .min_by(|&a, &b| {
+                    let gap_a = self.centers[a + 1].0 - self.centers[a].0;
+                    let gap_b = self.centers[b + 1].0 - self.centers[b].0;
+                    gap_a.total_cmp(&gap_b)
+                })
+                .unwrap();
+            let (c1, w1) = self.centers[i];
+            let (c2, w2) = self.centers.remove(i + 1);
+            self.centers[i] = ((c1 * w1 + c2 * w2) / (w1 + w2), w1 + w2);
+        }
+    }
+
+    pub fn bandwidth(&self) -> f64 {
+        if let Some(h) = self.bandwidth {
+            return h;
+        }
+        let n = self.var.mean.n.get();
+        let std = self.var.get().sqrt();
+        if std > 0.0 {
+            1.06 * std * n.powf(-0.2)
+        } else {
+            1.0
+        }
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.centers.iter().map(|&(_, w)| w).sum()
+    }
+
+    pub fn density(&self, x: f64) -> f64 {
+        if self.centers.is_empty() {
+            return 0.0;
+        }
+        let h = self.bandwidth();
+        let total: f64 = self
+            .centers
+            .iter()
+            .map(|&(c, w)| w * normal_pdf((x - c) / h))
+            .sum();
+        total / (h * self.total_weight())
+    }
+
+    pub fn cdf(&self, x: f64) -> f64 {
+        if self.centers.is_empty() {
+            return 0.0;
+        }
+        let h = self.bandwidth();
+        let total: f64 = self
+            .centers
+            .iter()
+            .map(|&(c, w)| w * normal_cdf((x - c) / h))
+            .sum();
+        total / self.total_weight()
+    }
+
+    pub fn n_centers(&self) -> usize {
+        self.centers.len()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsKernelDensity {
+    kde: KernelDensity,
+    bandwidth: Option<f64>,
+    max_centers: usize,
+}
+
+impl HeapSize for RsKernelDensity {
+    fn heap_size(&self) -> usize {
+        self.kde.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsKernelDensity {
+    #[new]
+    #[args(bandwidth = "None", max_centers = "256")]
+    pub fn new(bandwidth: Option<f64>, max_centers: usize) -> PyResult<Self> {
+        if let Some(h) = bandwidth {
+            if h.is_nan() || h <= 0.0 {
+                return Err(PyValueError::new_err(
+                    "bandwidth should be strictly positive",
+                ));
+            }
+        }
+        if max_centers < 2 {
+            return Err(PyValueError::new_err("max_centers should be at least 2"));
+        }
+        Ok(RsKernelDensity {
+            kde: KernelDensity::new(bandwidth, max_centers),
+            bandwidth,
+            max_centers,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if !x.is_finite() {
+            return Err(PyValueError::new_err("x should be finite"));
+        }
+        self.kde.update(x);
+        Ok(())
+    }
+    pub fn density(&self, x: f64) -> f64 {
+        self.kde.density(x)
+    }
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.kde.cdf(x)
+    }
+    #[getter]
+    pub fn bandwidth(&self) -> f64 {
+        self.kde.bandwidth()
+    }
+    #[getter]
+    pub fn n_centers(&self) -> usize {
+        self.kde.n_centers()
+    }
+}
@@ -0,0 +1,342 @@
+// Running quantile with the P² algorithm, extended to more than five markers, or with a
+// t-digest.
+//
+// `P2Quantile` has the same layout as the watermill `Quantile`, which it replaces in
+// `RsQuantile`, so the states pickled before keep loading. The number of markers is the
+// length of the marker vectors, and with five of them the estimates are those of watermill.
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+use crate::sketch::TDigest;
+
+/// P² quantile estimator with `2k + 3` markers.
+///
+/// Besides the extremes and the target quantile `q`, `k` markers are spread evenly on each
+/// side of `q`. The extra markers give the piecewise-parabolic interpolation more anchors,
+/// which reduces the bias of the classic five-marker estimator on skewed data.
+///
+/// References:
+/// Jain, R. and Chlamtac, I., 1985. The P² algorithm for dynamic calculation of quantiles
+/// and histograms without storing observations. Communications of the ACM, 28(10).
+/// Raatikainen, K.E.E., 1987. Simultaneous estimation of several percentiles. Simulation,
+/// 49(4), pp.159-163.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct P2Quantile {
+    q: f64,
+    desired_marker_position: Vec<f64>,
+    marker_position: Vec<f64>,
+    position: Vec<f64>,
+    heights: Vec<f64>,
+    heights_sorted: bool,
+}
+
+impl P2Quantile {
+    /// `n_markers` must be odd and at least 5.
+    pub fn new(q: f64, n_markers: usize) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&q) {
+            return Err("q should be between 0 and 1".to_string());
+        }
+        if n_markers < 5 || n_markers.is_multiple_of(2) {
+            return Err("n_markers should be an odd number, at least 5".to_string());
+        }
+        let k = (n_markers - 3) / 2;
+        let step = 1.0 / (k + 1) as f64;
+        let probabilities: Vec<f64> = (0..=k)
+            .map(|i| q * i as f64 * step)
+            .chain(std::iter::once(q))
+            .chain((1..=k + 1).map(|i| q + (1.0 - q) * i as f64 * step))
+            .collect();
+        let last = (n_markers - 1) as f64;
+        Ok(P2Quantile {
+            q,
+            marker_position: probabilities.iter().map(|p| 1.0 + last * p).collect(),
+            desired_marker_position: probabilities,
+            position: (1..=n_markers).map(|i| i as f64).collect(),
+            heights: Vec::with_capacity(n_markers),
+            heights_sorted: false,
+        })
+    }
+
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+
+    fn n_markers(&self) -> usize {
+        self.position.len()
+    }
+
+    // Cell of `x` among the markers, stretching the extremes if needed.
+    fn find_k(&mut self, x: f64) -> usize {
+        let last = self.n_markers() - 1;
+        if x < self.heights[0] {
+            self.heights[0] = x;
+            return 1;
+        }
+        match (1..=last).find(|&i| self.heights[i - 1] <= x && x < self.heights[i]) {
+            Some(k) => k,
+            None => {
+                if self.heights[last] < x {
+                    self.heights[last] = x;
+                }
+                last
+            }
+        }
+    }
+
+    fn parabolic(qp1: f64, q: f64, qm1: f64, d: f64, np1: f64, n: f64, nm1: f64) -> f64 {
+        let outer = d / (np1 - nm1);
+        let inner_left = (n - nm1 + d) * (qp1 - q) / (np1 - n);
+        let inner_right = (np1 - n - d) * (q - qm1) / (n - nm1);
+        q + outer * (inner_left + inner_right)
+    }
+
+    fn adjust(&mut self) {
+        for i in 1..self.n_markers() - 1 {
+            let n = self.position[i];
+            let q = self.heights[i];
+            let d = self.marker_position[i] - n;
+            if (d >= 1.0 && self.position[i + 1] - n > 1.0)
+                || (d <= -1.0 && self.position[i - 1] - n < -1.0)
+            {
+                let d = 1f64.copysign(d);
+                let (qp1, qm1) = (self.heights[i + 1], self.heights[i - 1]);
+                let (np1, nm1) = (self.position[i + 1], self.position[i - 1]);
+                let qn = Self::parabolic(qp1, q, qm1, d, np1, n, nm1);
+                self.heights[i] = if qm1 < qn && qn < qp1 {
+                    qn
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q + d * (self.heights[j] - q) / (self.position[j] - n)
+                };
+                self.position[i] = n + d;
+            }
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if self.heights.len() != self.n_markers() {
+            self.heights.push(x);
+        } else {
+            if !self.heights_sorted {
+                self.heights.sort_by(f64::total_cmp);
+                self.heights_sorted = true;
+            }
+            let k = self.find_k(x);
+            for position in self.position.iter_mut().skip(k) {
+                *position += 1.0;
+            }
+            for (marker, desired) in self
+                .marker_position
+                .iter_mut()
+                .zip(&self.desired_marker_position)
+            {
+                *marker += desired;
+            }
+            self.adjust();
+        }
+        self.heights.sort_by(f64::total_cmp);
+    }
+
+    /// Number of values seen so far, which is the position of the last marker once all the
+    /// markers are set.
+    pub fn n(&self) -> f64 {
+        if self.heights.len() < self.n_markers() {
+            self.heights.len() as f64
+        } else {
+            self.position[self.n_markers() - 1]
+        }
+    }
+
+    /// The estimate, NaN until a value is seen.
+    pub fn get(&self) -> f64 {
+        if self.heights.is_empty() {
+            return f64::NAN;
+        }
+        if self.heights_sorted {
+            return self.heights[self.n_markers() / 2];
+        }
+        // Until the markers are set, the quantile of the values seen so far.
+        let length = self.heights.len() as f64;
+        let index = (length - 1.0).max(0.0).min(length * self.q) as usize;
+        self.heights[index]
+    }
+}
+
+impl HeapSize for P2Quantile {
+    fn heap_size(&self) -> usize {
+        self.desired_marker_position.heap_size()
+            + self.marker_position.heap_size()
+            + self.position.heap_size()
+            + self.heights.heap_size()
+    }
+}
+
+/// Running estimate of the `q` quantile, with the P² algorithm or a t-digest. The P²
+/// markers take a constant space but drift on heavy-tailed data, which the t-digest follows
+/// more closely with a few hundred centroids.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum QuantileEstimator {
+    P2(P2Quantile),
+    TDigest { q: f64, digest: TDigest },
+}
+
+// Largest weight of a value for the P² algorithm, which goes through every copy of it.
+const MAX_REPETITIONS: f64 = 1e6;
+
+/// Number of copies of a value of weight `w` for the P² algorithm, which can't weight the
+/// values otherwise.
+pub fn repetitions(w: f64) -> Result<u64, String> {
+    if w.fract() != 0.0 {
+        return Err(format!(
+            "w should be a whole number with the P² algorithm, got {}",
+            w
+        ));
+    }
+    if w > MAX_REPETITIONS {
+        return Err(format!(
+            "w should be at most {} with the P² algorithm, got {}",
+            MAX_REPETITIONS, w
+        ));
+    }
+    Ok(w as u64)
+}
+
+impl QuantileEstimator {
+    /// Adds `x` with weight `w`, which should be a whole number for the P² algorithm.
+    pub fn update(&mut self, x: f64, w: f64) -> Result<(), String> {
+        match self {
+            QuantileEstimator::P2(quantile) => {
+                for _ in 0..repetitions(w)? {
+                    quantile.update(x);
+                }
+            }
+            QuantileEstimator::TDigest { digest, .. } => {
+                if w > 0.0 {
+                    digest.update(x, w);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn q(&self) -> f64 {
+        match self {
+            QuantileEstimator::P2(quantile) => quantile.q(),
+            QuantileEstimator::TDigest { q, .. } => *q,
+        }
+    }
+
+    /// Total weight of the values seen so far.
+    pub fn n(&self) -> f64 {
+        match self {
+            QuantileEstimator::P2(quantile) => quantile.n(),
+            QuantileEstimator::TDigest { digest, .. } => digest.total_weight(),
+        }
+    }
+
+    pub fn get(&self) -> f64 {
+        match self {
+            QuantileEstimator::P2(quantile) => quantile.get(),
+            QuantileEstimator::TDigest { q, digest } => digest.quantile(*q),
+        }
+    }
+}
+
+impl HeapSize for QuantileEstimator {
+    fn heap_size(&self) -> usize {
+        match self {
+            QuantileEstimator::P2(quantile) => quantile.heap_size(),
+            QuantileEstimator::TDigest { digest, .. } => digest.heap_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn invalid_arguments_are_refused() {
+        assert!(P2Quantile::new(1.5, 5).is_err());
+        assert!(P2Quantile::new(0.5, 3).is_err());
+        assert!(P2Quantile::new(0.5, 6).is_err());
+        assert!(P2Quantile::new(0.5, 7).is_ok());
+    }
+
+    #[test]
+    fn markers_are_spread_evenly_on_both_sides_of_q() {
+        let quantile = P2Quantile::new(0.5, 7).unwrap();
+        let expected = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0].map(|i| i / 6.0);
+        for (p, e) in quantile.desired_marker_position.iter().zip(expected) {
+            assert!((p - e).abs() < 1e-12);
+        }
+        assert_eq!(
+            quantile.marker_position,
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]
+        );
+    }
+
+    #[test]
+    fn values_are_kept_until_the_markers_are_set() {
+        let mut quantile = P2Quantile::new(0.5, 5).unwrap();
+        assert!(quantile.get().is_nan());
+        for x in [3.0, 1.0, 2.0] {
+            quantile.update(x);
+        }
+        assert_eq!(quantile.get(), 2.0);
+        assert_eq!(quantile.n(), 3.0);
+        for x in [5.0, 4.0, 6.0] {
+            quantile.update(x);
+        }
+        assert_eq!(quantile.n(), 6.0);
+    }
+
+    #[test]
+    fn estimates_of_an_exponential_stream() {
+        // The 0.9 quantile of a standard exponential is ln(10).
+        let expected = 10f64.ln();
+        let mut rng = Rng::from_seed(1);
+        let values: Vec<f64> = (0..20_000).map(|_| -(1.0 - rng.next_f64()).ln()).collect();
+        for n_markers in [5, 9, 15] {
+            let mut quantile = P2Quantile::new(0.9, n_markers).unwrap();
+            for &x in &values {
+                quantile.update(x);
+            }
+            assert!(
+                (quantile.get() - expected).abs() < 0.1,
+                "{} markers",
+                n_markers
+            );
+        }
+        let mut digest = QuantileEstimator::TDigest {
+            q: 0.9,
+            digest: TDigest::new(100.0),
+        };
+        for &x in &values {
+            digest.update(x, 1.0).unwrap();
+        }
+        assert!((digest.get() - expected).abs() < 0.05);
+        assert_eq!(digest.n(), 20_000.0);
+    }
+
+    #[test]
+    fn whole_weights_repeat_the_value() {
+        assert_eq!(repetitions(3.0), Ok(3));
+        assert!(repetitions(0.5).is_err());
+        assert!(repetitions(1e18).is_err());
+        assert!(repetitions(f64::INFINITY).is_err());
+        let mut weighted = QuantileEstimator::P2(P2Quantile::new(0.5, 5).unwrap());
+        let mut repeated = weighted.clone();
+        for (i, w) in [2.0, 0.0, 3.0, 1.0, 4.0].into_iter().enumerate() {
+            weighted.update(i as f64, w).unwrap();
+            for _ in 0..w as usize {
+                repeated.update(i as f64, 1.0).unwrap();
+            }
+        }
+        assert_eq!(weighted.get(), repeated.get());
+        assert_eq!(weighted.n(), 10.0);
+        assert!(weighted.update(1.0, 1.5).is_err());
+    }
+}
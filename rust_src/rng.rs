@@ -0,0 +1,264 @@
+// Random numbers of the stochastic components.
+//
+// Each component owns an `Rng`, seeded either with its own seed or with a draw from the
+// module-level generator. Calling `set_seed` before creating the components thus makes a
+// whole experiment reproducible, while a component given its own seed does not depend on
+// how many others were created before it. The generator is part of the serialized state,
+// so a restored component carries on with the same sequence.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+#[cfg(feature = "python")]
+use numpy::PyArray1;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+use crate::special::ln_gamma;
+
+// Seeded lazily from the per-process keys of the standard library when `set_seed` was
+// never called.
+static GLOBAL: Mutex<Option<Rng>> = Mutex::new(None);
+
+fn entropy() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// xoshiro256++ generator.
+///
+/// Reference: Blackman, D. and Vigna, S., 2021. Scrambled linear pseudorandom number
+/// generators. ACM Transactions on Mathematical Software, 47(4), pp.1-32.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rng {
+    s: [u64; 4],
+}
+
+impl Rng {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut state = seed;
+        Rng {
+            s: [
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+            ],
+        }
+    }
+
+    /// Generator seeded with `seed`, or with a draw from the module-level generator.
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Rng::from_seed(seed),
+            None => {
+                let mut global = GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+                let seed = global
+                    .get_or_insert_with(|| Rng::from_seed(entropy()))
+                    .next_u64();
+                Rng::from_seed(seed)
+            }
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform draw in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform draw in [0, n), without modulo bias. `n` must be positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// Standard normal draw, by the Box-Muller transform.
+    pub fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+
+    /// Gamma draw of unit scale and positive `shape`.
+    ///
+    /// Reference: Marsaglia, G. and Tsang, W.W., 2000. A simple method for generating gamma
+    /// variables. ACM Transactions on Mathematical Software, 26(3), pp.363-372.
+    pub fn gamma(&mut self, shape: f64) -> f64 {
+        if shape < 1.0 {
+            let u = 1.0 - self.next_f64();
+            return self.gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.normal();
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = 1.0 - self.next_f64();
+            if u < 1.0 - 0.0331 * x.powi(4) || u.ln() < 0.5 * x * x + d * (1.0 - v + v.ln()) {
+                return d * v;
+            }
+        }
+    }
+
+    /// Beta draw of positive shapes `a` and `b`.
+    pub fn beta(&mut self, a: f64, b: f64) -> f64 {
+        let x = self.gamma(a);
+        x / (x + self.gamma(b))
+    }
+
+    /// Poisson draw of mean `lambda`, which must be finite and non-negative.
+    ///
+    /// Small means are drawn by multiplying uniforms until their product falls below
+    /// `exp(-lambda)`, which takes `lambda + 1` draws on average; large ones use the PTRS
+    /// transformed rejection of Hörmann, whose cost doesn't depend on `lambda`.
+    ///
+    /// Reference: Hörmann, W., 1993. The transformed rejection method for generating Poisson
+    /// random variables. Insurance: Mathematics and Economics, 12(1), pp.39-45.
+    pub fn poisson(&mut self, lambda: f64) -> u64 {
+        if lambda < 10.0 {
+            let limit = (-lambda).exp();
+            let mut k = 0;
+            let mut product = self.next_f64();
+            while product > limit {
+                k += 1;
+                product *= self.next_f64();
+            }
+            return k;
+        }
+        let slam = lambda.sqrt();
+        let loglam = lambda.ln();
+        let b = 0.931 + 2.53 * slam;
+        let a = -0.059 + 0.02483 * b;
+        let invalpha = 1.1239 + 1.1328 / (b - 3.4);
+        let vr = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.next_f64() - 0.5;
+            let v = self.next_f64();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+            if us >= 0.07 && v <= vr {
+                return k as u64;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            if v.ln() + invalpha.ln() - (a / (us * us) + b).ln()
+                <= -lambda + k * loglam - ln_gamma(k + 1.0)
+            {
+                return k as u64;
+            }
+        }
+    }
+}
+
+impl HeapSize for Rng {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Seeds the module-level generator, from which the components without a seed of their
+/// own draw theirs. `None` reseeds it unpredictably.
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn set_seed(seed: Option<u64>) {
+    let seed = seed.unwrap_or_else(entropy);
+    *GLOBAL.lock().unwrap_or_else(|e| e.into_inner()) = Some(Rng::from_seed(seed));
+}
+
+/// Random number generator handle, drawing from the same source as the Rust components.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRng {
+    rng: Rng,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsRng {
+    fn heap_size(&self) -> usize {
+        self.rng.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsRng {
+    #[new]
+    #[args(seed = "None")]
+    pub fn new(seed: Option<u64>) -> Self {
+        RsRng {
+            rng: Rng::new(seed),
+        }
+    }
+    /// Uniform float in [0, 1).
+    pub fn random(&mut self) -> f64 {
+        self.rng.next_f64()
+    }
+    /// Uniform integer in [0, n).
+    pub fn randrange(&mut self, n: u64) -> PyResult<u64> {
+        if n == 0 {
+            return Err(PyValueError::new_err("n should be at least 1"));
+        }
+        Ok(self.rng.below(n))
+    }
+    /// Poisson(`lam`) weights of the `n_models` members of an online bagging ensemble for
+    /// one sample, or, given `n_samples`, an array of shape `(n_samples, n_models)` holding
+    /// the weights of a whole batch.
+    #[args(lam = "1.0", n_samples = "None")]
+    pub fn poisson(
+        &mut self,
+        py: Python,
+        n_models: usize,
+        lam: f64,
+        n_samples: Option<usize>,
+    ) -> PyResult<PyObject> {
+        if !lam.is_finite() || lam < 0.0 {
+            return Err(PyValueError::new_err(
+                "lam should be finite and non-negative",
+            ));
+        }
+        let rows = n_samples.unwrap_or(1);
+        let weights: Vec<u64> = (0..rows * n_models)
+            .map(|_| self.rng.poisson(lam))
+            .collect();
+        let weights = PyArray1::from_vec(py, weights);
+        Ok(match n_samples {
+            Some(n_samples) => weights.reshape([n_samples, n_models])?.into_py(py),
+            None => weights.into_py(py),
+        })
+    }
+}
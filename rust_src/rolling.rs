@@ -0,0 +1,668 @@
+// Rolling statistics.
+//
+// The order statistics have the same layout as their watermill counterparts, so states
+// pickled by the watermill-backed bindings keep loading, but they also give access to the
+// window.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory::HeapSize;
+
+/// Window of the most recent values, along with a sorted copy of it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OrderedWindow {
+    sorted: VecDeque<f64>,
+    unsorted: VecDeque<f64>,
+    window_size: usize,
+}
+
+impl HeapSize for OrderedWindow {
+    fn heap_size(&self) -> usize {
+        self.sorted.heap_size() + self.unsorted.heap_size()
+    }
+}
+
+impl OrderedWindow {
+    pub fn new(window_size: usize) -> Self {
+        OrderedWindow {
+            sorted: VecDeque::with_capacity(window_size),
+            unsorted: VecDeque::with_capacity(window_size),
+            window_size,
+        }
+    }
+
+    /// Number of values in the window, not counting the missing ones.
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    fn evict(&mut self) {
+        if self.unsorted.len() == self.window_size {
+            let oldest = self.unsorted.pop_front().unwrap();
+            if !oldest.is_nan() {
+                let pos = self.sorted.partition_point(|&v| v < oldest);
+                self.sorted.remove(pos);
+            }
+        }
+    }
+
+    /// Inserts `x`, which must not be NaN, evicting the oldest value if the window is full.
+    pub fn push(&mut self, x: f64) {
+        self.evict();
+        self.unsorted.push_back(x);
+        let pos = self.sorted.partition_point(|&v| v < x);
+        self.sorted.insert(pos, x);
+    }
+
+    /// Takes a slot of the window for a missing value, which is stored as NaN and left out
+    /// of the sorted values.
+    pub fn push_missing(&mut self) {
+        self.evict();
+        self.unsorted.push_back(f64::NAN);
+    }
+
+    /// The window, from the oldest to the most recent value.
+    pub fn values(&mut self) -> &[f64] {
+        self.unsorted.make_contiguous()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.unsorted.iter()
+    }
+
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &f64> {
+        self.sorted.iter()
+    }
+
+    /// Number of values strictly lower than `x`.
+    pub fn rank(&self, x: f64) -> usize {
+        self.sorted.partition_point(|&v| v < x)
+    }
+
+    /// Quantiles of the window with linear interpolation, NaN while it's empty.
+    pub fn quantiles(&self, qs: &[f64]) -> Result<Vec<f64>, String> {
+        qs.iter()
+            .map(|&q| {
+                check_q(q, "q")?;
+                let n = self.len();
+                if n == 0 {
+                    return Ok(f64::NAN);
+                }
+                let (lower, higher, frac) = positions(q, n);
+                Ok(self.sorted[lower] + (self.sorted[higher] - self.sorted[lower]) * frac)
+            })
+            .collect()
+    }
+
+    /// Median absolute deviation from the median of the window, NaN while it's empty.
+    ///
+    /// The deviations are taken in increasing order by walking away from the median on both
+    /// sides of the sorted values, so that their median is read without sorting them.
+    pub fn mad(&self) -> f64 {
+        let n = self.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        let (lower, higher, frac) = positions(0.5, n);
+        let median = self.sorted[lower] + (self.sorted[higher] - self.sorted[lower]) * frac;
+        let split = self.rank(median);
+        let (mut below, mut above) = (split, split);
+        let mut deviations = Vec::with_capacity(higher + 1);
+        while deviations.len() <= higher {
+            let left = below.checked_sub(1).map(|i| median - self.sorted[i]);
+            let right = self.sorted.get(above).map(|&v| v - median);
+            match (left, right) {
+                (Some(l), Some(r)) if l <= r => {
+                    deviations.push(l);
+                    below -= 1;
+                }
+                (Some(l), None) => {
+                    deviations.push(l);
+                    below -= 1;
+                }
+                (_, Some(r)) => {
+                    deviations.push(r);
+                    above += 1;
+                }
+                (None, None) => unreachable!("the window holds n values"),
+            }
+        }
+        deviations[lower] + (deviations[higher] - deviations[lower]) * frac
+    }
+}
+
+// Interpolation positions of the `q` quantile within `n` sorted values.
+fn positions(q: f64, n: usize) -> (usize, usize, f64) {
+    let idx = q * (n as f64 - 1.0);
+    let lower = idx.floor() as usize;
+    let higher = (lower + 1).min(n - 1);
+    (lower, higher, idx - lower as f64)
+}
+
+/// Checks that the quantile `q`, named `name` in the message, is within [0, 1].
+pub fn check_q(q: f64, name: &'static str) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&q) {
+        return Err(format!("{} should be between 0 and 1", name));
+    }
+    Ok(())
+}
+
+/// Exact quantile of a sliding window, with linear interpolation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RollingQuantile {
+    window: OrderedWindow,
+    q: f64,
+    window_size: usize,
+    lower: usize,
+    higher: usize,
+    frac: f64,
+}
+
+impl HeapSize for RollingQuantile {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl RollingQuantile {
+    pub fn new(q: f64, window_size: usize) -> Result<Self, String> {
+        check_q(q, "q")?;
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        let (lower, higher, frac) = positions(q, window_size);
+        Ok(RollingQuantile {
+            window: OrderedWindow::new(window_size),
+            q,
+            window_size,
+            lower,
+            higher,
+            frac,
+        })
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.window.push(x);
+    }
+
+    pub fn get(&self) -> f64 {
+        let n = self.window.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        let (lower, higher, frac) = if n < self.window_size {
+            positions(self.q, n)
+        } else {
+            (self.lower, self.higher, self.frac)
+        };
+        let sorted = &self.window.sorted;
+        sorted[lower] + (sorted[higher] - sorted[lower]) * frac
+    }
+
+    pub fn window(&self) -> &OrderedWindow {
+        &self.window
+    }
+
+    pub fn window_mut(&mut self) -> &mut OrderedWindow {
+        &mut self.window
+    }
+}
+
+/// Exact interquartile range of a sliding window.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RollingIQR {
+    window: OrderedWindow,
+    q_inf: f64,
+    q_sup: f64,
+    window_size: usize,
+    lower_inf: usize,
+    higher_inf: usize,
+    frac_inf: f64,
+    lower_sup: usize,
+    higher_sup: usize,
+    frac_sup: f64,
+}
+
+impl HeapSize for RollingIQR {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl RollingIQR {
+    pub fn new(q_inf: f64, q_sup: f64, window_size: usize) -> Result<Self, String> {
+        check_q(q_inf, "q_inf")?;
+        check_q(q_sup, "q_sup")?;
+        if q_inf >= q_sup {
+            return Err("q_inf should be strictly less than q_sup".to_string());
+        }
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        let (lower_inf, higher_inf, frac_inf) = positions(q_inf, window_size);
+        let (lower_sup, higher_sup, frac_sup) = positions(q_sup, window_size);
+        Ok(RollingIQR {
+            window: OrderedWindow::new(window_size),
+            q_inf,
+            q_sup,
+            window_size,
+            lower_inf,
+            higher_inf,
+            frac_inf,
+            lower_sup,
+            higher_sup,
+            frac_sup,
+        })
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.window.push(x);
+    }
+
+    pub fn get(&self) -> f64 {
+        let n = self.window.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        let (inf, sup) = if n < self.window_size {
+            (positions(self.q_inf, n), positions(self.q_sup, n))
+        } else {
+            (
+                (self.lower_inf, self.higher_inf, self.frac_inf),
+                (self.lower_sup, self.higher_sup, self.frac_sup),
+            )
+        };
+        let sorted = &self.window.sorted;
+        let quantile = |(lower, higher, frac): (usize, usize, f64)| {
+            sorted[lower] + (sorted[higher] - sorted[lower]) * frac
+        };
+        quantile(sup) - quantile(inf)
+    }
+
+    pub fn window(&self) -> &OrderedWindow {
+        &self.window
+    }
+
+    pub fn window_mut(&mut self) -> &mut OrderedWindow {
+        &mut self.window
+    }
+}
+
+/// Mean and variance of a sliding window, updated in constant time.
+///
+/// Each value is added to Welford's running sums as it enters the window, and removed from
+/// them as it leaves it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RollingMoments {
+    window: VecDeque<f64>,
+    window_size: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl HeapSize for RollingMoments {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl RollingMoments {
+    pub fn new(window_size: usize) -> Result<Self, String> {
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        Ok(RollingMoments {
+            window: VecDeque::with_capacity(window_size),
+            window_size,
+            mean: 0.0,
+            m2: 0.0,
+        })
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if self.window.len() == self.window_size {
+            let oldest = self.window.pop_front().unwrap();
+            if self.window.is_empty() {
+                self.mean = 0.0;
+                self.m2 = 0.0;
+            } else {
+                let delta = oldest - self.mean;
+                self.mean -= delta / self.window.len() as f64;
+                self.m2 -= delta * (oldest - self.mean);
+            }
+        }
+        self.window.push_back(x);
+        let delta = x - self.mean;
+        self.mean += delta / self.window.len() as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Variance with `ddof` delta degrees of freedom, which is 0 until the window holds
+    /// more than `ddof` values.
+    pub fn variance(&self, ddof: usize) -> f64 {
+        let n = self.window.len();
+        if n <= ddof {
+            return 0.0;
+        }
+        // Removals can leave a tiny negative sum of squares behind.
+        self.m2.max(0.0) / (n - ddof) as f64
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.window.iter()
+    }
+}
+
+/// Values of a stream delayed by a fixed number of steps.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Lag {
+    window: VecDeque<f64>,
+    amount: usize,
+}
+
+impl HeapSize for Lag {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl Lag {
+    pub fn new(amount: usize) -> Self {
+        Lag {
+            window: VecDeque::with_capacity(amount + 1),
+            amount,
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if self.window.len() > self.amount {
+            self.window.pop_front();
+        }
+        self.window.push_back(x);
+    }
+
+    /// The value seen `amount` steps before the last one, once there is one.
+    pub fn get(&self) -> Option<f64> {
+        if self.window.len() > self.amount {
+            self.window.front().copied()
+        } else {
+            None
+        }
+    }
+
+    pub fn amount(&self) -> usize {
+        self.amount
+    }
+
+    /// Number of values held, at most `amount + 1`.
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+}
+
+/// Minimum or maximum of a sliding window, kept in a monotonic deque.
+///
+/// The deque holds the values of the window that can still become the extremum, each with
+/// its position in the stream, so a value which is beaten by a more recent one is dropped.
+/// An update takes amortized constant time.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RollingExtremum {
+    candidates: VecDeque<(u64, f64)>,
+    window_size: usize,
+    n: u64,
+    max: bool,
+}
+
+impl HeapSize for RollingExtremum {
+    fn heap_size(&self) -> usize {
+        self.candidates.heap_size()
+    }
+}
+
+impl RollingExtremum {
+    fn new(window_size: usize, max: bool) -> Result<Self, String> {
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        Ok(RollingExtremum {
+            candidates: VecDeque::new(),
+            window_size,
+            n: 0,
+            max,
+        })
+    }
+
+    pub fn min(window_size: usize) -> Result<Self, String> {
+        RollingExtremum::new(window_size, false)
+    }
+
+    pub fn max(window_size: usize) -> Result<Self, String> {
+        RollingExtremum::new(window_size, true)
+    }
+
+    pub fn update(&mut self, x: f64) {
+        while let Some(&(_, last)) = self.candidates.back() {
+            if (self.max && last <= x) || (!self.max && last >= x) {
+                self.candidates.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.candidates.push_back((self.n, x));
+        self.n += 1;
+        // Only the oldest value of the window can have left it.
+        if let Some(&(i, _)) = self.candidates.front() {
+            if i + (self.window_size as u64) < self.n {
+                self.candidates.pop_front();
+            }
+        }
+    }
+
+    /// The extremum of the window, NaN while it is empty.
+    pub fn get(&self) -> f64 {
+        self.candidates.front().map_or(f64::NAN, |&(_, x)| x)
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Number of values seen so far, including those which have left the window.
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn draws(n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = Rng::from_seed(seed);
+        (0..n).map(|_| rng.normal()).collect()
+    }
+
+    // Quantile of `values` with the same linear interpolation as the windows.
+    fn scan_quantile(values: &[f64], q: f64) -> f64 {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let (lower, higher, frac) = positions(q, sorted.len());
+        sorted[lower] + (sorted[higher] - sorted[lower]) * frac
+    }
+
+    #[test]
+    fn ordered_window_keeps_both_orders() {
+        let mut window = OrderedWindow::new(3);
+        assert!(window.is_empty());
+        for x in [3.0, 1.0, 2.0, 0.0] {
+            window.push(x);
+        }
+        assert_eq!(window.values(), &[1.0, 2.0, 0.0]);
+        assert_eq!(
+            window.iter_sorted().copied().collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0]
+        );
+        assert_eq!(window.rank(1.5), 2);
+        assert_eq!(window.quantiles(&[0.0, 0.25, 1.0]), Ok(vec![0.0, 0.5, 2.0]));
+        assert!(window.quantiles(&[1.5]).is_err());
+    }
+
+    #[test]
+    fn missing_values_take_a_slot_of_the_window() {
+        let mut window = OrderedWindow::new(3);
+        window.push(1.0);
+        window.push_missing();
+        window.push(2.0);
+        assert_eq!(window.len(), 2);
+        window.push(3.0);
+        window.push(4.0);
+        assert_eq!(window.len(), 3);
+        assert_eq!(
+            window.iter_sorted().copied().collect::<Vec<_>>(),
+            vec![2.0, 3.0, 4.0]
+        );
+        window.push_missing();
+        window.push_missing();
+        window.push_missing();
+        assert!(window.is_empty());
+        assert!(window.quantiles(&[0.5]).unwrap()[0].is_nan());
+        assert!(window.mad().is_nan());
+    }
+
+    #[test]
+    fn rolling_quantile_matches_a_window_scan() {
+        let values = draws(200, 1);
+        for &(q, window_size) in &[(0.0, 1), (0.5, 10), (0.9, 7), (1.0, 50)] {
+            let mut stat = RollingQuantile::new(q, window_size).unwrap();
+            assert!(stat.get().is_nan());
+            for (i, &x) in values.iter().enumerate() {
+                stat.update(x);
+                let window = &values[(i + 1).saturating_sub(window_size)..=i];
+                assert_eq!(stat.get(), scan_quantile(window, q));
+            }
+        }
+    }
+
+    #[test]
+    fn rolling_iqr_matches_a_window_scan() {
+        let values = draws(100, 2);
+        let mut stat = RollingIQR::new(0.25, 0.75, 9).unwrap();
+        for (i, &x) in values.iter().enumerate() {
+            stat.update(x);
+            let window = &values[(i + 1).saturating_sub(9)..=i];
+            let expected = scan_quantile(window, 0.75) - scan_quantile(window, 0.25);
+            assert!((stat.get() - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_are_refused() {
+        assert!(RollingQuantile::new(-0.1, 5).is_err());
+        assert!(RollingQuantile::new(0.5, 0).is_err());
+        assert!(RollingIQR::new(0.75, 0.25, 5).is_err());
+        assert!(RollingMoments::new(0).is_err());
+        assert!(RollingExtremum::min(0).is_err());
+    }
+
+    #[test]
+    fn mad_matches_the_median_of_the_deviations() {
+        let values = draws(60, 3);
+        for window_size in [1, 2, 5, 12] {
+            let mut window = OrderedWindow::new(window_size);
+            for (i, &x) in values.iter().enumerate() {
+                window.push(x);
+                let values = &values[(i + 1).saturating_sub(window_size)..=i];
+                let median = scan_quantile(values, 0.5);
+                let deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+                assert!((window.mad() - scan_quantile(&deviations, 0.5)).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn rolling_moments_match_two_passes() {
+        let values = draws(100, 4);
+        let mut stat = RollingMoments::new(8).unwrap();
+        for (i, &x) in values.iter().enumerate() {
+            stat.update(x);
+            let window = &values[(i + 1).saturating_sub(8)..=i];
+            let n = window.len() as f64;
+            let mean = window.iter().sum::<f64>() / n;
+            let m2: f64 = window.iter().map(|v| (v - mean).powi(2)).sum();
+            assert!((stat.mean() - mean).abs() < 1e-12);
+            assert!((stat.variance(0) - m2 / n).abs() < 1e-12);
+            if window.len() > 1 {
+                assert!((stat.variance(1) - m2 / (n - 1.0)).abs() < 1e-12);
+            } else {
+                assert_eq!(stat.variance(1), 0.0);
+            }
+        }
+        assert_eq!(stat.len(), 8);
+    }
+
+    #[test]
+    fn lag_delays_the_stream() {
+        let mut lag = Lag::new(2);
+        lag.update(1.0);
+        lag.update(2.0);
+        assert_eq!(lag.get(), None);
+        lag.update(3.0);
+        assert_eq!(lag.get(), Some(1.0));
+        lag.update(4.0);
+        assert_eq!(lag.get(), Some(2.0));
+        let mut identity = Lag::new(0);
+        identity.update(5.0);
+        assert_eq!(identity.get(), Some(5.0));
+    }
+
+    #[test]
+    fn rolling_extrema_match_a_window_scan() {
+        let values = draws(200, 5);
+        for window_size in [1, 3, 16] {
+            let mut min = RollingExtremum::min(window_size).unwrap();
+            let mut max = RollingExtremum::max(window_size).unwrap();
+            assert!(min.get().is_nan());
+            for (i, &x) in values.iter().enumerate() {
+                min.update(x);
+                max.update(x);
+                let window = &values[(i + 1).saturating_sub(window_size)..=i];
+                assert_eq!(
+                    min.get(),
+                    window.iter().copied().fold(f64::INFINITY, f64::min)
+                );
+                assert_eq!(
+                    max.get(),
+                    window.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                );
+            }
+            assert_eq!(min.n(), 200);
+        }
+    }
+}
@@ -0,0 +1,637 @@
+// Self Adjusting Memory coupled with the kNN classifier, the memories of
+// `river.neighbors.SAMKNNClassifier` kept and searched in Rust.
+//
+// Reference: Losing, V., Hammer, B. and Wersing, H., 2016. KNN classifier with self
+// adjusting memory for heterogeneous concept drift. In 2016 IEEE 16th International
+// Conference on Data Mining (ICDM), pp.291-300.
+
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+#[cfg(feature = "python")]
+use crate::neighbors::FeatureOrder;
+use crate::neighbors::{k_smallest, kmeans, sq_euclidean, vote, LabeledSamples};
+use crate::rng::Rng;
+
+/// How the size of the short-term memory adapts, by picking among the most recent halves
+/// of the STM the one with the best interleaved test-train accuracy.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StmAdaption {
+    /// The accuracies are carried over from one step to the next where possible.
+    Approximate,
+    /// The accuracies are recomputed at each step.
+    Exact,
+    /// The STM keeps its size, so that without the LTM this is a kNN over a sliding window.
+    Disabled,
+}
+
+// Outcomes of the predictions of one of the memories, oldest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct History {
+    outcomes: VecDeque<bool>,
+    n_correct: usize,
+}
+
+impl History {
+    fn push(&mut self, correct: bool) {
+        self.outcomes.push_back(correct);
+        self.n_correct += correct as usize;
+    }
+
+    fn pop_front(&mut self, n: usize) {
+        for _ in 0..n {
+            match self.outcomes.pop_front() {
+                Some(correct) => self.n_correct -= correct as usize,
+                None => break,
+            }
+        }
+    }
+}
+
+// Predictions of the STM, of the LTM, of both memories together, and the one picked.
+#[derive(Default)]
+struct Predictions {
+    stm: Option<Key>,
+    ltm: Option<Key>,
+    both: Option<Key>,
+    chosen: Option<Key>,
+}
+
+// Unlike `f64::max`, a NaN wins, as the accuracy of an empty window does with `np.argmax`.
+fn argmax(values: &[f64]) -> usize {
+    let mut best = 0;
+    for (i, &v) in values.iter().enumerate() {
+        if values[best].is_nan() {
+            break;
+        }
+        if v.is_nan() || v > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+fn accuracy(outcomes: &[bool]) -> f64 {
+    outcomes.iter().filter(|&&correct| correct).count() as f64 / outcomes.len() as f64
+}
+
+fn round4(x: f64) -> f64 {
+    (x * 1e4).round() / 1e4
+}
+
+/// SAM-kNN.
+///
+/// The short-term memory (STM) holds the most recent samples, as many as fit the current
+/// concept. The samples it drops are moved to the long-term memory (LTM) once those of the
+/// LTM that contradict them are removed. When the memories are full, the LTM is compressed
+/// by replacing the samples of each class with half as many k-means centers. A prediction
+/// comes from the STM, from the LTM or from both, whichever has been the most accurate so
+/// far.
+///
+/// The distances are Euclidean, and the labels with the most votes win, ties going to the
+/// lowest label.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SamKnn {
+    n_neighbors: usize,
+    weighted: bool,
+    window_size: usize,
+    max_ltm_size: f64,
+    min_stm_size: usize,
+    adaption: StmAdaption,
+    use_ltm: bool,
+    stm: LabeledSamples,
+    ltm: LabeledSamples,
+    // The `i`-th row holds the squared distances from the `i`-th sample of the STM to the
+    // ones before it. They are only kept while the size of the STM adapts.
+    stm_distances: VecDeque<Vec<f64>>,
+    // Outcomes of the interleaved test-train predictions over the most recent samples of
+    // the STM, keyed by the index of the first sample of the window.
+    interleaved: BTreeMap<usize, Vec<bool>>,
+    stm_history: History,
+    ltm_history: History,
+    both_history: History,
+    rng: Rng,
+}
+
+impl HeapSize for SamKnn {
+    fn heap_size(&self) -> usize {
+        self.stm.heap_size()
+            + self.ltm.heap_size()
+            + self.stm_distances.heap_size()
+            + self
+                .interleaved
+                .values()
+                .map(|outcomes| size_of::<(usize, Vec<bool>)>() + outcomes.capacity())
+                .sum::<usize>()
+            + [&self.stm_history, &self.ltm_history, &self.both_history]
+                .iter()
+                .map(|history| history.outcomes.capacity())
+                .sum::<usize>()
+    }
+}
+
+impl SamKnn {
+    /// `window_size` is the maximum number of samples of both memories, `ltm_size` the
+    /// fraction of them that the LTM can hold, and `min_stm_size` the smallest size the STM
+    /// can shrink to.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        n_neighbors: usize,
+        distance_weighting: bool,
+        window_size: usize,
+        ltm_size: f64,
+        min_stm_size: usize,
+        adaption: StmAdaption,
+        use_ltm: bool,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        if n_neighbors == 0 {
+            return Err("n_neighbors should be at least 1".to_string());
+        }
+        if window_size == 0 {
+            return Err("window_size should be at least 1".to_string());
+        }
+        if ltm_size.is_nan() || !(0.0..=1.0).contains(&ltm_size) {
+            return Err("ltm_size should be between 0 and 1".to_string());
+        }
+        if min_stm_size == 0 {
+            return Err("min_stm_size should be at least 1".to_string());
+        }
+        Ok(SamKnn {
+            n_neighbors,
+            weighted: distance_weighting,
+            window_size,
+            max_ltm_size: ltm_size * window_size as f64,
+            min_stm_size,
+            adaption,
+            use_ltm,
+            stm: LabeledSamples::new(0),
+            ltm: LabeledSamples::new(0),
+            stm_distances: VecDeque::new(),
+            interleaved: BTreeMap::new(),
+            stm_history: History::default(),
+            ltm_history: History::default(),
+            both_history: History::default(),
+            rng: Rng::new(seed),
+        })
+    }
+
+    pub fn stm(&self) -> &LabeledSamples {
+        &self.stm
+    }
+
+    pub fn ltm(&self) -> &LabeledSamples {
+        &self.ltm
+    }
+
+    /// Learns a sample, which should have as many values as the first one.
+    pub fn learn_one(&mut self, x: &[f64], y: Key) {
+        if self.stm.is_empty() && self.ltm.is_empty() {
+            self.stm = LabeledSamples::new(x.len());
+            self.ltm = LabeledSamples::new(x.len());
+        }
+        let mut distances = self.stm.distances(x);
+        if self.use_ltm {
+            let predictions = self.predict_by_all_memories(x, &distances);
+            self.stm_history.push(predictions.stm.as_ref() == Some(&y));
+            self.ltm_history.push(predictions.ltm.as_ref() == Some(&y));
+            self.both_history
+                .push(predictions.both.as_ref() == Some(&y));
+        }
+
+        self.stm.push(x, y);
+        let stm_shortened = self.check_size();
+        let ltm = std::mem::take(&mut self.ltm);
+        self.ltm = self.clean(ltm, true);
+
+        if self.adaption != StmAdaption::Disabled {
+            let n = self.stm.len();
+            if stm_shortened {
+                distances = (0..n.saturating_sub(1))
+                    .map(|i| sq_euclidean(x, self.stm.row(i)))
+                    .collect();
+            }
+            self.stm_distances.push_back(distances);
+            let new_size = self.new_stm_size();
+            if new_size < n {
+                let n_removed = n - new_size;
+                let removed = self.stm.drain_front(n_removed);
+                self.shift_distances(n_removed);
+                if self.use_ltm {
+                    for history in [
+                        &mut self.stm_history,
+                        &mut self.ltm_history,
+                        &mut self.both_history,
+                    ] {
+                        history.pop_front(n_removed);
+                    }
+                    let mut removed = self.clean(removed, false);
+                    self.ltm.append(&mut removed);
+                    self.check_size();
+                }
+            }
+        }
+    }
+
+    /// Predicted label, or `None` before any sample is learnt.
+    pub fn predict_one(&self, x: &[f64]) -> Option<Key> {
+        let distances = self.stm.distances(x);
+        if self.use_ltm {
+            self.predict_by_all_memories(x, &distances).chosen
+        } else {
+            self.stm
+                .k_nearest_label(&distances, self.n_neighbors, self.weighted)
+        }
+    }
+
+    fn predict_by_all_memories(&self, x: &[f64], stm_distances: &[f64]) -> Predictions {
+        let k = self.n_neighbors;
+        if self.stm.is_empty() {
+            return Predictions::default();
+        }
+        if self.stm.len() < k {
+            let label = self.stm.k_nearest_label(stm_distances, k, self.weighted);
+            return Predictions {
+                stm: label.clone(),
+                chosen: label,
+                ..Default::default()
+            };
+        }
+
+        let ltm_distances = self.ltm.distances(x);
+        let stm = self.stm.k_nearest_label(stm_distances, k, self.weighted);
+        let all_distances: Vec<f64> = stm_distances
+            .iter()
+            .chain(&ltm_distances)
+            .copied()
+            .collect();
+        let n_stm = self.stm.len();
+        let both = vote(
+            k_smallest(k, &all_distances).into_iter().map(|i| {
+                let label = if i < n_stm {
+                    self.stm.label(i)
+                } else {
+                    self.ltm.label(i - n_stm)
+                };
+                (label, all_distances[i])
+            }),
+            self.weighted,
+        );
+        if self.ltm.len() < k {
+            return Predictions {
+                chosen: stm.clone(),
+                stm,
+                both,
+                ltm: None,
+            };
+        }
+        let ltm = self.ltm.k_nearest_label(&ltm_distances, k, self.weighted);
+        let n_correct = [
+            self.stm_history.n_correct as f64,
+            self.ltm_history.n_correct as f64,
+            self.both_history.n_correct as f64,
+        ];
+        let chosen = [&stm, &ltm, &both][argmax(&n_correct)].clone();
+        Predictions {
+            stm,
+            ltm,
+            both,
+            chosen,
+        }
+    }
+
+    // Removes the samples of `samples` that contradict those of the STM: for each sample of
+    // the STM, or only the most recent one, the nearest samples of `samples` with another
+    // label that are closer than its farthest nearest neighbor with the same label in the
+    // STM.
+    fn clean(&self, mut samples: LabeledSamples, only_last: bool) -> LabeledSamples {
+        let k = self.n_neighbors;
+        let n = self.stm.len();
+        if n <= k || samples.is_empty() {
+            return samples;
+        }
+        let start = if only_last { n - 1 } else { 0 };
+        for i in start..n {
+            if samples.is_empty() {
+                break;
+            }
+            let x = self.stm.row(i);
+            let y = self.stm.label(i);
+            let mut stm_distances = self.stm.distances(x);
+            stm_distances.remove(i);
+            let threshold = k_smallest(k, &stm_distances)
+                .into_iter()
+                .filter(|&j| self.stm.label(if j < i { j } else { j + 1 }) == y)
+                .map(|j| stm_distances[j])
+                .reduce(f64::max);
+            if let Some(threshold) = threshold {
+                let distances = samples.distances(x);
+                let contradicting: Vec<usize> = k_smallest(k, &distances)
+                    .into_iter()
+                    .filter(|&j| samples.label(j) != y && distances[j] <= threshold)
+                    .collect();
+                samples.remove(&contradicting);
+            }
+        }
+        samples
+    }
+
+    // Replaces the samples of each class by half as many k-means centers.
+    fn cluster_down(&mut self, samples: LabeledSamples) -> LabeledSamples {
+        let f = samples.n_features();
+        let mut by_label: BTreeMap<&Key, Vec<f64>> = BTreeMap::new();
+        for i in 0..samples.len() {
+            by_label
+                .entry(samples.label(i))
+                .or_default()
+                .extend_from_slice(samples.row(i));
+        }
+        let mut compressed = LabeledSamples::new(f);
+        for (label, values) in by_label {
+            let n = values.len().checked_div(f).unwrap_or(0);
+            let centers = kmeans(&values, f, (n / 2).max(1), &mut self.rng);
+            for center in centers.chunks_exact(f.max(1)) {
+                compressed.push(center, label.clone());
+            }
+        }
+        compressed
+    }
+
+    // Makes room once the memories are full, and tells whether the STM lost samples.
+    fn check_size(&mut self) -> bool {
+        if !self.use_ltm {
+            if self.stm.len() <= self.window_size {
+                return false;
+            }
+            self.stm.drain_front(1);
+            self.shift_distances(1);
+            if self.adaption == StmAdaption::Approximate {
+                self.fade_interleaved();
+            } else {
+                self.interleaved.clear();
+            }
+            return true;
+        }
+
+        if self.stm.len() + self.ltm.len() <= self.window_size {
+            return false;
+        }
+        let ltm = std::mem::take(&mut self.ltm);
+        if ltm.len() as f64 > self.max_ltm_size {
+            self.ltm = self.cluster_down(ltm);
+            return false;
+        }
+        let n_shifts = (self.max_ltm_size - ltm.len() as f64 + 1.0) as usize;
+        let n_shifts = n_shifts.min(self.stm.len());
+        let mut ltm = ltm;
+        ltm.append(&mut self.stm.drain_front(n_shifts));
+        self.ltm = self.cluster_down(ltm);
+        self.shift_distances(n_shifts);
+        for history in [
+            &mut self.stm_history,
+            &mut self.ltm_history,
+            &mut self.both_history,
+        ] {
+            history.pop_front(n_shifts);
+        }
+        self.interleaved.clear();
+        true
+    }
+
+    fn shift_distances(&mut self, n: usize) {
+        for _ in 0..n.min(self.stm_distances.len()) {
+            self.stm_distances.pop_front();
+        }
+        for row in self.stm_distances.iter_mut() {
+            row.drain(..n.min(row.len()));
+        }
+    }
+
+    // Once the oldest sample of the STM is dropped, each window starts one sample earlier.
+    fn fade_interleaved(&mut self) {
+        let mut interleaved = std::mem::take(&mut self.interleaved);
+        if let Some(outcomes) = interleaved.get_mut(&0) {
+            if !outcomes.is_empty() {
+                outcomes.remove(0);
+            }
+        }
+        let replaced = interleaved.contains_key(&1);
+        for (start, outcomes) in interleaved {
+            if start > 0 {
+                self.interleaved.insert(start - 1, outcomes);
+            } else if !replaced {
+                self.interleaved.insert(0, outcomes);
+            }
+        }
+    }
+
+    // Drops the windows of the `n_removed` largest candidate sizes, and renumbers the others
+    // so that they start from the new first sample of the STM.
+    fn adapt_interleaved(&mut self, n_removed: usize) {
+        for _ in 0..n_removed {
+            let starts: Vec<usize> = self.interleaved.keys().copied().collect();
+            if starts.len() < 2 {
+                self.interleaved.clear();
+                break;
+            }
+            self.interleaved.remove(&starts[0]);
+            let delta = starts[1];
+            for &start in &starts[1..] {
+                if let Some(outcomes) = self.interleaved.remove(&start) {
+                    self.interleaved.insert(start - delta, outcomes);
+                }
+            }
+        }
+    }
+
+    // The size of the STM, then each half of the previous one, down to `min_stm_size`.
+    fn candidate_sizes(&self) -> Vec<f64> {
+        let mut sizes = vec![self.stm.len() as f64];
+        while let Some(&last) = sizes.last() {
+            if last / 2.0 < self.min_stm_size as f64 {
+                break;
+            }
+            sizes.push(last / 2.0);
+        }
+        sizes
+    }
+
+    // Outcomes of predicting each sample of the STM from `start` on with the ones between
+    // `start` and itself, beyond those already in `outcomes`.
+    fn interleaved_outcomes(&self, start: usize, mut outcomes: Vec<bool>) -> (f64, Vec<bool>) {
+        let k = self.n_neighbors;
+        let labels = &self.stm.labels()[start..];
+        for i in outcomes.len() + k..labels.len() {
+            let distances = &self.stm_distances[start + i][start..start + i];
+            let predicted = vote(
+                k_smallest(k, distances)
+                    .into_iter()
+                    .map(|j| (&labels[j], distances[j])),
+                self.weighted,
+            );
+            outcomes.push(predicted.as_ref() == Some(&labels[i]));
+        }
+        (accuracy(&outcomes), outcomes)
+    }
+
+    fn new_stm_size(&mut self) -> usize {
+        let n = self.stm.len();
+        if self.adaption == StmAdaption::Disabled || n < 2 * self.min_stm_size {
+            return n;
+        }
+        let approximate = self.adaption == StmAdaption::Approximate;
+        let sizes = self.candidate_sizes();
+        if !approximate {
+            self.interleaved
+                .retain(|&start, _| sizes.iter().any(|&size| n as f64 - size == start as f64));
+        }
+
+        let mut accuracies = Vec::with_capacity(sizes.len());
+        for &size in &sizes {
+            let start = (n as f64 - size) as usize;
+            let outcomes = match self.interleaved.remove(&start) {
+                Some(outcomes) => outcomes,
+                None if approximate && start > 0 => match self.interleaved.remove(&(start - 1)) {
+                    Some(mut outcomes) => {
+                        if !outcomes.is_empty() {
+                            outcomes.remove(0);
+                        }
+                        outcomes
+                    }
+                    None => Vec::new(),
+                },
+                None => Vec::new(),
+            };
+            let (acc, outcomes) = self.interleaved_outcomes(start, outcomes);
+            self.interleaved.insert(start, outcomes);
+            accuracies.push(round4(acc));
+        }
+
+        let mut best = argmax(&accuracies);
+        // The carried over accuracies are approximate, so those that beat the full STM are
+        // recomputed before trusting them.
+        if approximate && best > 0 {
+            for (i, &size) in sizes.iter().enumerate() {
+                if accuracies[i] > accuracies[0] {
+                    let start = (n as f64 - size) as usize;
+                    let (acc, outcomes) = self.interleaved_outcomes(start, Vec::new());
+                    self.interleaved.insert(start, outcomes);
+                    accuracies[i] = round4(acc);
+                }
+            }
+            best = argmax(&accuracies);
+        }
+
+        let size = sizes[best] as usize;
+        if size < n {
+            self.adapt_interleaved(best);
+        }
+        size
+    }
+}
+
+/// SAM-kNN over dicts of numeric features, with `int` or `str` labels.
+#[cfg(feature = "python")]
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSAMKNN {
+    model: SamKnn,
+    features: FeatureOrder,
+}
+
+#[cfg(feature = "python")]
+impl HeapSize for RsSAMKNN {
+    fn heap_size(&self) -> usize {
+        self.model.heap_size() + self.features.heap_size()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RsSAMKNN {
+    /// Same parameters as `river.neighbors.SAMKNNClassifier`: `stm_aprox_adaption` is
+    /// `True` for the approximate adaption of the size of the STM, `False` for the exact one
+    /// and `None` to keep it fixed. `seed` drives the k-means compression of the LTM.
+    #[new]
+    #[args(
+        n_neighbors = "5",
+        distance_weighting = "true",
+        window_size = "5000",
+        ltm_size = "0.4",
+        min_stm_size = "50",
+        stm_aprox_adaption = "true",
+        use_ltm = "true",
+        seed = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        n_neighbors: usize,
+        distance_weighting: bool,
+        window_size: usize,
+        ltm_size: f64,
+        min_stm_size: usize,
+        stm_aprox_adaption: Option<bool>,
+        use_ltm: bool,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let adaption = match stm_aprox_adaption {
+            Some(true) => StmAdaption::Approximate,
+            Some(false) => StmAdaption::Exact,
+            None => StmAdaption::Disabled,
+        };
+        let model = SamKnn::new(
+            n_neighbors,
+            distance_weighting,
+            window_size,
+            ltm_size,
+            min_stm_size,
+            adaption,
+            use_ltm,
+            seed,
+        )
+        .map_err(PyValueError::new_err)?;
+        Ok(RsSAMKNN {
+            model,
+            features: FeatureOrder::default(),
+        })
+    }
+
+    pub fn learn_one(&mut self, x: HashMap<String, f64>, y: Key) {
+        let x = self.features.learn(&x);
+        self.model.learn_one(&x, y);
+    }
+
+    /// Predicted label, or `None` before any sample is learnt.
+    pub fn predict_one(&self, x: HashMap<String, f64>) -> Option<Key> {
+        self.model.predict_one(&self.features.read(&x))
+    }
+
+    #[getter]
+    pub fn stm_size(&self) -> usize {
+        self.model.stm().len()
+    }
+
+    #[getter]
+    pub fn ltm_size(&self) -> usize {
+        self.model.ltm().len()
+    }
+
+    #[getter]
+    pub fn stm_labels(&self) -> Vec<Key> {
+        self.model.stm().labels().to_vec()
+    }
+
+    #[getter]
+    pub fn ltm_labels(&self) -> Vec<Key> {
+        self.model.ltm().labels().to_vec()
+    }
+}
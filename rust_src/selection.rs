@@ -0,0 +1,580 @@
+// Online model selection among Rust-backed candidates, like `river.model_selection`.
+//
+// The candidates are copies of calibrators, each scored by its own copy of a native metric,
+// so that a sample is dispatched to all of them, and their metrics compared, without going
+// through Python.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::{
+    check_score, IsotonicCalibrator, PlattCalibrator, RsIsotonicCalibrator, RsPlattCalibrator,
+};
+use crate::ingest::Values;
+use crate::memory::HeapSize;
+use crate::metrics::{Accuracy, LogLoss, RsAccuracy, RsLogLoss};
+use crate::rng::Rng;
+
+/// Copy of a Rust-backed binary classifier.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Candidate {
+    Isotonic(IsotonicCalibrator),
+    Platt(PlattCalibrator),
+}
+
+impl HeapSize for Candidate {
+    fn heap_size(&self) -> usize {
+        match self {
+            Candidate::Isotonic(model) => model.heap_size(),
+            Candidate::Platt(model) => model.heap_size(),
+        }
+    }
+}
+
+impl Candidate {
+    pub fn from_py(model: &PyAny) -> PyResult<Self> {
+        if let Ok(model) = model.extract::<PyRef<RsIsotonicCalibrator>>() {
+            return Ok(Candidate::Isotonic(model.calibrator.clone()));
+        }
+        if let Ok(model) = model.extract::<PyRef<RsPlattCalibrator>>() {
+            return Ok(Candidate::Platt(model.calibrator.clone()));
+        }
+        Err(PyTypeError::new_err(format!(
+            "{} is not a Rust-backed model",
+            model.get_type().name()?
+        )))
+    }
+
+    pub fn to_py(&self, py: Python) -> PyResult<PyObject> {
+        Ok(match self {
+            Candidate::Isotonic(model) => Py::new(
+                py,
+                RsIsotonicCalibrator {
+                    calibrator: model.clone(),
+                },
+            )?
+            .into_py(py),
+            Candidate::Platt(model) => Py::new(
+                py,
+                RsPlattCalibrator {
+                    calibrator: model.clone(),
+                },
+            )?
+            .into_py(py),
+        })
+    }
+
+    pub fn predict_proba(&self, p: f64) -> f64 {
+        match self {
+            Candidate::Isotonic(model) => model.transform_one(p),
+            Candidate::Platt(model) => model.transform_one(p),
+        }
+    }
+
+    pub fn learn(&mut self, p: f64, y: bool) {
+        match self {
+            Candidate::Isotonic(model) => model.learn_one(p, y),
+            Candidate::Platt(model) => model.learn_one(p, y),
+        }
+    }
+}
+
+/// Native metric of a candidate.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Score {
+    LogLoss(LogLoss),
+    Accuracy(Accuracy),
+}
+
+impl Score {
+    /// Fresh metric of the same kind as `metric`.
+    pub fn from_py(metric: &PyAny) -> PyResult<Self> {
+        if metric.extract::<PyRef<RsLogLoss>>().is_ok() {
+            return Ok(Score::LogLoss(LogLoss::default()));
+        }
+        if metric.extract::<PyRef<RsAccuracy>>().is_ok() {
+            return Ok(Score::Accuracy(Accuracy::default()));
+        }
+        Err(PyTypeError::new_err(format!(
+            "{} is not a Rust-backed metric",
+            metric.get_type().name()?
+        )))
+    }
+
+    // Hard predictions threshold the probability of the positive class at 0.5.
+    pub fn update(&mut self, y: bool, p: f64) {
+        match self {
+            Score::LogLoss(metric) => metric.update(y, p),
+            Score::Accuracy(metric) => metric.update((p >= 0.5) == y),
+        }
+    }
+
+    pub fn get(&self) -> f64 {
+        match self {
+            Score::LogLoss(metric) => metric.get(),
+            Score::Accuracy(metric) => metric.get(),
+        }
+    }
+
+    pub fn bigger_is_better(&self) -> bool {
+        matches!(self, Score::Accuracy(_))
+    }
+
+    /// Value of the metric, signed so that bigger is better.
+    pub fn reward(&self) -> f64 {
+        if self.bigger_is_better() {
+            self.get()
+        } else {
+            -self.get()
+        }
+    }
+
+    pub fn is_better_than(&self, other: &Score) -> bool {
+        self.reward() > other.reward()
+    }
+}
+
+// Index of the first largest value, like `max` in Python.
+fn argmax(values: impl Iterator<Item = f64>) -> usize {
+    let mut best = (0, f64::NEG_INFINITY);
+    for (i, value) in values.enumerate() {
+        if value > best.1 {
+            best = (i, value);
+        }
+    }
+    best.0
+}
+
+fn extract_batch(x: &PyAny, y: &PyAny) -> PyResult<(Vec<f64>, Vec<f64>)> {
+    let x = Values::extract(x)?.slices().concat();
+    let y = Values::extract(y)?.slices().concat();
+    if x.len() != y.len() {
+        return Err(PyValueError::new_err(format!(
+            "x and y should have the same length, got {} and {}",
+            x.len(),
+            y.len()
+        )));
+    }
+    for &xi in &x {
+        check_score(xi)?;
+    }
+    Ok((x, y))
+}
+
+fn extract_candidates(models: Vec<&PyAny>) -> PyResult<Vec<Candidate>> {
+    if models.len() < 2 {
+        return Err(PyValueError::new_err("there should be at least two models"));
+    }
+    models.into_iter().map(Candidate::from_py).collect()
+}
+
+/// Successive halving, as in `model_selection.SuccessiveHalvingClassifier`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SuccessiveHalving {
+    models: Vec<Candidate>,
+    metrics: Vec<Score>,
+    budget: usize,
+    eta: f64,
+    // Models by rank at the last rung, of which the first `n_survivors` are still trained.
+    rankings: Vec<usize>,
+    n_survivors: usize,
+    n_rungs: usize,
+    iterations_per_rung: usize,
+    budget_used: usize,
+    n_iterations: usize,
+    best: usize,
+}
+
+impl HeapSize for SuccessiveHalving {
+    fn heap_size(&self) -> usize {
+        self.models.heap_size()
+            + self.metrics.capacity() * std::mem::size_of::<Score>()
+            + self.rankings.heap_size()
+    }
+}
+
+impl SuccessiveHalving {
+    // Iterations after which the rung with `n_survivors` models ends, counted from the
+    // start, as in `river`.
+    fn iterations_per_rung(&self) -> usize {
+        let n_rungs = (self.models.len() as f64).log(self.eta).ceil();
+        (self.budget as f64 / (self.n_survivors as f64 * n_rungs)).floor() as usize
+    }
+
+    pub fn learn_one(&mut self, p: f64, y: bool) {
+        for &i in &self.rankings[..self.n_survivors] {
+            let y_pred = self.models[i].predict_proba(p);
+            self.metrics[i].update(y, y_pred);
+            self.models[i].learn(p, y);
+            if self.metrics[i].is_better_than(&self.metrics[self.best]) {
+                self.best = i;
+            }
+        }
+        self.n_iterations += 1;
+        if self.n_survivors > 1 && self.n_iterations == self.iterations_per_rung {
+            self.n_rungs += 1;
+            self.budget_used += self.n_survivors * self.iterations_per_rung;
+            let metrics = &self.metrics;
+            self.rankings[..self.n_survivors]
+                .sort_by(|&a, &b| metrics[b].reward().total_cmp(&metrics[a].reward()));
+            self.n_survivors = (self.n_survivors as f64 / self.eta).ceil() as usize;
+            self.iterations_per_rung = self.iterations_per_rung();
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSuccessiveHalving {
+    selector: SuccessiveHalving,
+}
+
+impl HeapSize for RsSuccessiveHalving {
+    fn heap_size(&self) -> usize {
+        self.selector.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsSuccessiveHalving {
+    /// Trains copies of the `models`, `RsIsotonicCalibrator`s or `RsPlattCalibrator`s, each
+    /// scored by a fresh copy of `metric`, a `RsLogLoss` or a `RsAccuracy`.
+    ///
+    /// After each rung, only the best `1 / eta` of the models are trained on, and the
+    /// rungs are sized so that the whole selection takes about `budget` model updates.
+    #[new]
+    #[args(eta = "2.0")]
+    pub fn new(models: Vec<&PyAny>, metric: &PyAny, budget: usize, eta: f64) -> PyResult<Self> {
+        let models = extract_candidates(models)?;
+        let metric = Score::from_py(metric)?;
+        if eta.is_nan() || eta <= 1.0 {
+            return Err(PyValueError::new_err("eta should be greater than 1"));
+        }
+        let n = models.len();
+        let mut selector = SuccessiveHalving {
+            models,
+            metrics: vec![metric; n],
+            budget,
+            eta,
+            rankings: (0..n).collect(),
+            n_survivors: n,
+            n_rungs: 0,
+            iterations_per_rung: 0,
+            budget_used: 0,
+            n_iterations: 0,
+            best: 0,
+        };
+        selector.iterations_per_rung = selector.iterations_per_rung();
+        Ok(RsSuccessiveHalving { selector })
+    }
+
+    /// `x` is the score to calibrate.
+    pub fn learn_one(&mut self, x: f64, y: bool) -> PyResult<()> {
+        check_score(x)?;
+        self.selector.learn_one(x, y);
+        Ok(())
+    }
+
+    /// Learns the scores of `x` and the labels of `y` in turn, without the GIL.
+    pub fn learn_many(&mut self, py: Python, x: &PyAny, y: &PyAny) -> PyResult<()> {
+        let (x, y) = extract_batch(x, y)?;
+        let selector = &mut self.selector;
+        py.allow_threads(|| {
+            for (&xi, &yi) in x.iter().zip(&y) {
+                selector.learn_one(xi, yi != 0.0);
+            }
+        });
+        Ok(())
+    }
+
+    /// Probability of the positive class according to the best model.
+    pub fn predict_proba_one(&self, x: f64) -> PyResult<f64> {
+        check_score(x)?;
+        Ok(self.selector.models[self.selector.best].predict_proba(x))
+    }
+
+    /// Copy of the best model so far.
+    #[getter]
+    pub fn best_model(&self, py: Python) -> PyResult<PyObject> {
+        self.selector.models[self.selector.best].to_py(py)
+    }
+
+    #[getter]
+    pub fn best(&self) -> usize {
+        self.selector.best
+    }
+
+    /// Models still being trained, best first as of the last rung.
+    #[getter]
+    pub fn survivors(&self) -> Vec<usize> {
+        self.selector.rankings[..self.selector.n_survivors].to_vec()
+    }
+
+    /// Value of the metric of each model.
+    pub fn scores(&self) -> Vec<f64> {
+        self.selector.metrics.iter().map(Score::get).collect()
+    }
+
+    #[getter]
+    pub fn n_rungs(&self) -> usize {
+        self.selector.n_rungs
+    }
+
+    #[getter]
+    pub fn budget_used(&self) -> usize {
+        self.selector.budget_used
+    }
+}
+
+/// Bandit policy choosing the model to train, as in `river.bandit`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Policy {
+    EpsilonGreedy { epsilon: f64, decay: f64 },
+    Ucb { delta: f64 },
+    Thompson,
+}
+
+impl Policy {
+    fn name(&self) -> &'static str {
+        match self {
+            Policy::EpsilonGreedy { .. } => "epsilon_greedy",
+            Policy::Ucb { .. } => "ucb",
+            Policy::Thompson => "thompson",
+        }
+    }
+}
+
+/// Bandit-based selection, as in `model_selection.BanditClassifier`.
+///
+/// The reward of a model is its metric. Thompson sampling draws from a beta posterior of
+/// the share of correct predictions of each model instead.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BanditSelection {
+    models: Vec<Candidate>,
+    metrics: Vec<Score>,
+    // Correct and wrong predictions of each model.
+    outcomes: Vec<(f64, f64)>,
+    counts: Vec<u64>,
+    n: u64,
+    policy: Policy,
+    burn_in: u64,
+    rng: Rng,
+}
+
+impl HeapSize for BanditSelection {
+    fn heap_size(&self) -> usize {
+        self.models.heap_size()
+            + self.metrics.capacity() * std::mem::size_of::<Score>()
+            + self.outcomes.capacity() * std::mem::size_of::<(f64, f64)>()
+            + self.counts.capacity() * std::mem::size_of::<u64>()
+    }
+}
+
+impl BanditSelection {
+    fn pull(&mut self) -> usize {
+        let n_arms = self.models.len();
+        match self.policy {
+            Policy::EpsilonGreedy { epsilon, decay } => {
+                let epsilon = epsilon * (-(self.n as f64) * decay).exp();
+                if self.rng.next_f64() < epsilon {
+                    self.rng.below(n_arms as u64) as usize
+                } else {
+                    argmax(self.metrics.iter().map(Score::reward))
+                }
+            }
+            Policy::Ucb { delta } => {
+                let log_n = (self.n as f64).ln();
+                argmax(self.metrics.iter().zip(&self.counts).map(|(m, &c)| {
+                    if c == 0 {
+                        f64::INFINITY
+                    } else {
+                        m.reward() + delta * (2.0 * log_n / c as f64).sqrt()
+                    }
+                }))
+            }
+            Policy::Thompson => {
+                let rng = &mut self.rng;
+                argmax(
+                    self.outcomes
+                        .iter()
+                        .map(|&(wins, losses)| rng.beta(1.0 + wins, 1.0 + losses)),
+                )
+            }
+        }
+    }
+
+    // Every model is trained `burn_in` times before the policy chooses.
+    fn arms(&mut self) -> Vec<usize> {
+        let burning: Vec<usize> = (0..self.models.len())
+            .filter(|&i| self.counts[i] < self.burn_in)
+            .collect();
+        if burning.is_empty() {
+            vec![self.pull()]
+        } else {
+            burning
+        }
+    }
+
+    pub fn learn_one(&mut self, p: f64, y: bool) {
+        for i in self.arms() {
+            let y_pred = self.models[i].predict_proba(p);
+            self.metrics[i].update(y, y_pred);
+            if (y_pred >= 0.5) == y {
+                self.outcomes[i].0 += 1.0;
+            } else {
+                self.outcomes[i].1 += 1.0;
+            }
+            self.counts[i] += 1;
+            self.n += 1;
+            self.models[i].learn(p, y);
+        }
+    }
+
+    /// Model with the best metric among those trained, if any.
+    pub fn best(&self) -> Option<usize> {
+        let rewards = self.metrics.iter().zip(&self.counts).map(|(m, &c)| {
+            if c > 0 {
+                m.reward()
+            } else {
+                f64::NEG_INFINITY
+            }
+        });
+        let best = argmax(rewards);
+        (self.counts[best] > 0).then_some(best)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsBanditSelection {
+    selector: BanditSelection,
+}
+
+impl HeapSize for RsBanditSelection {
+    fn heap_size(&self) -> usize {
+        self.selector.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsBanditSelection {
+    /// Trains copies of the `models`, `RsIsotonicCalibrator`s or `RsPlattCalibrator`s, each
+    /// scored by a fresh copy of `metric`, a `RsLogLoss` or a `RsAccuracy`. Each sample
+    /// trains the model chosen by the `policy`:
+    ///
+    /// - `"epsilon_greedy"`, the best model, or a random one with probability `epsilon`,
+    ///   which decays as `epsilon * exp(-decay * n)` after `n` updates,
+    /// - `"ucb"`, the model with the best metric plus `delta` times the usual bonus of the
+    ///   models trained less often,
+    /// - `"thompson"`, the model with the best draw from the posterior of its accuracy.
+    #[new]
+    #[args(
+        policy = "\"epsilon_greedy\"",
+        epsilon = "0.1",
+        decay = "0.0",
+        delta = "1.0",
+        burn_in = "0",
+        seed = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        models: Vec<&PyAny>,
+        metric: &PyAny,
+        policy: &str,
+        epsilon: f64,
+        decay: f64,
+        delta: f64,
+        burn_in: u64,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let models = extract_candidates(models)?;
+        let metric = Score::from_py(metric)?;
+        let policy = match policy {
+            "epsilon_greedy" => {
+                if !(0.0..=1.0).contains(&epsilon) {
+                    return Err(PyValueError::new_err("epsilon should be between 0 and 1"));
+                }
+                Policy::EpsilonGreedy { epsilon, decay }
+            }
+            "ucb" => Policy::Ucb { delta },
+            "thompson" => Policy::Thompson,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown policy '{}', expected 'epsilon_greedy', 'ucb' or 'thompson'",
+                    policy
+                )))
+            }
+        };
+        let n = models.len();
+        Ok(RsBanditSelection {
+            selector: BanditSelection {
+                models,
+                metrics: vec![metric; n],
+                outcomes: vec![(0.0, 0.0); n],
+                counts: vec![0; n],
+                n: 0,
+                policy,
+                burn_in,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+
+    /// `x` is the score to calibrate.
+    pub fn learn_one(&mut self, x: f64, y: bool) -> PyResult<()> {
+        check_score(x)?;
+        self.selector.learn_one(x, y);
+        Ok(())
+    }
+
+    /// Learns the scores of `x` and the labels of `y` in turn, without the GIL.
+    pub fn learn_many(&mut self, py: Python, x: &PyAny, y: &PyAny) -> PyResult<()> {
+        let (x, y) = extract_batch(x, y)?;
+        let selector = &mut self.selector;
+        py.allow_threads(|| {
+            for (&xi, &yi) in x.iter().zip(&y) {
+                selector.learn_one(xi, yi != 0.0);
+            }
+        });
+        Ok(())
+    }
+
+    /// Probability of the positive class according to the best model, or the first one
+    /// before any is trained.
+    pub fn predict_proba_one(&self, x: f64) -> PyResult<f64> {
+        check_score(x)?;
+        let best = self.selector.best().unwrap_or(0);
+        Ok(self.selector.models[best].predict_proba(x))
+    }
+
+    /// Copy of the best model so far, or `None` before any is trained.
+    #[getter]
+    pub fn best_model(&self, py: Python) -> PyResult<Option<PyObject>> {
+        self.selector
+            .best()
+            .map(|best| self.selector.models[best].to_py(py))
+            .transpose()
+    }
+
+    #[getter]
+    pub fn best(&self) -> Option<usize> {
+        self.selector.best()
+    }
+
+    /// Number of samples each model was trained on.
+    #[getter]
+    pub fn counts(&self) -> Vec<u64> {
+        self.selector.counts.clone()
+    }
+
+    /// Value of the metric of each model.
+    pub fn scores(&self) -> Vec<f64> {
+        self.selector.metrics.iter().map(Score::get).collect()
+    }
+
+    #[getter]
+    pub fn policy(&self) -> &'static str {
+        self.selector.policy.name()
+    }
+}
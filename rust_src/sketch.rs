@@ -0,0 +1,1075 @@
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::{splitmix64, Rng};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+impl HeapSize for Centroid {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Merging t-digest with the arcsine scale function.
+///
+/// Incoming points are buffered and merged into the centroids once the buffer is full, each
+/// centroid being limited to one unit of the scale function so that the tails are summarized
+/// much more finely than the bulk of the distribution.
+///
+/// Reference: Dunning, T. and Ertl, O., 2019. Computing extremely accurate quantiles using
+/// t-digests. arXiv preprint arXiv:1902.04023.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    total_weight: f64,
+    min: f64,
+    max: f64,
+    // Knots of the merged centroids, built by the first query following an update.
+    #[serde(skip)]
+    knots: OnceCell<Vec<(f64, f64)>>,
+}
+
+impl HeapSize for TDigest {
+    fn heap_size(&self) -> usize {
+        self.centroids.heap_size()
+            + self.buffer.heap_size()
+            + self.knots.get().map_or(0, |knots| knots.heap_size())
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            total_weight: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            knots: OnceCell::new(),
+        }
+    }
+
+    pub fn total_weight(&self) -> f64 {
+        self.total_weight
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_weight == 0.0
+    }
+
+    /// Adds `x` with weight `w`, a null weight leaving the digest as it is.
+    pub fn update(&mut self, x: f64, w: f64) {
+        if w == 0.0 {
+            return;
+        }
+        self.knots.take();
+        self.buffer.push(Centroid { mean: x, weight: w });
+        self.total_weight += w;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        if self.buffer.len() as f64 >= 5.0 * self.compression {
+            self.compress();
+        }
+    }
+
+    /// Folds in the centroids of `other`, keeping the compression of this digest.
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        self.knots.take();
+        self.buffer.extend(other.merged().iter().copied());
+        self.total_weight += other.total_weight;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Merges the buffered points into the centroids.
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        self.centroids = self.merged().into_owned();
+        self.buffer.clear();
+    }
+
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_inv(&self, k: f64) -> f64 {
+        if k >= self.compression / 4.0 {
+            return 1.0;
+        }
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    fn merged(&self) -> Cow<'_, [Centroid]> {
+        if self.buffer.is_empty() {
+            return Cow::Borrowed(&self.centroids);
+        }
+        let mut all: Vec<Centroid> = self.centroids.iter().chain(&self.buffer).cloned().collect();
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total = self.total_weight;
+        let mut merged = Vec::with_capacity(all.len());
+        let mut current = all[0];
+        let mut weight_so_far = 0.0;
+        let mut q_limit = self.k_inv(self.k(0.0) + 1.0);
+        for c in all.into_iter().skip(1) {
+            let q = (weight_so_far + current.weight + c.weight) / total;
+            if q <= q_limit {
+                let weight = current.weight + c.weight;
+                current.mean += (c.mean - current.mean) * c.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                merged.push(current);
+                q_limit = self.k_inv(self.k(weight_so_far / total) + 1.0);
+                current = c;
+            }
+        }
+        merged.push(current);
+        Cow::Owned(merged)
+    }
+
+    // Each centroid is placed at the cumulative weight of its middle, with the extremes
+    // anchoring both ends of the distribution. The buffer is merged once for all the
+    // queries until the next update.
+    fn knots(&self) -> &[(f64, f64)] {
+        self.knots.get_or_init(|| self.build_knots())
+    }
+
+    fn build_knots(&self) -> Vec<(f64, f64)> {
+        let centroids = self.merged();
+        let mut knots = Vec::with_capacity(centroids.len() + 2);
+        knots.push((0.0, self.min));
+        let mut cum = 0.0;
+        for c in centroids.iter() {
+            knots.push((cum + c.weight / 2.0, c.mean));
+            cum += c.weight;
+        }
+        knots.push((cum, self.max));
+        knots
+    }
+
+    /// Estimated value below which a fraction `q` of the weight lies.
+    pub fn quantile(&self, q: f64) -> f64 {
+        self.quantiles(&[q])[0]
+    }
+
+    /// Same as `quantile` for several fractions, with a single pass over the centroids.
+    pub fn quantiles(&self, qs: &[f64]) -> Vec<f64> {
+        if self.is_empty() {
+            return vec![f64::NAN; qs.len()];
+        }
+        let knots = self.knots();
+        qs.iter()
+            .map(|&q| {
+                let target = q.clamp(0.0, 1.0) * self.total_weight;
+                let i = knots.partition_point(|&(pos, _)| pos < target);
+                if i == 0 {
+                    return knots[0].1;
+                }
+                if i == knots.len() {
+                    return knots[knots.len() - 1].1;
+                }
+                let (p0, v0) = knots[i - 1];
+                let (p1, v1) = knots[i];
+                if p1 == p0 {
+                    return v1;
+                }
+                v0 + (v1 - v0) * (target - p0) / (p1 - p0)
+            })
+            .collect()
+    }
+
+    /// Estimated fraction of the weight lying below `x`, NaN while the digest is empty or
+    /// for a NaN `x`.
+    pub fn cdf(&self, x: f64) -> f64 {
+        if self.is_empty() || x.is_nan() {
+            return f64::NAN;
+        }
+        self.cdf_with(self.knots(), x)
+    }
+
+    /// Estimated median absolute deviation from the median, NaN while the digest is empty.
+    ///
+    /// The deviation `d` is the half-width of the interval around the median which holds
+    /// half of the weight, found by bisection on the estimated CDF.
+    pub fn mad(&self) -> f64 {
+        if self.is_empty() {
+            return f64::NAN;
+        }
+        let knots = self.knots();
+        let median = self.quantile(0.5);
+        let (mut lo, mut hi) = (0.0, (self.max - median).max(median - self.min));
+        // The interval shrinks below the precision of the floats well before that.
+        for _ in 0..100 {
+            let d = (lo + hi) / 2.0;
+            if d <= lo || d >= hi {
+                break;
+            }
+            if self.cdf_with(knots, median + d) - self.cdf_with(knots, median - d) < 0.5 {
+                lo = d;
+            } else {
+                hi = d;
+            }
+        }
+        hi
+    }
+
+    fn cdf_with(&self, knots: &[(f64, f64)], x: f64) -> f64 {
+        if x < self.min {
+            return 0.0;
+        }
+        if x >= self.max {
+            return 1.0;
+        }
+        let i = knots.partition_point(|&(_, v)| v <= x);
+        let (p0, v0) = knots[i - 1];
+        let (p1, v1) = knots[i];
+        let pos = if v1 == v0 {
+            p1
+        } else {
+            p0 + (p1 - p0) * (x - v0) / (v1 - v0)
+        };
+        pos / self.total_weight
+    }
+}
+
+// Forward-decayed weights are rescaled before their exponent grows large enough to lose
+// precision.
+const MAX_DECAY_EXPONENT: f64 = 64.0;
+
+/// Space-Saving sketch of the heaviest keys, whose counts halve every `halflife` time units.
+///
+/// Counts are stored forward-decayed: an update at time `t` adds `2^((t - landmark) /
+/// halflife)` rather than decaying every counter, which keeps the updates in constant time
+/// and the counters in the same order as their decayed values. At most `capacity` keys are
+/// tracked; a new key replaces the one with the lowest count and inherits that count, which
+/// bounds its overestimation.
+///
+/// Reference: Cormode, G., Korn, F. and Tirthapura, S., 2008. Exponentially decayed
+/// aggregates on data streams. In 2008 IEEE 24th International Conference on Data
+/// Engineering, pp.1379-1381.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DecayedTopK {
+    capacity: usize,
+    halflife: f64,
+    landmark: f64,
+    now: f64,
+    // Forward-decayed count and overestimation of every tracked key.
+    #[serde(with = "crate::key::pairs")]
+    counters: HashMap<Key, (f64, f64)>,
+}
+
+impl HeapSize for DecayedTopK {
+    fn heap_size(&self) -> usize {
+        self.counters.heap_size()
+    }
+}
+
+impl DecayedTopK {
+    pub fn new(capacity: usize, halflife: f64) -> Self {
+        DecayedTopK {
+            capacity,
+            halflife,
+            landmark: 0.0,
+            now: 0.0,
+            counters: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    pub fn update(&mut self, key: Key, w: f64, t: f64) -> Result<(), String> {
+        if t.is_nan() || t < self.now {
+            return Err(format!(
+                "t should not decrease, got {} after {}",
+                t, self.now
+            ));
+        }
+        self.now = t;
+        let exponent = (t - self.landmark) / self.halflife;
+        if exponent > MAX_DECAY_EXPONENT {
+            let scale = (-exponent).exp2();
+            for (count, error) in self.counters.values_mut() {
+                *count *= scale;
+                *error *= scale;
+            }
+            self.landmark = t;
+        }
+        let w = w * ((t - self.landmark) / self.halflife).exp2();
+        if let Some((count, _)) = self.counters.get_mut(&key) {
+            *count += w;
+        } else if self.counters.len() < self.capacity {
+            self.counters.insert(key, (w, 0.0));
+        } else {
+            let (lightest, &(min, _)) = self
+                .counters
+                .iter()
+                .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0))
+                .unwrap();
+            let lightest = lightest.clone();
+            self.counters.remove(&lightest);
+            self.counters.insert(key, (min + w, min));
+        }
+        Ok(())
+    }
+
+    fn decay(&self) -> f64 {
+        (-(self.now - self.landmark) / self.halflife).exp2()
+    }
+
+    /// Decayed count of `key`, 0 if it isn't tracked.
+    pub fn get(&self, key: &Key) -> f64 {
+        self.counters
+            .get(key)
+            .map_or(0.0, |&(count, _)| count * self.decay())
+    }
+
+    /// The `k` keys with the highest decayed counts, along with their counts and their
+    /// maximal overestimations, from the heaviest down.
+    pub fn top(&self, k: usize) -> Vec<(Key, f64, f64)> {
+        let decay = self.decay();
+        let mut top: Vec<(Key, f64, f64)> = self
+            .counters
+            .iter()
+            .map(|(key, &(count, error))| (key.clone(), count * decay, error * decay))
+            .collect();
+        top.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(k);
+        top
+    }
+}
+
+/// Space-Saving sketch of the heaviest keys of a stream.
+///
+/// At most `capacity` keys are tracked; a new key replaces the one with the lowest count and
+/// inherits that count, which is then an upper bound of its overestimation. Any key whose
+/// weight exceeds `total / capacity` is tracked.
+///
+/// References:
+/// - Metwally, A., Agrawal, D. and El Abbadi, A., 2005. Efficient computation of frequent
+///   and top-k elements in data streams. In International Conference on Database Theory,
+///   pp.398-412.
+/// - Agarwal, P.K., Cormode, G., Huang, Z., Phillips, J.M., Wei, Z. and Yi, K., 2013.
+///   Mergeable summaries. ACM Transactions on Database Systems, 38(4), pp.1-28.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpaceSaving {
+    capacity: usize,
+    total: f64,
+    // Count and overestimation of every tracked key, ordered so that equal sketches
+    // serialize the same and ties are evicted deterministically.
+    #[serde(with = "crate::key::pairs")]
+    counters: BTreeMap<Key, (f64, f64)>,
+}
+
+impl HeapSize for SpaceSaving {
+    fn heap_size(&self) -> usize {
+        self.counters.heap_size()
+    }
+}
+
+impl SpaceSaving {
+    pub fn new(capacity: usize) -> Self {
+        SpaceSaving {
+            capacity,
+            total: 0.0,
+            counters: BTreeMap::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    pub fn update(&mut self, key: Key, w: f64) {
+        self.total += w;
+        if let Some((count, _)) = self.counters.get_mut(&key) {
+            *count += w;
+        } else if self.counters.len() < self.capacity {
+            self.counters.insert(key, (w, 0.0));
+        } else {
+            let (lightest, &(min, _)) = self
+                .counters
+                .iter()
+                .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0))
+                .unwrap();
+            let lightest = lightest.clone();
+            self.counters.remove(&lightest);
+            self.counters.insert(key, (min + w, min));
+        }
+    }
+
+    // Count which the untracked keys may have reached.
+    fn floor(&self) -> f64 {
+        if self.counters.len() < self.capacity {
+            return 0.0;
+        }
+        self.counters
+            .values()
+            .map(|&(count, _)| count)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Count of `key`, 0 if it isn't tracked.
+    pub fn get(&self, key: &Key) -> f64 {
+        self.counters.get(key).map_or(0.0, |&(count, _)| count)
+    }
+
+    /// Upper bound of the overestimation of the count of `key`.
+    pub fn error(&self, key: &Key) -> f64 {
+        self.counters
+            .get(key)
+            .map_or_else(|| self.floor(), |&(_, error)| error)
+    }
+
+    /// The `k` keys with the highest counts, along with their counts and their maximal
+    /// overestimations, from the heaviest down.
+    pub fn top(&self, k: usize) -> Vec<(Key, f64, f64)> {
+        let mut top: Vec<(Key, f64, f64)> = self
+            .counters
+            .iter()
+            .map(|(key, &(count, error))| (key.clone(), count, error))
+            .collect();
+        top.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(k);
+        top
+    }
+
+    /// Counts the keys of `other` as well. A key missing from either sketch is counted with
+    /// the count it may have reached there, and only the `capacity` heaviest keys are kept.
+    pub fn merge(&mut self, other: &SpaceSaving) {
+        let (floor, other_floor) = (self.floor(), other.floor());
+        let mut merged = self.counters.clone();
+        for (count, error) in merged.values_mut() {
+            *count += other_floor;
+            *error += other_floor;
+        }
+        for (key, &(count, error)) in &other.counters {
+            let entry = merged.entry(key.clone());
+            let (c, e) = entry.or_insert((floor + other_floor, floor + other_floor));
+            *c += count - other_floor;
+            *e += error - other_floor;
+        }
+        let mut counters: Vec<(Key, (f64, f64))> = merged.into_iter().collect();
+        counters.sort_by(|a, b| b.1 .0.total_cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+        counters.truncate(self.capacity);
+        self.counters = counters.into_iter().collect();
+        self.total += other.total;
+    }
+}
+
+/// Checks the precision of a `HyperLogLog`, which needs some index bits and keeps its
+/// registers small.
+pub fn check_precision(precision: u32) -> Result<(), String> {
+    if !(4..=16).contains(&precision) {
+        return Err("precision should be between 4 and 16".to_string());
+    }
+    Ok(())
+}
+
+/// HyperLogLog estimate of the number of distinct hashes, with `2^precision` registers.
+///
+/// Reference: Flajolet, P., Fusy, É., Gandouet, O. and Meunier, F., 2007. HyperLogLog: the
+/// analysis of a near-optimal cardinality estimation algorithm. In Discrete Mathematics and
+/// Theoretical Computer Science, pp.137-156.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    precision: u32,
+    registers: Vec<u8>,
+}
+
+impl HeapSize for HyperLogLog {
+    fn heap_size(&self) -> usize {
+        self.registers.capacity()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new(precision: u32) -> Result<Self, String> {
+        check_precision(precision)?;
+        Ok(HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+        })
+    }
+
+    pub fn update(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.precision)) as usize;
+        // Position of the first 1 bit after the index bits, the sentinel bounding it.
+        let rank = ((hash << self.precision) | (1 << (self.precision - 1))).leading_zeros() + 1;
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// Takes the union with the hashes counted by `other`, which should have the same
+    /// precision.
+    pub fn merge(&mut self, other: &HyperLogLog) -> Result<(), String> {
+        if self.precision != other.precision {
+            return Err("the sketches should have the same precision".to_string());
+        }
+        for (register, &other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(other);
+        }
+        Ok(())
+    }
+
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|&r| (-(r as f64)).exp2()).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // Linear counting is more accurate while many registers are still empty.
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// Bin of a `Histogram`, holding the `count` values seen between `left` and `right`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bin {
+    pub left: f64,
+    pub right: f64,
+    pub count: u64,
+}
+
+impl HeapSize for Bin {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Streaming histogram of at most `max_bins` bins, which merges the two closest bins
+/// whenever a value falls outside of the existing ones and there are too many of them.
+///
+/// As in `river.sketch.Histogram`, the bins are intervals, and the distance between two
+/// bins is the one between their right ends. Two histograms are merged by pooling their
+/// bins and then merging the closest ones down to `max_bins`.
+///
+/// Reference: Ben-Haim, Y. and Tom-Tov, E., 2010. A streaming parallel decision tree
+/// algorithm. Journal of Machine Learning Research, 11(Feb), pp.849-872.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Histogram {
+    max_bins: usize,
+    bins: Vec<Bin>,
+    n: u64,
+}
+
+impl HeapSize for Histogram {
+    fn heap_size(&self) -> usize {
+        self.bins.heap_size()
+    }
+}
+
+impl Histogram {
+    pub fn new(max_bins: usize) -> Self {
+        Histogram {
+            max_bins,
+            bins: Vec::with_capacity(max_bins + 1),
+            n: 0,
+        }
+    }
+
+    /// Number of values seen.
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+
+    pub fn bins(&self) -> &[Bin] {
+        &self.bins
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.n += 1;
+        let i = self.bins.partition_point(|b| b.right < x);
+        if i < self.bins.len() && x >= self.bins[i].left {
+            self.bins[i].count += 1;
+            return;
+        }
+        self.bins.insert(
+            i,
+            Bin {
+                left: x,
+                right: x,
+                count: 1,
+            },
+        );
+        self.shrink();
+    }
+
+    // Merges the closest bins until there are at most `max_bins` of them.
+    fn shrink(&mut self) {
+        while self.bins.len() > self.max_bins.max(1) {
+            let i = (0..self.bins.len() - 1)
+                .min_by(|&i, &j| {
+                    let gap = |i: usize| self.bins[i + 1].right - self.bins[i].right;
+                    gap(i).total_cmp(&gap(j))
+                })
+                .unwrap();
+            let next = self.bins.remove(i + 1);
+            let bin = &mut self.bins[i];
+            bin.left = bin.left.min(next.left);
+            bin.right = bin.right.max(next.right);
+            bin.count += next.count;
+        }
+    }
+
+    /// Pools the bins of `other` with these ones, keeping the larger `max_bins`.
+    pub fn merge(&mut self, other: &Histogram) {
+        self.max_bins = self.max_bins.max(other.max_bins);
+        self.n += other.n;
+        self.bins.extend_from_slice(&other.bins);
+        // Sorted by their right ends, which updates search by bisection.
+        self.bins
+            .sort_by(|a, b| a.right.total_cmp(&b.right).then(a.left.total_cmp(&b.left)));
+        self.shrink();
+    }
+
+    /// Estimated fraction of the values that are lower than or equal to `x`, assuming that
+    /// the values of a bin are spread evenly over it. Every bin is visited, as merged bins
+    /// may overlap.
+    pub fn cdf(&self, x: f64) -> f64 {
+        if self.n == 0 {
+            return 0.0;
+        }
+        let below: f64 = self
+            .bins
+            .iter()
+            .map(|b| {
+                if x >= b.right {
+                    b.count as f64
+                } else if x > b.left {
+                    b.count as f64 * (x - b.left) / (b.right - b.left)
+                } else {
+                    0.0
+                }
+            })
+            .sum();
+        below / self.n as f64
+    }
+}
+
+/// Count-Min sketch of the weights of hashed keys, in `depth` rows of `width` counters.
+///
+/// Each row adds the weight of a key to one of its counters, picked by hashing the key with
+/// the mask of the row, and a key is estimated by the lowest of its counters. With
+/// non-negative weights the estimate never underestimates, and overestimates by at most
+/// `e / width` times the total weight with probability `1 - exp(-depth)`.
+///
+/// Reference: Cormode, G. and Muthukrishnan, S., 2005. An improved data stream summary: the
+/// count-min sketch and its applications. Journal of Algorithms, 55(1), pp.58-75.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CountMin {
+    width: usize,
+    masks: Vec<u64>,
+    counts: Vec<f64>,
+    total: f64,
+}
+
+impl HeapSize for CountMin {
+    fn heap_size(&self) -> usize {
+        self.masks.heap_size() + self.counts.heap_size()
+    }
+}
+
+impl CountMin {
+    pub fn new(width: usize, depth: usize, rng: &mut Rng) -> Self {
+        CountMin {
+            width,
+            masks: (0..depth).map(|_| rng.next_u64()).collect(),
+            counts: vec![0.0; width * depth],
+            total: 0.0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.masks.len()
+    }
+
+    /// Sum of the weights of all the keys.
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    // Position of the counter of a key in a row.
+    fn cell(&self, row: usize, hash: u64) -> usize {
+        let mut state = hash ^ self.masks[row];
+        row * self.width + (splitmix64(&mut state) % self.width as u64) as usize
+    }
+
+    pub fn update(&mut self, hash: u64, w: f64) {
+        for row in 0..self.depth() {
+            let cell = self.cell(row, hash);
+            self.counts[cell] += w;
+        }
+        self.total += w;
+    }
+
+    pub fn query(&self, hash: u64) -> f64 {
+        (0..self.depth())
+            .map(|row| self.counts[self.cell(row, hash)])
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Adds the counters of `other`, which should have the same width and masks.
+    pub fn merge(&mut self, other: &CountMin) -> Result<(), String> {
+        if self.width != other.width || self.masks != other.masks {
+            return Err("the sketches should have the same width, depth and seed".to_string());
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        Ok(())
+    }
+}
+
+/// Approximate number of ones among the last `window_size` bits of a stream, in a space
+/// logarithmic in `window_size`.
+///
+/// The ones are grouped into buckets whose sizes are powers of two, each stamped with the
+/// time of its most recent one. There are at most `ceil(1 / (2 eps)) + 1` buckets of each
+/// size, so that when a new one overflows a size the two oldest buckets of that size are
+/// merged. Only the oldest bucket may straddle the edge of the window, which bounds the
+/// relative error by `eps`.
+///
+/// Reference: Datar, M., Gionis, A., Indyk, P. and Motwani, R., 2002. Maintaining stream
+/// statistics over sliding windows. SIAM Journal on Computing, 31(6), pp.1794-1813.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExpHistogram {
+    window_size: u64,
+    max_per_size: usize,
+    // (time of the most recent one, number of ones), from the oldest to the most recent.
+    buckets: VecDeque<(u64, u64)>,
+    total: u64,
+    time: u64,
+}
+
+impl HeapSize for ExpHistogram {
+    fn heap_size(&self) -> usize {
+        self.buckets.heap_size()
+    }
+}
+
+impl ExpHistogram {
+    pub fn new(window_size: u64, eps: f64) -> Self {
+        ExpHistogram {
+            window_size,
+            max_per_size: (1.0 / (2.0 * eps)).ceil() as usize + 1,
+            buckets: VecDeque::new(),
+            total: 0,
+            time: 0,
+        }
+    }
+
+    pub fn window_size(&self) -> u64 {
+        self.window_size
+    }
+
+    pub fn n_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn update(&mut self, bit: bool) {
+        self.time += 1;
+        while let Some(&(time, size)) = self.buckets.front() {
+            if time + self.window_size > self.time {
+                break;
+            }
+            self.buckets.pop_front();
+            self.total -= size;
+        }
+        if !bit {
+            return;
+        }
+        self.buckets.push_back((self.time, 1));
+        self.total += 1;
+
+        // Walks the sizes from the smallest, the buckets of a size being contiguous.
+        let mut end = self.buckets.len();
+        let mut size = 1;
+        loop {
+            let start = self
+                .buckets
+                .range(..end)
+                .rev()
+                .take_while(|b| b.1 == size)
+                .count();
+            let start = end - start;
+            if end - start <= self.max_per_size {
+                break;
+            }
+            let (_, older) = self.buckets.remove(start).unwrap();
+            self.buckets[start].1 += older;
+            end = start + 1;
+            size *= 2;
+        }
+    }
+
+    /// Estimated number of ones among the last `window_size` bits. Between one and all of
+    /// the ones of the oldest bucket are in the window, so it counts half of the others.
+    pub fn get(&self) -> f64 {
+        match self.buckets.front() {
+            Some(&(_, oldest)) => self.total as f64 - (oldest - 1) as f64 / 2.0,
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(n: u64, seed: u64) -> Vec<u64> {
+        let mut state = seed;
+        (0..n).map(|_| splitmix64(&mut state)).collect()
+    }
+
+    #[test]
+    fn tdigest_quantiles_of_a_uniform_stream() {
+        let mut rng = Rng::from_seed(1);
+        let mut digest = TDigest::new(100.0);
+        assert!(digest.quantile(0.5).is_nan());
+        assert!(digest.cdf(0.0).is_nan());
+        for _ in 0..10_000 {
+            digest.update(rng.next_f64(), 1.0);
+        }
+        assert_eq!(digest.total_weight(), 10_000.0);
+        for q in [0.01, 0.1, 0.5, 0.9, 0.99] {
+            assert!((digest.quantile(q) - q).abs() < 0.01, "q={}", q);
+            assert!((digest.cdf(q) - q).abs() < 0.01, "x={}", q);
+        }
+        assert_eq!(digest.cdf(-1.0), 0.0);
+        assert_eq!(digest.cdf(2.0), 1.0);
+        assert!(digest.cdf(f64::NAN).is_nan());
+        assert!((digest.mad() - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn tdigest_extremes_are_exact() {
+        let mut digest = TDigest::new(20.0);
+        for x in 0..1000 {
+            digest.update(x as f64, 1.0);
+        }
+        assert_eq!(digest.quantile(0.0), 0.0);
+        assert_eq!(digest.quantile(1.0), 999.0);
+        digest.compress();
+        assert!(digest.centroids.len() < 100);
+    }
+
+    #[test]
+    fn tdigest_merge_matches_a_single_digest() {
+        let mut rng = Rng::from_seed(2);
+        let values: Vec<f64> = (0..4000).map(|_| rng.normal()).collect();
+        let mut whole = TDigest::new(100.0);
+        let (mut left, mut right) = (TDigest::new(100.0), TDigest::new(100.0));
+        for (i, &x) in values.iter().enumerate() {
+            whole.update(x, 1.0);
+            if i % 2 == 0 {
+                left.update(x, 1.0);
+            } else {
+                right.update(x, 1.0);
+            }
+        }
+        left.merge(&right);
+        left.merge(&TDigest::new(100.0));
+        assert_eq!(left.total_weight(), whole.total_weight());
+        for q in [0.05, 0.25, 0.5, 0.75, 0.95] {
+            assert!(
+                (left.quantile(q) - whole.quantile(q)).abs() < 0.02,
+                "q={}",
+                q
+            );
+        }
+        // The median absolute deviation of a standard normal is about 0.6745.
+        assert!((left.mad() - 0.6745).abs() < 0.05);
+    }
+
+    #[test]
+    fn tdigest_weights_count_as_repeated_points() {
+        let mut weighted = TDigest::new(100.0);
+        weighted.update(1.0, 3.0);
+        weighted.update(2.0, 1.0);
+        assert_eq!(weighted.total_weight(), 4.0);
+        assert!(weighted.quantile(0.25) < 1.5);
+        assert!(weighted.cdf(1.5) >= 0.5);
+    }
+
+    #[test]
+    fn tdigest_queries_follow_the_updates() {
+        let mut digest = TDigest::new(100.0);
+        for x in 0..100 {
+            digest.update(x as f64, 1.0);
+        }
+        assert!((digest.cdf(49.5) - 0.5).abs() < 0.01);
+        assert!(digest.knots.get().is_some());
+        for x in 100..200 {
+            digest.update(x as f64, 1.0);
+        }
+        assert!(digest.knots.get().is_none());
+        assert!((digest.cdf(99.5) - 0.5).abs() < 0.01);
+        assert_eq!(digest.quantile(1.0), 199.0);
+        let mut other = TDigest::new(100.0);
+        other.update(1000.0, 200.0);
+        digest.merge(&other);
+        assert_eq!(digest.quantile(1.0), 1000.0);
+    }
+
+    #[test]
+    fn tdigest_ignores_null_weights() {
+        let mut digest = TDigest::new(100.0);
+        digest.update(100.0, 0.0);
+        assert!(digest.is_empty());
+        assert!(digest.buffer.is_empty());
+        digest.update(1.0, 1.0);
+        digest.update(-100.0, 0.0);
+        digest.update(2.0, 1.0);
+        assert_eq!(digest.quantile(0.0), 1.0);
+        assert_eq!(digest.quantile(1.0), 2.0);
+        assert!((digest.mad() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn hyperloglog_estimates_the_distinct_hashes() {
+        let mut sketch = HyperLogLog::new(12).unwrap();
+        assert_eq!(sketch.estimate(), 0.0);
+        let values = hashes(20_000, 3);
+        for &h in &values {
+            sketch.update(h);
+            sketch.update(h);
+        }
+        assert!((sketch.estimate() / 20_000.0 - 1.0).abs() < 0.05);
+
+        let mut small = HyperLogLog::new(12).unwrap();
+        for &h in &values[..100] {
+            small.update(h);
+        }
+        assert!((small.estimate() - 100.0).abs() < 3.0);
+    }
+
+    #[test]
+    fn hyperloglog_merge_takes_the_union() {
+        let values = hashes(10_000, 4);
+        let (mut left, mut right, mut whole) = (
+            HyperLogLog::new(10).unwrap(),
+            HyperLogLog::new(10).unwrap(),
+            HyperLogLog::new(10).unwrap(),
+        );
+        for &h in &values[..6000] {
+            left.update(h);
+        }
+        for &h in &values[4000..] {
+            right.update(h);
+        }
+        for &h in &values {
+            whole.update(h);
+        }
+        left.merge(&right).unwrap();
+        assert_eq!(left.registers, whole.registers);
+        assert!(left.merge(&HyperLogLog::new(11).unwrap()).is_err());
+    }
+
+    #[test]
+    fn hyperloglog_precision_is_bounded() {
+        // Without any index bit, the rank sentinel would shift by -1.
+        assert!(HyperLogLog::new(0).is_err());
+        assert!(HyperLogLog::new(3).is_err());
+        assert!(HyperLogLog::new(17).is_err());
+        assert!(HyperLogLog::new(4).is_ok() && HyperLogLog::new(16).is_ok());
+    }
+
+    #[test]
+    fn count_min_never_underestimates() {
+        let mut rng = Rng::from_seed(5);
+        let mut sketch = CountMin::new(50, 4, &mut rng);
+        let keys = hashes(500, 6);
+        let mut exact = HashMap::new();
+        for (i, &h) in keys.iter().enumerate() {
+            let w = (i % 7) as f64;
+            sketch.update(h, w);
+            *exact.entry(h).or_insert(0.0) += w;
+        }
+        let total: f64 = exact.values().sum();
+        assert_eq!(sketch.total(), total);
+        for (&h, &count) in &exact {
+            let estimate = sketch.query(h);
+            assert!(estimate >= count);
+            assert!(estimate <= count + total);
+        }
+        assert_eq!((sketch.width(), sketch.depth()), (50, 4));
+    }
+
+    #[test]
+    fn count_min_merge_adds_the_counters() {
+        let mut rng = Rng::from_seed(7);
+        let mut left = CountMin::new(20, 3, &mut rng);
+        let mut right = left.clone();
+        left.update(1, 2.0);
+        right.update(1, 3.0);
+        right.update(2, 1.0);
+        left.merge(&right).unwrap();
+        assert!(left.query(1) >= 5.0);
+        assert_eq!(left.total(), 6.0);
+        let other = CountMin::new(20, 3, &mut rng);
+        assert!(left.merge(&other).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// Versioned envelope around the bincode states, so that states written by older releases
+// keep loading after the fields of a binding change.
+//
+// A state is made of `MAGIC`, the layout version of the binding as a little-endian `u16`,
+// and the bincode payload. States without the magic bytes predate the envelope and are
+// read as version 0.
+
+use std::io::{Read, Write};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::view::{self, Exporting};
+
+const MAGIC: [u8; 4] = *b"RVRS";
+
+/// Serialized binding, along with the migrations from its older layouts.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Version of the current layout, to bump whenever the serialized fields change.
+    const VERSION: u16 = 1;
+
+    /// Reads a payload written with an older layout. Version 0 is the bare bincode payload
+    /// used before the envelope, which has the same layout as version 1.
+    fn migrate(version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let _ = version;
+        bincode::deserialize_from(payload)
+    }
+}
+
+pub fn name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+pub fn write_state<T: Versioned, W: Write>(mut writer: W, state: &T) -> PyResult<W> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&T::VERSION.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, state).map_err(|e| {
+        PyValueError::new_err(format!(
+            "failed to save the state of {}: {}",
+            name::<T>(),
+            e
+        ))
+    })?;
+    Ok(writer)
+}
+
+// A state decoded without reaching the end of the payload was written with another layout,
+// which bincode can read as a shorter one holding garbage.
+fn consumed(reader: &mut dyn Read) -> bincode::Result<()> {
+    let left = std::io::copy(reader, &mut std::io::sink())?;
+    if left > 0 {
+        return Err(Box::new(bincode::ErrorKind::Custom(format!(
+            "{} bytes left after the end of the state, which was probably written with \
+             another layout",
+            left
+        ))));
+    }
+    Ok(())
+}
+
+pub fn read_state<T: Versioned, R: Read>(mut reader: R) -> PyResult<T> {
+    let mut head = Vec::with_capacity(MAGIC.len() + 2);
+    reader
+        .by_ref()
+        .take(head.capacity() as u64)
+        .read_to_end(&mut head)?;
+    let result = if head.len() == MAGIC.len() + 2 && head[..MAGIC.len()] == MAGIC {
+        let version = u16::from_le_bytes([head[4], head[5]]);
+        if version > T::VERSION {
+            return Err(PyValueError::new_err(format!(
+                "the state of {} has version {}, but this release only reads up to version {}",
+                name::<T>(),
+                version,
+                T::VERSION
+            )));
+        }
+        if version == T::VERSION {
+            bincode::deserialize_from(&mut reader)
+        } else {
+            T::migrate(version, &mut reader)
+        }
+        .and_then(|state| consumed(&mut reader).map(|_| state))
+    } else {
+        let mut reader = head.as_slice().chain(reader);
+        T::migrate(0, &mut reader).and_then(|state| consumed(&mut reader).map(|_| state))
+    };
+    result.map_err(|e| {
+        PyValueError::new_err(format!(
+            "failed to restore the state of {}: {}",
+            name::<T>(),
+            e
+        ))
+    })
+}
+
+pub fn encode<T: Versioned>(state: &T) -> Vec<u8> {
+    write_state(Vec::new(), state).expect("serializing into memory cannot fail")
+}
+
+pub fn decode<T: Versioned>(bytes: &[u8]) -> PyResult<T> {
+    read_state(bytes)
+}
+
+// The bindings whose layout changed implement `Versioned` next to their definition, with
+// the migrations from their older layouts.
+macro_rules! versioned {
+    (@one crate::bindings::RsQuantile) => {};
+    (@one crate::bindings::RsEWMean) => {};
+    (@one crate::bindings::RsEWVar) => {};
+    (@one crate::bindings::RsPeakToPeak) => {};
+    (@one crate::bindings::RsRollingMean) => {};
+    (@one crate::bindings::RsRollingVar) => {};
+    (@one crate::bindings::RsRollingQuantile) => {};
+    (@one crate::bindings::RsRollingIQR) => {};
+    (@one crate::bindings::RsRollingMin) => {};
+    (@one crate::bindings::RsRollingMax) => {};
+    (@one crate::bindings::RsRollingPeakToPeak) => {};
+    (@one crate::stats::RsRollingPercentileRank) => {};
+    (@one crate::stats::RsShift) => {};
+    (@one crate::stats::RsAutoCorr) => {};
+    (@one crate::stats::RsTimeEWMean) => {};
+    (@one crate::stats::RsTimeEWVar) => {};
+    (@one crate::stats::RsDistinctCount) => {};
+    (@one crate::stats::RsHyperLogLog) => {};
+    (@one crate::stats::RsCountMin) => {};
+    (@one $($segment:ident)::+) => {
+        impl Versioned for $($segment)::+ {}
+    };
+    ($($($segment:ident)::+),* $(,)?) => {
+        $(versioned!(@one $($segment)::+);)*
+    };
+}
+
+for_each_binding!(versioned);
+
+// Pickling goes through `__reduce__` rather than the constructor, so that it doesn't depend
+// on the arguments of `__new__`, which are neither stored in every binding nor always
+// positional.
+macro_rules! picklable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                /// Rebuilds an instance from the state returned by `__getstate__`.
+                #[staticmethod]
+                pub fn _from_state(state: &PyBytes) -> PyResult<Self> {
+                    decode(state.as_bytes())
+                }
+                pub fn __getstate__<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+                    PyBytes::new(py, &encode(self))
+                }
+                pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
+                    view::check_not_exported(Exporting::exports(self))?;
+                    *self = decode(state.as_bytes())?;
+                    Ok(())
+                }
+                pub fn __reduce__<'py>(
+                    &self,
+                    py: Python<'py>,
+                ) -> PyResult<(&'py PyAny, (&'py PyBytes,))> {
+                    let from_state = py.get_type::<Self>().getattr("_from_state")?;
+                    Ok((from_state, (self.__getstate__(py),)))
+                }
+            }
+        )*
+    };
+}
+
+for_each_binding!(picklable);
+
+// The bindings own their whole state on the Rust side, so copying one is a plain `Clone`
+// rather than a round trip through `__getstate__`, and shallow and deep copies are the same.
+macro_rules! copyable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                /// Independent copy of the binding.
+                #[pyo3(name = "clone")]
+                pub fn py_clone(&self) -> Self {
+                    Clone::clone(self)
+                }
+                pub fn __copy__(&self) -> Self {
+                    Clone::clone(self)
+                }
+                pub fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+                    Clone::clone(self)
+                }
+            }
+        )*
+    };
+}
+
+for_each_binding!(copyable);
@@ -0,0 +1,1764 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::io::Read;
+use std::os::raw::c_int;
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::{ffi, AsPyPointer};
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::{check_unit_w, check_w};
+use crate::hypothesis::SortedWindow;
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::moments::CoMoments;
+use crate::rng::Rng;
+use crate::rolling::Lag;
+use crate::sketch::{
+    check_precision, CountMin, DecayedTopK, ExpHistogram, Histogram, HyperLogLog, SpaceSaving,
+    TDigest,
+};
+use crate::state::Versioned;
+use crate::univariate::{Param, Params};
+use crate::view;
+
+/// Compressed empirical distribution, backed by a t-digest.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsECDF {
+    digest: TDigest,
+    compression: f64,
+}
+
+impl HeapSize for RsECDF {
+    fn heap_size(&self) -> usize {
+        self.digest.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsECDF {
+    #[new]
+    #[args(compression = "100.0")]
+    pub fn new(compression: f64) -> PyResult<Self> {
+        if compression.is_nan() || compression < 1.0 {
+            return Err(PyValueError::new_err("compression should be at least 1"));
+        }
+        Ok(RsECDF {
+            digest: TDigest::new(compression),
+            compression,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if !x.is_finite() {
+            return Err(PyValueError::new_err("x should be finite"));
+        }
+        self.digest.update(x, 1.0);
+        Ok(())
+    }
+    /// Fraction of the observed values that are lower than or equal to `x`.
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.digest.cdf(x)
+    }
+    /// Percent point function, i.e. the inverse of `cdf`.
+    pub fn ppf(&self, q: f64) -> PyResult<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return Err(PyValueError::new_err("q should be between 0 and 1"));
+        }
+        Ok(self.digest.quantile(q))
+    }
+    /// Same as `ppf` for each of `qs`, with a single pass over the digest.
+    pub fn get_many(&self, qs: Vec<f64>) -> PyResult<Vec<f64>> {
+        if !qs.iter().all(|q| (0.0..=1.0).contains(q)) {
+            return Err(PyValueError::new_err("q should be between 0 and 1"));
+        }
+        Ok(self.digest.quantiles(&qs))
+    }
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.digest.total_weight()
+    }
+}
+
+/// Weighted t-digest, which can be merged with the digests of other shards.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsTDigest {
+    digest: TDigest,
+}
+
+impl HeapSize for RsTDigest {
+    fn heap_size(&self) -> usize {
+        self.digest.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsTDigest {
+    /// `compression` bounds the number of centroids, of the order of `compression / 2`.
+    #[new]
+    #[args(compression = "100.0")]
+    pub fn new(compression: f64) -> PyResult<Self> {
+        if compression.is_nan() || compression < 1.0 {
+            return Err(PyValueError::new_err("compression should be at least 1"));
+        }
+        Ok(RsTDigest {
+            digest: TDigest::new(compression),
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        if !w.is_finite() || w <= 0.0 {
+            return Err(PyValueError::new_err("w should be finite and positive"));
+        }
+        self.digest.update(x, w);
+        Ok(())
+    }
+    /// Estimated value below which a fraction `q` of the weight lies.
+    pub fn quantile(&self, q: f64) -> PyResult<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return Err(PyValueError::new_err("q should be between 0 and 1"));
+        }
+        Ok(self.digest.quantile(q))
+    }
+    /// Estimated fraction of the weight lying below `x`.
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.digest.cdf(x)
+    }
+    /// Folds in the centroids of `other`, e.g. a digest built on another shard.
+    pub fn merge(&mut self, other: PyRef<RsTDigest>) {
+        self.digest.merge(&other.digest);
+    }
+    /// Total weight of the values.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.digest.total_weight()
+    }
+}
+
+/// Sums of the sines and cosines of a set of angles, expressed in radians.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CircularMoments {
+    sum_sin: f64,
+    sum_cos: f64,
+    n: f64,
+}
+
+impl CircularMoments {
+    pub fn update(&mut self, theta: f64) {
+        self.sum_sin += theta.sin();
+        self.sum_cos += theta.cos();
+        self.n += 1.0;
+    }
+
+    pub fn revert(&mut self, theta: f64) {
+        self.sum_sin -= theta.sin();
+        self.sum_cos -= theta.cos();
+        self.n -= 1.0;
+    }
+
+    /// Direction of the mean resultant vector, in [0, 2pi).
+    pub fn mean(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        self.sum_sin.atan2(self.sum_cos).rem_euclid(2.0 * PI)
+    }
+
+    /// Length of the mean resultant vector, in [0, 1].
+    pub fn resultant_length(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        (self.sum_sin.hypot(self.sum_cos) / self.n).min(1.0)
+    }
+
+    pub fn var(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        1.0 - self.resultant_length()
+    }
+
+    pub fn std(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        (-2.0 * self.resultant_length().ln()).sqrt()
+    }
+}
+
+fn to_radians(x: f64, degrees: bool) -> f64 {
+    if degrees {
+        x.to_radians()
+    } else {
+        x
+    }
+}
+
+fn from_radians(x: f64, degrees: bool) -> f64 {
+    if degrees {
+        x.to_degrees()
+    } else {
+        x
+    }
+}
+
+/// Circular mean, variance and standard deviation of a stream of angles.
+///
+/// The variance is `1 - R`, where `R` is the mean resultant length, and is therefore
+/// unitless. The mean and the standard deviation are expressed in the unit of the inputs.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCircularStats {
+    moments: CircularMoments,
+    degrees: bool,
+}
+
+impl HeapSize for RsCircularStats {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsCircularStats {
+    #[new]
+    #[args(degrees = "false")]
+    pub fn new(degrees: bool) -> RsCircularStats {
+        RsCircularStats {
+            moments: CircularMoments::default(),
+            degrees,
+        }
+    }
+    pub fn update(&mut self, x: f64) {
+        self.moments.update(to_radians(x, self.degrees));
+    }
+    pub fn revert(&mut self, x: f64) {
+        self.moments.revert(to_radians(x, self.degrees));
+    }
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        from_radians(self.moments.mean(), self.degrees)
+    }
+    #[getter]
+    pub fn var(&self) -> f64 {
+        self.moments.var()
+    }
+    #[getter]
+    pub fn std(&self) -> f64 {
+        from_radians(self.moments.std(), self.degrees)
+    }
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.moments.n
+    }
+}
+
+/// Circular statistics over a sliding window of angles.
+///
+/// The buffer protocol gives a read-only view of the window, in radians.
+#[derive(Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingCircularStats {
+    moments: CircularMoments,
+    window: VecDeque<f64>,
+    window_size: usize,
+    degrees: bool,
+    #[serde(skip)]
+    exports: usize,
+}
+
+impl HeapSize for RsRollingCircularStats {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl view::Exporting for RsRollingCircularStats {
+    fn exports(&self) -> usize {
+        self.exports
+    }
+}
+
+// A copy doesn't share the views exported by the original.
+impl Clone for RsRollingCircularStats {
+    fn clone(&self) -> Self {
+        RsRollingCircularStats {
+            moments: self.moments.clone(),
+            window: self.window.clone(),
+            window_size: self.window_size,
+            degrees: self.degrees,
+            exports: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl RsRollingCircularStats {
+    #[new]
+    #[args(degrees = "false")]
+    pub fn new(window_size: usize, degrees: bool) -> PyResult<Self> {
+        if window_size == 0 {
+            return Err(PyValueError::new_err("window_size should be at least 1"));
+        }
+        Ok(RsRollingCircularStats {
+            moments: CircularMoments::default(),
+            window: VecDeque::with_capacity(window_size),
+            window_size,
+            degrees,
+            exports: 0,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        view::check_not_exported(self.exports)?;
+        let theta = to_radians(x, self.degrees);
+        if self.window.len() == self.window_size {
+            let oldest = self.window.pop_front().unwrap();
+            self.moments.revert(oldest);
+        }
+        self.window.push_back(theta);
+        self.moments.update(theta);
+        Ok(())
+    }
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        from_radians(self.moments.mean(), self.degrees)
+    }
+    #[getter]
+    pub fn var(&self) -> f64 {
+        self.moments.var()
+    }
+    #[getter]
+    pub fn std(&self) -> f64 {
+        from_radians(self.moments.std(), self.degrees)
+    }
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.moments.n
+    }
+    /// Copy of the window, from the oldest to the most recent angle, in radians.
+    pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_iter(py, self.window.iter().copied())
+    }
+
+    unsafe fn __getbuffer__(
+        mut slf: PyRefMut<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let owner = slf.as_ptr();
+        view::fill_view(view, flags, owner, slf.window.make_contiguous())?;
+        slf.exports += 1;
+        Ok(())
+    }
+    unsafe fn __releasebuffer__(mut slf: PyRefMut<Self>, view: *mut ffi::Py_buffer) {
+        view::release_view(view);
+        slf.exports -= 1;
+    }
+}
+
+/// Fraction of the previously seen values that are below the latest observation.
+///
+/// The past values are summarized with a t-digest; `get` returns 0 until a second value
+/// has been observed.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsPercentileRank {
+    digest: TDigest,
+    rank: f64,
+    compression: f64,
+}
+
+impl HeapSize for RsPercentileRank {
+    fn heap_size(&self) -> usize {
+        self.digest.heap_size()
+    }
+}
+
+impl Params for RsPercentileRank {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("compression", Param::Float(self.compression))]
+    }
+}
+
+#[pymethods]
+impl RsPercentileRank {
+    #[new]
+    #[args(compression = "100.0")]
+    pub fn new(compression: f64) -> PyResult<Self> {
+        if compression.is_nan() || compression < 1.0 {
+            return Err(PyValueError::new_err("compression should be at least 1"));
+        }
+        Ok(RsPercentileRank {
+            digest: TDigest::new(compression),
+            rank: 0.0,
+            compression,
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.digest.compress();
+        self.rank = if self.digest.is_empty() {
+            0.0
+        } else {
+            self.digest.cdf(x)
+        };
+        self.digest.update(x, 1.0);
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.rank
+    }
+    /// Number of values seen so far.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.digest.total_weight() as u64
+    }
+}
+
+/// Median absolute deviation from the median, as used for robust scaling.
+///
+/// The values are summarized with a t-digest, from which both the median and the deviation
+/// are estimated, so that shards can be merged.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsMAD {
+    digest: TDigest,
+    compression: f64,
+}
+
+impl HeapSize for RsMAD {
+    fn heap_size(&self) -> usize {
+        self.digest.heap_size()
+    }
+}
+
+impl Params for RsMAD {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("compression", Param::Float(self.compression))]
+    }
+}
+
+#[pymethods]
+impl RsMAD {
+    #[new]
+    #[args(compression = "100.0")]
+    pub fn new(compression: f64) -> PyResult<Self> {
+        if compression.is_nan() || compression < 1.0 {
+            return Err(PyValueError::new_err("compression should be at least 1"));
+        }
+        Ok(RsMAD {
+            digest: TDigest::new(compression),
+            compression,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_w(w)?;
+        if !x.is_finite() {
+            return Err(PyValueError::new_err("x should be finite"));
+        }
+        self.digest.update(x, w);
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.digest.mad()
+    }
+    /// Estimated median of the values, around which the deviations are taken.
+    pub fn median(&self) -> f64 {
+        self.digest.quantile(0.5)
+    }
+    /// Total weight of the values seen.
+    #[getter]
+    pub fn n(&self) -> f64 {
+        self.digest.total_weight()
+    }
+    /// Folds in the values seen by `other`, e.g. on another shard.
+    pub fn merge(&mut self, other: PyRef<RsMAD>) {
+        self.digest.merge(&other.digest);
+    }
+}
+
+/// Fraction of the values in a sliding window that are below the latest observation.
+///
+/// Ties count for one half, and the window does not include the latest observation. The
+/// buffer protocol gives a read-only view of the window.
+#[derive(Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRollingPercentileRank {
+    window: SortedWindow,
+    rank: f64,
+    window_size: usize,
+    n: u64,
+    #[serde(skip)]
+    exports: usize,
+}
+
+impl HeapSize for RsRollingPercentileRank {
+    fn heap_size(&self) -> usize {
+        self.window.heap_size()
+    }
+}
+
+impl view::Exporting for RsRollingPercentileRank {
+    fn exports(&self) -> usize {
+        self.exports
+    }
+}
+
+impl Clone for RsRollingPercentileRank {
+    fn clone(&self) -> Self {
+        RsRollingPercentileRank {
+            window: self.window.clone(),
+            rank: self.rank,
+            window_size: self.window_size,
+            n: self.n,
+            exports: 0,
+        }
+    }
+}
+
+impl Versioned for RsRollingPercentileRank {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those of the
+    // window.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (window, rank, window_size): (SortedWindow, f64, usize) =
+            bincode::deserialize_from(payload)?;
+        let n = window.sorted().len() as u64;
+        Ok(RsRollingPercentileRank {
+            window,
+            rank,
+            window_size,
+            n,
+            exports: 0,
+        })
+    }
+}
+
+impl RsRollingPercentileRank {
+    pub fn push_missing(&mut self) -> PyResult<()> {
+        view::check_not_exported(self.exports)?;
+        self.window.push_missing();
+        if self.window.len() > self.window_size {
+            self.window.pop();
+        }
+        Ok(())
+    }
+}
+
+impl Params for RsRollingPercentileRank {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("window_size", Param::Int(self.window_size))]
+    }
+}
+
+#[pymethods]
+impl RsRollingPercentileRank {
+    #[new]
+    pub fn new(window_size: usize) -> PyResult<Self> {
+        if window_size == 0 {
+            return Err(PyValueError::new_err("window_size should be at least 1"));
+        }
+        Ok(RsRollingPercentileRank {
+            window: SortedWindow::new(window_size),
+            rank: 0.0,
+            window_size,
+            n: 0,
+            exports: 0,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, x: f64, w: f64) -> PyResult<()> {
+        check_unit_w(w)?;
+        view::check_not_exported(self.exports)?;
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        let n = self.window.sorted().len();
+        self.rank = if n == 0 {
+            0.0
+        } else {
+            let (lower, equal) = self.window.rank(x);
+            (lower as f64 + 0.5 * equal as f64) / n as f64
+        };
+        self.window.push(x);
+        if self.window.len() > self.window_size {
+            self.window.pop();
+        }
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.rank
+    }
+    /// Number of values seen so far, including those which have left the window.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    /// Copy of the window, from the oldest to the most recent value.
+    pub fn window<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_iter(py, self.window.iter().copied())
+    }
+
+    unsafe fn __getbuffer__(
+        mut slf: PyRefMut<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let owner = slf.as_ptr();
+        view::fill_view(view, flags, owner, slf.window.values())?;
+        slf.exports += 1;
+        Ok(())
+    }
+    unsafe fn __releasebuffer__(mut slf: PyRefMut<Self>, view: *mut ffi::Py_buffer) {
+        view::release_view(view);
+        slf.exports -= 1;
+    }
+}
+
+/// Value of the stream `amount` steps ago, as `stats.Shift`, which is `fill_value` until
+/// that many values have been seen.
+///
+/// Since the stats return floats, the default `fill_value` is NaN rather than `None`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsShift {
+    lag: Lag,
+    fill_value: f64,
+    n: u64,
+}
+
+impl HeapSize for RsShift {
+    fn heap_size(&self) -> usize {
+        self.lag.heap_size()
+    }
+}
+
+impl Versioned for RsShift {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from those which
+    // are held to be shifted.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (lag, fill_value): (Lag, f64) = bincode::deserialize_from(payload)?;
+        let n = lag.len() as u64;
+        Ok(RsShift { lag, fill_value, n })
+    }
+}
+
+impl Params for RsShift {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![
+            ("amount", Param::Int(self.lag.amount())),
+            ("fill_value", Param::Float(self.fill_value)),
+        ]
+    }
+}
+
+#[pymethods]
+impl RsShift {
+    #[new]
+    #[args(amount = "1", fill_value = "f64::NAN")]
+    pub fn new(amount: usize, fill_value: f64) -> Self {
+        RsShift {
+            lag: Lag::new(amount),
+            fill_value,
+            n: 0,
+        }
+    }
+    pub fn update(&mut self, x: f64) {
+        self.lag.update(x);
+        self.n += 1;
+    }
+    pub fn get(&self) -> f64 {
+        self.lag.get().unwrap_or(self.fill_value)
+    }
+    /// Number of values seen so far.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    #[getter]
+    pub fn amount(&self) -> usize {
+        self.lag.amount()
+    }
+}
+
+/// Pearson correlation between the stream and itself `lag` steps before, as
+/// `stats.AutoCorr`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAutoCorr {
+    lagged: Lag,
+    moments: CoMoments,
+    n: u64,
+}
+
+impl HeapSize for RsAutoCorr {
+    fn heap_size(&self) -> usize {
+        self.lagged.heap_size()
+    }
+}
+
+impl Versioned for RsAutoCorr {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from the pairs
+    // seen, each of which follows `lag` values.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (lagged, moments): (Lag, CoMoments) = bincode::deserialize_from(payload)?;
+        let n = if moments.n() > 0.0 {
+            moments.n() as u64 + lagged.amount() as u64
+        } else {
+            lagged.len() as u64
+        };
+        Ok(RsAutoCorr { lagged, moments, n })
+    }
+}
+
+impl Params for RsAutoCorr {
+    fn params(&self) -> Vec<(&'static str, Param)> {
+        vec![("lag", Param::Int(self.lagged.amount()))]
+    }
+}
+
+#[pymethods]
+impl RsAutoCorr {
+    #[new]
+    pub fn new(lag: usize) -> PyResult<Self> {
+        if lag == 0 {
+            return Err(PyValueError::new_err("lag should be at least 1"));
+        }
+        Ok(RsAutoCorr {
+            lagged: Lag::new(lag),
+            moments: CoMoments::new(),
+            n: 0,
+        })
+    }
+    pub fn update(&mut self, x: f64) {
+        self.lagged.update(x);
+        if let Some(before) = self.lagged.get() {
+            self.moments.update(x, before, 1.0);
+        }
+        self.n += 1;
+    }
+    pub fn get(&self) -> f64 {
+        self.moments.corr()
+    }
+    /// Number of values seen so far.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    #[getter]
+    pub fn lag(&self) -> usize {
+        self.lagged.amount()
+    }
+}
+
+/// Exponentially weighted mean and variance of a stream sampled at irregular times.
+///
+/// The weight of a value halves every `halflife` time units after it was observed, so a
+/// burst of values counts for more than a single one, and a long gap lets the older values
+/// fade away.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DecayedMoments {
+    halflife: f64,
+    weight: f64,
+    mean: f64,
+    // Weighted sum of squared deviations from the mean.
+    m2: f64,
+    last_t: Option<f64>,
+}
+
+impl DecayedMoments {
+    pub fn new(halflife: f64) -> Self {
+        DecayedMoments {
+            halflife,
+            weight: 0.0,
+            mean: 0.0,
+            m2: 0.0,
+            last_t: None,
+        }
+    }
+
+    pub fn update(&mut self, x: f64, t: f64) -> Result<(), String> {
+        if t.is_nan() {
+            return Err("t should not be NaN".to_string());
+        }
+        let decay = match self.last_t {
+            Some(last_t) if t < last_t => {
+                return Err(format!("t should not decrease, got {} after {}", t, last_t));
+            }
+            Some(last_t) => (-(t - last_t) / self.halflife).exp2(),
+            None => 0.0,
+        };
+        self.weight = decay * self.weight + 1.0;
+        let diff = x - self.mean;
+        self.mean += diff / self.weight;
+        self.m2 = decay * self.m2 + diff * (x - self.mean);
+        self.last_t = Some(t);
+        Ok(())
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn var(&self) -> f64 {
+        if self.weight == 0.0 {
+            return 0.0;
+        }
+        self.m2 / self.weight
+    }
+}
+
+fn check_halflife(halflife: f64) -> PyResult<()> {
+    if halflife.is_nan() || halflife <= 0.0 {
+        return Err(PyValueError::new_err("halflife should be positive"));
+    }
+    Ok(())
+}
+
+/// Exponentially weighted mean whose decay depends on the time elapsed between values.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsTimeEWMean {
+    moments: DecayedMoments,
+    halflife: f64,
+    n: u64,
+}
+
+impl HeapSize for RsTimeEWMean {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Versioned for RsTimeEWMean {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from 0 again.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (moments, halflife) = bincode::deserialize_from(payload)?;
+        Ok(RsTimeEWMean {
+            moments,
+            halflife,
+            n: 0,
+        })
+    }
+}
+
+#[pymethods]
+impl RsTimeEWMean {
+    #[new]
+    pub fn new(halflife: f64) -> PyResult<Self> {
+        check_halflife(halflife)?;
+        Ok(RsTimeEWMean {
+            moments: DecayedMoments::new(halflife),
+            halflife,
+            n: 0,
+        })
+    }
+    /// Updates with a value observed at time `t`, e.g. in seconds since the epoch.
+    pub fn update(&mut self, x: f64, t: f64) -> PyResult<()> {
+        self.moments.update(x, t).map_err(PyValueError::new_err)?;
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.moments.mean()
+    }
+    /// Number of values seen so far.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+}
+
+/// Exponentially weighted variance whose decay depends on the time elapsed between values.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsTimeEWVar {
+    moments: DecayedMoments,
+    halflife: f64,
+    n: u64,
+}
+
+impl HeapSize for RsTimeEWVar {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl Versioned for RsTimeEWVar {
+    const VERSION: u16 = 2;
+
+    // Up to version 1, the number of values seen isn't stored, and counts from 0 again.
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let (moments, halflife) = bincode::deserialize_from(payload)?;
+        Ok(RsTimeEWVar {
+            moments,
+            halflife,
+            n: 0,
+        })
+    }
+}
+
+#[pymethods]
+impl RsTimeEWVar {
+    #[new]
+    pub fn new(halflife: f64) -> PyResult<Self> {
+        check_halflife(halflife)?;
+        Ok(RsTimeEWVar {
+            moments: DecayedMoments::new(halflife),
+            halflife,
+            n: 0,
+        })
+    }
+    /// Updates with a value observed at time `t`, e.g. in seconds since the epoch.
+    pub fn update(&mut self, x: f64, t: f64) -> PyResult<()> {
+        self.moments.update(x, t).map_err(PyValueError::new_err)?;
+        self.n += 1;
+        Ok(())
+    }
+    pub fn get(&self) -> f64 {
+        self.moments.var()
+    }
+    /// Number of values seen so far.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        self.moments.mean()
+    }
+}
+
+/// Heaviest keys of a stream, by counts that halve every `halflife` time units.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsDecayedTopK {
+    top_k: DecayedTopK,
+}
+
+impl HeapSize for RsDecayedTopK {
+    fn heap_size(&self) -> usize {
+        self.top_k.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsDecayedTopK {
+    /// Tracks at most `capacity` keys, which should be a few times the number of keys
+    /// queried for the counts of the last ones to be accurate.
+    #[new]
+    pub fn new(capacity: usize, halflife: f64) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(PyValueError::new_err("capacity should be at least 1"));
+        }
+        check_halflife(halflife)?;
+        Ok(RsDecayedTopK {
+            top_k: DecayedTopK::new(capacity, halflife),
+        })
+    }
+    /// Counts `key` with weight `w` at time `t`. Without `t`, every update advances the
+    /// clock by one, so that the counts decay per update.
+    #[args(w = "1.0", t = "None")]
+    pub fn update(&mut self, key: Key, w: f64, t: Option<f64>) -> PyResult<()> {
+        if !w.is_finite() || w < 0.0 {
+            return Err(PyValueError::new_err("w should be finite and non-negative"));
+        }
+        let t = t.unwrap_or(self.top_k.now() + 1.0);
+        self.top_k.update(key, w, t).map_err(PyValueError::new_err)
+    }
+    /// Decayed count of `key`, 0 if it isn't tracked.
+    pub fn get(&self, key: Key) -> f64 {
+        self.top_k.get(&key)
+    }
+    /// The `k` trending keys, or all the tracked ones, as `(key, count, error)` tuples from
+    /// the heaviest down, where `error` bounds the overestimation of `count`.
+    #[args(k = "None")]
+    pub fn top(&self, k: Option<usize>) -> Vec<(Key, f64, f64)> {
+        self.top_k.top(k.unwrap_or(self.top_k.capacity()))
+    }
+    pub fn __len__(&self) -> usize {
+        self.top_k.len()
+    }
+}
+
+/// Heaviest keys of a stream, in a fixed amount of memory.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSpaceSaving {
+    sketch: SpaceSaving,
+}
+
+impl HeapSize for RsSpaceSaving {
+    fn heap_size(&self) -> usize {
+        self.sketch.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsSpaceSaving {
+    /// Tracks at most `k` keys. A count is overestimated by at most `total / k`.
+    #[new]
+    pub fn new(k: usize) -> PyResult<Self> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k should be at least 1"));
+        }
+        Ok(RsSpaceSaving {
+            sketch: SpaceSaving::new(k),
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, key: Key, w: f64) -> PyResult<()> {
+        if !w.is_finite() || w < 0.0 {
+            return Err(PyValueError::new_err("w should be finite and non-negative"));
+        }
+        self.sketch.update(key, w);
+        Ok(())
+    }
+    /// Count of `key`, 0 if it isn't tracked.
+    pub fn get(&self, key: Key) -> f64 {
+        self.sketch.get(&key)
+    }
+    /// Upper bound of the overestimation of the count of `key`.
+    pub fn error(&self, key: Key) -> f64 {
+        self.sketch.error(&key)
+    }
+    /// The `n` heaviest keys, or all the tracked ones, as `(key, count)` pairs from the
+    /// heaviest down.
+    #[args(n = "None")]
+    pub fn most_common(&self, n: Option<usize>) -> Vec<(Key, f64)> {
+        self.sketch
+            .top(n.unwrap_or(self.sketch.capacity()))
+            .into_iter()
+            .map(|(key, count, _)| (key, count))
+            .collect()
+    }
+    /// Counts the keys of `other` as well, e.g. a sketch built on another shard.
+    pub fn merge(&mut self, other: PyRef<RsSpaceSaving>) {
+        self.sketch.merge(&other.sketch);
+    }
+    /// Sum of the weights of all the keys.
+    #[getter]
+    pub fn total(&self) -> f64 {
+        self.sketch.total()
+    }
+    #[getter]
+    pub fn k(&self) -> usize {
+        self.sketch.capacity()
+    }
+    pub fn __len__(&self) -> usize {
+        self.sketch.len()
+    }
+}
+
+/// Distinct count which is exact up to `threshold` items and then switches to HyperLogLog.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DistinctCount {
+    // Ordered, so that equal sets serialize, and thus compare, the same.
+    Exact(BTreeSet<Key>),
+    Approximate(HyperLogLog),
+}
+
+impl HeapSize for DistinctCount {
+    fn heap_size(&self) -> usize {
+        match self {
+            DistinctCount::Exact(items) => items.heap_size(),
+            DistinctCount::Approximate(hll) => hll.heap_size(),
+        }
+    }
+}
+
+// The sketches only hold the hashes of the items, which can't be hashed again with another
+// function.
+fn rehash_error() -> bincode::Error {
+    Box::new(bincode::ErrorKind::Custom(
+        "the sketch was filled with the item hash of an older release, which this one no \
+         longer uses"
+            .to_string(),
+    ))
+}
+
+impl DistinctCount {
+    pub fn update(&mut self, key: Key, threshold: usize, precision: u32) {
+        match self {
+            DistinctCount::Exact(items) => {
+                items.insert(key);
+                if items.len() > threshold {
+                    let mut hll = HyperLogLog::new(precision)
+                        .expect("the precision is checked when the count is built");
+                    for key in items.iter() {
+                        hll.update(key.stable_hash());
+                    }
+                    *self = DistinctCount::Approximate(hll);
+                }
+            }
+            DistinctCount::Approximate(hll) => hll.update(key.stable_hash()),
+        }
+    }
+
+    pub fn get(&self) -> f64 {
+        match self {
+            DistinctCount::Exact(items) => items.len() as f64,
+            DistinctCount::Approximate(hll) => hll.estimate(),
+        }
+    }
+}
+
+/// Number of distinct items, counted exactly while there are few of them.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsDistinctCount {
+    count: DistinctCount,
+    threshold: usize,
+    precision: u32,
+}
+
+impl HeapSize for RsDistinctCount {
+    fn heap_size(&self) -> usize {
+        self.count.heap_size()
+    }
+}
+
+// Up to version 1, the items were hashed with the hash of `std`, which isn't stable, so only
+// the states which still hold the items themselves can be read.
+impl Versioned for RsDistinctCount {
+    const VERSION: u16 = 2;
+
+    fn migrate(_version: u16, payload: &mut dyn Read) -> bincode::Result<Self> {
+        let state: RsDistinctCount = bincode::deserialize_from(payload)?;
+        match state.count {
+            DistinctCount::Exact(_) => Ok(state),
+            DistinctCount::Approximate(_) => Err(rehash_error()),
+        }
+    }
+}
+
+#[pymethods]
+impl RsDistinctCount {
+    /// Items are kept in a set until there are more than `threshold` of them, and then
+    /// counted by a HyperLogLog with `2^precision` registers, whose relative standard error
+    /// is about `1.04 / sqrt(2^precision)`.
+    #[new]
+    #[args(threshold = "1000", precision = "12")]
+    pub fn new(threshold: usize, precision: u32) -> PyResult<Self> {
+        check_precision(precision).map_err(PyValueError::new_err)?;
+        Ok(RsDistinctCount {
+            count: DistinctCount::Exact(BTreeSet::new()),
+            threshold,
+            precision,
+        })
+    }
+    pub fn update(&mut self, item: Key) {
+        self.count.update(item, self.threshold, self.precision);
+    }
+    pub fn get(&self) -> usize {
+        self.count.get().round() as usize
+    }
+    /// Whether the count is still exact.
+    #[getter]
+    pub fn is_exact(&self) -> bool {
+        matches!(self.count, DistinctCount::Exact(_))
+    }
+}
+
+/// Approximate number of distinct items, in `2^precision` bytes.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsHyperLogLog {
+    hll: HyperLogLog,
+}
+
+impl HeapSize for RsHyperLogLog {
+    fn heap_size(&self) -> usize {
+        self.hll.heap_size()
+    }
+}
+
+// Up to version 1, the items were hashed with the hash of `std`.
+impl Versioned for RsHyperLogLog {
+    const VERSION: u16 = 2;
+
+    fn migrate(_version: u16, _payload: &mut dyn Read) -> bincode::Result<Self> {
+        Err(rehash_error())
+    }
+}
+
+#[pymethods]
+impl RsHyperLogLog {
+    /// The relative standard error of the count is about `1.04 / sqrt(2^precision)`.
+    #[new]
+    #[args(precision = "12")]
+    pub fn new(precision: u32) -> PyResult<Self> {
+        Ok(RsHyperLogLog {
+            hll: HyperLogLog::new(precision).map_err(PyValueError::new_err)?,
+        })
+    }
+    pub fn update(&mut self, item: Key) {
+        self.hll.update(item.stable_hash());
+    }
+    pub fn get(&self) -> usize {
+        self.hll.estimate().round() as usize
+    }
+    /// Counts the items of `other` as well, e.g. a sketch built on another shard. Both
+    /// should have the same precision.
+    pub fn merge(&mut self, other: PyRef<RsHyperLogLog>) -> PyResult<()> {
+        self.hll.merge(&other.hll).map_err(PyValueError::new_err)
+    }
+    #[getter]
+    pub fn precision(&self) -> u32 {
+        self.hll.precision()
+    }
+}
+
+/// Streaming histogram, a drop-in for `river.sketch.Histogram`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsHistogram {
+    histogram: Histogram,
+}
+
+impl HeapSize for RsHistogram {
+    fn heap_size(&self) -> usize {
+        self.histogram.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsHistogram {
+    #[new]
+    #[args(max_bins = "256")]
+    pub fn new(max_bins: usize) -> PyResult<Self> {
+        if max_bins == 0 {
+            return Err(PyValueError::new_err("max_bins should be at least 1"));
+        }
+        Ok(RsHistogram {
+            histogram: Histogram::new(max_bins),
+        })
+    }
+    pub fn update(&mut self, x: f64) -> PyResult<()> {
+        if x.is_nan() {
+            return Err(PyValueError::new_err("x should not be NaN"));
+        }
+        self.histogram.update(x);
+        Ok(())
+    }
+    /// Estimated fraction of the values that are lower than or equal to `x`.
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.histogram.cdf(x)
+    }
+    /// The bins, as `(left, right, count)` tuples from left to right.
+    pub fn iter_bins(&self) -> Vec<(f64, f64, u64)> {
+        self.histogram
+            .bins()
+            .iter()
+            .map(|b| (b.left, b.right, b.count))
+            .collect()
+    }
+    /// Folds in the bins of `other`, e.g. a histogram built on another shard.
+    pub fn merge(&mut self, other: PyRef<RsHistogram>) {
+        self.histogram.merge(&other.histogram);
+    }
+    /// Number of values seen.
+    #[getter]
+    pub fn n(&self) -> u64 {
+        self.histogram.n()
+    }
+    pub fn __len__(&self) -> usize {
+        self.histogram.bins().len()
+    }
+}
+
+/// Approximate weights of the keys of a stream, in a fixed amount of memory.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCountMin {
+    sketch: CountMin,
+}
+
+impl HeapSize for RsCountMin {
+    fn heap_size(&self) -> usize {
+        self.sketch.heap_size()
+    }
+}
+
+// Up to version 1, the keys were hashed with the hash of `std`.
+impl Versioned for RsCountMin {
+    const VERSION: u16 = 2;
+
+    fn migrate(_version: u16, _payload: &mut dyn Read) -> bincode::Result<Self> {
+        Err(rehash_error())
+    }
+}
+
+#[pymethods]
+impl RsCountMin {
+    /// Keeps `depth` rows of `width` counters. A key is overestimated by at most
+    /// `e / width` times the total weight, except with probability `exp(-depth)`. Only the
+    /// sketches built with the same `seed` can be merged.
+    #[new]
+    #[args(seed = "None")]
+    pub fn new(width: usize, depth: usize, seed: Option<u64>) -> PyResult<Self> {
+        if width == 0 || depth == 0 {
+            return Err(PyValueError::new_err(
+                "width and depth should be at least 1",
+            ));
+        }
+        Ok(RsCountMin {
+            sketch: CountMin::new(width, depth, &mut Rng::new(seed)),
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, key: Key, w: f64) -> PyResult<()> {
+        if !w.is_finite() || w < 0.0 {
+            return Err(PyValueError::new_err("w should be finite and non-negative"));
+        }
+        self.sketch.update(key.stable_hash(), w);
+        Ok(())
+    }
+    /// Estimated weight of `key`, which is never lower than the actual one.
+    pub fn query(&self, key: Key) -> f64 {
+        self.sketch.query(key.stable_hash())
+    }
+    /// Adds the counters of `other`, e.g. a sketch built on another shard.
+    pub fn merge(&mut self, other: PyRef<RsCountMin>) -> PyResult<()> {
+        self.sketch
+            .merge(&other.sketch)
+            .map_err(PyValueError::new_err)
+    }
+    /// Sum of the weights of all the keys.
+    #[getter]
+    pub fn total(&self) -> f64 {
+        self.sketch.total()
+    }
+    #[getter]
+    pub fn width(&self) -> usize {
+        self.sketch.width()
+    }
+    #[getter]
+    pub fn depth(&self) -> usize {
+        self.sketch.depth()
+    }
+}
+
+/// Approximate number of ones among the last `window_size` bits of a stream, in a memory
+/// logarithmic in `window_size`.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsExpHistogram {
+    histogram: ExpHistogram,
+}
+
+impl HeapSize for RsExpHistogram {
+    fn heap_size(&self) -> usize {
+        self.histogram.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsExpHistogram {
+    /// The count is off by at most `eps` times the actual one, with
+    /// `O(log(window_size)^2 / eps)` bits of memory.
+    #[new]
+    pub fn new(window_size: u64, eps: f64) -> PyResult<Self> {
+        if window_size == 0 {
+            return Err(PyValueError::new_err("window_size should be at least 1"));
+        }
+        if !(eps > 0.0 && eps <= 1.0) {
+            return Err(PyValueError::new_err("eps should be in (0, 1]"));
+        }
+        Ok(RsExpHistogram {
+            histogram: ExpHistogram::new(window_size, eps),
+        })
+    }
+    pub fn update(&mut self, bit: bool) {
+        self.histogram.update(bit);
+    }
+    pub fn get(&self) -> f64 {
+        self.histogram.get()
+    }
+    #[getter]
+    pub fn window_size(&self) -> u64 {
+        self.histogram.window_size()
+    }
+    #[getter]
+    pub fn n_buckets(&self) -> usize {
+        self.histogram.n_buckets()
+    }
+}
+
+// Faded counts are rescaled before the scale of the new ones underflows.
+const MIN_FADING_SCALE: f64 = 1e-100;
+
+/// Count of a key, along with the updates at which it was first and last seen.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tally {
+    count: f64,
+    first_seen: u64,
+    last_seen: u64,
+}
+
+impl HeapSize for Tally {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Counts of the keys of a categorical stream.
+///
+/// With a `fading` factor below 1, every count is multiplied by it at each update, which is
+/// done lazily by scaling up the new weights instead. With `max_size`, the lightest key is
+/// dropped whenever a new one would exceed it, so that the heavy hitters are kept; the
+/// frequencies are then relative to the keys still counted.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CategoricalCounter {
+    max_size: Option<usize>,
+    fading: f64,
+    // Factor turning the stored counts into the actual ones.
+    scale: f64,
+    #[serde(with = "crate::key::pairs")]
+    tallies: HashMap<Key, Tally>,
+    total: f64,
+    n_updates: u64,
+}
+
+impl HeapSize for CategoricalCounter {
+    fn heap_size(&self) -> usize {
+        self.tallies.heap_size()
+    }
+}
+
+impl CategoricalCounter {
+    pub fn new(max_size: Option<usize>, fading: f64) -> Self {
+        CategoricalCounter {
+            max_size,
+            fading,
+            scale: 1.0,
+            tallies: HashMap::new(),
+            total: 0.0,
+            n_updates: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tallies.len()
+    }
+
+    pub fn contains(&self, key: &Key) -> bool {
+        self.tallies.contains_key(key)
+    }
+
+    pub fn update(&mut self, key: Key, w: f64) {
+        self.n_updates += 1;
+        if self.fading < 1.0 {
+            self.scale *= self.fading;
+            if self.scale < MIN_FADING_SCALE {
+                for tally in self.tallies.values_mut() {
+                    tally.count *= self.scale;
+                }
+                self.total *= self.scale;
+                self.scale = 1.0;
+            }
+        }
+        let w = w / self.scale;
+        self.total += w;
+        let n_updates = self.n_updates;
+        if let Some(tally) = self.tallies.get_mut(&key) {
+            tally.count += w;
+            tally.last_seen = n_updates;
+            return;
+        }
+        if self.max_size == Some(self.tallies.len()) {
+            let lightest = self
+                .tallies
+                .iter()
+                .min_by(|a, b| a.1.count.total_cmp(&b.1.count))
+                .map(|(key, _)| key.clone());
+            if let Some(lightest) = lightest {
+                let tally = self.tallies.remove(&lightest).unwrap();
+                self.total -= tally.count;
+            }
+        }
+        self.tallies.insert(
+            key,
+            Tally {
+                count: w,
+                first_seen: n_updates,
+                last_seen: n_updates,
+            },
+        );
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total * self.scale
+    }
+
+    pub fn get(&self, key: &Key) -> f64 {
+        self.tallies
+            .get(key)
+            .map_or(0.0, |tally| tally.count * self.scale)
+    }
+
+    pub fn frequency(&self, key: &Key) -> f64 {
+        if self.total == 0.0 {
+            return 0.0;
+        }
+        self.tallies
+            .get(key)
+            .map_or(0.0, |tally| tally.count / self.total)
+    }
+
+    /// The `n` most frequent keys with their counts, from the most frequent down, the keys
+    /// seen first coming first among equal counts.
+    pub fn most_common(&self, n: usize) -> Vec<(Key, f64)> {
+        let mut tallies: Vec<(&Key, &Tally)> = self.tallies.iter().collect();
+        tallies.sort_by(|a, b| {
+            b.1.count
+                .total_cmp(&a.1.count)
+                .then(a.1.first_seen.cmp(&b.1.first_seen))
+        });
+        tallies
+            .into_iter()
+            .take(n)
+            .map(|(key, tally)| (key.clone(), tally.count * self.scale))
+            .collect()
+    }
+
+    /// The keys sharing the highest count, in no particular order.
+    pub fn modes(&self) -> Vec<(&Key, &Tally)> {
+        let max = self
+            .tallies
+            .values()
+            .map(|tally| tally.count)
+            .fold(f64::NEG_INFINITY, f64::max);
+        self.tallies
+            .iter()
+            .filter(|(_, tally)| tally.count == max)
+            .collect()
+    }
+
+    pub fn n_updates(&self) -> u64 {
+        self.n_updates
+    }
+
+    /// Shannon entropy of the frequencies, in nats.
+    pub fn entropy(&self) -> f64 {
+        if self.total == 0.0 {
+            return 0.0;
+        }
+        -self
+            .tallies
+            .values()
+            .map(|tally| tally.count / self.total)
+            .filter(|&p| p > 0.0)
+            .map(|p| p * p.ln())
+            .sum::<f64>()
+    }
+}
+
+/// Counts, frequencies, mode and entropy of a categorical stream.
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsCounter {
+    counter: CategoricalCounter,
+}
+
+impl HeapSize for RsCounter {
+    fn heap_size(&self) -> usize {
+        self.counter.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsCounter {
+    /// `max_size` bounds the number of keys counted, and `fading` is the factor applied to
+    /// the counts at every update, 1 meaning no fading.
+    #[new]
+    #[args(max_size = "None", fading = "1.0")]
+    pub fn new(max_size: Option<usize>, fading: f64) -> PyResult<Self> {
+        if fading.is_nan() || fading <= 0.0 || fading > 1.0 {
+            return Err(PyValueError::new_err("fading should be in (0, 1]"));
+        }
+        if max_size == Some(0) {
+            return Err(PyValueError::new_err("max_size should be at least 1"));
+        }
+        Ok(RsCounter {
+            counter: CategoricalCounter::new(max_size, fading),
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, key: Key, w: f64) -> PyResult<()> {
+        if !w.is_finite() || w < 0.0 {
+            return Err(PyValueError::new_err("w should be finite and non-negative"));
+        }
+        self.counter.update(key, w);
+        Ok(())
+    }
+    /// Count of `key`, 0 if it isn't counted.
+    pub fn get(&self, key: Key) -> f64 {
+        self.counter.get(&key)
+    }
+    /// Share of the total count held by `key`.
+    pub fn frequency(&self, key: Key) -> f64 {
+        self.counter.frequency(&key)
+    }
+    /// The `n` most frequent keys, or all of them, with their counts.
+    #[args(n = "None")]
+    pub fn most_common(&self, n: Option<usize>) -> Vec<(Key, f64)> {
+        self.counter.most_common(n.unwrap_or(usize::MAX))
+    }
+    /// Most frequent key, or `None` before the first update.
+    pub fn mode(&self) -> Option<Key> {
+        self.counter.most_common(1).pop().map(|(key, _)| key)
+    }
+    /// Shannon entropy of the frequencies, in nats.
+    pub fn entropy(&self) -> f64 {
+        self.counter.entropy()
+    }
+    /// Sum of the counts of the keys being counted.
+    #[getter]
+    pub fn total(&self) -> f64 {
+        self.counter.total()
+    }
+    pub fn __len__(&self) -> usize {
+        self.counter.len()
+    }
+    pub fn __contains__(&self, key: Key) -> bool {
+        self.counter.contains(&key)
+    }
+}
+
+/// Which of the keys sharing the highest count is the mode.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TieBreak {
+    FirstSeen,
+    LastSeen,
+    Lexicographic,
+    Random(u64),
+}
+
+impl TieBreak {
+    pub fn parse(ties: &str, seed: Option<u64>) -> PyResult<Self> {
+        match ties {
+            "first" => Ok(TieBreak::FirstSeen),
+            "last" => Ok(TieBreak::LastSeen),
+            "lexicographic" => Ok(TieBreak::Lexicographic),
+            "random" => Ok(TieBreak::Random(
+                seed.unwrap_or_else(|| Rng::new(None).next_u64()),
+            )),
+            _ => Err(PyValueError::new_err(format!(
+                "ties should be one of 'first', 'last', 'lexicographic' and 'random', got '{}'",
+                ties
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TieBreak::FirstSeen => "first",
+            TieBreak::LastSeen => "last",
+            TieBreak::Lexicographic => "lexicographic",
+            TieBreak::Random(_) => "random",
+        }
+    }
+
+    // The random choice only depends on the seed and on the number of updates, so that
+    // reading the mode twice gives the same key.
+    fn choose<'a>(&self, mut modes: Vec<(&'a Key, &Tally)>, n_updates: u64) -> Option<&'a Key> {
+        let mode = match self {
+            TieBreak::FirstSeen => modes.into_iter().min_by_key(|(_, tally)| tally.first_seen),
+            TieBreak::LastSeen => modes.into_iter().max_by_key(|(_, tally)| tally.last_seen),
+            TieBreak::Lexicographic => modes.into_iter().min_by_key(|&(key, _)| key),
+            TieBreak::Random(seed) => {
+                if modes.is_empty() {
+                    return None;
+                }
+                modes.sort_by_key(|&(key, _)| key);
+                let mut rng = Rng::from_seed(seed ^ n_updates);
+                Some(modes.swap_remove(rng.below(modes.len() as u64) as usize))
+            }
+        };
+        mode.map(|(key, _)| key)
+    }
+}
+
+/// Most frequent key of a categorical stream, with a choice of tie-breaking.
+///
+/// Among the keys sharing the highest count, the mode is the one seen first (`"first"`),
+/// seen last (`"last"`), the lowest (`"lexicographic"`, integers coming before strings), or
+/// one drawn at random with `seed` (`"random"`).
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsMode {
+    counter: CategoricalCounter,
+    ties: TieBreak,
+}
+
+impl HeapSize for RsMode {
+    fn heap_size(&self) -> usize {
+        self.counter.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsMode {
+    #[new]
+    #[args(ties = "\"first\"", seed = "None", max_size = "None", fading = "1.0")]
+    pub fn new(
+        ties: &str,
+        seed: Option<u64>,
+        max_size: Option<usize>,
+        fading: f64,
+    ) -> PyResult<Self> {
+        let counter = RsCounter::new(max_size, fading)?.counter;
+        Ok(RsMode {
+            counter,
+            ties: TieBreak::parse(ties, seed)?,
+        })
+    }
+    #[args(w = "1.0")]
+    pub fn update(&mut self, key: Key, w: f64) -> PyResult<()> {
+        if !w.is_finite() || w < 0.0 {
+            return Err(PyValueError::new_err("w should be finite and non-negative"));
+        }
+        self.counter.update(key, w);
+        Ok(())
+    }
+    /// The mode, or `None` before the first update.
+    pub fn get(&self) -> Option<Key> {
+        self.ties
+            .choose(self.counter.modes(), self.counter.n_updates())
+            .cloned()
+    }
+    /// Every key sharing the highest count, in increasing order.
+    pub fn modes(&self) -> Vec<Key> {
+        let mut modes: Vec<Key> = self
+            .counter
+            .modes()
+            .into_iter()
+            .map(|(key, _)| key.clone())
+            .collect();
+        modes.sort();
+        modes
+    }
+    #[getter]
+    pub fn ties(&self) -> &'static str {
+        self.ties.name()
+    }
+}
@@ -0,0 +1,256 @@
+// Wrappers of Python iterables, which reorder them, as in `river.stream`, or alter them.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyIterator, PyLong};
+use watermill::{stats::Univariate, variance::Variance};
+
+use crate::key::Key;
+use crate::rng::Rng;
+
+/// Iterator over a shuffled iterable, made by `shuffle`.
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsShuffle {
+    source: Py<PyIterator>,
+    buffer: Vec<PyObject>,
+    buffer_size: usize,
+    rng: Rng,
+    exhausted: bool,
+}
+
+impl RsShuffle {
+    fn pull(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        if self.exhausted {
+            return Ok(None);
+        }
+        match self.source.as_ref(py).next() {
+            Some(item) => Ok(Some(item?.into())),
+            None => {
+                self.exhausted = true;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl RsShuffle {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        while self.buffer.len() < self.buffer_size {
+            match self.pull(py)? {
+                Some(item) => self.buffer.push(item),
+                None => break,
+            }
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let i = self.rng.below(self.buffer.len() as u64) as usize;
+        Ok(Some(match self.pull(py)? {
+            Some(item) => std::mem::replace(&mut self.buffer[i], item),
+            None => self.buffer.swap_remove(i),
+        }))
+    }
+}
+
+/// Shuffles `iterable` on the fly, with a buffer of `buffer_size` items.
+///
+/// Each item read replaces one drawn at random from the buffer, which is yielded. The items
+/// left in the buffer at the end are yielded in random order. A larger buffer mixes the
+/// stream more, at the cost of memory, and a buffer as large as the stream shuffles it
+/// uniformly.
+#[pyfunction(seed = "None")]
+pub fn shuffle(iterable: &PyAny, buffer_size: usize, seed: Option<u64>) -> PyResult<RsShuffle> {
+    if buffer_size == 0 {
+        return Err(PyValueError::new_err("buffer_size should be at least 1"));
+    }
+    Ok(RsShuffle {
+        source: iterable.iter()?.into(),
+        buffer: Vec::with_capacity(buffer_size),
+        buffer_size,
+        rng: Rng::new(seed),
+        exhausted: false,
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Anomaly {
+    Spike,
+    Shift,
+    Burst,
+}
+
+impl Anomaly {
+    fn parse(kind: &str) -> PyResult<Self> {
+        match kind {
+            "spike" => Ok(Anomaly::Spike),
+            "shift" => Ok(Anomaly::Shift),
+            "burst" => Ok(Anomaly::Burst),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown anomaly '{}', expected 'spike', 'shift' or 'burst'",
+                kind
+            ))),
+        }
+    }
+}
+
+struct Episode {
+    kind: Anomaly,
+    remaining: usize,
+    // Direction of the spike or of the shift.
+    sign: f64,
+}
+
+/// Iterator over a stream with injected anomalies, made by `inject_anomalies`.
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAnomalyInjector {
+    source: Py<PyIterator>,
+    kinds: Vec<Anomaly>,
+    rate: f64,
+    magnitude: f64,
+    duration: usize,
+    scales: HashMap<Key, Variance<f64>>,
+    episode: Option<Episode>,
+    n_samples: usize,
+    #[pyo3(get)]
+    n_anomalies: usize,
+    rng: Rng,
+}
+
+impl RsAnomalyInjector {
+    fn start_episode(&mut self) -> Option<Episode> {
+        if self.n_samples < 2 || self.rng.next_f64() >= self.rate {
+            return None;
+        }
+        let kind = self.kinds[self.rng.below(self.kinds.len() as u64) as usize];
+        Some(Episode {
+            kind,
+            remaining: if kind == Anomaly::Spike {
+                1
+            } else {
+                self.duration
+            },
+            sign: if self.rng.below(2) == 0 { -1.0 } else { 1.0 },
+        })
+    }
+
+    fn perturb(&mut self, kind: Anomaly, sign: f64, key: &Key, value: f64) -> f64 {
+        let std = match self.scales.get(key) {
+            Some(var) if var.mean.n.get() >= 2.0 => var.get().sqrt(),
+            _ => return value,
+        };
+        match kind {
+            Anomaly::Spike | Anomaly::Shift => value + sign * self.magnitude * std,
+            Anomaly::Burst => value + self.rng.normal() * self.magnitude * std,
+        }
+    }
+}
+
+#[pymethods]
+impl RsAnomalyInjector {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(PyObject, bool)>> {
+        let source = self.source.clone_ref(py);
+        let item = match source.as_ref(py).next() {
+            Some(item) => item?,
+            None => return Ok(None),
+        };
+        let x: &PyDict = match item.downcast::<PyDict>() {
+            Ok(x) => x,
+            Err(_) => item.get_item(0)?.downcast()?,
+        };
+        let x = x.copy()?;
+        if self.episode.is_none() {
+            self.episode = self.start_episode();
+            if self.episode.is_some() {
+                self.n_anomalies += 1;
+            }
+        }
+        let anomaly = self.episode.as_ref().map(|e| (e.kind, e.sign));
+        for (name, value) in x.iter() {
+            let numeric = value.downcast::<PyFloat>().is_ok()
+                || (value.downcast::<PyLong>().is_ok() && value.downcast::<PyBool>().is_err());
+            let key = match name.extract::<Key>() {
+                Ok(key) if numeric => key,
+                _ => continue,
+            };
+            let value: f64 = value.extract()?;
+            if let Some((kind, sign)) = anomaly {
+                x.set_item(name, self.perturb(kind, sign, &key, value))?;
+            }
+            self.scales
+                .entry(key)
+                .or_insert_with(|| Variance::new(1))
+                .update(value);
+        }
+        self.n_samples += 1;
+        if let Some(episode) = &mut self.episode {
+            episode.remaining -= 1;
+            if episode.remaining == 0 {
+                self.episode = None;
+            }
+        }
+        Ok(Some((x.into(), anomaly.is_some())))
+    }
+}
+
+/// Injects anomalies in the numeric features of `stream`, whose items are dicts or `(x, y)`
+/// pairs, and streams `(x, is_anomaly)` pairs, where `x` is a copy of the features.
+///
+/// Outside of anomalies, one starts with probability `rate` at each sample, and its kind is
+/// drawn among `kinds`:
+///
+/// - `"spike"` moves one sample by `magnitude` standard deviations, up or down,
+/// - `"shift"` moves the next `duration` samples by as much, in the same direction,
+/// - `"burst"` adds gaussian noise of `magnitude` standard deviations to the next
+///   `duration` samples.
+///
+/// The standard deviations are those of the original values of each feature, so far. The
+/// features which haven't been seen twice are left as they are.
+#[pyfunction(kinds = "None", magnitude = "5.0", duration = "20", seed = "None")]
+pub fn inject_anomalies(
+    stream: &PyAny,
+    rate: f64,
+    kinds: Option<Vec<&str>>,
+    magnitude: f64,
+    duration: usize,
+    seed: Option<u64>,
+) -> PyResult<RsAnomalyInjector> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(PyValueError::new_err("rate should be between 0 and 1"));
+    }
+    if duration == 0 {
+        return Err(PyValueError::new_err("duration should be at least 1"));
+    }
+    let kinds = match kinds {
+        Some(kinds) => kinds
+            .into_iter()
+            .map(Anomaly::parse)
+            .collect::<PyResult<Vec<_>>>()?,
+        None => vec![Anomaly::Spike, Anomaly::Shift, Anomaly::Burst],
+    };
+    if kinds.is_empty() {
+        return Err(PyValueError::new_err("kinds should not be empty"));
+    }
+    Ok(RsAnomalyInjector {
+        source: stream.iter()?.into(),
+        kinds,
+        rate,
+        magnitude,
+        duration,
+        scales: HashMap::new(),
+        episode: None,
+        n_samples: 0,
+        n_anomalies: 0,
+        rng: Rng::new(seed),
+    })
+}
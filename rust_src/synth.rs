@@ -0,0 +1,1328 @@
+// Synthetic stream generators of the concept drift literature, ported from
+// `river.datasets.synth`.
+//
+// They draw from the same `Rng` as the other stochastic components, so they are seeded in
+// the same way, and they are their own iterators: the state, generator included, is
+// serialized, and a restored generator carries on with the same samples. Unlike the Python
+// datasets, iterating again doesn't restart the stream.
+//
+// The drift streams splice copies of other generators, held in a `Generator`, so that the
+// whole stream is generated in Rust.
+
+use std::mem::size_of;
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+use crate::memory::HeapSize;
+use crate::rng::Rng;
+
+/// Feature or target value of a sample.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+impl IntoPy<PyObject> for Value {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Value::Bool(b) => b.into_py(py),
+            Value::Int(i) => i.into_py(py),
+            Value::Float(f) => f.into_py(py),
+        }
+    }
+}
+
+pub type Sample = (Vec<Value>, Value);
+
+fn to_py(py: Python, names: &[Key], (x, y): Sample) -> PyResult<(PyObject, PyObject)> {
+    let dict = PyDict::new(py);
+    for (name, value) in names.iter().zip(x) {
+        dict.set_item(name.clone().into_py(py), value.into_py(py))?;
+    }
+    Ok((dict.into(), y.into_py(py)))
+}
+
+fn check_probability(name: &str, p: f64) -> PyResult<()> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err(PyValueError::new_err(format!(
+            "{} should be between 0 and 1",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Inclusive uniform integer draw, like `random.randint`.
+fn randint(rng: &mut Rng, low: i64, high: i64) -> i64 {
+    low + rng.below((high - low + 1) as u64) as i64
+}
+
+/// SEA generator: three uniform features in [0, 10), of which the label only depends on the
+/// first two, `x0 + x1 > threshold`. Each `variant` has its own threshold, and the label is
+/// flipped with probability `noise`.
+///
+/// Reference: Street, W.N. and Kim, Y., 2001. A streaming ensemble algorithm (SEA) for
+/// large-scale classification. KDD'01, pp.377-382.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sea {
+    threshold: f64,
+    noise: f64,
+    rng: Rng,
+}
+
+impl Sea {
+    pub fn names(&self) -> Vec<Key> {
+        (0..3).map(Key::Int).collect()
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let x: Vec<f64> = (0..3).map(|_| 10.0 * self.rng.next_f64()).collect();
+        let mut y = x[0] + x[1] > self.threshold;
+        if self.noise > 0.0 && self.rng.next_f64() < self.noise {
+            y = !y;
+        }
+        (x.into_iter().map(Value::Float).collect(), Value::Bool(y))
+    }
+}
+
+/// Rotating hyperplane generator: uniform features in [0, 1), labelled by the side of a
+/// hyperplane on which they lie. The weights of the first `n_drift_features` move by
+/// `mag_change` after each sample, and change direction with probability `sigma`. The label
+/// is flipped with probability `noise_percentage`.
+///
+/// Reference: Hulten, G., Spencer, L. and Domingos, P., 2001. Mining time-changing data
+/// streams. KDD'01, pp.97-106.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Hyperplane {
+    n_drift_features: usize,
+    mag_change: f64,
+    noise_percentage: f64,
+    sigma: f64,
+    weights: Vec<f64>,
+    change_direction: Vec<f64>,
+    rng: Rng,
+}
+
+impl HeapSize for Hyperplane {
+    fn heap_size(&self) -> usize {
+        self.weights.heap_size() + self.change_direction.heap_size()
+    }
+}
+
+impl Hyperplane {
+    pub fn names(&self) -> Vec<Key> {
+        (0..self.weights.len() as i64).map(Key::Int).collect()
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let sum_weights: f64 = self.weights.iter().sum();
+        let x: Vec<f64> = self.weights.iter().map(|_| self.rng.next_f64()).collect();
+        let sum_value: f64 = self.weights.iter().zip(&x).map(|(w, x)| w * x).sum();
+        let mut y = sum_value >= sum_weights * 0.5;
+        if 0.01 + self.rng.next_f64() <= self.noise_percentage {
+            y = !y;
+        }
+        for i in 0..self.n_drift_features {
+            self.weights[i] += self.change_direction[i] * self.mag_change;
+            if 0.01 + self.rng.next_f64() <= self.sigma {
+                self.change_direction[i] = -self.change_direction[i];
+            }
+        }
+        (
+            x.into_iter().map(Value::Float).collect(),
+            Value::Int(y as i64),
+        )
+    }
+}
+
+const AGRAWAL_FEATURES: [&str; 9] = [
+    "salary",
+    "commission",
+    "age",
+    "elevel",
+    "car",
+    "zipcode",
+    "hvalue",
+    "hyears",
+    "loan",
+];
+
+struct Applicant {
+    salary: f64,
+    commission: f64,
+    age: i64,
+    elevel: i64,
+    car: i64,
+    zipcode: i64,
+    hvalue: f64,
+    hyears: i64,
+    loan: f64,
+}
+
+impl Applicant {
+    // The ten functions of Agrawal et al., as written in `river`, typos included, so that
+    // both generators label the samples in the same way.
+    fn label(&self, function: usize) -> bool {
+        let in_range = |v: f64, low: f64, high: f64| low <= v && v <= high;
+        let (salary, age, elevel, loan) = (self.salary, self.age, self.elevel, self.loan);
+        let total = salary + self.commission;
+        match function {
+            0 => !(40..60).contains(&age),
+            1 => match age {
+                ..=39 => in_range(salary, 50000.0, 100000.0),
+                40..=59 => in_range(salary, 75000.0, 125000.0),
+                _ => in_range(salary, 25000.0, 75000.0),
+            },
+            2 => match age {
+                ..=39 => (0..=1).contains(&elevel),
+                40..=59 => (1..=3).contains(&elevel),
+                _ => (2..=4).contains(&elevel),
+            },
+            3 => match age {
+                ..=39 if (0..=1).contains(&elevel) => in_range(salary, 25000.0, 75000.0),
+                ..=39 => in_range(salary, 50000.0, 100000.0),
+                40..=59 if (1..=3).contains(&elevel) => in_range(salary, 50000.0, 100000.0),
+                40..=59 => in_range(salary, 75000.0, 125000.0),
+                _ if (2..=4).contains(&elevel) => in_range(salary, 50000.0, 100000.0),
+                _ => in_range(salary, 25000.0, 75000.0),
+            },
+            4 => match age {
+                ..=39 if in_range(salary, 50000.0, 100000.0) => in_range(loan, 100000.0, 300000.0),
+                ..=39 => in_range(salary, 200000.0, 400000.0),
+                40..=59 if in_range(salary, 75000.0, 125000.0) => {
+                    200000.0 <= salary && loan <= 400000.0
+                }
+                40..=59 => in_range(salary, 300000.0, 500000.0),
+                _ if in_range(salary, 25000.0, 75000.0) => in_range(loan, 300000.0, 500000.0),
+                _ => in_range(loan, 75000.0, 300000.0),
+            },
+            5 => match age {
+                ..=39 => in_range(total, 50000.0, 100000.0),
+                40..=59 => in_range(total, 75000.0, 125000.0),
+                _ => in_range(total, 25000.0, 75000.0),
+            },
+            6 => 2.0 * total / 3.0 - loan / 5.0 - 20000.0 <= 1.0,
+            7 => 2.0 * total / 3.0 - 5000.0 * elevel as f64 - 20000.0 <= 1.0,
+            8 => 2.0 * total / 3.0 - 5000.0 * elevel as f64 - loan / 5.0 - 10000.0 <= 1.0,
+            _ => {
+                let equity = if self.hyears >= 20 {
+                    self.hvalue * (self.hyears - 20) as f64 / 10.0
+                } else {
+                    0.0
+                };
+                2.0 * total / 3.0 - 5000.0 * elevel as f64 + equity / 5.0 - 10000.0 <= 1.0
+            }
+        }
+    }
+}
+
+/// Agrawal generator: loan applications with six numeric and three categorical features,
+/// labelled by one of ten `classification_function`s. With `balance_classes`, the labels
+/// alternate, and `perturbation` adds uniform noise, relative to their range, to the
+/// numeric features.
+///
+/// Reference: Agrawal, R., Imielinski, T. and Swami, A., 1993. Database mining: a
+/// performance perspective. IEEE Transactions on Knowledge and Data Engineering, 5(6),
+/// pp.914-925.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Agrawal {
+    function: usize,
+    balance_classes: bool,
+    perturbation: f64,
+    next_class_should_be_zero: bool,
+    rng: Rng,
+}
+
+impl Agrawal {
+    pub fn names(&self) -> Vec<Key> {
+        AGRAWAL_FEATURES
+            .iter()
+            .map(|name| Key::Str(name.to_string()))
+            .collect()
+    }
+
+    fn draw(&mut self) -> Applicant {
+        let rng = &mut self.rng;
+        let salary = 20000.0 + 130000.0 * rng.next_f64();
+        let commission = if salary >= 75000.0 {
+            0.0
+        } else {
+            10000.0 + 75000.0 * rng.next_f64()
+        };
+        let age = randint(rng, 20, 80);
+        let elevel = randint(rng, 0, 4);
+        let car = randint(rng, 1, 20);
+        let zipcode = randint(rng, 0, 8);
+        let hvalue = (8 - zipcode) as f64 * 100000.0 * (0.5 + rng.next_f64());
+        let hyears = randint(rng, 1, 30);
+        let loan = rng.next_f64() * 500000.0;
+        Applicant {
+            salary,
+            commission,
+            age,
+            elevel,
+            car,
+            zipcode,
+            hvalue,
+            hyears,
+            loan,
+        }
+    }
+
+    // Not `f64::clamp`, since `river` calls it with `min > max` for `hvalue`.
+    fn perturb(&mut self, value: f64, min: f64, max: f64, range: f64) -> f64 {
+        let value = value + range * (2.0 * (self.rng.next_f64() - 0.5)) * self.perturbation;
+        if value < min {
+            min
+        } else if value > max {
+            max
+        } else {
+            value
+        }
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let (mut a, y) = loop {
+            let a = self.draw();
+            let y = a.label(self.function);
+            if !self.balance_classes || y != self.next_class_should_be_zero {
+                self.next_class_should_be_zero ^= self.balance_classes;
+                break (a, y);
+            }
+        };
+        if self.perturbation > 0.0 {
+            a.salary = self.perturb(a.salary, 20000.0, 150000.0, 130000.0);
+            if a.commission > 0.0 {
+                a.commission = self.perturb(a.commission, 10000.0, 75000.0, 65000.0);
+            }
+            a.age = self.perturb(a.age as f64, 20.0, 80.0, 60.0).round() as i64;
+            let hvalue_min = (9 - a.zipcode) as f64 * 100000.0;
+            a.hvalue = self.perturb(a.hvalue, hvalue_min, 0.0, 135000.0);
+            a.hyears = self.perturb(a.hyears as f64, 1.0, 30.0, 29.0).round() as i64;
+            a.loan = self.perturb(a.loan, 0.0, 500000.0, 500000.0);
+        }
+        let x = vec![
+            Value::Float(a.salary),
+            Value::Float(a.commission),
+            Value::Int(a.age),
+            Value::Int(a.elevel),
+            Value::Int(a.car),
+            Value::Int(a.zipcode),
+            Value::Float(a.hvalue),
+            Value::Int(a.hyears),
+            Value::Float(a.loan),
+        ];
+        (x, Value::Int(y as i64))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Centroid {
+    centre: Vec<f64>,
+    label: i64,
+    std_dev: f64,
+}
+
+/// Random RBF generator: numeric features drawn around centroids of random centres, labels,
+/// spreads and weights, which are set by `seed_model`. The samples of a centroid lie in a
+/// random direction from its centre, at a normally distributed distance.
+///
+/// With a `change_speed`, the first `n_drift_centroids` move by that much after each
+/// sample, in random directions, and bounce off the unit hypercube.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RandomRbf {
+    centroids: Vec<Centroid>,
+    weights: Vec<f64>,
+    speeds: Vec<Vec<f64>>,
+    change_speed: f64,
+    rng: Rng,
+}
+
+impl HeapSize for RandomRbf {
+    fn heap_size(&self) -> usize {
+        let centroids: usize = self.centroids.iter().map(|c| c.centre.heap_size()).sum();
+        let speeds: usize = self.speeds.iter().map(|s| s.heap_size()).sum();
+        centroids
+            + self.centroids.capacity() * size_of::<Centroid>()
+            + self.weights.heap_size()
+            + speeds
+            + self.speeds.capacity() * size_of::<Vec<f64>>()
+    }
+}
+
+impl RandomRbf {
+    pub fn names(&self) -> Vec<Key> {
+        (0..self.centroids[0].centre.len() as i64)
+            .map(Key::Int)
+            .collect()
+    }
+
+    // Same walk over the cumulated weights as `river`.
+    fn pick_centroid(&mut self) -> usize {
+        let total: f64 = self.weights.iter().sum();
+        let value = self.rng.next_f64() * total;
+        let (mut index, mut sum) = (0, 0.0);
+        while sum <= value && index < self.weights.len() {
+            sum += self.weights[index];
+            index += 1;
+        }
+        index.saturating_sub(1)
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        for (centroid, speed) in self.centroids.iter_mut().zip(&mut self.speeds) {
+            for (c, s) in centroid.centre.iter_mut().zip(speed.iter_mut()) {
+                *c += *s * self.change_speed;
+                if *c > 1.0 || *c < 0.0 {
+                    *c = if *c > 1.0 { 1.0 } else { 0.0 };
+                    *s = -*s;
+                }
+            }
+        }
+        let index = self.pick_centroid();
+        let n_features = self.centroids[index].centre.len();
+        let offsets: Vec<f64> = (0..n_features)
+            .map(|_| 2.0 * self.rng.next_f64() - 1.0)
+            .collect();
+        let magnitude = offsets.iter().map(|o| o * o).sum::<f64>().sqrt();
+        let centroid = &self.centroids[index];
+        let scale = self.rng.normal() * centroid.std_dev / magnitude;
+        let x = centroid
+            .centre
+            .iter()
+            .zip(&offsets)
+            .map(|(c, o)| Value::Float(c + o * scale))
+            .collect();
+        (x, Value::Int(centroid.label))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum Node {
+    Leaf(i64),
+    Numeric {
+        feature: usize,
+        threshold: f64,
+        below: Box<Node>,
+        above: Box<Node>,
+    },
+    Categorical {
+        feature: usize,
+        children: Vec<Node>,
+    },
+}
+
+impl HeapSize for Node {
+    fn heap_size(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 0,
+            Node::Numeric { below, above, .. } => {
+                2 * size_of::<Node>() + below.heap_size() + above.heap_size()
+            }
+            Node::Categorical { children, .. } => {
+                children.capacity() * size_of::<Node>()
+                    + children.iter().map(Node::heap_size).sum::<usize>()
+            }
+        }
+    }
+}
+
+/// Parameters of the growth of a random tree.
+struct TreeShape {
+    n_classes: u64,
+    n_num_features: usize,
+    n_categories: usize,
+    max_depth: usize,
+    first_leaf_level: usize,
+    fraction_leaves_per_level: f64,
+}
+
+impl TreeShape {
+    // As in `river`: the numeric features stay candidates, with the bounds of their values
+    // on the branch, while a categorical feature is used at most once on a branch.
+    fn grow(
+        &self,
+        rng: &mut Rng,
+        depth: usize,
+        candidates: &[usize],
+        min: &[f64],
+        max: &[f64],
+    ) -> Node {
+        if depth >= self.max_depth
+            || candidates.is_empty()
+            || (depth >= self.first_leaf_level
+                && self.fraction_leaves_per_level >= 1.0 - rng.next_f64())
+        {
+            return Node::Leaf(rng.below(self.n_classes) as i64);
+        }
+        let chosen = rng.below(candidates.len() as u64) as usize;
+        if chosen < self.n_num_features {
+            let threshold = (max[chosen] - min[chosen]) * rng.next_f64() + min[chosen];
+            let mut below_max = max.to_vec();
+            below_max[chosen] = threshold;
+            let below = self.grow(rng, depth + 1, candidates, min, &below_max);
+            let mut above_min = min.to_vec();
+            above_min[chosen] = threshold;
+            let above = self.grow(rng, depth + 1, candidates, &above_min, max);
+            Node::Numeric {
+                feature: chosen,
+                threshold,
+                below: Box::new(below),
+                above: Box::new(above),
+            }
+        } else {
+            let feature = candidates[chosen];
+            let rest: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&c| c != feature)
+                .collect();
+            Node::Categorical {
+                feature,
+                children: (0..self.n_categories)
+                    .map(|_| self.grow(rng, depth + 1, &rest, min, max))
+                    .collect(),
+            }
+        }
+    }
+}
+
+/// Random tree generator: uniform numeric features and uniform categorical ones, labelled
+/// by a tree of random splits and leaf labels, which is set by `seed_tree`. The branches
+/// may end from `first_leaf_level` on, with probability `fraction_leaves_per_level` at each
+/// level, and all end at `max_tree_depth`.
+///
+/// Reference: Domingos, P. and Hulten, G., 2000. Mining high-speed data streams. KDD'00,
+/// pp.71-80.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RandomTree {
+    root: Node,
+    n_num_features: usize,
+    n_cat_features: usize,
+    n_categories: usize,
+    rng: Rng,
+}
+
+impl HeapSize for RandomTree {
+    fn heap_size(&self) -> usize {
+        self.root.heap_size()
+    }
+}
+
+impl RandomTree {
+    pub fn names(&self) -> Vec<Key> {
+        let numeric = (0..self.n_num_features).map(|i| format!("x_num_{}", i));
+        let categorical = (0..self.n_cat_features).map(|i| format!("x_cat_{}", i));
+        numeric.chain(categorical).map(Key::Str).collect()
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let numeric: Vec<f64> = (0..self.n_num_features)
+            .map(|_| self.rng.next_f64())
+            .collect();
+        let categorical: Vec<usize> = (0..self.n_cat_features)
+            .map(|_| self.rng.below(self.n_categories as u64) as usize)
+            .collect();
+        let mut node = &self.root;
+        let y = loop {
+            node = match node {
+                Node::Leaf(label) => break *label,
+                Node::Numeric {
+                    feature,
+                    threshold,
+                    below,
+                    above,
+                } => {
+                    if numeric[*feature] < *threshold {
+                        below
+                    } else {
+                        above
+                    }
+                }
+                Node::Categorical { feature, children } => {
+                    &children[categorical[feature - self.n_num_features]]
+                }
+            };
+        };
+        let x = numeric
+            .into_iter()
+            .map(Value::Float)
+            .chain(categorical.into_iter().map(|c| Value::Int(c as i64)))
+            .collect();
+        (x, Value::Int(y))
+    }
+}
+
+// Segments of the digits 0 to 9 on a seven-segment display.
+const LED_DIGITS: [[i64; 7]; 10] = [
+    [1, 1, 1, 0, 1, 1, 1],
+    [0, 0, 1, 0, 0, 1, 0],
+    [1, 0, 1, 1, 1, 0, 1],
+    [1, 0, 1, 1, 0, 1, 1],
+    [0, 1, 1, 1, 0, 1, 0],
+    [1, 1, 0, 1, 0, 1, 1],
+    [1, 1, 0, 1, 1, 1, 1],
+    [1, 0, 1, 0, 0, 1, 0],
+    [1, 1, 1, 1, 1, 1, 1],
+    [1, 1, 1, 1, 0, 1, 1],
+];
+const LED_IRRELEVANT_FEATURES: usize = 17;
+
+/// LED generator: the seven segments of a digit shown on a display, each of which is
+/// flipped with probability `noise_percentage`, and the digit as label. With
+/// `irrelevant_features`, 17 random bits follow the segments, and `n_drift_features` of the
+/// segments are swapped with some of them.
+///
+/// Reference: Breiman, L., Friedman, J.H., Olshen, R.A. and Stone, C.J., 1984.
+/// Classification and regression trees. Wadsworth.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Led {
+    // Feature holding each segment, then each irrelevant bit.
+    positions: Vec<usize>,
+    noise_percentage: f64,
+    rng: Rng,
+}
+
+impl HeapSize for Led {
+    fn heap_size(&self) -> usize {
+        self.positions.heap_size()
+    }
+}
+
+impl Led {
+    pub fn names(&self) -> Vec<Key> {
+        (0..self.positions.len() as i64).map(Key::Int).collect()
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let mut x = vec![Value::Int(0); self.positions.len()];
+        let y = self.rng.below(10) as usize;
+        for (i, &segment) in LED_DIGITS[y].iter().enumerate() {
+            let flip = 0.01 + self.rng.next_f64() <= self.noise_percentage;
+            x[self.positions[i]] = Value::Int(if flip { 1 - segment } else { segment });
+        }
+        for &position in &self.positions[7..] {
+            x[position] = Value::Int(self.rng.below(2) as i64);
+        }
+        (x, Value::Int(y as i64))
+    }
+}
+
+const STAGGER_FEATURES: [&str; 3] = ["size", "color", "shape"];
+
+/// STAGGER generator: objects of three sizes, colors and shapes, coded from 0 to 2, and
+/// labelled by one of three `classification_function`s. The labels alternate with
+/// `balance_classes`, and are flipped with probability `noise`.
+///
+/// Reference: Schlimmer, J.C. and Granger, R.H., 1986. Incremental learning from noisy
+/// data. Machine Learning, 1(3), pp.317-354.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Stagger {
+    function: usize,
+    balance_classes: bool,
+    noise: f64,
+    next_class_should_be_zero: bool,
+    rng: Rng,
+}
+
+impl Stagger {
+    pub fn names(&self) -> Vec<Key> {
+        STAGGER_FEATURES
+            .iter()
+            .map(|name| Key::Str(name.to_string()))
+            .collect()
+    }
+
+    fn label(&self, size: i64, color: i64, shape: i64) -> bool {
+        match self.function {
+            0 => size == 0 && color == 0,
+            1 => color == 2 || shape == 0,
+            _ => size == 1 || size == 2,
+        }
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let (x, mut y) = loop {
+            let x = [0; 3].map(|_| randint(&mut self.rng, 0, 2));
+            let y = self.label(x[0], x[1], x[2]);
+            if !self.balance_classes || y != self.next_class_should_be_zero {
+                self.next_class_should_be_zero ^= self.balance_classes;
+                break (x, y);
+            }
+        };
+        if self.noise > 0.0 && self.rng.next_f64() < self.noise {
+            y = !y;
+        }
+        (x.map(Value::Int).to_vec(), Value::Int(y as i64))
+    }
+
+    pub fn generate_drift(&mut self) {
+        let shift = 1 + self.rng.below(2) as usize;
+        self.function = (self.function + shift) % 3;
+    }
+}
+
+/// Any generator, for the streams made of others.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Generator {
+    Sea(Sea),
+    Hyperplane(Hyperplane),
+    Agrawal(Agrawal),
+    RandomRbf(RandomRbf),
+    RandomTree(RandomTree),
+    Led(Led),
+    Stagger(Stagger),
+    ConceptDrift(Box<ConceptDrift>),
+}
+
+impl HeapSize for Generator {
+    fn heap_size(&self) -> usize {
+        match self {
+            Generator::Hyperplane(g) => g.heap_size(),
+            Generator::RandomRbf(g) => g.heap_size(),
+            Generator::RandomTree(g) => g.heap_size(),
+            Generator::Led(g) => g.heap_size(),
+            Generator::ConceptDrift(g) => size_of::<ConceptDrift>() + g.heap_size(),
+            _ => 0,
+        }
+    }
+}
+
+impl Generator {
+    /// Copy of the generator held by one of the pyclasses of this module.
+    pub fn from_py(generator: &PyAny) -> PyResult<Self> {
+        if let Ok(g) = generator.extract::<PyRef<RsSEA>>() {
+            Ok(Generator::Sea(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsHyperplane>>() {
+            Ok(Generator::Hyperplane(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsAgrawal>>() {
+            Ok(Generator::Agrawal(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsRandomRBF>>() {
+            Ok(Generator::RandomRbf(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsRandomTree>>() {
+            Ok(Generator::RandomTree(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsLED>>() {
+            Ok(Generator::Led(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsSTAGGER>>() {
+            Ok(Generator::Stagger(g.generator.clone()))
+        } else if let Ok(g) = generator.extract::<PyRef<RsConceptDriftStream>>() {
+            Ok(Generator::ConceptDrift(Box::new(g.generator.clone())))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "{} is not a synthetic stream generator",
+                generator.get_type().name()?
+            )))
+        }
+    }
+
+    pub fn names(&self) -> Vec<Key> {
+        match self {
+            Generator::Sea(g) => g.names(),
+            Generator::Hyperplane(g) => g.names(),
+            Generator::Agrawal(g) => g.names(),
+            Generator::RandomRbf(g) => g.names(),
+            Generator::RandomTree(g) => g.names(),
+            Generator::Led(g) => g.names(),
+            Generator::Stagger(g) => g.names(),
+            Generator::ConceptDrift(g) => g.names(),
+        }
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        match self {
+            Generator::Sea(g) => g.sample(),
+            Generator::Hyperplane(g) => g.sample(),
+            Generator::Agrawal(g) => g.sample(),
+            Generator::RandomRbf(g) => g.sample(),
+            Generator::RandomTree(g) => g.sample(),
+            Generator::Led(g) => g.sample(),
+            Generator::Stagger(g) => g.sample(),
+            Generator::ConceptDrift(g) => g.sample(),
+        }
+    }
+}
+
+/// Concept drift stream: the `t`-th sample (from 1) is drawn from `drift_stream` with
+/// probability `1 / (1 + exp(-4 (t - position) / width))`, and from `stream` otherwise, so
+/// that the concept changes around `position`, over about `width` samples. Drift streams
+/// can be nested to chain several drifts.
+///
+/// Reference: Bifet, A., Holmes, G., Pfahringer, B., Kirkby, R. and Gavaldà, R., 2009. New
+/// ensemble methods for evolving data streams. KDD'09, pp.139-148.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConceptDrift {
+    stream: Generator,
+    drift_stream: Generator,
+    position: f64,
+    width: f64,
+    n_samples: u64,
+    rng: Rng,
+}
+
+impl HeapSize for ConceptDrift {
+    fn heap_size(&self) -> usize {
+        self.stream.heap_size() + self.drift_stream.heap_size()
+    }
+}
+
+impl ConceptDrift {
+    pub fn names(&self) -> Vec<Key> {
+        self.stream.names()
+    }
+
+    /// Probability that the next sample is drawn from the drift stream.
+    pub fn drift_probability(&self) -> f64 {
+        let t = (self.n_samples + 1) as f64;
+        1.0 / (1.0 + (-4.0 * (t - self.position) / self.width).exp())
+    }
+
+    pub fn sample(&mut self) -> Sample {
+        let p = self.drift_probability();
+        self.n_samples += 1;
+        if self.rng.next_f64() > p {
+            self.stream.sample()
+        } else {
+            self.drift_stream.sample()
+        }
+    }
+}
+
+macro_rules! generator {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $name {
+                fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+                    slf
+                }
+                fn __next__(&mut self, py: Python) -> PyResult<Option<(PyObject, PyObject)>> {
+                    let names = self.generator.names();
+                    Ok(Some(to_py(py, &names, self.generator.sample())?))
+                }
+                /// The next `n` samples, as `(x, y)` pairs.
+                pub fn take(&mut self, py: Python, n: usize) -> PyResult<Vec<(PyObject, PyObject)>> {
+                    let names = self.generator.names();
+                    (0..n)
+                        .map(|_| to_py(py, &names, self.generator.sample()))
+                        .collect()
+                }
+            }
+        )*
+    };
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSEA {
+    generator: Sea,
+}
+
+impl HeapSize for RsSEA {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsSEA {
+    #[new]
+    #[args(variant = "0", noise = "0.0", seed = "None")]
+    pub fn new(variant: usize, noise: f64, seed: Option<u64>) -> PyResult<Self> {
+        let threshold = match variant {
+            0 => 8.0,
+            1 => 9.0,
+            2 => 7.0,
+            3 => 9.5,
+            _ => return Err(PyValueError::new_err("variant should be 0, 1, 2 or 3")),
+        };
+        check_probability("noise", noise)?;
+        Ok(RsSEA {
+            generator: Sea {
+                threshold,
+                noise,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsHyperplane {
+    generator: Hyperplane,
+}
+
+impl HeapSize for RsHyperplane {
+    fn heap_size(&self) -> usize {
+        self.generator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsHyperplane {
+    #[new]
+    #[args(
+        seed = "None",
+        n_features = "10",
+        n_drift_features = "2",
+        mag_change = "0.0",
+        noise_percentage = "0.05",
+        sigma = "0.1"
+    )]
+    pub fn new(
+        seed: Option<u64>,
+        n_features: usize,
+        n_drift_features: usize,
+        mag_change: f64,
+        noise_percentage: f64,
+        sigma: f64,
+    ) -> PyResult<Self> {
+        if n_drift_features > n_features {
+            return Err(PyValueError::new_err(
+                "n_drift_features should be at most n_features",
+            ));
+        }
+        check_probability("mag_change", mag_change)?;
+        check_probability("noise_percentage", noise_percentage)?;
+        check_probability("sigma", sigma)?;
+        let mut rng = Rng::new(seed);
+        let weights = (0..n_features).map(|_| rng.next_f64()).collect();
+        let change_direction = (0..n_features)
+            .map(|i| if i < n_drift_features { 1.0 } else { 0.0 })
+            .collect();
+        Ok(RsHyperplane {
+            generator: Hyperplane {
+                n_drift_features,
+                mag_change,
+                noise_percentage,
+                sigma,
+                weights,
+                change_direction,
+                rng,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsAgrawal {
+    generator: Agrawal,
+}
+
+impl HeapSize for RsAgrawal {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsAgrawal {
+    #[new]
+    #[args(
+        classification_function = "0",
+        seed = "None",
+        balance_classes = "false",
+        perturbation = "0.0"
+    )]
+    pub fn new(
+        classification_function: usize,
+        seed: Option<u64>,
+        balance_classes: bool,
+        perturbation: f64,
+    ) -> PyResult<Self> {
+        if classification_function > 9 {
+            return Err(PyValueError::new_err(
+                "classification_function should be between 0 and 9",
+            ));
+        }
+        check_probability("perturbation", perturbation)?;
+        Ok(RsAgrawal {
+            generator: Agrawal {
+                function: classification_function,
+                balance_classes,
+                perturbation,
+                next_class_should_be_zero: false,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRandomRBF {
+    generator: RandomRbf,
+}
+
+impl HeapSize for RsRandomRBF {
+    fn heap_size(&self) -> usize {
+        self.generator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsRandomRBF {
+    /// `n_drift_centroids` is capped at `n_centroids`.
+    #[new]
+    #[args(
+        seed_model = "None",
+        seed_sample = "None",
+        n_classes = "2",
+        n_features = "10",
+        n_centroids = "50",
+        change_speed = "0.0",
+        n_drift_centroids = "50"
+    )]
+    pub fn new(
+        seed_model: Option<u64>,
+        seed_sample: Option<u64>,
+        n_classes: u64,
+        n_features: usize,
+        n_centroids: usize,
+        change_speed: f64,
+        n_drift_centroids: usize,
+    ) -> PyResult<Self> {
+        if n_classes == 0 || n_features == 0 || n_centroids == 0 {
+            return Err(PyValueError::new_err(
+                "n_classes, n_features and n_centroids should be at least 1",
+            ));
+        }
+        let mut rng = Rng::new(seed_model);
+        let mut centroids = Vec::with_capacity(n_centroids);
+        let mut weights = Vec::with_capacity(n_centroids);
+        for _ in 0..n_centroids {
+            centroids.push(Centroid {
+                centre: (0..n_features).map(|_| rng.next_f64()).collect(),
+                label: rng.below(n_classes) as i64,
+                std_dev: rng.next_f64(),
+            });
+            weights.push(rng.next_f64());
+        }
+        // `river` draws the speeds from a second generator seeded like the first.
+        let mut rng = Rng::new(seed_model);
+        let speeds = (0..n_drift_centroids.min(n_centroids))
+            .map(|_| {
+                let speed: Vec<f64> = (0..n_features).map(|_| rng.next_f64()).collect();
+                let norm = speed.iter().map(|s| s * s).sum::<f64>().sqrt();
+                speed.into_iter().map(|s| s / norm).collect()
+            })
+            .collect();
+        Ok(RsRandomRBF {
+            generator: RandomRbf {
+                centroids,
+                weights,
+                speeds,
+                change_speed,
+                rng: Rng::new(seed_sample),
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsRandomTree {
+    generator: RandomTree,
+}
+
+impl HeapSize for RsRandomTree {
+    fn heap_size(&self) -> usize {
+        self.generator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsRandomTree {
+    #[new]
+    #[args(
+        seed_tree = "None",
+        seed_sample = "None",
+        n_classes = "2",
+        n_num_features = "5",
+        n_cat_features = "5",
+        n_categories_per_feature = "5",
+        max_tree_depth = "5",
+        first_leaf_level = "3",
+        fraction_leaves_per_level = "0.15"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        seed_tree: Option<u64>,
+        seed_sample: Option<u64>,
+        n_classes: u64,
+        n_num_features: usize,
+        n_cat_features: usize,
+        n_categories_per_feature: usize,
+        max_tree_depth: usize,
+        first_leaf_level: usize,
+        fraction_leaves_per_level: f64,
+    ) -> PyResult<Self> {
+        if n_classes == 0 || n_categories_per_feature == 0 {
+            return Err(PyValueError::new_err(
+                "n_classes and n_categories_per_feature should be at least 1",
+            ));
+        }
+        if n_num_features + n_cat_features == 0 {
+            return Err(PyValueError::new_err(
+                "there should be at least one feature",
+            ));
+        }
+        check_probability("fraction_leaves_per_level", fraction_leaves_per_level)?;
+        let shape = TreeShape {
+            n_classes,
+            n_num_features,
+            n_categories: n_categories_per_feature,
+            max_depth: max_tree_depth,
+            first_leaf_level,
+            fraction_leaves_per_level,
+        };
+        let candidates: Vec<usize> = (0..n_num_features + n_cat_features).collect();
+        let root = shape.grow(
+            &mut Rng::new(seed_tree),
+            0,
+            &candidates,
+            &vec![0.0; n_num_features],
+            &vec![1.0; n_num_features],
+        );
+        Ok(RsRandomTree {
+            generator: RandomTree {
+                root,
+                n_num_features,
+                n_cat_features,
+                n_categories: n_categories_per_feature,
+                rng: Rng::new(seed_sample),
+            },
+        })
+    }
+
+    /// Depth of the deepest leaf of the tree.
+    #[getter]
+    pub fn depth(&self) -> usize {
+        fn depth(node: &Node) -> usize {
+            match node {
+                Node::Leaf(_) => 0,
+                Node::Numeric { below, above, .. } => 1 + depth(below).max(depth(above)),
+                Node::Categorical { children, .. } => {
+                    1 + children.iter().map(depth).max().unwrap_or(0)
+                }
+            }
+        }
+        depth(&self.generator.root)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsLED {
+    generator: Led,
+}
+
+impl HeapSize for RsLED {
+    fn heap_size(&self) -> usize {
+        self.generator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsLED {
+    /// The swapped segments and bits are drawn first, as in `river.datasets.synth.LEDDrift`,
+    /// and only with `irrelevant_features`.
+    #[new]
+    #[args(
+        seed = "None",
+        noise_percentage = "0.0",
+        irrelevant_features = "false",
+        n_drift_features = "0"
+    )]
+    pub fn new(
+        seed: Option<u64>,
+        noise_percentage: f64,
+        irrelevant_features: bool,
+        n_drift_features: usize,
+    ) -> PyResult<Self> {
+        check_probability("noise_percentage", noise_percentage)?;
+        if n_drift_features > 7 {
+            return Err(PyValueError::new_err(
+                "n_drift_features should be at most 7",
+            ));
+        }
+        let mut rng = Rng::new(seed);
+        let n_features = if irrelevant_features {
+            7 + LED_IRRELEVANT_FEATURES
+        } else {
+            7
+        };
+        let mut positions: Vec<usize> = (0..n_features).collect();
+        if irrelevant_features && n_drift_features > 0 {
+            let segment = randint(&mut rng, 0, 6) as usize;
+            let bit = randint(&mut rng, 0, LED_IRRELEVANT_FEATURES as i64 - 1) as usize;
+            for i in 0..n_drift_features {
+                let a = (i + segment) % 7;
+                let b = 7 + (i + bit) % LED_IRRELEVANT_FEATURES;
+                positions.swap(a, b);
+            }
+        }
+        Ok(RsLED {
+            generator: Led {
+                positions,
+                noise_percentage,
+                rng,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsSTAGGER {
+    generator: Stagger,
+}
+
+impl HeapSize for RsSTAGGER {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[pymethods]
+impl RsSTAGGER {
+    #[new]
+    #[args(
+        classification_function = "0",
+        seed = "None",
+        balance_classes = "false",
+        noise = "0.0"
+    )]
+    pub fn new(
+        classification_function: usize,
+        seed: Option<u64>,
+        balance_classes: bool,
+        noise: f64,
+    ) -> PyResult<Self> {
+        if classification_function > 2 {
+            return Err(PyValueError::new_err(
+                "classification_function should be 0, 1 or 2",
+            ));
+        }
+        check_probability("noise", noise)?;
+        Ok(RsSTAGGER {
+            generator: Stagger {
+                function: classification_function,
+                balance_classes,
+                noise,
+                next_class_should_be_zero: false,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+
+    #[getter]
+    pub fn classification_function(&self) -> usize {
+        self.generator.function
+    }
+
+    /// Switches to another classification function, drawn at random.
+    pub fn generate_drift(&mut self) {
+        self.generator.generate_drift()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[pyclass(module = "river.stats._rust_stats")]
+pub struct RsConceptDriftStream {
+    generator: ConceptDrift,
+}
+
+impl HeapSize for RsConceptDriftStream {
+    fn heap_size(&self) -> usize {
+        self.generator.heap_size()
+    }
+}
+
+#[pymethods]
+impl RsConceptDriftStream {
+    /// The generators are copied, and default to Agrawal generators with the classification
+    /// functions 0 and 2. When `alpha`, the angle of the change in degrees, is given, the
+    /// width is `1 / tan(alpha)`, rounded down and at least 1.
+    #[new]
+    #[args(
+        stream = "None",
+        drift_stream = "None",
+        position = "5000",
+        width = "1000",
+        seed = "None",
+        alpha = "None"
+    )]
+    pub fn new(
+        stream: Option<&PyAny>,
+        drift_stream: Option<&PyAny>,
+        position: u64,
+        width: u64,
+        seed: Option<u64>,
+        alpha: Option<f64>,
+    ) -> PyResult<Self> {
+        let stream = match stream {
+            Some(stream) => Generator::from_py(stream)?,
+            None => Generator::Agrawal(RsAgrawal::new(0, seed, false, 0.0)?.generator),
+        };
+        let drift_stream = match drift_stream {
+            Some(stream) => Generator::from_py(stream)?,
+            None => Generator::Agrawal(RsAgrawal::new(2, seed, false, 0.0)?.generator),
+        };
+        if stream.names() != drift_stream.names() {
+            return Err(PyValueError::new_err(
+                "stream and drift_stream should have the same features",
+            ));
+        }
+        let width = match alpha {
+            Some(alpha) if alpha > 0.0 && alpha <= 90.0 => {
+                ((1.0 / alpha.to_radians().tan()) as u64).max(1)
+            }
+            Some(_) => {
+                return Err(PyValueError::new_err(
+                    "alpha should be in the range (0.0, 90.0]",
+                ))
+            }
+            None if width == 0 => return Err(PyValueError::new_err("width should be at least 1")),
+            None => width,
+        };
+        Ok(RsConceptDriftStream {
+            generator: ConceptDrift {
+                stream,
+                drift_stream,
+                position: position as f64,
+                width: width as f64,
+                n_samples: 0,
+                rng: Rng::new(seed),
+            },
+        })
+    }
+
+    #[getter]
+    pub fn width(&self) -> u64 {
+        self.generator.width as u64
+    }
+
+    /// Probability that the next sample is drawn from `drift_stream`.
+    pub fn drift_probability(&self) -> f64 {
+        self.generator.drift_probability()
+    }
+}
+
+generator!(
+    RsSEA,
+    RsHyperplane,
+    RsAgrawal,
+    RsRandomRBF,
+    RsRandomTree,
+    RsLED,
+    RsSTAGGER,
+    RsConceptDriftStream
+);
@@ -0,0 +1,365 @@
+// Type-erased univariate stats, so that containers can own any of the Rust stats
+// without going through Python for every update.
+
+use std::fmt;
+
+use numpy::PyArray1;
+use pyo3::exceptions::{PyNotImplementedError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::{
+    RsEWMean, RsEWVar, RsIQR, RsKurtosis, RsPeakToPeak, RsQuantile, RsRollingIQR, RsRollingMAD,
+    RsRollingMax, RsRollingMean, RsRollingMin, RsRollingPeakToPeak, RsRollingQuantile,
+    RsRollingVar, RsSkew,
+};
+use crate::drift::{RsAdaptiveMean, RsAdaptiveVar};
+use crate::frozen::RsFrozenStat;
+use crate::ingest::Values;
+use crate::memory::HeapSize;
+use crate::state;
+use crate::stats::{RsAutoCorr, RsMAD, RsPercentileRank, RsRollingPercentileRank, RsShift};
+
+// Some bindings validate their input and others don't.
+pub(crate) trait IntoPyResult {
+    fn into_result(self) -> PyResult<()>;
+}
+
+impl IntoPyResult for () {
+    fn into_result(self) -> PyResult<()> {
+        Ok(())
+    }
+}
+
+impl IntoPyResult for PyResult<()> {
+    fn into_result(self) -> PyResult<()> {
+        self
+    }
+}
+
+/// Constructor argument of a stat, written as in Python.
+pub(crate) enum Param {
+    Float(f64),
+    Int(usize),
+    Bool(bool),
+    Str(&'static str),
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Float(x) if x.is_nan() => write!(f, "nan"),
+            Param::Float(x) if x.is_infinite() => {
+                write!(f, "{}", if *x > 0.0 { "inf" } else { "-inf" })
+            }
+            Param::Float(x) => write!(f, "{:?}", x),
+            Param::Int(n) => write!(f, "{}", n),
+            Param::Bool(b) => write!(f, "{}", if *b { "True" } else { "False" }),
+            Param::Str(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+/// The arguments which set a stat apart from the others of its class, for its `__repr__`.
+pub(crate) trait Params {
+    fn params(&self) -> Vec<(&'static str, Param)>;
+}
+
+// The stats which take a weight are updated with a weight of 1.
+trait UpdateOne {
+    fn update_one(&mut self, x: f64) -> PyResult<()>;
+}
+
+macro_rules! update_one {
+    (weighted: $($weighted:ty),* ; unweighted: $($unweighted:ty),* $(,)?) => {
+        $(
+            impl UpdateOne for $weighted {
+                fn update_one(&mut self, x: f64) -> PyResult<()> {
+                    self.update(x, 1.0)
+                }
+            }
+        )*
+        $(
+            impl UpdateOne for $unweighted {
+                fn update_one(&mut self, x: f64) -> PyResult<()> {
+                    self.update(x).into_result()
+                }
+            }
+        )*
+    };
+}
+
+update_one!(
+    weighted:
+        RsQuantile,
+        RsEWMean,
+        RsEWVar,
+        RsIQR,
+        RsKurtosis,
+        RsPeakToPeak,
+        RsSkew,
+        RsMAD,
+        RsRollingQuantile,
+        RsRollingIQR,
+        RsRollingMean,
+        RsRollingVar,
+        RsRollingPercentileRank,
+        RsRollingMin,
+        RsRollingMax,
+        RsRollingPeakToPeak,
+        RsRollingMAD;
+    unweighted:
+        RsPercentileRank,
+        RsAdaptiveMean,
+        RsAdaptiveVar,
+        RsShift,
+        RsAutoCorr,
+);
+
+macro_rules! any_stat {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        /// One of the univariate stats exposed to Python.
+        #[derive(Clone, Serialize, Deserialize)]
+        pub enum AnyStat {
+            $($variant($ty)),*
+        }
+
+        impl AnyStat {
+            /// Copies the state of a Python-side stat.
+            pub fn from_py(obj: &PyAny) -> PyResult<Self> {
+                $(
+                    if let Ok(stat) = obj.extract::<PyRef<$ty>>() {
+                        return Ok(AnyStat::$variant((*stat).clone()));
+                    }
+                )*
+                Err(PyTypeError::new_err(format!(
+                    "{} is not a Rust univariate stat",
+                    obj.get_type().name()?
+                )))
+            }
+            /// Wraps a copy of the state into a new Python object.
+            pub fn to_py(&self, py: Python) -> PyResult<PyObject> {
+                match self {
+                    $(AnyStat::$variant(stat) => Ok(Py::new(py, stat.clone())?.into_py(py)),)*
+                }
+            }
+            pub fn update(&mut self, x: f64) -> PyResult<()> {
+                match self {
+                    $(AnyStat::$variant(stat) => stat.update_one(x),)*
+                }
+            }
+            pub fn get(&self) -> f64 {
+                match self {
+                    $(AnyStat::$variant(stat) => stat.get(),)*
+                }
+            }
+        }
+
+        /// Exclusive borrow of a Python-side stat, to update it in place.
+        pub enum StatRefMut<'py> {
+            $($variant(PyRefMut<'py, $ty>)),*
+        }
+
+        impl<'py> StatRefMut<'py> {
+            pub fn extract(obj: &'py PyAny) -> PyResult<Self> {
+                $(
+                    if let Ok(cell) = obj.downcast::<PyCell<$ty>>() {
+                        return Ok(StatRefMut::$variant(cell.try_borrow_mut()?));
+                    }
+                )*
+                Err(PyTypeError::new_err(format!(
+                    "{} is not a Rust univariate stat",
+                    obj.get_type().name()?
+                )))
+            }
+            /// The borrowed stat, which unlike the borrow itself can be sent to other threads.
+            pub fn as_mut(&mut self) -> StatMut<'_> {
+                match self {
+                    $(StatRefMut::$variant(stat) => StatMut::$variant(&mut *stat),)*
+                }
+            }
+        }
+
+        pub enum StatMut<'a> {
+            $($variant(&'a mut $ty)),*
+        }
+
+        impl StatMut<'_> {
+            pub fn update(&mut self, x: f64) -> PyResult<()> {
+                match self {
+                    $(StatMut::$variant(stat) => stat.update_one(x),)*
+                }
+            }
+        }
+
+        impl HeapSize for AnyStat {
+            fn heap_size(&self) -> usize {
+                match self {
+                    $(AnyStat::$variant(stat) => stat.heap_size(),)*
+                }
+            }
+        }
+
+        $(
+            #[pymethods]
+            impl $ty {
+                /// The class, its arguments and the current value, e.g.
+                /// `RsEWMean(alpha=0.5): 2.5`.
+                pub fn __repr__(&self) -> String {
+                    let params: Vec<String> = self
+                        .params()
+                        .into_iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect();
+                    format!(
+                        "{}({}): {}",
+                        state::name::<Self>(),
+                        params.join(", "),
+                        Param::Float(self.get())
+                    )
+                }
+                /// Read-only copy of the current state, to share with serving threads.
+                pub fn snapshot(&self) -> RsFrozenStat {
+                    RsFrozenStat::from_stat(AnyStat::$variant(self.clone()))
+                }
+                /// Updates with each value of a 1D NumPy array, buffer-protocol object, pandas
+                /// Series, Arrow array or list, in order, with the GIL released.
+                pub fn update_many(&mut self, py: Python, values: &PyAny) -> PyResult<()> {
+                    let values = Values::extract(values)?;
+                    let slices = values.slices();
+                    py.allow_threads(|| {
+                        slices
+                            .iter()
+                            .flat_map(|slice| slice.iter())
+                            .try_for_each(|&x| self.update_one(x))
+                    })
+                }
+                /// Updates with each value of a 1D batch, in order, and returns the value of
+                /// the stat after each update, as `ewm().mean()` does in pandas.
+                pub fn transform_many<'py>(
+                    &mut self,
+                    py: Python<'py>,
+                    values: &PyAny,
+                ) -> PyResult<&'py PyArray1<f64>> {
+                    let values = Values::extract(values)?;
+                    let slices = values.slices();
+                    let transformed = py.allow_threads(|| {
+                        slices
+                            .iter()
+                            .flat_map(|slice| slice.iter())
+                            .map(|&x| {
+                                self.update_one(x)?;
+                                Ok(self.get())
+                            })
+                            .collect::<PyResult<Vec<f64>>>()
+                    })?;
+                    Ok(PyArray1::from_vec(py, transformed))
+                }
+            }
+        )*
+    };
+}
+
+fn no_placeholders<T>() -> PyResult<()> {
+    Err(PyValueError::new_err(format!(
+        "{} can't hold placeholders for the missing values, use placeholders=False",
+        state::name::<T>()
+    )))
+}
+
+impl AnyStat {
+    /// Stands for a missing value in the rolling windows, and does nothing for the others.
+    /// The windows summarized by running sums or by their extrema can't leave a slot empty.
+    pub fn push_missing(&mut self) -> PyResult<()> {
+        match self {
+            AnyStat::RollingQuantile(stat) => stat.push_missing(),
+            AnyStat::RollingIqr(stat) => stat.push_missing(),
+            AnyStat::RollingPercentileRank(stat) => stat.push_missing(),
+            AnyStat::RollingMad(stat) => stat.push_missing(),
+            AnyStat::RollingMean(_) => no_placeholders::<RsRollingMean>(),
+            AnyStat::RollingVar(_) => no_placeholders::<RsRollingVar>(),
+            AnyStat::RollingMin(_) => no_placeholders::<RsRollingMin>(),
+            AnyStat::RollingMax(_) => no_placeholders::<RsRollingMax>(),
+            AnyStat::RollingPeakToPeak(_) => no_placeholders::<RsRollingPeakToPeak>(),
+            AnyStat::Quantile(_)
+            | AnyStat::EWMean(_)
+            | AnyStat::EWVar(_)
+            | AnyStat::Iqr(_)
+            | AnyStat::Kurtosis(_)
+            | AnyStat::PeakToPeak(_)
+            | AnyStat::Skew(_)
+            | AnyStat::PercentileRank(_)
+            | AnyStat::AdaptiveMean(_)
+            | AnyStat::AdaptiveVar(_)
+            | AnyStat::Shift(_)
+            | AnyStat::AutoCorr(_)
+            | AnyStat::Mad(_) => Ok(()),
+        }
+    }
+}
+
+any_stat!(
+    Quantile(RsQuantile),
+    EWMean(RsEWMean),
+    EWVar(RsEWVar),
+    Iqr(RsIQR),
+    Kurtosis(RsKurtosis),
+    PeakToPeak(RsPeakToPeak),
+    Skew(RsSkew),
+    RollingQuantile(RsRollingQuantile),
+    RollingIqr(RsRollingIQR),
+    RollingMean(RsRollingMean),
+    RollingVar(RsRollingVar),
+    PercentileRank(RsPercentileRank),
+    RollingPercentileRank(RsRollingPercentileRank),
+    AdaptiveMean(RsAdaptiveMean),
+    AdaptiveVar(RsAdaptiveVar),
+    Shift(RsShift),
+    AutoCorr(RsAutoCorr),
+    RollingMin(RsRollingMin),
+    RollingMax(RsRollingMax),
+    RollingPeakToPeak(RsRollingPeakToPeak),
+    Mad(RsMAD),
+    RollingMad(RsRollingMAD),
+);
+
+// The stats that summarize the values in a way that can't forget one of them, or that
+// already forget the values on their own.
+macro_rules! not_revertible {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[pymethods]
+            impl $ty {
+                pub fn revert(&self, _x: f64) -> PyResult<()> {
+                    Err(PyNotImplementedError::new_err(format!(
+                        "{} can't revert a value",
+                        stringify!($ty)
+                    )))
+                }
+            }
+        )*
+    };
+}
+
+not_revertible!(
+    RsQuantile,
+    RsEWMean,
+    RsEWVar,
+    RsIQR,
+    RsPeakToPeak,
+    RsRollingQuantile,
+    RsRollingIQR,
+    RsRollingMean,
+    RsRollingVar,
+    RsPercentileRank,
+    RsRollingPercentileRank,
+    RsAdaptiveMean,
+    RsAdaptiveVar,
+    RsShift,
+    RsAutoCorr,
+    RsRollingMin,
+    RsRollingMax,
+    RsRollingPeakToPeak,
+    RsMAD,
+    RsRollingMAD,
+);
@@ -0,0 +1,108 @@
+// Read-only buffer-protocol views over the windows held by the rolling stats, so that
+// `np.asarray(stat)` or `memoryview(stat)` see the window without copying it.
+//
+// The pyclasses count their live exports and refuse to be updated while a view is alive,
+// in the same way a `bytearray` refuses to be resized.
+
+use std::mem::size_of;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+
+/// Fills `view` with a 1D view of `data`, which must stay in place until the view is
+/// released.
+///
+/// # Safety
+///
+/// `view` must be the pointer handed to `__getbuffer__` and `owner` the object exporting
+/// the buffer.
+pub unsafe fn fill_view(
+    view: *mut ffi::Py_buffer,
+    flags: c_int,
+    owner: *mut ffi::PyObject,
+    data: &[f64],
+) -> PyResult<()> {
+    if view.is_null() {
+        return Err(PyBufferError::new_err("View is null"));
+    }
+    if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+        return Err(PyBufferError::new_err("The window is read-only"));
+    }
+    // Shape and strides live until the view is released.
+    let layout = Box::into_raw(Box::new([
+        data.len() as ffi::Py_ssize_t,
+        size_of::<f64>() as ffi::Py_ssize_t,
+    ])) as *mut ffi::Py_ssize_t;
+
+    ffi::Py_INCREF(owner);
+    (*view).obj = owner;
+    (*view).buf = data.as_ptr() as *mut c_void;
+    (*view).len = std::mem::size_of_val(data) as ffi::Py_ssize_t;
+    (*view).readonly = 1;
+    (*view).itemsize = size_of::<f64>() as ffi::Py_ssize_t;
+    (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+        c"d".as_ptr() as *mut c_char
+    } else {
+        ptr::null_mut()
+    };
+    (*view).ndim = 1;
+    (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+        layout
+    } else {
+        ptr::null_mut()
+    };
+    (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+        layout.add(1)
+    } else {
+        ptr::null_mut()
+    };
+    (*view).suboffsets = ptr::null_mut();
+    (*view).internal = layout as *mut c_void;
+    Ok(())
+}
+
+/// Frees what `fill_view` allocated.
+///
+/// # Safety
+///
+/// `view` must have been filled by `fill_view`.
+pub unsafe fn release_view(view: *mut ffi::Py_buffer) {
+    drop(Box::from_raw((*view).internal as *mut [ffi::Py_ssize_t; 2]));
+}
+
+/// Error raised when updating a stat whose window is exported.
+pub fn check_not_exported(exports: usize) -> PyResult<()> {
+    if exports > 0 {
+        return Err(PyBufferError::new_err(
+            "Existing exports of the window prevent it from being updated",
+        ));
+    }
+    Ok(())
+}
+
+/// Count of the live views over a binding, whose state can't be replaced while one is alive.
+pub trait Exporting {
+    fn exports(&self) -> usize {
+        0
+    }
+}
+
+// The rolling stats exporting their window count their views next to their definition.
+macro_rules! exporting {
+    (@one crate::bindings::RsRollingQuantile) => {};
+    (@one crate::bindings::RsRollingIQR) => {};
+    (@one crate::bindings::RsRollingMAD) => {};
+    (@one crate::stats::RsRollingCircularStats) => {};
+    (@one crate::stats::RsRollingPercentileRank) => {};
+    (@one $($segment:ident)::+) => {
+        impl Exporting for $($segment)::+ {}
+    };
+    ($($($segment:ident)::+),* $(,)?) => {
+        $(exporting!(@one $($segment)::+);)*
+    };
+}
+
+for_each_binding!(exporting);